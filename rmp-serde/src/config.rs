@@ -23,6 +23,9 @@ pub(crate) mod sealed {
         /// String struct fields
         fn is_named(&self) -> bool;
         fn bytes(&self) -> BytesMode;
+
+        /// Sort map entries by their serialized keys
+        fn sort_map_keys(&self) -> bool;
     }
 }
 
@@ -31,6 +34,7 @@ pub(crate) struct RuntimeConfig {
     pub(crate) is_human_readable: bool,
     pub(crate) is_named: bool,
     pub(crate) bytes: BytesMode,
+    pub(crate) sort_map_keys: bool,
 }
 
 /// When to encode `[u8]` as `bytes` rather than a sequence
@@ -62,6 +66,7 @@ impl RuntimeConfig {
             is_human_readable: other.is_human_readable(),
             is_named: other.is_named(),
             bytes: other.bytes(),
+            sort_map_keys: other.sort_map_keys(),
         }
     }
 }
@@ -81,6 +86,11 @@ impl sealed::SerializerConfig for RuntimeConfig {
     fn bytes(&self) -> BytesMode {
         self.bytes
     }
+
+    #[inline]
+    fn sort_map_keys(&self) -> bool {
+        self.sort_map_keys
+    }
 }
 
 /// The default serializer/deserializer configuration.
@@ -109,6 +119,11 @@ impl sealed::SerializerConfig for DefaultConfig {
     fn bytes(&self) -> BytesMode {
         BytesMode::default()
     }
+
+    #[inline(always)]
+    fn sort_map_keys(&self) -> bool {
+        false
+    }
 }
 
 /// Config wrapper, that overrides struct serialization by packing as a map with field names.
@@ -146,6 +161,10 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn sort_map_keys(&self) -> bool {
+        self.0.sort_map_keys()
+    }
 }
 
 /// Config wrapper that overrides struct serlization by packing as a tuple without field
@@ -178,6 +197,10 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn sort_map_keys(&self) -> bool {
+        self.0.sort_map_keys()
+    }
 }
 
 /// Config wrapper that overrides `Serializer::is_human_readable` and
@@ -210,6 +233,10 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn sort_map_keys(&self) -> bool {
+        self.0.sort_map_keys()
+    }
 }
 
 /// Config wrapper that overrides `Serializer::is_human_readable` and
@@ -242,4 +269,8 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn sort_map_keys(&self) -> bool {
+        self.0.sort_map_keys()
+    }
 }
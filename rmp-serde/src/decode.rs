@@ -6,6 +6,7 @@ use std::error;
 use std::fmt::{self, Display, Formatter};
 use std::io::{self, Cursor, ErrorKind, Read};
 use std::marker::PhantomData;
+use std::mem;
 use std::num::TryFromIntError;
 use std::str::{self, Utf8Error};
 
@@ -25,6 +26,7 @@ use crate::MSGPACK_EXT_STRUCT_NAME;
 
 /// Enum representing errors that can occur while decoding MessagePack data.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// The enclosed I/O error occurred while trying to read a MessagePack
     /// marker.
@@ -48,6 +50,23 @@ pub enum Error {
     Utf8Error(Utf8Error),
     /// The depth limit was exceeded.
     DepthLimitExceeded,
+    /// An array or a map claimed more elements than the configured limit allows.
+    LengthLimitExceeded {
+        /// The length claimed by the encoded container.
+        len: u32,
+        /// The maximum allowed length.
+        limit: u32,
+    },
+    /// The enclosed error occurred at the given location within the decoded value, such as
+    /// `users[3].name`.
+    ///
+    /// Only returned when path tracking is enabled with [`Deserializer::set_track_path`].
+    AtPath {
+        /// The trail of struct fields, map keys and sequence indices leading to the failure.
+        path: String,
+        /// The error that occurred at that location.
+        err: Box<Error>,
+    },
 }
 
 macro_rules! depth_count(
@@ -77,6 +96,8 @@ impl error::Error for Error {
             Self::Syntax(..) => None,
             Self::Utf8Error(ref err) => Some(err),
             Self::DepthLimitExceeded => None,
+            Self::LengthLimitExceeded { .. } => None,
+            Self::AtPath { ref err, .. } => Some(&**err),
         }
     }
 }
@@ -106,10 +127,39 @@ impl Display for Error {
             Self::Syntax(ref msg) => fmt.write_str(msg),
             Self::Utf8Error(ref err) => write!(fmt, "string found to be invalid utf8: {err}"),
             Self::DepthLimitExceeded => fmt.write_str("depth limit exceeded"),
+            Self::LengthLimitExceeded { len, limit } => {
+                write!(fmt, "container length {len} exceeds the limit of {limit}")
+            }
+            Self::AtPath { ref path, ref err } => write!(fmt, "{err} at {path}"),
         }
     }
 }
 
+impl Error {
+    /// Prepends a path segment to the location of this error, wrapping it in
+    /// [`Error::AtPath`] first if needed.
+    ///
+    /// A segment is either a field or key name, or a sequence index.
+    #[cold]
+    fn at(self, name: Option<&str>, index: u32) -> Self {
+        let (path, err) = match self {
+            Self::AtPath { path, err } => (path, err),
+            err => (String::new(), Box::new(err)),
+        };
+
+        let mut segment = match name {
+            Some(name) => name.to_owned(),
+            None => format!("[{index}]"),
+        };
+        if !path.is_empty() && !path.starts_with('[') {
+            segment.push('.');
+        }
+        segment.push_str(&path);
+
+        Self::AtPath { path: segment, err }
+    }
+}
+
 impl From<MarkerReadError> for Error {
     #[cold]
     fn from(err: MarkerReadError) -> Self {
@@ -182,6 +232,14 @@ pub struct Deserializer<R, C = DefaultConfig> {
     is_human_readable: bool,
     marker: Option<Marker>,
     depth: u16,
+    max_seq_len: u32,
+    max_map_len: u32,
+    track_path: bool,
+    // Field names of the struct being deserialized, used to name its elements in error paths.
+    fields: &'static [&'static str],
+    // Set while deserializing a map key, so that a string key is recorded in `key`.
+    capture_key: bool,
+    key: Option<String>,
 }
 
 impl<R: Read, C> Deserializer<R, C> {
@@ -214,6 +272,12 @@ impl<R: Read> Deserializer<ReadReader<R>, DefaultConfig> {
             // Cached marker in case of deserializing optional values.
             marker: None,
             depth: 1024,
+            max_seq_len: u32::MAX,
+            max_map_len: u32::MAX,
+            track_path: false,
+            fields: &[],
+            capture_key: false,
+            key: None,
         }
     }
 }
@@ -246,13 +310,22 @@ impl<R: Read, C: SerializerConfig> Deserializer<R, C> {
     /// versions of `rmp-serde`.
     #[inline]
     pub fn with_human_readable(self) -> Deserializer<R, HumanReadableConfig<C>> {
-        let Self { rd, _config: _, is_human_readable: _, marker, depth } = self;
+        let Self {
+            rd, _config: _, is_human_readable: _, marker, depth, max_seq_len, max_map_len, track_path, fields,
+            capture_key, key,
+        } = self;
         Deserializer {
             rd,
             is_human_readable: true,
             _config: PhantomData,
             marker,
             depth,
+            max_seq_len,
+            max_map_len,
+            track_path,
+            fields,
+            capture_key,
+            key,
         }
     }
 
@@ -263,13 +336,22 @@ impl<R: Read, C: SerializerConfig> Deserializer<R, C> {
     /// representation.
     #[inline]
     pub fn with_binary(self) -> Deserializer<R, BinaryConfig<C>> {
-        let Self { rd, _config: _, is_human_readable: _, marker, depth } = self;
+        let Self {
+            rd, _config: _, is_human_readable: _, marker, depth, max_seq_len, max_map_len, track_path, fields,
+            capture_key, key,
+        } = self;
         Deserializer {
             rd,
             is_human_readable: false,
             _config: PhantomData,
             marker,
             depth,
+            max_seq_len,
+            max_map_len,
+            track_path,
+            fields,
+            capture_key,
+            key,
         }
     }
 }
@@ -295,6 +377,12 @@ where
             _config: PhantomData,
             marker: None,
             depth: 1024,
+            max_seq_len: u32::MAX,
+            max_map_len: u32::MAX,
+            track_path: false,
+            fields: &[],
+            capture_key: false,
+            key: None,
         }
     }
 
@@ -312,6 +400,36 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
     pub fn set_max_depth(&mut self, depth: usize) {
         self.depth = depth.min(u16::MAX as _) as u16;
     }
+
+    /// Changes the maximum number of elements an array may claim to have.
+    ///
+    /// Arrays exceeding the limit are rejected with [`Error::LengthLimitExceeded`] right after
+    /// reading their length, before any element is decoded. There is no limit by default.
+    #[inline(always)]
+    pub fn set_max_seq_len(&mut self, len: usize) {
+        self.max_seq_len = len.min(u32::MAX as _) as u32;
+    }
+
+    /// Changes the maximum number of entries a map may claim to have.
+    ///
+    /// Maps exceeding the limit are rejected with [`Error::LengthLimitExceeded`] right after
+    /// reading their length, before any entry is decoded. There is no limit by default.
+    #[inline(always)]
+    pub fn set_max_map_len(&mut self, len: usize) {
+        self.max_map_len = len.min(u32::MAX as _) as u32;
+    }
+
+    /// Enables tracking the location of errors within the decoded value.
+    ///
+    /// When enabled, errors occurring inside structs, sequences or maps are wrapped in
+    /// [`Error::AtPath`], which records the trail of struct fields, string map keys and sequence
+    /// indices leading to the failure, e.g. `users[3].name`. Entries of maps with non-string
+    /// keys are identified by their index instead. Disabled by default, as it costs an
+    /// allocation per map key and changes the error variants returned.
+    #[inline(always)]
+    pub fn set_track_path(&mut self, enabled: bool) {
+        self.track_path = enabled;
+    }
 }
 
 #[inline(never)]
@@ -350,9 +468,15 @@ fn read_128_buf<'de, R: ReadSlice<'de>>(rd: &mut R, len: u8) -> Result<i128, Err
 fn read_str_data<'de, V, R>(rd: &mut R, len: u32, visitor: V) -> Result<V::Value, Error>
     where V: Visitor<'de>, R: ReadSlice<'de>
 {
-    match read_bin_data(rd, len)? {
+    visit_str_data(read_bin_data(rd, len)?, visitor)
+}
+
+fn visit_str_data<'de, V>(buf: Reference<'de, '_, [u8]>, visitor: V) -> Result<V::Value, Error>
+    where V: Visitor<'de>
+{
+    match buf {
         Reference::Borrowed(buf) => {
-            match str::from_utf8(buf) {
+            match rmp::decode::from_utf8(buf) {
                 Ok(s) => visitor.visit_borrowed_str(s),
                 Err(err) => {
                     // Allow to unpack invalid UTF-8 bytes into a byte array.
@@ -364,7 +488,7 @@ fn read_str_data<'de, V, R>(rd: &mut R, len: u32, visitor: V) -> Result<V::Value
             }
         }
         Reference::Copied(buf) => {
-            match str::from_utf8(buf) {
+            match rmp::decode::from_utf8(buf) {
                 Ok(s) => visitor.visit_str(s),
                 Err(err) => {
                     // Allow to unpack invalid UTF-8 bytes into a byte array.
@@ -527,6 +651,8 @@ fn any_num<'de, R: ReadSlice<'de>, V: Visitor<'de>>(rd: &mut R, visitor: V, mark
 
 impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
     fn any_inner<V: Visitor<'de>>(&mut self, visitor: V, allow_bytes: bool) -> Result<V::Value, Error> {
+        let fields = mem::take(&mut self.fields);
+        let capture_key = mem::take(&mut self.capture_key);
         let marker = self.take_or_read_marker()?;
         match marker {
             Marker::Null |
@@ -552,6 +678,12 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
                     Marker::Str32 => read_u32(&mut self.rd),
                     _ => return Err(Error::TypeMismatch(Marker::Reserved)),
                 }?;
+                if capture_key {
+                    let buf = read_bin_data(&mut self.rd, len)?;
+                    let (Reference::Borrowed(bytes) | Reference::Copied(bytes)) = buf;
+                    self.key = Some(String::from_utf8_lossy(bytes).into_owned());
+                    return visit_str_data(buf, visitor);
+                }
                 read_str_data(&mut self.rd, len, visitor)
             }
             Marker::FixArray(_) |
@@ -563,9 +695,12 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
                     Marker::Array32 => read_u32(&mut self.rd)?,
                     _ => return Err(Error::TypeMismatch(Marker::Reserved)),
                 };
+                if len > self.max_seq_len {
+                    return Err(Error::LengthLimitExceeded { len, limit: self.max_seq_len });
+                }
 
                 depth_count!(self.depth, {
-                    let mut seq = SeqAccess::new(self, len);
+                    let mut seq = SeqAccess::new(self, len, fields);
                     let res = visitor.visit_seq(&mut seq)?;
                     match seq.left {
                         0 => Ok(res),
@@ -582,6 +717,9 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
                     Marker::Map32 => read_u32(&mut self.rd)?,
                     _ => return Err(Error::TypeMismatch(Marker::Reserved)),
                 };
+                if len > self.max_map_len {
+                    return Err(Error::LengthLimitExceeded { len, limit: self.max_map_len });
+                }
 
                 depth_count!(self.depth, {
                     let mut seq = MapAccess::new(self, len);
@@ -741,7 +879,8 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> for &
     }
 
     #[inline]
-    fn deserialize_struct<V>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+    fn deserialize_struct<V>(self, _: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        self.fields = fields;
         self.any_inner(visitor, false)
     }
 
@@ -815,12 +954,14 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> for &
 struct SeqAccess<'a, R, C> {
     de: &'a mut Deserializer<R, C>,
     left: u32,
+    index: u32,
+    fields: &'static [&'static str],
 }
 
 impl<'a, R: 'a, C> SeqAccess<'a, R, C> {
     #[inline]
-    const fn new(de: &'a mut Deserializer<R, C>, len: u32) -> Self {
-        SeqAccess { de, left: len }
+    const fn new(de: &'a mut Deserializer<R, C>, len: u32, fields: &'static [&'static str]) -> Self {
+        SeqAccess { de, left: len, index: 0, fields }
     }
 }
 
@@ -833,8 +974,18 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::SeqAccess<'de> fo
     {
         if self.left > 0 {
             self.left -= 1;
-            Ok(Some(seed.deserialize(&mut *self.de)?))
+            let index = self.index;
+            self.index += 1;
+            match seed.deserialize(&mut *self.de) {
+                Ok(val) => Ok(Some(val)),
+                Err(err) if self.de.track_path => {
+                    Err(err.at(self.fields.get(index as usize).copied(), index))
+                }
+                Err(err) => Err(err),
+            }
         } else {
+            // Running out of elements is not an error here: it lets `#[serde(default)]` fields
+            // be filled in when decoding arrays written by an older version of a struct.
             Ok(None)
         }
     }
@@ -848,12 +999,14 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::SeqAccess<'de> fo
 struct MapAccess<'a, R, C> {
     de: &'a mut Deserializer<R, C>,
     left: u32,
+    index: u32,
+    key: Option<String>,
 }
 
 impl<'a, R: 'a, C> MapAccess<'a, R, C> {
     #[inline]
     const fn new(de: &'a mut Deserializer<R, C>, len: u32) -> Self {
-        MapAccess { de, left: len }
+        MapAccess { de, left: len, index: 0, key: None }
     }
 }
 
@@ -866,7 +1019,20 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::MapAccess<'de> fo
     {
         if self.left > 0 {
             self.left -= 1;
-            seed.deserialize(&mut *self.de).map(Some)
+            if !self.de.track_path {
+                return seed.deserialize(&mut *self.de).map(Some);
+            }
+
+            self.de.capture_key = true;
+            self.de.key = None;
+            let res = seed.deserialize(&mut *self.de);
+            self.de.capture_key = false;
+            self.key = self.de.key.take();
+            self.index += 1;
+            match res {
+                Ok(key) => Ok(Some(key)),
+                Err(err) => Err(err.at(self.key.as_deref(), self.index - 1)),
+            }
         } else {
             Ok(None)
         }
@@ -876,7 +1042,11 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::MapAccess<'de> fo
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
         where V: DeserializeSeed<'de>
     {
-        seed.deserialize(&mut *self.de)
+        match seed.deserialize(&mut *self.de) {
+            Ok(val) => Ok(val),
+            Err(err) if self.de.track_path => Err(err.at(self.key.as_deref(), self.index - 1)),
+            Err(err) => Err(err),
+        }
     }
 
     #[inline(always)]
@@ -1004,7 +1174,7 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> de::VariantAccess<'de> for Var
     fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>
     {
-        de::Deserializer::deserialize_tuple(self.de, fields.len(), visitor)
+        de::Deserializer::deserialize_struct(self.de, "", fields, visitor)
     }
 }
 
@@ -281,6 +281,33 @@ impl<W: Write, C> Serializer<W, C> {
         self.config.bytes = mode;
         self
     }
+
+    /// Writes the entries of every map sorted by their serialized keys, compared bytewise.
+    ///
+    /// This makes the output deterministic for types with an unspecified iteration order, such
+    /// as `HashMap`, which is handy for tests and caching. Each map is buffered before being
+    /// written, so this is slower than the default streaming mode, but cheaper than converting
+    /// the whole structure into an `rmpv::Value` to canonicalize it.
+    ///
+    /// Structs serialized as maps keep their field order, which is already deterministic.
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use serde::ser::Serialize;
+    ///
+    /// let map: HashMap<&str, u8> = [("b", 2), ("a", 1)].into_iter().collect();
+    ///
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data).sort_map_keys(true);
+    /// map.serialize(&mut serializer).unwrap();
+    ///
+    /// assert_eq!(vec![0x82, 0xa1, b'a', 0x01, 0xa1, b'b', 0x02], msgpack_data);
+    /// ```
+    #[inline]
+    pub const fn sort_map_keys(mut self, sort: bool) -> Self {
+        self.config.sort_map_keys = sort;
+        self
+    }
 }
 
 impl<W: Write, C> UnderlyingWrite for Serializer<W, C> {
@@ -479,6 +506,8 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStructVariant for Compound
 struct UnknownLengthCompound {
     se: Serializer<Vec<u8>, DefaultConfig>,
     elem_count: u32,
+    /// Buffer ranges of the map keys, recorded only when the entries have to be sorted.
+    keys: Vec<(usize, usize)>,
 }
 
 impl<W, C: SerializerConfig> From<&Serializer<W, C>> for UnknownLengthCompound {
@@ -491,6 +520,7 @@ impl<W, C: SerializerConfig> From<&Serializer<W, C>> for UnknownLengthCompound {
                 _back_compat_config: PhantomData,
             },
             elem_count: 0,
+            keys: Vec::new(),
         }
     }
 }
@@ -577,7 +607,16 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeMap for MaybeUnknownLength
     type Error = Error;
 
     fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
-        <Self as SerializeSeq>::serialize_element(self, key)
+        match self.compound.as_mut() {
+            Some(buf) if buf.se.config.sort_map_keys => {
+                let start = buf.se.wr.len();
+                key.serialize(&mut buf.se)?;
+                buf.keys.push((start, buf.se.wr.len()));
+                buf.elem_count += 1;
+                Ok(())
+            }
+            _ => <Self as SerializeSeq>::serialize_element(self, key),
+        }
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
@@ -587,8 +626,24 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeMap for MaybeUnknownLength
     fn end(self) -> Result<Self::Ok, Self::Error> {
         if let Some(compound) = self.compound {
             encode::write_map_len(&mut self.se.wr, compound.elem_count / 2)?;
-            self.se.wr.write_all(&compound.se.into_inner())
-                .map_err(ValueWriteError::InvalidDataWrite)?;
+            let buf = compound.se.into_inner();
+            if compound.keys.is_empty() {
+                self.se.wr.write_all(&buf)
+                    .map_err(ValueWriteError::InvalidDataWrite)?;
+            } else {
+                // Each entry spans from the start of its key to the start of the next key.
+                let mut entries: Vec<(&[u8], &[u8])> = compound.keys.iter().enumerate()
+                    .map(|(idx, &(start, end))| {
+                        let next = compound.keys.get(idx + 1).map_or(buf.len(), |&(next, _)| next);
+                        (&buf[start..end], &buf[start..next])
+                    })
+                    .collect();
+                entries.sort_by_key(|&(key, _)| key);
+                for (_, entry) in entries {
+                    self.se.wr.write_all(entry)
+                        .map_err(ValueWriteError::InvalidDataWrite)?;
+                }
+            }
         }
         Ok(())
     }
@@ -770,6 +825,8 @@ where
 
     #[inline]
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        // Sorting requires buffering the entries, just like maps of an unknown length do.
+        let len = if self.config.sort_map_keys { None } else { len };
         self.maybe_unknown_len_compound(len.map(|len| len as u32), |wr, len| encode::write_map_len(wr, len))
     }
 
@@ -3,6 +3,7 @@
 #![warn(missing_debug_implementations, missing_docs)]
 
 use std::fmt::{self, Display, Formatter};
+use std::ops::{Deref, DerefMut};
 use std::str::{self, Utf8Error};
 
 use serde::de;
@@ -19,6 +20,7 @@ mod bytes;
 pub mod config;
 pub mod decode;
 pub mod encode;
+pub mod with;
 
 /// Hack used to serialize MessagePack Extension types.
 ///
@@ -332,3 +334,216 @@ impl<'de> Deserialize<'de> for RawRef<'de> {
         de.deserialize_any(RawRefVisitor)
     }
 }
+
+/// A borrowed byte slice that is always serialized using the MessagePack `bin` format family.
+///
+/// Serde serializes `&[u8]` as a sequence of integers by default. Wrapping it into `ByteSlice`
+/// makes it use `bin` instead, and can be used directly as a struct field type, without the
+/// `#[serde(with = "serde_bytes")]` attribute and without depending on `serde_bytes`.
+///
+/// Deserialization borrows from the input, so it only succeeds when decoding from a slice (e.g.
+/// with [`from_slice`]). Use [`ByteBuf`] when reading from a stream.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use rmp_serde::ByteSlice;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Message<'a> {
+///     #[serde(borrow)]
+///     payload: ByteSlice<'a>,
+/// }
+///
+/// let buf = rmp_serde::to_vec(&Message { payload: ByteSlice(&[1, 2, 3]) }).unwrap();
+/// assert_eq!(vec![0x91, 0xc4, 0x03, 0x01, 0x02, 0x03], buf);
+///
+/// let msg: Message<'_> = rmp_serde::from_slice(&buf).unwrap();
+/// assert_eq!(&[1, 2, 3], msg.payload.0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ByteSlice<'a>(pub &'a [u8]);
+
+impl Deref for ByteSlice<'_> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a> From<&'a [u8]> for ByteSlice<'a> {
+    #[inline]
+    fn from(v: &'a [u8]) -> Self {
+        Self(v)
+    }
+}
+
+impl Serialize for ByteSlice<'_> {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        se.serialize_bytes(self.0)
+    }
+}
+
+struct ByteSliceVisitor;
+
+impl<'de> de::Visitor<'de> for ByteSliceVisitor {
+    type Value = ByteSlice<'de>;
+
+    #[cold]
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        "borrowed bytes".fmt(fmt)
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+        where E: de::Error
+    {
+        Ok(ByteSlice(v))
+    }
+
+    #[inline]
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+        where E: de::Error
+    {
+        Ok(ByteSlice(v.as_bytes()))
+    }
+}
+
+impl<'a, 'de: 'a> Deserialize<'de> for ByteSlice<'a> {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_bytes(ByteSliceVisitor)
+    }
+}
+
+/// An owned byte buffer that is always serialized using the MessagePack `bin` format family.
+///
+/// This is the owned counterpart of [`ByteSlice`]. Unlike `Vec<u8>`, which Serde serializes as a
+/// sequence of integers, it is written as `bin` without any `#[serde(with)]` attribute.
+///
+/// Besides `bin`, it also accepts strings and arrays of integers when deserializing, so it can
+/// read data written before a field was switched from `Vec<u8>` to `ByteBuf`.
+///
+/// # Examples
+///
+/// ```
+/// use rmp_serde::ByteBuf;
+///
+/// let buf = rmp_serde::to_vec(&ByteBuf(vec![1, 2, 3])).unwrap();
+/// assert_eq!(vec![0xc4, 0x03, 0x01, 0x02, 0x03], buf);
+///
+/// // Also decodes from the array written for a plain `Vec<u8>`.
+/// let val: ByteBuf = rmp_serde::from_slice(&[0x93, 0x01, 0x02, 0x03]).unwrap();
+/// assert_eq!(ByteBuf(vec![1, 2, 3]), val);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ByteBuf(pub Vec<u8>);
+
+impl ByteBuf {
+    /// Consumes this buffer, returning the underlying vector.
+    #[inline]
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for ByteBuf {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl DerefMut for ByteBuf {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl From<Vec<u8>> for ByteBuf {
+    #[inline]
+    fn from(v: Vec<u8>) -> Self {
+        Self(v)
+    }
+}
+
+impl From<ByteBuf> for Vec<u8> {
+    #[inline]
+    fn from(v: ByteBuf) -> Self {
+        v.0
+    }
+}
+
+impl Serialize for ByteBuf {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        se.serialize_bytes(&self.0)
+    }
+}
+
+struct ByteBufVisitor;
+
+impl<'de> de::Visitor<'de> for ByteBufVisitor {
+    type Value = ByteBuf;
+
+    #[cold]
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        "bytes".fmt(fmt)
+    }
+
+    #[inline]
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+        where E: de::Error
+    {
+        Ok(ByteBuf(v.into()))
+    }
+
+    #[inline]
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+        where E: de::Error
+    {
+        Ok(ByteBuf(v))
+    }
+
+    #[inline]
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where E: de::Error
+    {
+        Ok(ByteBuf(v.as_bytes().into()))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where A: de::SeqAccess<'de>
+    {
+        let mut vec = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            vec.push(byte);
+        }
+        Ok(ByteBuf(vec))
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_byte_buf(ByteBufVisitor)
+    }
+}
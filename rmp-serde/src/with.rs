@@ -0,0 +1,55 @@
+//! Alternative representations of common types, for use with `#[serde(with = "...")]`.
+
+/// Serializes a [`Duration`](std::time::Duration) as a single `u64` of total nanoseconds.
+///
+/// This is a compact encoding that any MessagePack implementation can read, unlike the
+/// `(secs, nanos)` pair produced by serde by default. Durations longer than `u64::MAX`
+/// nanoseconds (about 584 years) fail to serialize.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Sample {
+///     #[serde(with = "rmp_serde::with::duration_nanos")]
+///     elapsed: Duration,
+/// }
+///
+/// let sample = Sample { elapsed: Duration::from_micros(1) };
+/// let buf = rmp_serde::to_vec(&sample).unwrap();
+///
+/// // A single-element array holding the integer 1000.
+/// assert_eq!(vec![0x91, 0xcd, 0x03, 0xe8], buf);
+/// assert_eq!(sample, rmp_serde::from_slice(&buf).unwrap());
+/// ```
+pub mod duration_nanos {
+    use std::time::Duration;
+
+    use serde::de::Deserialize;
+    use serde::ser::Error;
+    use serde::{Deserializer, Serializer};
+
+    /// Serializes the duration as its total number of nanoseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the duration does not fit into a `u64` of nanoseconds.
+    pub fn serialize<S>(val: &Duration, se: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        let nanos = u64::try_from(val.as_nanos())
+            .map_err(|_| S::Error::custom(format_args!("duration {val:?} overflows u64 nanoseconds")))?;
+        se.serialize_u64(nanos)
+    }
+
+    /// Deserializes a duration from its total number of nanoseconds.
+    pub fn deserialize<'de, D>(de: D) -> Result<Duration, D::Error>
+        where D: Deserializer<'de>
+    {
+        u64::deserialize(de).map(Duration::from_nanos)
+    }
+}
@@ -567,3 +567,45 @@ fn fail_depth_limit() {
         other => panic!("unexpected result: {other:?}"),
     }
 }
+
+#[test]
+fn fail_seq_len_limit() {
+    // An array32 claiming 1_000_000 elements, followed by no data at all.
+    let buf = [0xdd, 0x00, 0x0f, 0x42, 0x40];
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_max_seq_len(1024);
+
+    let res: Result<Vec<u8>, Error> = Deserialize::deserialize(&mut de);
+    match res.err().unwrap() {
+        ref err @ Error::LengthLimitExceeded { len: 1_000_000, limit: 1024 } => {
+            assert_eq!("container length 1000000 exceeds the limit of 1024", err.to_string());
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_seq_len_limit() {
+    let buf = [0x93, 0x01, 0x02, 0x03];
+    let mut de = Deserializer::from_read_ref(&buf[..]);
+    de.set_max_seq_len(3);
+
+    let actual: Vec<u8> = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(vec![1, 2, 3], actual);
+}
+
+#[test]
+fn fail_map_len_limit() {
+    use std::collections::HashMap;
+
+    // A map16 claiming 3 entries.
+    let buf = [0xde, 0x00, 0x03, 0x01, 0x01, 0x02, 0x02, 0x03, 0x03];
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_max_map_len(2);
+
+    let res: Result<HashMap<u8, u8>, Error> = Deserialize::deserialize(&mut de);
+    match res.err().unwrap() {
+        Error::LengthLimitExceeded { len: 3, limit: 2 } => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
@@ -66,6 +66,47 @@ fn pass_struct() {
     assert_eq!(Decoded { id: 42, value: 100500 }, actual);
 }
 
+#[test]
+fn pass_struct_with_default_field_from_short_array() {
+    // Encoded by an older version of the struct, which had only two fields.
+    let buf = [0x92, 0x2a, 0xce, 0x0, 0x1, 0x88, 0x94];
+    let cur = Cursor::new(&buf[..]);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Decoded {
+        id: u32,
+        value: u32,
+        #[serde(default)]
+        extra: Option<String>,
+    }
+
+    let mut de = Deserializer::new(cur);
+    let actual: Decoded = Deserialize::deserialize(&mut de).unwrap();
+
+    assert_eq!(Decoded { id: 42, value: 100500, extra: None }, actual);
+}
+
+#[test]
+fn fail_struct_without_default_field_from_short_array() {
+    let buf = [0x92, 0x2a, 0xce, 0x0, 0x1, 0x88, 0x94];
+    let cur = Cursor::new(&buf[..]);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Decoded {
+        id: u32,
+        value: u32,
+        extra: u32,
+    }
+
+    let mut de = Deserializer::new(cur);
+    let res: Result<Decoded, Error> = Deserialize::deserialize(&mut de);
+
+    match res.err() {
+        Some(Error::Syntax(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
 #[test]
 fn pass_struct_from_map() {
     #[derive(Debug, PartialEq, Deserialize)]
@@ -474,3 +515,75 @@ fn pass_from_ref() {
 
     assert_eq!(Dog { name: "Bobby", age: 8 }, rmp_serde::from_read_ref(&buf).unwrap());
 }
+
+#[test]
+fn fail_nested_struct_reports_path() {
+    #[derive(Debug, serde::Serialize)]
+    #[serde(untagged)]
+    enum BadName {
+        Str(String),
+        Int(u32),
+    }
+
+    #[derive(Debug, serde::Serialize)]
+    struct BadUser {
+        id: u32,
+        name: BadName,
+    }
+
+    #[derive(Debug, serde::Serialize)]
+    struct BadRoot {
+        users: Vec<BadUser>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct User {
+        id: u32,
+        name: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Root {
+        users: Vec<User>,
+    }
+
+    let root = BadRoot {
+        users: (0..5).map(|id| BadUser {
+            id,
+            name: if id == 3 { BadName::Int(id) } else { BadName::Str(id.to_string()) },
+        }).collect(),
+    };
+
+    for buf in [rmp_serde::to_vec(&root).unwrap(), rmp_serde::to_vec_named(&root).unwrap()] {
+        let mut de = Deserializer::new(&buf[..]);
+        de.set_track_path(true);
+
+        match Root::deserialize(&mut de).unwrap_err() {
+            Error::AtPath { ref path, ref err } => {
+                assert_eq!("users[3].name", path);
+                assert!(matches!(**err, Error::Syntax(..)));
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    // Without tracking, the error is returned as it is.
+    let buf = rmp_serde::to_vec(&root).unwrap();
+    assert!(matches!(rmp_serde::from_slice::<Root>(&buf).unwrap_err(), Error::Syntax(..)));
+}
+
+#[test]
+fn fail_map_value_reports_key_and_index_path() {
+    use std::collections::BTreeMap;
+
+    // {"a": [1, 2, "x"]}
+    let buf = [0x81, 0xa1, b'a', 0x93, 0x01, 0x02, 0xa1, b'x'];
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_track_path(true);
+
+    let err = BTreeMap::<String, Vec<u8>>::deserialize(&mut de).unwrap_err();
+    assert!(matches!(err, Error::AtPath { ref path, .. } if path == "a[2]"));
+    assert!(err.to_string().ends_with(" at a[2]"), "{err}");
+}
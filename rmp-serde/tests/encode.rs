@@ -300,6 +300,57 @@ fn pass_map() {
     assert_eq!(out, buf);
 }
 
+#[test]
+fn pass_map_sorted_keys() {
+    use std::collections::{BTreeMap, HashMap};
+
+    fn encode(val: &HashMap<String, HashMap<String, u32>>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        val.serialize(&mut Serializer::new(&mut buf).sort_map_keys(true)).unwrap();
+        buf
+    }
+
+    let keys = ["k0", "k1", "k2", "k3", "k4", "k5", "k6", "k7", "k8", "k9"];
+
+    // Each map gets its own random hasher, so the iteration orders differ.
+    let mut lhs = HashMap::new();
+    for (idx, key) in keys.iter().enumerate() {
+        let inner: HashMap<String, u32> = keys.iter().map(|k| (k.to_string(), idx as u32)).collect();
+        lhs.insert(key.to_string(), inner);
+    }
+    let mut rhs = HashMap::new();
+    for (idx, key) in keys.iter().enumerate().rev() {
+        let inner: HashMap<String, u32> = keys.iter().rev().map(|k| (k.to_string(), idx as u32)).collect();
+        rhs.insert(key.to_string(), inner);
+    }
+
+    assert_eq!(encode(&lhs), encode(&rhs));
+
+    // Keys of equal length sort the same way bytewise and lexicographically.
+    let sorted: BTreeMap<_, BTreeMap<_, _>> = lhs.into_iter().map(|(k, v)| (k, v.into_iter().collect())).collect();
+    assert_eq!(rmp_serde::to_vec(&sorted).unwrap(), encode(&rhs));
+}
+
+#[test]
+fn pass_map_sorted_keys_by_encoded_bytes() {
+    use std::collections::HashMap;
+
+    let mut val = HashMap::new();
+    val.insert(200u8, ());
+    val.insert(1u8, ());
+
+    let mut buf = Vec::new();
+    val.serialize(&mut Serializer::new(&mut buf).with_struct_map().sort_map_keys(true)).unwrap();
+
+    // 1 is encoded as a positive fixint, which sorts before the u8 marker of 200.
+    assert_eq!(vec![0x82, 0x01, 0xc0, 0xcc, 0xc8, 0xc0], buf);
+
+    let mut buf = Vec::new();
+    val.serialize(&mut Serializer::new(&mut buf).sort_map_keys(true).with_struct_map()).unwrap();
+
+    assert_eq!(vec![0x82, 0x01, 0xc0, 0xcc, 0xc8, 0xc0], buf);
+}
+
 #[test]
 fn pass_empty_map() {
     use std::collections::BTreeMap;
@@ -537,6 +537,83 @@ fn roundtrip_vec() {
     assert_roundtrips(vec![vec![Some(3u16), None, Some(10000)]]);
 }
 
+#[test]
+fn roundtrip_byte_buf() {
+    use rmp_serde::ByteBuf;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+        id: u32,
+        payload: ByteBuf,
+    }
+
+    assert_roundtrips(ByteBuf(vec![]));
+    assert_roundtrips(ByteBuf(vec![0u8, 1, 11, 111, 255]));
+    assert_roundtrips(vec![ByteBuf(vec![1]), ByteBuf(vec![2, 3])]);
+    assert_roundtrips(Message { id: 42, payload: ByteBuf(vec![0xde, 0xad]) });
+
+    // Always encoded as bin, even when every byte would fit into a positive fixint.
+    let buf = rmp_serde::to_vec(&Message { id: 42, payload: ByteBuf(vec![1, 2]) }).unwrap();
+    assert_eq!(vec![0x92, 0x2a, 0xc4, 0x02, 0x01, 0x02], buf);
+}
+
+#[test]
+fn roundtrip_duration_nanos() {
+    use std::time::Duration;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        #[serde(with = "rmp_serde::with::duration_nanos")]
+        elapsed: Duration,
+    }
+
+    let max = Duration::from_nanos(u64::MAX);
+    for elapsed in [
+        Duration::ZERO,
+        Duration::from_nanos(1),
+        Duration::new(0, 999_999_999),
+        Duration::new(1, 0),
+        Duration::new(1, 1),
+        // Not representable in nanoseconds, so truncated when constructed.
+        Duration::from_secs_f64(1e-10),
+        max - Duration::from_nanos(1),
+        max,
+    ] {
+        assert_roundtrips(Sample { elapsed });
+    }
+
+    let buf = rmp_serde::to_vec(&Sample { elapsed: max }).unwrap();
+    assert_eq!(vec![0x91, 0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], buf);
+
+    let err = rmp_serde::to_vec(&Sample { elapsed: max + Duration::from_nanos(1) }).unwrap_err();
+    assert!(matches!(err, rmp_serde::encode::Error::Syntax(..)), "{err}");
+    assert!(rmp_serde::to_vec(&Sample { elapsed: Duration::MAX }).is_err());
+}
+
+#[test]
+fn roundtrip_byte_slice() {
+    use rmp_serde::ByteSlice;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message<'a> {
+        id: u32,
+        #[serde(borrow)]
+        payload: ByteSlice<'a>,
+    }
+
+    let data = [0u8, 1, 11, 111, 255];
+    let val = Message { id: 42, payload: ByteSlice(&data) };
+    let buf = rmp_serde::to_vec(&val).unwrap();
+    assert_eq!(&[0x92, 0x2a, 0xc4, 0x05], &buf[..4]);
+
+    let decoded: Message<'_> = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(val, decoded);
+
+    // The same bytes decode into an owned buffer too.
+    let decoded: (u32, rmp_serde::ByteBuf) = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(&data[..], &decoded.1[..]);
+}
+
 #[test]
 fn roundtrip_hashsets() {
     use std::collections::HashSet;
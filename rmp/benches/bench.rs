@@ -42,3 +42,37 @@ fn from_string_read_str(b: &mut Bencher) {
         test::black_box(res);
     });
 }
+
+/// An array of 1000 small integers, read one by one.
+fn small_ints() -> Vec<u8> {
+    let mut buf = Vec::new();
+    rmp::encode::write_array_len(&mut buf, 1000).unwrap();
+    for idx in 0..1000u64 {
+        rmp::encode::write_uint(&mut buf, idx * 100).unwrap();
+    }
+    buf
+}
+
+#[bench]
+fn from_buf_reader_read_ints(b: &mut Bencher) {
+    let buf = small_ints();
+
+    b.iter(|| {
+        let mut rd = std::io::BufReader::new(&buf[..]);
+        for _ in 0..read_array_len(&mut rd).unwrap() {
+            test::black_box(read_int::<u64, _>(&mut rd).unwrap());
+        }
+    });
+}
+
+#[bench]
+fn from_buffered_read_ints(b: &mut Bencher) {
+    let buf = small_ints();
+
+    b.iter(|| {
+        let mut rd = Buffered::new(std::io::BufReader::new(&buf[..]));
+        for _ in 0..read_array_len(&mut rd).unwrap() {
+            test::black_box(read_int::<u64, _>(&mut rd).unwrap());
+        }
+    });
+}
@@ -0,0 +1,319 @@
+//! The asynchronous functions, generated for the traits of each supported runtime.
+
+/// Generates the asynchronous counterparts of the `encode` and `decode` functions.
+///
+/// The expanding module must have `io`, the `AsyncRead` and `AsyncWrite` traits, and the
+/// `read_exact` and `write_all` functions over them in scope.
+macro_rules! async_io {
+    () => {
+        use num_traits::cast::FromPrimitive;
+
+        use $crate::decode::{ExtMeta, MarkerReadError, NumValueReadError, ValueReadError};
+        use $crate::encode::ValueWriteError;
+        use $crate::Marker;
+
+        /// Attempts to read a single byte from the given reader and to decode it as a MessagePack
+        /// marker.
+        #[inline]
+        pub async fn read_marker<R>(rd: &mut R) -> Result<Marker, MarkerReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            let mut buf = [0];
+            read_exact(rd, &mut buf).await?;
+            Ok(Marker::from_u8(buf[0]))
+        }
+
+        /// Reads the `N` bytes of data following a marker.
+        #[inline]
+        async fn read_data<R, const N: usize>(rd: &mut R) -> Result<[u8; N], ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            let mut buf = [0; N];
+            read_exact(rd, &mut buf).await.map_err(ValueReadError::InvalidDataRead)?;
+            Ok(buf)
+        }
+
+        /// Asynchronous counterpart of [`read_nil`](crate::decode::read_nil).
+        pub async fn read_nil<R>(rd: &mut R) -> Result<(), ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            match read_marker(rd).await? {
+                Marker::Null => Ok(()),
+                marker => Err(ValueReadError::TypeMismatch(marker)),
+            }
+        }
+
+        /// Asynchronous counterpart of [`read_bool`](crate::decode::read_bool).
+        pub async fn read_bool<R>(rd: &mut R) -> Result<bool, ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            match read_marker(rd).await? {
+                Marker::True => Ok(true),
+                Marker::False => Ok(false),
+                marker => Err(ValueReadError::TypeMismatch(marker)),
+            }
+        }
+
+        /// Asynchronous counterpart of [`read_pfix`](crate::decode::read_pfix).
+        pub async fn read_pfix<R>(rd: &mut R) -> Result<u8, ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            match read_marker(rd).await? {
+                Marker::FixPos(val) => Ok(val),
+                marker => Err(ValueReadError::TypeMismatch(marker)),
+            }
+        }
+
+        /// Asynchronous counterpart of [`read_nfix`](crate::decode::read_nfix).
+        pub async fn read_nfix<R>(rd: &mut R) -> Result<i8, ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            match read_marker(rd).await? {
+                Marker::FixNeg(val) => Ok(val),
+                marker => Err(ValueReadError::TypeMismatch(marker)),
+            }
+        }
+
+        async_read_fixed! {
+            read_u8 => u8, U8;
+            read_u16 => u16, U16;
+            read_u32 => u32, U32;
+            read_u64 => u64, U64;
+            read_i8 => i8, I8;
+            read_i16 => i16, I16;
+            read_i32 => i32, I32;
+            read_i64 => i64, I64;
+            read_f32 => f32, F32;
+            read_f64 => f64, F64;
+        }
+
+        /// Asynchronous counterpart of [`read_int`](crate::decode::read_int).
+        pub async fn read_int<T, R>(rd: &mut R) -> Result<T, NumValueReadError<io::Error>>
+            where T: FromPrimitive,
+                  R: AsyncRead + Unpin
+        {
+            let val = match read_marker(rd).await? {
+                Marker::FixPos(val) => T::from_u8(val),
+                Marker::FixNeg(val) => T::from_i8(val),
+                Marker::U8 => T::from_u8(u8::from_be_bytes(read_data(rd).await?)),
+                Marker::U16 => T::from_u16(u16::from_be_bytes(read_data(rd).await?)),
+                Marker::U32 => T::from_u32(u32::from_be_bytes(read_data(rd).await?)),
+                Marker::U64 => T::from_u64(u64::from_be_bytes(read_data(rd).await?)),
+                Marker::I8 => T::from_i8(i8::from_be_bytes(read_data(rd).await?)),
+                Marker::I16 => T::from_i16(i16::from_be_bytes(read_data(rd).await?)),
+                Marker::I32 => T::from_i32(i32::from_be_bytes(read_data(rd).await?)),
+                Marker::I64 => T::from_i64(i64::from_be_bytes(read_data(rd).await?)),
+                marker => return Err(NumValueReadError::TypeMismatch(marker)),
+            };
+
+            val.ok_or(NumValueReadError::OutOfRange)
+        }
+
+        /// Asynchronous counterpart of [`read_str_len`](crate::decode::read_str_len).
+        pub async fn read_str_len<R>(rd: &mut R) -> Result<u32, ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            match read_marker(rd).await? {
+                Marker::FixStr(size) => Ok(u32::from(size)),
+                Marker::Str8 => Ok(u32::from(u8::from_be_bytes(read_data(rd).await?))),
+                Marker::Str16 => Ok(u32::from(u16::from_be_bytes(read_data(rd).await?))),
+                Marker::Str32 => Ok(u32::from_be_bytes(read_data(rd).await?)),
+                marker => Err(ValueReadError::TypeMismatch(marker)),
+            }
+        }
+
+        /// Asynchronous counterpart of [`read_bin_len`](crate::decode::read_bin_len).
+        pub async fn read_bin_len<R>(rd: &mut R) -> Result<u32, ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            match read_marker(rd).await? {
+                Marker::Bin8 => Ok(u32::from(u8::from_be_bytes(read_data(rd).await?))),
+                Marker::Bin16 => Ok(u32::from(u16::from_be_bytes(read_data(rd).await?))),
+                Marker::Bin32 => Ok(u32::from_be_bytes(read_data(rd).await?)),
+                marker => Err(ValueReadError::TypeMismatch(marker)),
+            }
+        }
+
+        /// Asynchronous counterpart of [`read_array_len`](crate::decode::read_array_len).
+        pub async fn read_array_len<R>(rd: &mut R) -> Result<u32, ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            match read_marker(rd).await? {
+                Marker::FixArray(size) => Ok(u32::from(size)),
+                Marker::Array16 => Ok(u32::from(u16::from_be_bytes(read_data(rd).await?))),
+                Marker::Array32 => Ok(u32::from_be_bytes(read_data(rd).await?)),
+                marker => Err(ValueReadError::TypeMismatch(marker)),
+            }
+        }
+
+        /// Asynchronous counterpart of [`read_map_len`](crate::decode::read_map_len).
+        pub async fn read_map_len<R>(rd: &mut R) -> Result<u32, ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            match read_marker(rd).await? {
+                Marker::FixMap(size) => Ok(u32::from(size)),
+                Marker::Map16 => Ok(u32::from(u16::from_be_bytes(read_data(rd).await?))),
+                Marker::Map32 => Ok(u32::from_be_bytes(read_data(rd).await?)),
+                marker => Err(ValueReadError::TypeMismatch(marker)),
+            }
+        }
+
+        /// Asynchronous counterpart of [`read_ext_meta`](crate::decode::read_ext_meta).
+        pub async fn read_ext_meta<R>(rd: &mut R) -> Result<ExtMeta, ValueReadError<io::Error>>
+            where R: AsyncRead + Unpin
+        {
+            let size = match read_marker(rd).await? {
+                Marker::FixExt1 => 1,
+                Marker::FixExt2 => 2,
+                Marker::FixExt4 => 4,
+                Marker::FixExt8 => 8,
+                Marker::FixExt16 => 16,
+                Marker::Ext8 => u32::from(u8::from_be_bytes(read_data(rd).await?)),
+                Marker::Ext16 => u32::from(u16::from_be_bytes(read_data(rd).await?)),
+                Marker::Ext32 => u32::from_be_bytes(read_data(rd).await?),
+                marker => return Err(ValueReadError::TypeMismatch(marker)),
+            };
+            let typeid = i8::from_be_bytes(read_data(rd).await?);
+
+            Ok(ExtMeta { typeid, size })
+        }
+
+        /// Encodes a marker and its data with the synchronous `encode` into a buffer on the stack, then
+        /// writes them into the given writer.
+        async fn write_header<W, T, F>(wr: &mut W, encode: F) -> Result<T, ValueWriteError<io::Error>>
+            where W: AsyncWrite + Unpin,
+                  F: FnOnce(&mut &mut [u8]) -> Result<T, ValueWriteError<io::Error>>
+        {
+            // A marker is followed by at most 8 bytes of data.
+            const MAX_LEN: usize = 9;
+
+            let mut buf = [0; MAX_LEN];
+            let mut rest = &mut buf[..];
+            let ret = encode(&mut rest).expect("MessagePack headers take at most 9 bytes");
+            let len = MAX_LEN - rest.len();
+
+            write_all(wr, &buf[..1]).await.map_err(ValueWriteError::InvalidMarkerWrite)?;
+            write_all(wr, &buf[1..len]).await.map_err(ValueWriteError::InvalidDataWrite)?;
+            Ok(ret)
+        }
+
+        /// Asynchronous counterpart of [`write_nil`](crate::encode::write_nil).
+        #[inline]
+        pub async fn write_nil<W>(wr: &mut W) -> io::Result<()>
+            where W: AsyncWrite + Unpin
+        {
+            write_all(wr, &[Marker::Null.to_u8()]).await
+        }
+
+        /// Asynchronous counterpart of [`write_bool`](crate::encode::write_bool).
+        #[inline]
+        pub async fn write_bool<W>(wr: &mut W, val: bool) -> io::Result<()>
+            where W: AsyncWrite + Unpin
+        {
+            let marker = if val { Marker::True } else { Marker::False };
+
+            write_all(wr, &[marker.to_u8()]).await
+        }
+
+        /// Asynchronous counterpart of [`write_pfix`](crate::encode::write_pfix).
+        ///
+        /// # Panics
+        ///
+        /// Panics if `val` is greater than 127.
+        #[inline]
+        pub async fn write_pfix<W>(wr: &mut W, val: u8) -> io::Result<()>
+            where W: AsyncWrite + Unpin
+        {
+            assert!(val < 128);
+            write_all(wr, &[Marker::FixPos(val).to_u8()]).await
+        }
+
+        /// Asynchronous counterpart of [`write_nfix`](crate::encode::write_nfix).
+        ///
+        /// # Panics
+        ///
+        /// Panics if `val` does not fit in `[-32; 0)` range.
+        #[inline]
+        pub async fn write_nfix<W>(wr: &mut W, val: i8) -> io::Result<()>
+            where W: AsyncWrite + Unpin
+        {
+            assert!((-32..0).contains(&val));
+            write_all(wr, &[Marker::FixNeg(val).to_u8()]).await
+        }
+
+        async_write_header! {
+            write_u8(u8) -> ();
+            write_u16(u16) -> ();
+            write_u32(u32) -> ();
+            write_u64(u64) -> ();
+            write_uint(u64) -> Marker;
+            write_i8(i8) -> ();
+            write_i16(i16) -> ();
+            write_i32(i32) -> ();
+            write_i64(i64) -> ();
+            write_sint(i64) -> Marker;
+            write_f32(f32) -> ();
+            write_f64(f64) -> ();
+            write_str_len(u32) -> Marker;
+            write_bin_len(u32) -> Marker;
+            write_array_len(u32) -> Marker;
+            write_map_len(u32) -> Marker;
+        }
+
+        /// Asynchronous counterpart of [`write_str`](crate::encode::write_str).
+        pub async fn write_str<W>(wr: &mut W, data: &str) -> Result<(), ValueWriteError<io::Error>>
+            where W: AsyncWrite + Unpin
+        {
+            write_str_len(wr, data.len() as u32).await?;
+            write_all(wr, data.as_bytes()).await.map_err(ValueWriteError::InvalidDataWrite)
+        }
+
+        /// Asynchronous counterpart of [`write_bin`](crate::encode::write_bin).
+        pub async fn write_bin<W>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError<io::Error>>
+            where W: AsyncWrite + Unpin
+        {
+            write_bin_len(wr, data.len() as u32).await?;
+            write_all(wr, data).await.map_err(ValueWriteError::InvalidDataWrite)
+        }
+
+        /// Asynchronous counterpart of [`write_ext_meta`](crate::encode::write_ext_meta).
+        #[inline]
+        pub async fn write_ext_meta<W>(wr: &mut W, len: u32, ty: i8) -> Result<Marker, ValueWriteError<io::Error>>
+            where W: AsyncWrite + Unpin
+        {
+            write_header(wr, |buf| $crate::encode::write_ext_meta(buf, len, ty)).await
+        }
+    };
+}
+
+/// Generates the functions reading a marker followed by a fixed-size number, for `async_io`.
+macro_rules! async_read_fixed {
+    ($($name:ident => $ty:ident, $marker:ident;)*) => {
+        $(
+            #[doc = concat!("Asynchronous counterpart of [`", stringify!($name), "`](crate::decode::", stringify!($name), ").")]
+            pub async fn $name<R>(rd: &mut R) -> Result<$ty, ValueReadError<io::Error>>
+                where R: AsyncRead + Unpin
+            {
+                match read_marker(rd).await? {
+                    Marker::$marker => Ok($ty::from_be_bytes(read_data(rd).await?)),
+                    marker => Err(ValueReadError::TypeMismatch(marker)),
+                }
+            }
+        )*
+    };
+}
+
+/// Generates the functions writing a header with their synchronous counterpart, for `async_io`.
+macro_rules! async_write_header {
+    ($($name:ident($ty:ty) -> $ret:ty;)*) => {
+        $(
+            #[doc = concat!("Asynchronous counterpart of [`", stringify!($name), "`](crate::encode::", stringify!($name), ").")]
+            #[inline]
+            pub async fn $name<W>(wr: &mut W, val: $ty) -> Result<$ret, ValueWriteError<io::Error>>
+                where W: AsyncWrite + Unpin
+            {
+                write_header(wr, |buf| $crate::encode::$name(buf, val)).await
+            }
+        )*
+    };
+}
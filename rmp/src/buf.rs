@@ -0,0 +1,153 @@
+//! Adapters reading from and writing to the buffers of the `bytes` crate, enabled by the `bytes`
+//! feature.
+//!
+//! [`BufReader`] reads from any [`Buf`] and [`BufMutWriter`] writes into any [`BufMut`], the same
+//! way with and without the `std` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use bytes::BytesMut;
+//! use rmp::buf::{BufMutWriter, BufReader};
+//!
+//! let mut wr = BufMutWriter::new(BytesMut::new());
+//! rmp::encode::write_array_len(&mut wr, 2).unwrap();
+//! rmp::encode::write_uint(&mut wr, 300).unwrap();
+//! rmp::encode::write_str(&mut wr, "le message").unwrap();
+//!
+//! let mut rd = BufReader::new(wr.into_inner().freeze());
+//! assert_eq!(2, rmp::decode::read_array_len(&mut rd).unwrap());
+//! assert_eq!(300u16, rmp::decode::read_int(&mut rd).unwrap());
+//! assert_eq!(10, rmp::decode::read_str_len(&mut rd).unwrap());
+//! assert_eq!(&b"le message"[..], &rd.get_ref()[..]);
+//! ```
+
+use bytes::{Buf, BufMut};
+
+use crate::decode::bytes::BytesReadError;
+use crate::decode::RmpRead;
+use crate::encode::{BufferFull, RmpWrite};
+
+/// A reader consuming the bytes of a [`Buf`], such as `Bytes`, `BytesMut` or a chain of buffers.
+///
+/// Errors report the number of bytes consumed so far as their position.
+#[derive(Debug, Clone, Default)]
+pub struct BufReader<B> {
+    buf: B,
+    position: u64,
+}
+
+impl<B> BufReader<B> {
+    /// Creates a reader starting at the current position of `buf`.
+    #[inline]
+    #[must_use]
+    pub const fn new(buf: B) -> Self {
+        Self { buf, position: 0 }
+    }
+
+    /// Returns the number of bytes consumed so far.
+    #[inline]
+    #[must_use]
+    pub const fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns a reference to the buffer, positioned after the bytes consumed so far.
+    #[inline]
+    pub const fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    /// Returns a mutable reference to the buffer.
+    ///
+    /// Bytes consumed through it are not counted by [`position`](Self::position).
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.buf
+    }
+
+    /// Returns the buffer, positioned after the bytes consumed so far.
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl<B: Buf> RmpRead for BufReader<B> {
+    type Error = BytesReadError;
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8, Self::Error> {
+        if !self.buf.has_remaining() {
+            return Err(BytesReadError::InsufficientBytes { expected: 1, actual: 0, position: self.position });
+        }
+        self.position += 1;
+        Ok(self.buf.get_u8())
+    }
+
+    #[inline]
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let remaining = self.buf.remaining();
+        if remaining < buf.len() {
+            return Err(BytesReadError::InsufficientBytes { expected: buf.len(), actual: remaining, position: self.position });
+        }
+        self.buf.copy_to_slice(buf);
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+}
+
+/// A writer appending to a [`BufMut`], such as `BytesMut` or `Vec<u8>`.
+///
+/// Writing past the remaining capacity of the buffer fails with [`BufferFull`], without writing
+/// anything.
+#[derive(Debug, Clone, Default)]
+pub struct BufMutWriter<B> {
+    buf: B,
+}
+
+impl<B> BufMutWriter<B> {
+    /// Creates a writer appending to `buf`.
+    #[inline]
+    #[must_use]
+    pub const fn new(buf: B) -> Self {
+        Self { buf }
+    }
+
+    /// Returns a reference to the buffer.
+    #[inline]
+    pub const fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    /// Returns a mutable reference to the buffer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.buf
+    }
+
+    /// Returns the buffer.
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl<B: BufMut> RmpWrite for BufMutWriter<B> {
+    type Error = BufferFull;
+
+    #[inline]
+    fn write_u8(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_bytes(&[val])
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        let remaining = self.buf.remaining_mut();
+        if remaining < buf.len() {
+            return Err(BufferFull { needed: buf.len() - remaining });
+        }
+        self.buf.put_slice(buf);
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+//! Decoding from buffered readers, see [`Buffered`].
+
+use std::io::{self, BufRead, ErrorKind};
+
+use super::slice::{read_item, Item, SliceReadError};
+use super::RmpRead;
+
+/// A reader which decodes directly from the internal buffer of a [`BufRead`].
+///
+/// [`read_item_with`](Self::read_item_with) parses a whole item, its marker, length and payload,
+/// from the slice returned by [`fill_buf`](BufRead::fill_buf), and lends the payload of strings,
+/// binaries and extensions without copying it. Only items that span the end of that slice are
+/// assembled in a scratch buffer first, which is kept for the next ones.
+///
+/// It can also be used with all the decode functions, like any other reader, reading from the
+/// buffer of the underlying reader without going through [`io::Read`].
+///
+/// # Examples
+///
+/// ```
+/// use std::io::BufReader;
+///
+/// use rmp::decode::{read_array_len, read_int, Buffered};
+///
+/// let buf = [0x92, 0xcd, 0x01, 0x2c, 0x2a];
+/// let mut rd = Buffered::new(BufReader::new(&buf[..]));
+///
+/// assert_eq!(2, read_array_len(&mut rd).unwrap());
+/// assert_eq!(300u16, read_int(&mut rd).unwrap());
+/// assert_eq!(42u8, read_int(&mut rd).unwrap());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Buffered<R> {
+    rd: R,
+    scratch: Vec<u8>,
+}
+
+impl<R: BufRead> Buffered<R> {
+    /// Wraps a buffered reader.
+    #[inline]
+    pub const fn new(rd: R) -> Self {
+        Self { rd, scratch: Vec::new() }
+    }
+
+    /// Gets a reference to the underlying reader.
+    #[inline]
+    pub const fn get_ref(&self) -> &R {
+        &self.rd
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Consuming from it directly, for example to skip the data of a string after its length, is
+    /// fine between two decode calls.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.rd
+    }
+
+    /// Unwraps the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.rd
+    }
+
+    /// Reads the next item and passes it to `f`, returning what `f` returns.
+    ///
+    /// The payload of the item borrows the internal buffer of the underlying reader when the whole
+    /// item is buffered, which is usually the case for items smaller than its capacity, and
+    /// the scratch buffer of `Buffered` otherwise. Arrays and maps only read their header, their
+    /// elements being the next items.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error of the underlying reader, except the EINTR, which is handled
+    /// internally, `UnexpectedEof` if it ends before the item does, and `InvalidData` wrapping
+    /// [`SliceReadError::TypeMismatch`] for the reserved marker `0xc1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::BufReader;
+    ///
+    /// use rmp::decode::slice::Item;
+    /// use rmp::decode::Buffered;
+    ///
+    /// // ["le message", 42]
+    /// let buf = [0x92, 0xaa, 0x6c, 0x65, 0x20, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65, 0x2a];
+    /// let mut rd = Buffered::new(BufReader::new(&buf[..]));
+    ///
+    /// assert!(rd.read_item_with(|item| item == Item::Array(2)).unwrap());
+    /// assert_eq!(10, rd.read_item_with(|item| item.as_str().unwrap().len()).unwrap());
+    /// assert!(rd.read_item_with(|item| item == Item::Uint(42)).unwrap());
+    /// ```
+    pub fn read_item_with<F, T>(&mut self, f: F) -> io::Result<T>
+        where F: FnOnce(Item<'_>) -> T
+    {
+        let mut needed = {
+            let available = fill_buf(&mut self.rd)?;
+            let mut rd = available;
+            match read_item(&mut rd) {
+                Ok(item) => {
+                    let len = available.len() - rd.len();
+                    let val = f(item);
+                    self.rd.consume(len);
+                    return Ok(val);
+                }
+                Err(SliceReadError::InsufficientBytes(..)) if available.is_empty() => {
+                    return Err(ErrorKind::UnexpectedEof.into());
+                }
+                Err(SliceReadError::InsufficientBytes(..)) => available.len(),
+                Err(err) => return Err(io::Error::new(ErrorKind::InvalidData, err)),
+            }
+        };
+
+        // The item spans the end of the buffer, so it is copied as it arrives, the length being
+        // only trusted as far as the data goes.
+        self.scratch.clear();
+        loop {
+            while needed > 0 {
+                let available = fill_buf(&mut self.rd)?;
+                if available.is_empty() {
+                    return Err(ErrorKind::UnexpectedEof.into());
+                }
+                let len = available.len().min(needed);
+                self.scratch.extend_from_slice(&available[..len]);
+                self.rd.consume(len);
+                needed -= len;
+            }
+            match read_item(&mut &self.scratch[..]) {
+                Ok(item) => return Ok(f(item)),
+                Err(SliceReadError::InsufficientBytes(more)) => needed = more,
+                Err(err) => return Err(io::Error::new(ErrorKind::InvalidData, err)),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> RmpRead for Buffered<R> {
+    type Error = io::Error;
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8, Self::Error> {
+        let byte = match fill_buf(&mut self.rd)?.first() {
+            Some(&byte) => byte,
+            None => return Err(ErrorKind::UnexpectedEof.into()),
+        };
+        self.rd.consume(1);
+        Ok(byte)
+    }
+
+    #[inline]
+    fn read_exact_buf(&mut self, mut buf: &mut [u8]) -> Result<(), Self::Error> {
+        while !buf.is_empty() {
+            let available = fill_buf(&mut self.rd)?;
+            if available.is_empty() {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            self.rd.consume(len);
+            buf = &mut buf[len..];
+        }
+        Ok(())
+    }
+}
+
+/// Returns the buffered data, filling it if empty, retrying on EINTR.
+#[inline]
+fn fill_buf<R: BufRead>(rd: &mut R) -> io::Result<&[u8]> {
+    loop {
+        match rd.fill_buf() {
+            Ok(..) => break,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    // Only returns what the successful call above buffered, which can not be returned from
+    // within the loop because of the borrow checker.
+    rd.fill_buf()
+}
@@ -1,4 +1,5 @@
-use std::num::{NonZeroU32, NonZeroUsize};
+use alloc::vec::Vec;
+use core::num::{NonZeroU32, NonZeroUsize};
 use crate::Marker;
 
 /// Incremental MessagePack parser that can parse incomplete messages,
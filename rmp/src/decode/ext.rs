@@ -1,4 +1,10 @@
-use super::{read_marker, RmpRead, ValueReadError};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::error;
+
+use super::{read_marker, RmpRead, RmpReadErr, ValueReadError};
 use crate::Marker;
 
 /// Attempts to read exactly 3 bytes from the given reader and interpret them as a fixext1 type
@@ -166,3 +172,143 @@ pub fn read_ext_meta<R: RmpRead>(rd: &mut R) -> Result<ExtMeta, ValueReadError<R
 
     Ok(meta)
 }
+
+/// Attempts to read an extension of any length, returning its type and a copy of its data.
+///
+/// The data is read in chunks, so a length announced by untrusted input does not allocate more
+/// than the input actually holds. To borrow the data from a byte slice instead, see
+/// [`slice::read_ext_ref`](super::slice::read_ext_ref).
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It also returns `ValueReadError::TypeMismatch` if the actual type is not an extension.
+///
+/// # Examples
+///
+/// ```
+/// let buf = [0xc7, 0x03, 0x07, 0x01, 0x02, 0x03];
+///
+/// assert_eq!((7, vec![1, 2, 3]), rmp::decode::read_ext(&mut &buf[..]).unwrap());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn read_ext<R: RmpRead>(rd: &mut R) -> Result<(i8, Vec<u8>), ValueReadError<R::Error>> {
+    const CHUNK: usize = 64 * 1024;
+
+    let ExtMeta { typeid, size } = read_ext_meta(rd)?;
+
+    let mut remaining = size as usize;
+    let mut data = Vec::new();
+    while remaining > 0 {
+        let start = data.len();
+        let len = remaining.min(CHUNK);
+        data.resize(start + len, 0);
+        rd.read_exact_buf(&mut data[start..]).map_err(ValueReadError::InvalidDataRead)?;
+        remaining -= len;
+    }
+    Ok((typeid, data))
+}
+
+/// An error that can occur when attempting to read a timestamp.
+#[derive(Debug)]
+#[non_exhaustive]
+#[allow(deprecated)] // Only for consistency with the other errors
+pub enum TimestampReadError<E: RmpReadErr = super::Error> {
+    /// Failed to read the marker.
+    InvalidMarkerRead(E),
+    /// Failed to read the extension type or data.
+    InvalidDataRead(E),
+    /// The marker is not one of an extension.
+    TypeMismatch(Marker),
+    /// The extension is not a timestamp, indicating the actual extension type.
+    ExtTypeMismatch(i8),
+    /// The timestamp data is not 4, 8 or 12 bytes long.
+    InvalidLength(u32),
+    /// The nanoseconds are not less than 1 000 000 000.
+    InvalidNanos(u32),
+}
+
+#[cfg(feature = "std")]
+impl<E: RmpReadErr> error::Error for TimestampReadError<E> {
+    #[cold]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            TimestampReadError::InvalidMarkerRead(ref err) |
+            TimestampReadError::InvalidDataRead(ref err) => Some(err),
+            TimestampReadError::TypeMismatch(..) |
+            TimestampReadError::ExtTypeMismatch(..) |
+            TimestampReadError::InvalidLength(..) |
+            TimestampReadError::InvalidNanos(..) => None,
+        }
+    }
+}
+
+impl<E: RmpReadErr> Display for TimestampReadError<E> {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            TimestampReadError::InvalidMarkerRead(ref err) => write!(f, "IO error while reading marker: {err}"),
+            TimestampReadError::InvalidDataRead(ref err) => write!(f, "IO error while reading data: {err}"),
+            TimestampReadError::TypeMismatch(ref marker) => write!(f, "wrong msgpack marker {marker:?}"),
+            TimestampReadError::ExtTypeMismatch(ty) => write!(f, "expected timestamp extension -1, got {ty}"),
+            TimestampReadError::InvalidLength(len) => write!(f, "invalid timestamp length {len}"),
+            TimestampReadError::InvalidNanos(nanos) => write!(f, "timestamp nanoseconds out of range: {nanos}"),
+        }
+    }
+}
+
+impl<E: RmpReadErr> From<ValueReadError<E>> for TimestampReadError<E> {
+    #[cold]
+    fn from(err: ValueReadError<E>) -> Self {
+        match err {
+            ValueReadError::InvalidMarkerRead(err) => TimestampReadError::InvalidMarkerRead(err),
+            ValueReadError::InvalidDataRead(err) => TimestampReadError::InvalidDataRead(err),
+            ValueReadError::TypeMismatch(marker) => TimestampReadError::TypeMismatch(marker),
+        }
+    }
+}
+
+/// Attempts to read a timestamp, the extension type `-1`, in any of its 32, 64 and 96-bit forms.
+///
+/// On success returns the number of seconds since the Unix epoch, which may be negative, and the
+/// number of nanoseconds within that second.
+///
+/// # Errors
+///
+/// This function will return `TimestampReadError` on any I/O error while reading either the marker
+/// or the data, if the value is not an extension of type `-1`, if its length is not 4, 8 or 12
+/// bytes, or if the nanoseconds are not less than 1 000 000 000.
+///
+/// # Examples
+///
+/// ```
+/// let buf = [0xd7, 0xff, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01];
+///
+/// assert_eq!((1, 1), rmp::decode::read_timestamp(&mut &buf[..]).unwrap());
+/// ```
+pub fn read_timestamp<R: RmpRead>(rd: &mut R) -> Result<(i64, u32), TimestampReadError<R::Error>> {
+    let meta = read_ext_meta(rd)?;
+    if meta.typeid != -1 {
+        return Err(TimestampReadError::ExtTypeMismatch(meta.typeid));
+    }
+
+    let (secs, nanos) = match meta.size {
+        4 => (i64::from(rd.read_data_u32()?), 0),
+        8 => {
+            let data = rd.read_data_u64()?;
+            ((data & 0x0000_0003_ffff_ffff) as i64, (data >> 34) as u32)
+        }
+        12 => {
+            let nanos = rd.read_data_u32()?;
+            (rd.read_data_i64()?, nanos)
+        }
+        len => return Err(TimestampReadError::InvalidLength(len)),
+    };
+
+    if nanos >= 1_000_000_000 {
+        return Err(TimestampReadError::InvalidNanos(nanos));
+    }
+    Ok((secs, nanos))
+}
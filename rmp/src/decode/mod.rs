@@ -9,26 +9,46 @@
 //! non-blocking socket and it returns EWOULDBLOCK) be sure that you buffer the data externally
 //! to avoid data loss (using `BufRead` readers with manual consuming or some other way).
 
+#[cfg(feature = "std")]
+mod buffered;
 mod dec;
 mod ext;
+mod offset;
 mod sint;
+mod skip;
 mod str;
 mod uint;
+mod validate;
 
-#[cfg(feature = "std")]
+pub mod raw;
+pub mod slice;
+#[cfg(feature = "alloc")]
+pub mod token;
+
+#[cfg(feature = "alloc")]
 mod est;
-#[cfg(feature = "std")]
+#[cfg(feature = "alloc")]
 pub use est::{MessageLen, LenError};
 
+#[cfg(feature = "std")]
+pub use self::buffered::Buffered;
 pub use self::dec::{read_f32, read_f64};
+#[cfg(feature = "alloc")]
+pub use self::ext::read_ext;
 pub use self::ext::{
-    read_ext_meta, read_fixext1, read_fixext16, read_fixext2, read_fixext4, read_fixext8, ExtMeta,
+    read_ext_meta, read_fixext1, read_fixext16, read_fixext2, read_fixext4, read_fixext8, read_timestamp, ExtMeta,
+    TimestampReadError,
 };
+pub use self::offset::{ErrorAt, OffsetReader};
 pub use self::sint::{read_i16, read_i32, read_i64, read_i8, read_nfix};
+pub use self::skip::{skip_value, skip_value_with};
 #[allow(deprecated)]
 // While we re-export deprecated items, we don't want to trigger warnings while compiling this crate
-pub use self::str::{read_str, read_str_from_slice, read_str_len, read_str_ref, DecodeStringError};
+pub use self::str::{from_utf8, read_str, read_str_from_slice, read_str_len, read_str_ref, DecodeStringError};
+#[cfg(feature = "alloc")]
+pub use self::str::string_from_utf8;
 pub use self::uint::{read_pfix, read_u16, read_u32, read_u64, read_u8};
+pub use self::validate::{validate, ValidateError, Validator};
 
 use core::fmt::{self, Debug, Display, Formatter};
 #[cfg(feature = "std")]
@@ -76,6 +96,11 @@ mod sealed {
     #[cfg(not(feature = "std"))]
     impl<'a> Sealed for &'a [u8] {}
     impl Sealed for super::Bytes<'_> {}
+    impl<R: super::RmpRead> Sealed for super::OffsetReader<R> {}
+    #[cfg(feature = "std")]
+    impl<R: std::io::BufRead> Sealed for super::Buffered<R> {}
+    #[cfg(feature = "bytes")]
+    impl<B: bytes::Buf> Sealed for crate::buf::BufReader<B> {}
 }
 
 /// A type that `rmp` supports reading from.
@@ -247,6 +272,52 @@ pub fn read_marker<R: RmpRead>(rd: &mut R) -> Result<Marker, MarkerReadError<R::
     Ok(Marker::from_u8(rd.read_u8()?))
 }
 
+/// Attempts to decode the next byte of the given buffered reader as a MessagePack marker, without
+/// consuming it.
+///
+/// This allows to branch on the type of the next value, for example to accept either string or
+/// integer keys, and then read it with the matching function. For byte slices, see
+/// [`slice::peek_marker`].
+///
+/// # Errors
+///
+/// This function will return `MarkerReadError` on any I/O error while filling the buffer, except
+/// the EINTR, which is handled internally, and with `UnexpectedEof` if the reader has ended.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{peek_marker, read_int, read_str_len};
+/// use rmp::Marker;
+///
+/// let buf = [0x2a];
+/// let mut rd = &buf[..];
+///
+/// match peek_marker(&mut rd).unwrap() {
+///     Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+///         read_str_len(&mut rd).unwrap();
+///     }
+///     _ => assert_eq!(42, read_int::<u32, _>(&mut rd).unwrap()),
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn peek_marker<R>(rd: &mut R) -> Result<Marker, MarkerReadError<std::io::Error>>
+    where R: std::io::BufRead + ?Sized
+{
+    loop {
+        match rd.fill_buf() {
+            Ok(buf) => {
+                return match buf.first() {
+                    Some(&byte) => Ok(Marker::from_u8(byte)),
+                    None => Err(MarkerReadError(std::io::ErrorKind::UnexpectedEof.into())),
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(MarkerReadError(err)),
+        }
+    }
+}
+
 /// Attempts to read a single byte from the given reader and to decode it as a nil value.
 ///
 /// According to the MessagePack specification, a nil value is represented as a single `0xc0` byte.
@@ -0,0 +1,116 @@
+//! Locating decode errors in the input, see [`OffsetReader`] and [`ErrorAt`].
+
+use core::fmt::{self, Display, Formatter};
+
+use super::RmpRead;
+
+/// An error along with the byte offset, from the start of the input, of the value whose decoding
+/// failed.
+///
+/// Returned by [`OffsetReader::read_at`] and [`SliceReadError::at`](super::slice::SliceReadError::at).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorAt<E> {
+    /// The offset of the value whose decoding failed.
+    pub offset: u64,
+    /// The error.
+    pub error: E,
+}
+
+impl<E: Display> Display for ErrorAt<E> {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.error, self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error> std::error::Error for ErrorAt<E> {
+    #[cold]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// A reader which counts the bytes read through it, so that decode errors can be located.
+///
+/// It can be used with all the decode functions, like the reader it wraps, and
+/// [`read_at`](Self::read_at) attaches the offset to their errors.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_int, read_nil, OffsetReader};
+///
+/// let buf = [0xc0, 0xcd, 0x01, 0x2c, 0xc0, 0xa2];
+/// let mut rd = OffsetReader::new(&buf[..]);
+///
+/// rd.read_at(read_nil).unwrap();
+/// assert_eq!(300u16, rd.read_at(read_int).unwrap());
+/// assert_eq!(4, rd.offset());
+///
+/// let err = rd.read_at(read_int::<u8, _>).unwrap_err();
+/// assert_eq!(4, err.offset);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OffsetReader<R> {
+    rd: R,
+    offset: u64,
+}
+
+impl<R: RmpRead> OffsetReader<R> {
+    /// Wraps a reader, counting from zero.
+    #[inline]
+    pub const fn new(rd: R) -> Self {
+        Self { rd, offset: 0 }
+    }
+
+    /// Returns the number of bytes read so far.
+    #[inline]
+    #[must_use]
+    pub const fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Gets a reference to the underlying reader.
+    #[inline]
+    pub const fn get_ref(&self) -> &R {
+        &self.rd
+    }
+
+    /// Unwraps the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.rd
+    }
+
+    /// Decodes a value with `f`, attaching the offset at which the value starts to its error.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `f`, located at the offset this call started at.
+    #[inline]
+    pub fn read_at<T, E, F>(&mut self, f: F) -> Result<T, ErrorAt<E>>
+        where F: FnOnce(&mut Self) -> Result<T, E>
+    {
+        let offset = self.offset;
+        f(self).map_err(|error| ErrorAt { offset, error })
+    }
+}
+
+impl<R: RmpRead> RmpRead for OffsetReader<R> {
+    type Error = R::Error;
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8, Self::Error> {
+        let val = self.rd.read_u8()?;
+        self.offset += 1;
+        Ok(val)
+    }
+
+    #[inline]
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.rd.read_exact_buf(buf)?;
+        self.offset += buf.len() as u64;
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+//! Helpers for reading raw fixed-width integers, without any MessagePack markers.
+//!
+//! Unlike the marker-aware [`read_u8`](super::read_u8) family, these functions read exactly
+//! `size_of::<T>()` bytes and interpret them as a **big-endian** (network byte order) integer.
+//! They are intended for parsing binary protocols embedded in ext payloads.
+//!
+//! # Examples
+//!
+//! ```
+//! use rmp::decode::raw;
+//!
+//! // The payload of an ext value: a big-endian u16 followed by a big-endian i32.
+//! let payload = [0x01, 0x02, 0xff, 0xff, 0xff, 0xfe];
+//! let mut rd = &payload[..];
+//!
+//! assert_eq!(0x0102, raw::read_u16(&mut rd).unwrap());
+//! assert_eq!(-2, raw::read_i32(&mut rd).unwrap());
+//! ```
+
+use super::RmpRead;
+
+macro_rules! read_raw_be {
+    ($($name:ident => $tp:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Reads a big-endian `", stringify!($tp), "` from the given reader.")]
+            ///
+            /// No MessagePack marker is expected or consumed.
+            ///
+            /// # Errors
+            ///
+            /// This function will return the reader's error if there are not enough bytes left.
+            #[inline]
+            pub fn $name<R: RmpRead>(rd: &mut R) -> Result<$tp, R::Error> {
+                let mut buf = [0; core::mem::size_of::<$tp>()];
+                rd.read_exact_buf(&mut buf)?;
+                Ok($tp::from_be_bytes(buf))
+            }
+        )*
+    };
+}
+
+read_raw_be!(
+    read_u8 => u8,
+    read_u16 => u16,
+    read_u32 => u32,
+    read_u64 => u64,
+    read_i8 => i8,
+    read_i16 => i16,
+    read_i32 => i32,
+    read_i64 => i64,
+);
@@ -0,0 +1,139 @@
+use super::slice::ReservedPolicy;
+use super::{read_marker, RmpRead, ValueReadError};
+use crate::Marker;
+
+/// Attempts to read and discard exactly one complete value from the given reader, including all
+/// the elements of an array or a map, recursively.
+///
+/// Nothing is allocated and nested values are not decoded: the elements still to skip are
+/// counted instead of recursing, and the payloads of strings, binaries and extensions are read
+/// through a small stack buffer. This makes it suitable to ignore unknown fields or jump past
+/// large blobs, whatever their nesting depth.
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either a marker or
+/// the data, including when the reader ends in the middle of the value.
+///
+/// It also returns `ValueReadError::TypeMismatch` with `Marker::Reserved` if the never used `0xc1`
+/// marker is encountered. Note that `rmpv` decodes that marker as nil instead, so a value that
+/// `rmpv::decode::read_value` accepts may be rejected here; use [`skip_value_with`] with
+/// [`ReservedPolicy::Token`] to skip the same values it decodes.
+///
+/// # Examples
+///
+/// ```
+/// // [[1, "two"], {"k": nil}], 42
+/// let buf = [0x92, 0x92, 0x01, 0xa3, 0x74, 0x77, 0x6f, 0x81, 0xa1, 0x6b, 0xc0, 0x2a];
+/// let mut rd = &buf[..];
+///
+/// rmp::decode::skip_value(&mut rd).unwrap();
+/// assert_eq!(42u8, rmp::decode::read_int(&mut rd).unwrap());
+/// ```
+#[inline]
+pub fn skip_value<R: RmpRead>(rd: &mut R) -> Result<(), ValueReadError<R::Error>> {
+    skip_value_with(rd, ReservedPolicy::Error)
+}
+
+/// Like [`skip_value`], handling the never used `0xc1` marker according to `reserved`.
+///
+/// # Errors
+///
+/// Same as [`skip_value`], except that `ValueReadError::TypeMismatch` is only returned for the
+/// `0xc1` marker with [`ReservedPolicy::Error`].
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::slice::ReservedPolicy;
+/// use rmp::decode::skip_value_with;
+///
+/// // [0xc1, 1], 42
+/// let buf = [0x92, 0xc1, 0x01, 0x2a];
+///
+/// let mut rd = &buf[..];
+/// skip_value_with(&mut rd, ReservedPolicy::Token).unwrap();
+/// assert_eq!(42u8, rmp::decode::read_int(&mut rd).unwrap());
+///
+/// // Skipped bytes are not counted as elements, so the array ends with 42.
+/// let mut rd = &buf[..];
+/// skip_value_with(&mut rd, ReservedPolicy::Skip).unwrap();
+/// assert!(rd.is_empty());
+///
+/// assert!(skip_value_with(&mut &buf[..], ReservedPolicy::Error).is_err());
+/// ```
+pub fn skip_value_with<R: RmpRead>(rd: &mut R, reserved: ReservedPolicy) -> Result<(), ValueReadError<R::Error>> {
+    let mut remaining = 1u64;
+
+    while remaining > 0 {
+        remaining -= 1;
+
+        let len = match read_marker(rd)? {
+            Marker::FixPos(..) | Marker::FixNeg(..) | Marker::Null | Marker::True | Marker::False => 0,
+            Marker::U8 | Marker::I8 => 1,
+            Marker::U16 | Marker::I16 => 2,
+            Marker::U32 | Marker::I32 | Marker::F32 => 4,
+            Marker::U64 | Marker::I64 | Marker::F64 => 8,
+            Marker::FixStr(len) => u64::from(len),
+            Marker::Str8 | Marker::Bin8 => u64::from(rd.read_data_u8()?),
+            Marker::Str16 | Marker::Bin16 => u64::from(rd.read_data_u16()?),
+            Marker::Str32 | Marker::Bin32 => u64::from(rd.read_data_u32()?),
+            // The extension type byte is skipped along with the data.
+            Marker::FixExt1 => 2,
+            Marker::FixExt2 => 3,
+            Marker::FixExt4 => 5,
+            Marker::FixExt8 => 9,
+            Marker::FixExt16 => 17,
+            Marker::Ext8 => u64::from(rd.read_data_u8()?) + 1,
+            Marker::Ext16 => u64::from(rd.read_data_u16()?) + 1,
+            Marker::Ext32 => u64::from(rd.read_data_u32()?) + 1,
+            Marker::FixArray(len) => {
+                remaining = remaining.saturating_add(u64::from(len));
+                0
+            }
+            Marker::Array16 => {
+                remaining = remaining.saturating_add(u64::from(rd.read_data_u16()?));
+                0
+            }
+            Marker::Array32 => {
+                remaining = remaining.saturating_add(u64::from(rd.read_data_u32()?));
+                0
+            }
+            Marker::FixMap(len) => {
+                remaining = remaining.saturating_add(u64::from(len) * 2);
+                0
+            }
+            Marker::Map16 => {
+                remaining = remaining.saturating_add(u64::from(rd.read_data_u16()?) * 2);
+                0
+            }
+            Marker::Map32 => {
+                remaining = remaining.saturating_add(u64::from(rd.read_data_u32()?) * 2);
+                0
+            }
+            Marker::Reserved => match reserved {
+                ReservedPolicy::Error => return Err(ValueReadError::TypeMismatch(Marker::Reserved)),
+                ReservedPolicy::Skip => {
+                    remaining += 1;
+                    0
+                }
+                ReservedPolicy::Token => 0,
+            },
+        };
+
+        skip_bytes(rd, len)?;
+    }
+
+    Ok(())
+}
+
+/// Reads and discards exactly `len` bytes.
+fn skip_bytes<R: RmpRead>(rd: &mut R, mut len: u64) -> Result<(), ValueReadError<R::Error>> {
+    let mut buf = [0; 256];
+    while len > 0 {
+        let chunk = len.min(buf.len() as u64) as usize;
+        rd.read_exact_buf(&mut buf[..chunk]).map_err(ValueReadError::InvalidDataRead)?;
+        len -= chunk as u64;
+    }
+    Ok(())
+}
@@ -0,0 +1,595 @@
+//! Decoding from byte slices without any allocation, see [`read_item`].
+//!
+//! Every function takes the input as `&mut &[u8]` and, on success, advances it past the value it
+//! read. Payloads are returned as slices borrowed from the input, so nothing is copied and this
+//! module works even without the `alloc` feature. On failure the input is left untouched.
+
+use core::fmt::{self, Display, Formatter};
+use core::str::Utf8Error;
+
+use num_traits::cast::FromPrimitive;
+
+use super::{from_utf8, ErrorAt};
+use crate::Marker;
+
+/// An error which can occur when decoding a value from a slice.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum SliceReadError {
+    /// The input ended before the value did, needing at least this many more bytes.
+    InsufficientBytes(usize),
+    /// The type decoded isn't match with the expected one.
+    TypeMismatch(Marker),
+    /// A string is not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// Out of range integral type conversion attempted.
+    OutOfRange,
+    /// The arrays and maps are nested deeper than the configured limit.
+    DepthLimitExceeded,
+}
+
+impl Display for SliceReadError {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InsufficientBytes(needed) => write!(f, "unexpected end of input, {needed} more bytes needed"),
+            Self::TypeMismatch(..) => f.write_str("the type decoded isn't match with the expected one"),
+            Self::InvalidUtf8(ref err) => write!(f, "string is not valid UTF-8: {err}"),
+            Self::OutOfRange => f.write_str("out of range integral type conversion attempted"),
+            Self::DepthLimitExceeded => f.write_str("depth limit exceeded"),
+        }
+    }
+}
+
+impl SliceReadError {
+    /// Attaches the offset at which the failed read started, given the whole `input` and `rest`,
+    /// the part of it which the failed read left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::decode::slice::{read_bool, read_nil};
+    ///
+    /// let buf = [0xc0, 0xc3, 0x2a];
+    /// let mut rd = &buf[..];
+    ///
+    /// read_nil(&mut rd).unwrap();
+    /// read_bool(&mut rd).unwrap();
+    /// let err = read_nil(&mut rd).map_err(|err| err.at(&buf, rd)).unwrap_err();
+    /// assert_eq!(2, err.offset);
+    /// ```
+    #[cold]
+    #[must_use]
+    pub fn at(self, input: &[u8], rest: &[u8]) -> ErrorAt<Self> {
+        debug_assert!(rest.len() <= input.len());
+        ErrorAt { offset: (input.len() - rest.len()) as u64, error: self }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SliceReadError {
+    #[cold]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::InvalidUtf8(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// How the never used marker `0xc1` is handled by a [`Tokenizer`](super::token::Tokenizer), a
+/// [`Validator`](super::Validator) or [`skip_value_with`](super::skip_value_with).
+///
+/// Strict consumers should reject it, which is the default, while tools recovering data from
+/// damaged input may rather keep going.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReservedPolicy {
+    /// Fail with [`SliceReadError::TypeMismatch`] with [`Marker::Reserved`].
+    #[default]
+    Error,
+    /// Ignore the byte, as if it was not there. It is not counted as an element of an array or a
+    /// map.
+    Skip,
+    /// Accept the byte as a value of its own, which is counted as an element of an array or a map.
+    Token,
+}
+
+/// A single MessagePack item decoded from a slice, borrowing its payload.
+///
+/// Arrays and maps are returned as their length only, their elements being the next items of the
+/// input. Integers keep the signedness of their encoding, so a non-negative value encoded with a
+/// signed marker is an `Int`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Item<'a> {
+    /// Nil represents nil.
+    Nil,
+    /// Boolean represents true or false.
+    Boolean(bool),
+    /// An integer encoded as a positive fixint or with an unsigned marker.
+    Uint(u64),
+    /// An integer encoded as a negative fixint or with a signed marker.
+    Int(i64),
+    /// A 32-bit floating point number.
+    F32(f32),
+    /// A 64-bit floating point number.
+    F64(f64),
+    /// The bytes of a string, which may not be valid UTF-8.
+    Str(&'a [u8]),
+    /// The bytes of a binary.
+    Bin(&'a [u8]),
+    /// The header of an array with the given number of elements.
+    Array(u32),
+    /// The header of a map with the given number of key-value pairs.
+    Map(u32),
+    /// The type and the data of an extension.
+    Ext(i8, &'a [u8]),
+}
+
+impl<'a> Item<'a> {
+    /// Returns the string if the item is a valid UTF-8 string.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'a str> {
+        match *self {
+            Item::Str(data) => from_utf8(data).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the marker of the shortest encoding of the item, as required by canonical
+    /// MessagePack.
+    ///
+    /// An item read with any other marker is not minimally encoded, like an integer `5` encoded
+    /// with a `U64` marker or a 3-byte string with a `Str32` one. Non-negative integers are only
+    /// minimal with an unsigned marker. A 64-bit float must be a 32-bit one if it converts to it
+    /// without loss, and a timestamp, the extension type `-1`, must use the shortest of its 32,
+    /// 64 and 96-bit forms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::decode::slice::{read_item, Item};
+    /// use rmp::Marker;
+    ///
+    /// let buf = [0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05];
+    /// let item = read_item(&mut &buf[..]).unwrap();
+    ///
+    /// assert_eq!(Item::Uint(5), item);
+    /// assert_eq!(Marker::FixPos(5), item.canonical_marker());
+    /// ```
+    #[must_use]
+    pub fn canonical_marker(&self) -> Marker {
+        let uint_marker = |val: u64| match val {
+            0..=0x7f => Marker::FixPos(val as u8),
+            0x80..=0xff => Marker::U8,
+            0x100..=0xffff => Marker::U16,
+            0x1_0000..=0xffff_ffff => Marker::U32,
+            _ => Marker::U64,
+        };
+        // Whether `len` fits in 8, 16 or 32 bits, for the lengths without a fixed form.
+        let sized = |len: usize, markers: [Marker; 3]| match len {
+            0..=0xff => markers[0],
+            0x100..=0xffff => markers[1],
+            _ => markers[2],
+        };
+
+        match *self {
+            Item::Nil => Marker::Null,
+            Item::Boolean(true) => Marker::True,
+            Item::Boolean(false) => Marker::False,
+            Item::Uint(val) => uint_marker(val),
+            Item::Int(val) => match val {
+                0.. => uint_marker(val as u64),
+                -32..=-1 => Marker::FixNeg(val as i8),
+                -0x80..=-33 => Marker::I8,
+                -0x8000..=-0x81 => Marker::I16,
+                -0x8000_0000..=-0x8001 => Marker::I32,
+                _ => Marker::I64,
+            },
+            Item::F32(..) => Marker::F32,
+            Item::F64(val) if f64::from(val as f32) == val => Marker::F32,
+            Item::F64(..) => Marker::F64,
+            Item::Str(data) if data.len() < 32 => Marker::FixStr(data.len() as u8),
+            Item::Str(data) => sized(data.len(), [Marker::Str8, Marker::Str16, Marker::Str32]),
+            Item::Bin(data) => sized(data.len(), [Marker::Bin8, Marker::Bin16, Marker::Bin32]),
+            Item::Array(len) if len < 16 => Marker::FixArray(len as u8),
+            Item::Array(len) if len <= 0xffff => Marker::Array16,
+            Item::Array(..) => Marker::Array32,
+            Item::Map(len) if len < 16 => Marker::FixMap(len as u8),
+            Item::Map(len) if len <= 0xffff => Marker::Map16,
+            Item::Map(..) => Marker::Map32,
+            Item::Ext(ty, data) => match canonical_timestamp_len(ty, data).unwrap_or(data.len()) {
+                1 => Marker::FixExt1,
+                2 => Marker::FixExt2,
+                4 => Marker::FixExt4,
+                8 => Marker::FixExt8,
+                16 => Marker::FixExt16,
+                len => sized(len, [Marker::Ext8, Marker::Ext16, Marker::Ext32]),
+            },
+        }
+    }
+}
+
+/// Returns the length of the shortest form of a timestamp, or `None` if the extension is not a
+/// valid one.
+fn canonical_timestamp_len(ty: i8, data: &[u8]) -> Option<usize> {
+    if ty != -1 {
+        return None;
+    }
+    let (secs, nanos) = match data.len() {
+        4 => return Some(4),
+        8 => {
+            let val = u64::from_be_bytes(data.try_into().ok()?);
+            ((val & ((1 << 34) - 1)) as i64, (val >> 34) as u32)
+        }
+        12 => {
+            let nanos = u32::from_be_bytes(data[..4].try_into().ok()?);
+            (i64::from_be_bytes(data[4..].try_into().ok()?), nanos)
+        }
+        _ => return None,
+    };
+    match secs {
+        _ if nanos >= 1_000_000_000 => None,
+        0..=0xffff_ffff if nanos == 0 => Some(4),
+        0..=0x3_ffff_ffff => Some(8),
+        _ => Some(12),
+    }
+}
+
+/// Splits the first `len` bytes off `buf`.
+#[inline]
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], SliceReadError> {
+    if buf.len() < len {
+        return Err(SliceReadError::InsufficientBytes(len - buf.len()));
+    }
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+    Ok(head)
+}
+
+/// Splits the next `N` bytes off `buf`.
+#[inline]
+fn take_array<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N], SliceReadError> {
+    let mut data = [0; N];
+    data.copy_from_slice(take(buf, N)?);
+    Ok(data)
+}
+
+/// Returns the marker of the value at the beginning of `buf`, without advancing it.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::InsufficientBytes`] if `buf` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::slice::{peek_marker, read_int, read_str};
+/// use rmp::Marker;
+///
+/// let mut rd = &[0xa2, 0x69, 0x64][..];
+///
+/// let key = match peek_marker(rd).unwrap() {
+///     Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => read_str(&mut rd).unwrap().len(),
+///     _ => read_int(&mut rd).unwrap(),
+/// };
+/// assert_eq!(2, key);
+/// ```
+#[inline]
+pub fn peek_marker(buf: &[u8]) -> Result<Marker, SliceReadError> {
+    match buf.first() {
+        Some(&byte) => Ok(Marker::from_u8(byte)),
+        None => Err(SliceReadError::InsufficientBytes(1)),
+    }
+}
+
+/// Reads the item at the beginning of `buf`, advancing it past the item.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::InsufficientBytes`] if `buf` ends before the item, and
+/// [`SliceReadError::TypeMismatch`] for the reserved marker `0xc1`. `buf` is left untouched.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::slice::{read_item, Item};
+///
+/// // ["le message", 42]
+/// let buf = [0x92, 0xaa, 0x6c, 0x65, 0x20, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65, 0x2a];
+/// let mut rd = &buf[..];
+///
+/// assert_eq!(Item::Array(2), read_item(&mut rd).unwrap());
+/// assert_eq!(Some("le message"), read_item(&mut rd).unwrap().as_str());
+/// assert_eq!(Item::Uint(42), read_item(&mut rd).unwrap());
+/// assert!(rd.is_empty());
+/// ```
+pub fn read_item<'a>(buf: &mut &'a [u8]) -> Result<Item<'a>, SliceReadError> {
+    let mut rd = *buf;
+    let item = read_item_from(&mut rd)?;
+    *buf = rd;
+    Ok(item)
+}
+
+fn read_item_from<'a>(rd: &mut &'a [u8]) -> Result<Item<'a>, SliceReadError> {
+    let marker = Marker::from_u8(take_array::<1>(rd)?[0]);
+    let item = match marker {
+        Marker::Null => Item::Nil,
+        Marker::True => Item::Boolean(true),
+        Marker::False => Item::Boolean(false),
+        Marker::FixPos(val) => Item::Uint(u64::from(val)),
+        Marker::FixNeg(val) => Item::Int(i64::from(val)),
+        Marker::U8 => Item::Uint(u64::from(u8::from_be_bytes(take_array(rd)?))),
+        Marker::U16 => Item::Uint(u64::from(u16::from_be_bytes(take_array(rd)?))),
+        Marker::U32 => Item::Uint(u64::from(u32::from_be_bytes(take_array(rd)?))),
+        Marker::U64 => Item::Uint(u64::from_be_bytes(take_array(rd)?)),
+        Marker::I8 => Item::Int(i64::from(i8::from_be_bytes(take_array(rd)?))),
+        Marker::I16 => Item::Int(i64::from(i16::from_be_bytes(take_array(rd)?))),
+        Marker::I32 => Item::Int(i64::from(i32::from_be_bytes(take_array(rd)?))),
+        Marker::I64 => Item::Int(i64::from_be_bytes(take_array(rd)?)),
+        Marker::F32 => Item::F32(f32::from_be_bytes(take_array(rd)?)),
+        Marker::F64 => Item::F64(f64::from_be_bytes(take_array(rd)?)),
+        Marker::FixStr(len) => Item::Str(take(rd, usize::from(len))?),
+        Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+            let len = read_len(rd, marker)?;
+            Item::Str(take(rd, len)?)
+        }
+        Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => {
+            let len = read_len(rd, marker)?;
+            Item::Bin(take(rd, len)?)
+        }
+        Marker::FixArray(len) => Item::Array(u32::from(len)),
+        Marker::Array16 => Item::Array(u32::from(u16::from_be_bytes(take_array(rd)?))),
+        Marker::Array32 => Item::Array(u32::from_be_bytes(take_array(rd)?)),
+        Marker::FixMap(len) => Item::Map(u32::from(len)),
+        Marker::Map16 => Item::Map(u32::from(u16::from_be_bytes(take_array(rd)?))),
+        Marker::Map32 => Item::Map(u32::from_be_bytes(take_array(rd)?)),
+        Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 | Marker::FixExt16 |
+        Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => {
+            let len = match marker {
+                Marker::FixExt1 => 1,
+                Marker::FixExt2 => 2,
+                Marker::FixExt4 => 4,
+                Marker::FixExt8 => 8,
+                Marker::FixExt16 => 16,
+                marker => read_len(rd, marker)?,
+            };
+            let ty = i8::from_be_bytes(take_array(rd)?);
+            Item::Ext(ty, take(rd, len)?)
+        }
+        Marker::Reserved => return Err(SliceReadError::TypeMismatch(marker)),
+    };
+    Ok(item)
+}
+
+/// Reads the length following a `Str`, `Bin` or `Ext` marker of 8, 16 or 32 bits.
+fn read_len(rd: &mut &[u8], marker: Marker) -> Result<usize, SliceReadError> {
+    let len = match marker {
+        Marker::Str8 | Marker::Bin8 | Marker::Ext8 => u32::from(u8::from_be_bytes(take_array(rd)?)),
+        Marker::Str16 | Marker::Bin16 | Marker::Ext16 => u32::from(u16::from_be_bytes(take_array(rd)?)),
+        _ => u32::from_be_bytes(take_array(rd)?),
+    };
+    usize::try_from(len).map_err(|_| SliceReadError::OutOfRange)
+}
+
+/// Reads an item, converting it with `f`, and only advances `buf` if the item has the type `f`
+/// expects.
+#[inline]
+fn read_with<'a, T, F>(buf: &mut &'a [u8], f: F) -> Result<T, SliceReadError>
+    where F: FnOnce(Item<'a>) -> Option<T>
+{
+    let mut rd = *buf;
+    match f(read_item_from(&mut rd)?) {
+        Some(val) => {
+            *buf = rd;
+            Ok(val)
+        }
+        // The item was read, so the input starts with its marker.
+        None => Err(SliceReadError::TypeMismatch(Marker::from_u8(buf[0]))),
+    }
+}
+
+/// Reads a nil value.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not nil.
+pub fn read_nil(buf: &mut &[u8]) -> Result<(), SliceReadError> {
+    read_with(buf, |item| match item {
+        Item::Nil => Some(()),
+        _ => None,
+    })
+}
+
+/// Reads a boolean value.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not a boolean.
+pub fn read_bool(buf: &mut &[u8]) -> Result<bool, SliceReadError> {
+    read_with(buf, |item| match item {
+        Item::Boolean(val) => Some(val),
+        _ => None,
+    })
+}
+
+/// Reads an integer of any encoding as a `T`.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not an integer, and
+/// [`SliceReadError::OutOfRange`] if it does not fit in `T`.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::slice::{read_int, SliceReadError};
+///
+/// assert_eq!(Ok(300u16), read_int(&mut &[0xcd, 0x01, 0x2c][..]));
+/// assert_eq!(Err(SliceReadError::OutOfRange), read_int::<u8>(&mut &[0xcd, 0x01, 0x2c][..]));
+/// ```
+pub fn read_int<T: FromPrimitive>(buf: &mut &[u8]) -> Result<T, SliceReadError> {
+    let mut rd = *buf;
+    let val = read_with(&mut rd, |item| match item {
+        Item::Uint(val) => Some(T::from_u64(val)),
+        Item::Int(val) => Some(T::from_i64(val)),
+        _ => None,
+    })?;
+    let val = val.ok_or(SliceReadError::OutOfRange)?;
+    *buf = rd;
+    Ok(val)
+}
+
+/// Reads a 32-bit float.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not a 32-bit float.
+pub fn read_f32(buf: &mut &[u8]) -> Result<f32, SliceReadError> {
+    read_with(buf, |item| match item {
+        Item::F32(val) => Some(val),
+        _ => None,
+    })
+}
+
+/// Reads a 64-bit float.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not a 64-bit float.
+pub fn read_f64(buf: &mut &[u8]) -> Result<f64, SliceReadError> {
+    read_with(buf, |item| match item {
+        Item::F64(val) => Some(val),
+        _ => None,
+    })
+}
+
+/// Reads a string, borrowing it from the input.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not a string, and
+/// [`SliceReadError::InvalidUtf8`] if it is not valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::slice::read_str;
+///
+/// let buf = [0xa2, 0x69, 0x64, 0xc0];
+/// let mut rd = &buf[..];
+///
+/// assert_eq!(Ok("id"), read_str(&mut rd));
+/// assert_eq!(&[0xc0], rd);
+/// ```
+pub fn read_str<'a>(buf: &mut &'a [u8]) -> Result<&'a str, SliceReadError> {
+    let mut rd = *buf;
+    let data = read_with(&mut rd, |item| match item {
+        Item::Str(data) => Some(data),
+        _ => None,
+    })?;
+    let val = from_utf8(data).map_err(SliceReadError::InvalidUtf8)?;
+    *buf = rd;
+    Ok(val)
+}
+
+/// Reads a binary, borrowing it from the input.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not a binary.
+pub fn read_bin<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], SliceReadError> {
+    read_with(buf, |item| match item {
+        Item::Bin(data) => Some(data),
+        _ => None,
+    })
+}
+
+/// Reads the header of an array, returning its number of elements.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not an array.
+pub fn read_array_len(buf: &mut &[u8]) -> Result<u32, SliceReadError> {
+    read_with(buf, |item| match item {
+        Item::Array(len) => Some(len),
+        _ => None,
+    })
+}
+
+/// Reads the header of a map, returning its number of key-value pairs.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not a map.
+pub fn read_map_len(buf: &mut &[u8]) -> Result<u32, SliceReadError> {
+    read_with(buf, |item| match item {
+        Item::Map(len) => Some(len),
+        _ => None,
+    })
+}
+
+/// Reads an extension, returning its type and borrowing its data from the input.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not an extension.
+pub fn read_ext<'a>(buf: &mut &'a [u8]) -> Result<(i8, &'a [u8]), SliceReadError> {
+    read_with(buf, |item| match item {
+        Item::Ext(ty, data) => Some((ty, data)),
+        _ => None,
+    })
+}
+
+/// Reads a string from the beginning of `buf`, returning it borrowed from the input along with
+/// the rest of the input.
+///
+/// This is [`read_str`] for callers that thread the remaining input through return values rather
+/// than advancing it in place, like [`read_bin_ref`] and [`read_ext_ref`] for the other types
+/// carrying a payload.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not a string, and
+/// [`SliceReadError::InvalidUtf8`] if it is not valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::slice::read_str_ref;
+///
+/// let buf = [0xa2, 0x69, 0x64, 0xa3, 0x6b, 0x65, 0x79];
+///
+/// let (first, tail) = read_str_ref(&buf).unwrap();
+/// let (second, tail) = read_str_ref(tail).unwrap();
+/// assert_eq!(("id", "key"), (first, second));
+/// assert!(tail.is_empty());
+/// ```
+#[inline]
+pub fn read_str_ref(mut buf: &[u8]) -> Result<(&str, &[u8]), SliceReadError> {
+    let val = read_str(&mut buf)?;
+    Ok((val, buf))
+}
+
+/// Reads a binary from the beginning of `buf`, returning it borrowed from the input along with
+/// the rest of the input.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not a binary.
+#[inline]
+pub fn read_bin_ref(mut buf: &[u8]) -> Result<(&[u8], &[u8]), SliceReadError> {
+    let data = read_bin(&mut buf)?;
+    Ok((data, buf))
+}
+
+/// Reads an extension from the beginning of `buf`, returning its type and its data borrowed from
+/// the input, along with the rest of the input.
+///
+/// # Errors
+///
+/// Returns [`SliceReadError::TypeMismatch`] if the next value is not an extension.
+#[inline]
+pub fn read_ext_ref(mut buf: &[u8]) -> Result<(i8, &[u8], &[u8]), SliceReadError> {
+    let (ty, data) = read_ext(&mut buf)?;
+    Ok((ty, data, buf))
+}
@@ -1,5 +1,9 @@
+#[cfg(feature = "alloc")]
+use alloc::string::{FromUtf8Error, String};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::fmt::{self, Display, Formatter};
-use core::str::{from_utf8, Utf8Error};
+use core::str::Utf8Error;
 #[cfg(feature = "std")]
 use std::error;
 
@@ -123,6 +127,51 @@ where
     read_str_data(rd, len, &mut buf[0..ulen])
 }
 
+/// Converts a slice of bytes to a string slice, like [`core::str::from_utf8`].
+///
+/// With the `simdutf8` feature, the bytes are checked with SIMD instructions when the CPU supports
+/// them, which is several times faster on long strings. Invalid input is then checked again, to
+/// report the same error as `core` does.
+///
+/// Every string decoded by this crate is checked with this function, and it is public so that
+/// crates building on `rmp` can check their strings the same way.
+///
+/// # Errors
+///
+/// Returns [`Utf8Error`] if the bytes are not valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Ok("le message"), rmp::decode::from_utf8(b"le message"));
+/// assert_eq!(1, rmp::decode::from_utf8(b"a\xff").unwrap_err().valid_up_to());
+/// ```
+#[inline]
+pub fn from_utf8(v: &[u8]) -> Result<&str, Utf8Error> {
+    #[cfg(feature = "simdutf8")]
+    if let Ok(s) = simdutf8::basic::from_utf8(v) {
+        return Ok(s);
+    }
+    core::str::from_utf8(v)
+}
+
+/// Converts a vector of bytes to a `String`, like [`String::from_utf8`], checking it with
+/// [`from_utf8`].
+///
+/// # Errors
+///
+/// Returns [`FromUtf8Error`] if the bytes are not valid UTF-8, which gives them back.
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn string_from_utf8(v: Vec<u8>) -> Result<String, FromUtf8Error> {
+    #[cfg(feature = "simdutf8")]
+    if simdutf8::basic::from_utf8(&v).is_ok() {
+        // SAFETY: The bytes were just checked to be valid UTF-8.
+        return Ok(unsafe { String::from_utf8_unchecked(v) });
+    }
+    String::from_utf8(v)
+}
+
 pub fn read_str_data<'r, R>(rd: &mut R,
                             len: u32,
                             buf: &'r mut [u8])
@@ -0,0 +1,266 @@
+//! Pull parsing of byte slices into a flat stream of tokens, see [`Tokenizer`].
+
+use alloc::vec::Vec;
+
+use super::slice::{read_item, Item, ReservedPolicy, SliceReadError};
+use crate::Marker;
+
+/// A single event of a [`Tokenizer`], borrowing its payload from the input.
+///
+/// Arrays and maps are delimited by a start token, carrying their length, and an end token. The
+/// elements of a map alternate between keys and values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token<'a> {
+    /// Nil represents nil.
+    Nil,
+    /// Boolean represents true or false.
+    Boolean(bool),
+    /// An integer encoded as a positive fixint or with an unsigned marker.
+    Uint(u64),
+    /// An integer encoded as a negative fixint or with a signed marker.
+    Int(i64),
+    /// A 32-bit floating point number.
+    F32(f32),
+    /// A 64-bit floating point number.
+    F64(f64),
+    /// The bytes of a string, which may not be valid UTF-8.
+    Str(&'a [u8]),
+    /// The bytes of a binary.
+    Bin(&'a [u8]),
+    /// The type and the data of an extension.
+    Ext(i8, &'a [u8]),
+    /// The start of an array with the given number of elements.
+    ArrayStart(u32),
+    /// The end of the innermost array.
+    ArrayEnd,
+    /// The start of a map with the given number of key-value pairs.
+    MapStart(u32),
+    /// The end of the innermost map.
+    MapEnd,
+    /// The never used marker `0xc1`, only produced with [`ReservedPolicy::Token`].
+    Reserved,
+}
+
+/// An array or a map whose elements are being tokenized.
+#[derive(Clone, Copy, Debug)]
+struct Open {
+    map: bool,
+    remaining: u64,
+}
+
+/// An iterator over the tokens of the values in a byte slice, each paired with its byte offset.
+///
+/// The offset of a token is the one of its marker, and the end of an array or a map is reported
+/// at the offset following its last element. The input may contain several concatenated values,
+/// the iteration ending with the input. Tokens are read one at a time with the [`slice`]
+/// functions, so that no value is built and only the nesting of arrays and maps is tracked.
+///
+/// After an error, including when the input ends in the middle of a value, the iteration ends.
+///
+/// The tokenizer itself never recurses, but consumers building nested structures from the tokens
+/// usually do, so [`max_depth`](Self::max_depth) allows to reject crafted deeply nested input
+/// before it overflows their stack.
+///
+/// [`slice`]: super::slice
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::token::{Token, Tokenizer};
+///
+/// // {"id": [1, -1]}
+/// let buf = [0x81, 0xa2, 0x69, 0x64, 0x92, 0x01, 0xff];
+/// let tokens: Result<Vec<_>, _> = Tokenizer::new(&buf).collect();
+///
+/// assert_eq!(
+///     vec![
+///         (0, Token::MapStart(1)),
+///         (1, Token::Str(b"id")),
+///         (4, Token::ArrayStart(2)),
+///         (5, Token::Uint(1)),
+///         (6, Token::Int(-1)),
+///         (7, Token::ArrayEnd),
+///         (7, Token::MapEnd),
+///     ],
+///     tokens.unwrap(),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Tokenizer<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    stack: Vec<Open>,
+    max_depth: usize,
+    reserved: ReservedPolicy,
+    failed: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Creates a tokenizer over the given input.
+    #[must_use]
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0, stack: Vec::new(), max_depth: usize::MAX, reserved: ReservedPolicy::Error, failed: false }
+    }
+
+    /// Sets the maximum number of arrays and maps a token may be nested in, unlimited by default.
+    ///
+    /// The start of an array or a map nested deeper fails with
+    /// [`SliceReadError::DepthLimitExceeded`], ending the iteration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::decode::slice::SliceReadError;
+    /// use rmp::decode::token::Tokenizer;
+    ///
+    /// // [[[]]]
+    /// let buf = [0x91, 0x91, 0x90];
+    ///
+    /// assert!(Tokenizer::new(&buf).max_depth(3).all(|token| token.is_ok()));
+    /// assert_eq!(
+    ///     Some(Err(SliceReadError::DepthLimitExceeded)),
+    ///     Tokenizer::new(&buf).max_depth(2).find(|token| token.is_err()),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets how the never used marker `0xc1` is handled, failing by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::decode::slice::ReservedPolicy;
+    /// use rmp::decode::token::{Token, Tokenizer};
+    ///
+    /// // [nil, 0xc1, true]
+    /// let buf = [0x92, 0xc0, 0xc1, 0xc3];
+    ///
+    /// let tokens = |policy| Tokenizer::new(&buf).reserved(policy).map(|token| token.unwrap().1).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     vec![Token::ArrayStart(2), Token::Nil, Token::Boolean(true), Token::ArrayEnd],
+    ///     tokens(ReservedPolicy::Skip),
+    /// );
+    /// assert_eq!(
+    ///     vec![Token::ArrayStart(2), Token::Nil, Token::Reserved, Token::ArrayEnd, Token::Boolean(true)],
+    ///     tokens(ReservedPolicy::Token),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn reserved(mut self, policy: ReservedPolicy) -> Self {
+        self.reserved = policy;
+        self
+    }
+
+    /// Returns the offset of the next token.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the number of arrays and maps the next token is nested in.
+    #[inline]
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the part of the input that has not been tokenized yet.
+    #[inline]
+    #[must_use]
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.buf[self.pos..]
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<(usize, Token<'a>), SliceReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        if let Some(open) = self.stack.last() {
+            if open.remaining == 0 {
+                let token = if open.map { Token::MapEnd } else { Token::ArrayEnd };
+                self.stack.pop();
+                return Some(Ok((self.pos, token)));
+            }
+        }
+        if self.reserved == ReservedPolicy::Skip {
+            while self.buf.get(self.pos) == Some(&RESERVED) {
+                self.pos += 1;
+            }
+        }
+        if self.stack.is_empty() && self.pos == self.buf.len() {
+            return None;
+        }
+
+        let offset = self.pos;
+        let mut rd = self.remaining();
+        if self.reserved == ReservedPolicy::Token && rd.first() == Some(&RESERVED) {
+            self.pos += 1;
+            if let Some(open) = self.stack.last_mut() {
+                open.remaining -= 1;
+            }
+            return Some(Ok((offset, Token::Reserved)));
+        }
+        if self.stack.len() >= self.max_depth && rd.first().is_some_and(|&byte| is_container(Marker::from_u8(byte))) {
+            self.failed = true;
+            return Some(Err(SliceReadError::DepthLimitExceeded));
+        }
+        let item = match read_item(&mut rd) {
+            Ok(item) => item,
+            Err(err) => {
+                self.failed = true;
+                return Some(Err(err));
+            }
+        };
+        self.pos = self.buf.len() - rd.len();
+
+        if let Some(open) = self.stack.last_mut() {
+            open.remaining -= 1;
+        }
+
+        let token = match item {
+            Item::Nil => Token::Nil,
+            Item::Boolean(val) => Token::Boolean(val),
+            Item::Uint(val) => Token::Uint(val),
+            Item::Int(val) => Token::Int(val),
+            Item::F32(val) => Token::F32(val),
+            Item::F64(val) => Token::F64(val),
+            Item::Str(data) => Token::Str(data),
+            Item::Bin(data) => Token::Bin(data),
+            Item::Ext(ty, data) => Token::Ext(ty, data),
+            Item::Array(len) => {
+                self.stack.push(Open { map: false, remaining: u64::from(len) });
+                Token::ArrayStart(len)
+            }
+            Item::Map(len) => {
+                self.stack.push(Open { map: true, remaining: u64::from(len) * 2 });
+                Token::MapStart(len)
+            }
+        };
+        Some(Ok((offset, token)))
+    }
+}
+
+/// The never used marker.
+const RESERVED: u8 = 0xc1;
+
+/// Returns `true` if the marker starts an array or a map.
+#[inline]
+fn is_container(marker: Marker) -> bool {
+    matches!(
+        marker,
+        Marker::FixArray(..) | Marker::Array16 | Marker::Array32 | Marker::FixMap(..) | Marker::Map16 | Marker::Map32
+    )
+}
+
+impl core::iter::FusedIterator for Tokenizer<'_> {}
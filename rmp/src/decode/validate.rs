@@ -0,0 +1,329 @@
+use core::fmt::{self, Display, Formatter};
+use core::str::Utf8Error;
+
+use super::from_utf8;
+use super::slice::{read_item, Item, ReservedPolicy, SliceReadError};
+use crate::Marker;
+
+/// An error which can occur when validating a buffer, see [`validate`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ValidateError {
+    /// The input ended before the value did, needing at least this many more bytes.
+    InsufficientBytes(usize),
+    /// The never used marker `0xc1` was found at this offset.
+    ReservedMarker(usize),
+    /// The string at this offset is not valid UTF-8.
+    InvalidUtf8(usize, Utf8Error),
+    /// The value is followed by this many more bytes.
+    TrailingBytes(usize),
+    /// The value at this offset is not minimally encoded, with the marker it should have used.
+    NonCanonical(usize, Marker),
+    /// The map key at this offset is encoded to smaller bytes than the key before it.
+    UnsortedKey(usize),
+}
+
+impl Display for ValidateError {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InsufficientBytes(needed) => write!(f, "unexpected end of input, {needed} more bytes needed"),
+            Self::ReservedMarker(offset) => write!(f, "reserved marker 0xc1 at offset {offset}"),
+            Self::InvalidUtf8(offset, ref err) => write!(f, "string at offset {offset} is not valid UTF-8: {err}"),
+            Self::TrailingBytes(len) => write!(f, "{len} trailing bytes after the value"),
+            Self::NonCanonical(offset, marker) => {
+                write!(f, "value at offset {offset} is not minimally encoded, expected marker {marker:?}")
+            }
+            Self::UnsortedKey(offset) => write!(f, "map key at offset {offset} is out of order"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidateError {
+    #[cold]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::InvalidUtf8(_, ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Configurable structural checker of MessagePack buffers, see [`validate`] for the defaults.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::Validator;
+///
+/// // "\xff" followed by nil
+/// let buf = [0xa1, 0xff, 0xc0];
+///
+/// assert!(Validator::new().validate(&buf).is_err());
+/// assert_eq!(Ok(2), Validator::new().utf8(false).allow_trailing(true).validate(&buf));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Validator {
+    utf8: bool,
+    allow_trailing: bool,
+    canonical: bool,
+    reserved: ReservedPolicy,
+}
+
+impl Validator {
+    /// Creates a validator which checks strings for UTF-8 and rejects trailing bytes.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { utf8: true, allow_trailing: false, canonical: false, reserved: ReservedPolicy::Error }
+    }
+
+    /// Sets whether strings must be valid UTF-8, `true` by default.
+    #[must_use]
+    pub const fn utf8(mut self, enabled: bool) -> Self {
+        self.utf8 = enabled;
+        self
+    }
+
+    /// Sets whether the value may be followed by more bytes, `false` by default.
+    ///
+    /// This allows to validate the first of several concatenated values, using the returned size
+    /// to find the next one.
+    #[must_use]
+    pub const fn allow_trailing(mut self, enabled: bool) -> Self {
+        self.allow_trailing = enabled;
+        self
+    }
+
+    /// Sets whether the value must be in canonical form, `false` by default.
+    ///
+    /// This is meant for protocols that require a unique byte representation of each value, for
+    /// example to sign or hash it. Every value must then use its shortest encoding, see
+    /// [`Item::canonical_marker`](super::slice::Item::canonical_marker), and the keys of every map
+    /// must be sorted by their encoded bytes. This is the form `rmpv` writes with its canonical
+    /// encoding.
+    ///
+    /// Checking the order of the keys of nested maps takes memory, so this needs the `alloc`
+    /// feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::decode::{ValidateError, Validator};
+    /// use rmp::Marker;
+    ///
+    /// // The 3-byte string "abc" encoded as a str32.
+    /// let buf = [0xdb, 0x00, 0x00, 0x00, 0x03, 0x61, 0x62, 0x63];
+    ///
+    /// assert_eq!(Ok(8), Validator::new().validate(&buf));
+    /// assert_eq!(
+    ///     Err(ValidateError::NonCanonical(0, Marker::FixStr(3))),
+    ///     Validator::new().canonical(true).validate(&buf),
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub const fn canonical(mut self, enabled: bool) -> Self {
+        self.canonical = enabled;
+        self
+    }
+
+    /// Sets how the never used marker `0xc1` is handled, rejected by default.
+    ///
+    /// With [`ReservedPolicy::Token`] it is accepted as a value on its own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::decode::slice::ReservedPolicy;
+    /// use rmp::decode::{ValidateError, Validator};
+    ///
+    /// // [nil, 0xc1, true]
+    /// let buf = [0x92, 0xc0, 0xc1, 0xc3];
+    ///
+    /// assert_eq!(Err(ValidateError::ReservedMarker(2)), Validator::new().validate(&buf));
+    /// assert_eq!(Ok(4), Validator::new().reserved(ReservedPolicy::Skip).validate(&buf));
+    /// assert_eq!(
+    ///     Err(ValidateError::TrailingBytes(1)),
+    ///     Validator::new().reserved(ReservedPolicy::Token).validate(&buf),
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn reserved(mut self, policy: ReservedPolicy) -> Self {
+        self.reserved = policy;
+        self
+    }
+
+    /// Checks that `buf` starts with one well-formed value, returning its size in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidateError`] describing the first problem found.
+    pub fn validate(&self, buf: &[u8]) -> Result<usize, ValidateError> {
+        let mut rd = buf;
+        let mut remaining = 1u64;
+
+        while remaining > 0 {
+            if let [0xc1, rest @ ..] = rd {
+                match self.reserved {
+                    ReservedPolicy::Error => {}
+                    ReservedPolicy::Skip => {
+                        rd = rest;
+                        continue;
+                    }
+                    ReservedPolicy::Token => {
+                        rd = rest;
+                        remaining -= 1;
+                        continue;
+                    }
+                }
+            }
+            remaining -= 1;
+
+            let offset = buf.len() - rd.len();
+            let item = read_item(&mut rd).map_err(|err| match err {
+                SliceReadError::InsufficientBytes(needed) => ValidateError::InsufficientBytes(needed),
+                SliceReadError::TypeMismatch(Marker::Reserved) => ValidateError::ReservedMarker(offset),
+                // A length that does not fit in `usize` can not fit in the buffer either.
+                _ => ValidateError::InsufficientBytes(usize::MAX),
+            })?;
+
+            if self.canonical {
+                let marker = item.canonical_marker();
+                if marker != Marker::from_u8(buf[offset]) {
+                    return Err(ValidateError::NonCanonical(offset, marker));
+                }
+            }
+
+            match item {
+                Item::Str(data) if self.utf8 => {
+                    from_utf8(data).map_err(|err| ValidateError::InvalidUtf8(offset, err))?;
+                }
+                Item::Array(len) => remaining += u64::from(len),
+                Item::Map(len) => remaining += u64::from(len) * 2,
+                _ => {}
+            }
+
+            // Every element takes at least one byte, so a length can be rejected before reading
+            // its elements one by one.
+            if remaining > rd.len() as u64 {
+                let needed = remaining - rd.len() as u64;
+                return Err(ValidateError::InsufficientBytes(usize::try_from(needed).unwrap_or(usize::MAX)));
+            }
+        }
+
+        let len = buf.len() - rd.len();
+        if !self.allow_trailing && !rd.is_empty() {
+            return Err(ValidateError::TrailingBytes(rd.len()));
+        }
+        #[cfg(feature = "alloc")]
+        if self.canonical {
+            check_key_order(&buf[..len], self.reserved)?;
+        }
+        Ok(len)
+    }
+}
+
+/// A map or array being walked by [`check_key_order`].
+#[cfg(feature = "alloc")]
+struct Container {
+    /// The number of keys and values of a map, or of elements of an array, left to read.
+    left: u64,
+    is_map: bool,
+    /// The start of the current key, and the range of the previous one.
+    key_start: usize,
+    prev_key: Option<core::ops::Range<usize>>,
+}
+
+/// Checks that the keys of every map of the well-formed value `buf` are sorted by their bytes.
+#[cfg(feature = "alloc")]
+fn check_key_order(buf: &[u8], reserved: ReservedPolicy) -> Result<(), ValidateError> {
+    let mut stack: alloc::vec::Vec<Container> = alloc::vec::Vec::new();
+    let mut rd = buf;
+
+    while !rd.is_empty() {
+        let offset = buf.len() - rd.len();
+        if let Some(top) = stack.last_mut() {
+            if top.is_map && top.left % 2 == 0 {
+                top.key_start = offset;
+            }
+        }
+
+        let item = match rd {
+            [0xc1, rest @ ..] if reserved == ReservedPolicy::Skip => {
+                rd = rest;
+                continue;
+            }
+            [0xc1, rest @ ..] => {
+                rd = rest;
+                None
+            }
+            _ => Some(read_item(&mut rd).map_err(|_| ValidateError::InsufficientBytes(1))?),
+        };
+        match item {
+            Some(Item::Array(len)) if len > 0 => {
+                stack.push(Container { left: len.into(), is_map: false, key_start: 0, prev_key: None });
+                continue;
+            }
+            Some(Item::Map(len)) if len > 0 => {
+                stack.push(Container { left: u64::from(len) * 2, is_map: true, key_start: 0, prev_key: None });
+                continue;
+            }
+            _ => {}
+        }
+
+        // A value ended here, which may end the containers around it too.
+        let end = buf.len() - rd.len();
+        while let Some(top) = stack.last_mut() {
+            if top.is_map && top.left % 2 == 0 {
+                let key = top.key_start..end;
+                if top.prev_key.as_ref().is_some_and(|prev| buf[prev.clone()] > buf[key.clone()]) {
+                    return Err(ValidateError::UnsortedKey(key.start));
+                }
+                top.prev_key = Some(key);
+            }
+            top.left -= 1;
+            if top.left > 0 {
+                break;
+            }
+            stack.pop();
+        }
+    }
+    Ok(())
+}
+
+impl Default for Validator {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks that `buf` contains exactly one well-formed value, returning its size in bytes.
+///
+/// Every marker and length is checked against the buffer, as well as the UTF-8 encoding of
+/// strings, without decoding or allocating anything, so that untrusted input can be rejected
+/// before it reaches the application. Nested arrays and maps are followed by counting their
+/// elements rather than by recursing, so arbitrarily deep input is fine too.
+///
+/// Use a [`Validator`] to skip the UTF-8 checks or to accept bytes after the value.
+///
+/// # Errors
+///
+/// Returns [`ValidateError`] describing the first problem found.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{validate, ValidateError};
+///
+/// // ["le message", 42]
+/// let buf = [0x92, 0xaa, 0x6c, 0x65, 0x20, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65, 0x2a];
+///
+/// assert_eq!(Ok(buf.len()), validate(&buf));
+/// assert_eq!(Err(ValidateError::InsufficientBytes(1)), validate(&buf[..12]));
+/// ```
+#[inline]
+pub fn validate(buf: &[u8]) -> Result<usize, ValidateError> {
+    Validator::new().validate(buf)
+}
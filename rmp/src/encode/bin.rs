@@ -1,5 +1,5 @@
 use super::RmpWrite;
-use crate::encode::{write_marker, ValueWriteError};
+use crate::encode::{write_marker, HeaderBuf, ValueWriteError};
 use crate::Marker;
 
 /// Encodes and attempts to write the most efficient binary array length implementation to the given
@@ -39,7 +39,6 @@ pub fn write_bin_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueW
 /// marker or the data.
 // TODO: Docs, range check, example, visibility.
 pub fn write_bin<W: RmpWrite>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError<W::Error>> {
-    write_bin_len(wr, data.len() as u32)?;
-    wr.write_bytes(data)
-        .map_err(ValueWriteError::InvalidDataWrite)
+    let (header, _) = HeaderBuf::encode(|header| write_bin_len(header, data.len() as u32));
+    wr.write_header_and_data(header.as_slice(), data)
 }
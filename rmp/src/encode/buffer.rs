@@ -1,6 +1,7 @@
 //! Implementation of the [`ByteBuf`] type
 
 use super::RmpWrite;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 #[cfg(not(feature = "std"))]
 use core::fmt::{self, Display, Formatter};
@@ -66,6 +67,7 @@ impl<'a> RmpWrite for &'a mut [u8] {
     }
 }
 
+#[cfg(feature = "alloc")]
 /// A wrapper around `Vec<u8>` to serialize more efficiently.
 ///
 /// This has a specialized implementation of `RmpWrite`
@@ -79,6 +81,7 @@ impl<'a> RmpWrite for &'a mut [u8] {
 pub struct ByteBuf {
     bytes: Vec<u8>,
 }
+#[cfg(feature = "alloc")]
 impl ByteBuf {
     /// Construct a new empty buffer
     #[inline]
@@ -126,29 +129,34 @@ impl ByteBuf {
         &self.bytes
     }
 }
+#[cfg(feature = "alloc")]
 impl AsRef<[u8]> for ByteBuf {
     fn as_ref(&self) -> &[u8] {
         &self.bytes
     }
 }
+#[cfg(feature = "alloc")]
 impl AsRef<Vec<u8>> for ByteBuf {
     #[inline]
     fn as_ref(&self) -> &Vec<u8> {
         &self.bytes
     }
 }
+#[cfg(feature = "alloc")]
 impl AsMut<Vec<u8>> for ByteBuf {
     #[inline]
     fn as_mut(&mut self) -> &mut Vec<u8> {
         &mut self.bytes
     }
 }
+#[cfg(feature = "alloc")]
 impl From<ByteBuf> for Vec<u8> {
     #[inline]
     fn from(buf: ByteBuf) -> Self {
         buf.bytes
     }
 }
+#[cfg(feature = "alloc")]
 impl From<Vec<u8>> for ByteBuf {
     #[inline]
     fn from(bytes: Vec<u8>) -> Self {
@@ -156,6 +164,7 @@ impl From<Vec<u8>> for ByteBuf {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl RmpWrite for ByteBuf {
     type Error = core::convert::Infallible;
 
@@ -171,7 +180,7 @@ impl RmpWrite for ByteBuf {
         Ok(())
     }
 }
-#[cfg(not(feature = "std"))]
+#[cfg(all(feature = "alloc", not(feature = "std")))]
 impl<'a> RmpWrite for Vec<u8> {
     type Error = core::convert::Infallible;
 
@@ -187,3 +196,255 @@ impl<'a> RmpWrite for Vec<u8> {
         Ok(())
     }
 }
+
+/// A writer that discards everything written into it, only counting the bytes.
+///
+/// This allows to compute the encoded size of a message, for example to prefix it with its length,
+/// without buffering it first. See also [`encoded_len_of`].
+///
+/// With the `std` feature it implements `std::io::Write`, so it can also be given to encoders that
+/// require it, like the serializer of `rmp-serde`. Writing into it never fails.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::{write_str, write_uint, CountingWriter};
+///
+/// let mut wr = CountingWriter::new();
+/// write_uint(&mut wr, 300).unwrap();
+/// write_str(&mut wr, "le message").unwrap();
+///
+/// assert_eq!(3 + 11, wr.len());
+/// ```
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct CountingWriter {
+    len: usize,
+}
+
+impl CountingWriter {
+    /// Creates a writer which has counted nothing yet.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { len: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if nothing was written so far.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for CountingWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.len += buf.len();
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.len += buf.len();
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Fallback implementation for counting
+///
+/// Only needed for no-std because we don't have
+/// the blanket impl for `std::io::Write`
+#[cfg(not(feature = "std"))]
+impl RmpWrite for CountingWriter {
+    type Error = core::convert::Infallible;
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.len += buf.len();
+        Ok(())
+    }
+}
+
+/// Returns the number of bytes written by `f` into a [`CountingWriter`].
+///
+/// # Errors
+///
+/// Returns the error of `f`, which can only come from `f` itself since counting never fails.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::{encoded_len_of, write_array_len, write_bin, write_uint};
+///
+/// let len = encoded_len_of(|wr| {
+///     write_array_len(wr, 2)?;
+///     write_uint(wr, 42)?;
+///     write_bin(wr, &[0; 1000])
+/// });
+///
+/// assert_eq!(1 + 1 + 3 + 1000, len.unwrap());
+/// ```
+pub fn encoded_len_of<F, T, E>(f: F) -> Result<usize, E>
+    where F: FnOnce(&mut CountingWriter) -> Result<T, E>
+{
+    let mut wr = CountingWriter::new();
+    f(&mut wr)?;
+    Ok(wr.len())
+}
+
+/// An error returned from writing past the capacity of a [`FixedBuf`], or of the buffer of a
+/// `BufMutWriter`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BufferFull {
+    /// The number of bytes that did not fit.
+    pub needed: usize,
+}
+
+impl core::fmt::Display for BufferFull {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "buffer full, {} more bytes needed", self.needed)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferFull {}
+impl crate::encode::RmpWriteErr for BufferFull {}
+
+/// A writer into a buffer of fixed capacity, which may be uninitialized.
+///
+/// Nothing is allocated and the buffer does not need to be zeroed first, so a message can be
+/// encoded into stack memory on latency-critical paths.
+///
+/// Encoding a value which does not fit fails with [`BufferFull`], possibly after writing its first
+/// bytes, like its marker. [`truncate`](Self::truncate) rolls back to a previous [`len`](Self::len).
+///
+/// # Examples
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// use rmp::encode::{write_str, write_uint, FixedBuf};
+///
+/// let mut storage = [MaybeUninit::uninit(); 16];
+/// let mut buf = FixedBuf::new(&mut storage);
+///
+/// write_uint(&mut buf, 300).unwrap();
+/// write_str(&mut buf, "le message").unwrap();
+/// assert_eq!([0xcd, 0x01, 0x2c, 0xaa], buf.as_slice()[..4]);
+///
+/// // Only 2 bytes are left.
+/// let len = buf.len();
+/// assert!(write_uint(&mut buf, u64::MAX).is_err());
+/// buf.truncate(len);
+/// assert_eq!(14, buf.len());
+/// ```
+#[derive(Debug)]
+pub struct FixedBuf<'a> {
+    buf: &'a mut [core::mem::MaybeUninit<u8>],
+    len: usize,
+}
+
+impl<'a> FixedBuf<'a> {
+    /// Wraps an uninitialized buffer, writing from its start.
+    #[inline]
+    pub fn new(buf: &'a mut [core::mem::MaybeUninit<u8>]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Returns the number of bytes written.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if nothing was written.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the total capacity of the buffer.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns the number of bytes that can still be written.
+    #[inline]
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.len
+    }
+
+    /// Forgets everything written after the first `len` bytes, if more were written.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Forgets everything written, to reuse the buffer.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the bytes written.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: the first `len` bytes were initialized by `write_bytes`.
+        unsafe { core::slice::from_raw_parts(self.buf.as_ptr().cast::<u8>(), self.len) }
+    }
+
+    /// Unwraps the bytes written, with the lifetime of the buffer.
+    #[inline]
+    #[must_use]
+    pub fn into_slice(self) -> &'a mut [u8] {
+        // SAFETY: the first `len` bytes were initialized by `write_bytes`.
+        unsafe { core::slice::from_raw_parts_mut(self.buf.as_mut_ptr().cast::<u8>(), self.len) }
+    }
+}
+
+impl<'a> From<&'a mut [u8]> for FixedBuf<'a> {
+    /// Wraps an initialized buffer, writing from its start.
+    #[inline]
+    fn from(buf: &'a mut [u8]) -> Self {
+        // SAFETY: `MaybeUninit<u8>` has the same layout as `u8`, and `FixedBuf` only ever writes
+        // initialized bytes, so the buffer stays initialized.
+        let buf = unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr().cast(), buf.len()) };
+        Self::new(buf)
+    }
+}
+
+impl RmpWrite for FixedBuf<'_> {
+    type Error = BufferFull;
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        let Some(dst) = self.buf.get_mut(self.len..self.len + buf.len()) else {
+            return Err(BufferFull { needed: buf.len() - self.remaining() });
+        };
+        for (dst, &byte) in dst.iter_mut().zip(buf) {
+            dst.write(byte);
+        }
+        self.len += buf.len();
+        Ok(())
+    }
+}
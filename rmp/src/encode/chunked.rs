@@ -0,0 +1,116 @@
+//! Streaming of large payloads, see [`BinWriter`].
+
+use std::io::{self, Write};
+
+use super::{write_bin_len, write_str_len, ValueWriteError};
+
+/// A writer of the payload of a binary or a string whose length was declared up front, so that
+/// the payload can be streamed in chunks instead of being contiguous in memory.
+///
+/// The header, with the shortest marker for the declared length, is written when the `BinWriter`
+/// is created. The payload is then written through [`io::Write`], which rejects any byte past the
+/// declared length, and [`finish`](Self::finish) checks that the whole payload was written.
+///
+/// The payload of a string is not checked, so it must be valid UTF-8 once complete, even though
+/// chunks may split characters.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Write};
+///
+/// use rmp::encode::BinWriter;
+///
+/// let mut buf = Vec::new();
+///
+/// let mut wr = BinWriter::new_bin(&mut buf, 300).unwrap();
+/// for chunk in [[0xaa; 100], [0xbb; 100], [0xcc; 100]] {
+///     wr.write_all(&chunk).unwrap();
+/// }
+/// wr.finish().unwrap();
+///
+/// assert_eq!([0xc5, 0x01, 0x2c, 0xaa], buf[..4]);
+/// assert_eq!(3 + 300, buf.len());
+/// ```
+#[derive(Debug)]
+pub struct BinWriter<W: Write> {
+    wr: W,
+    remaining: u32,
+}
+
+impl<W: Write> BinWriter<W> {
+    /// Writes the header of a binary of `len` bytes, returning a writer for its payload.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `ValueWriteError` on any I/O error occurred while writing the
+    /// header.
+    pub fn new_bin(mut wr: W, len: u32) -> Result<Self, ValueWriteError<io::Error>> {
+        write_bin_len(&mut wr, len)?;
+        Ok(Self { wr, remaining: len })
+    }
+
+    /// Writes the header of a string of `len` bytes, returning a writer for its payload.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `ValueWriteError` on any I/O error occurred while writing the
+    /// header.
+    pub fn new_str(mut wr: W, len: u32) -> Result<Self, ValueWriteError<io::Error>> {
+        write_str_len(&mut wr, len)?;
+        Ok(Self { wr, remaining: len })
+    }
+
+    /// Returns the number of payload bytes still to be written.
+    #[inline]
+    #[must_use]
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Gets a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.wr
+    }
+
+    /// Checks that the whole payload was written, returning the underlying writer.
+    ///
+    /// The underlying writer is not flushed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidInput` if fewer bytes than declared were written.
+    pub fn finish(self) -> io::Result<W> {
+        if self.remaining > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("payload is {} bytes shorter than declared", self.remaining),
+            ));
+        }
+        Ok(self.wr)
+    }
+}
+
+impl<W: Write> Write for BinWriter<W> {
+    /// Writes a chunk of the payload.
+    ///
+    /// Returns an error of kind `InvalidInput` if the chunk goes past the declared length, in
+    /// which case nothing is written.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.remaining as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("payload is longer than declared, {} bytes remaining", self.remaining),
+            ));
+        }
+        let len = self.wr.write(buf)?;
+        self.remaining -= len as u32;
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.wr.flush()
+    }
+}
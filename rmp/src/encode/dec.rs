@@ -1,4 +1,4 @@
-use super::{write_marker, RmpWrite};
+use super::{write_marker, write_sint, write_uint, RmpWrite};
 use crate::encode::ValueWriteError;
 use crate::Marker;
 
@@ -29,3 +29,58 @@ pub fn write_f64<W: RmpWrite>(wr: &mut W, val: f64) -> Result<(), ValueWriteErro
     wr.write_data_f64(val)?;
     Ok(())
 }
+
+/// Encodes and attempts to write an `f64` value using the smallest representation that loses
+/// nothing, returning the marker used.
+///
+/// The value is written as an `f32` when it converts back to exactly the same `f64`, which is
+/// the case for infinities and many constants such as `0.5`, and as an `f64` otherwise. NaN values
+/// are always written as an `f64`, keeping their payload.
+///
+/// With `int` enabled, values without a fractional part are written as the most compact integer
+/// instead, as [`write_uint`] and [`write_sint`] do, as long as they fit in 64 bits. Decoders then
+/// see an integer, so this is only for peers which accept integers where they expect floats.
+/// Negative zero keeps its sign, and is thus never written as an integer.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::write_f64_compact;
+/// use rmp::Marker;
+///
+/// let mut buf = Vec::new();
+/// assert_eq!(Marker::F32, write_f64_compact(&mut buf, 0.5, false).unwrap());
+/// assert_eq!(Marker::F64, write_f64_compact(&mut buf, 0.1, false).unwrap());
+/// assert_eq!(Marker::F32, write_f64_compact(&mut buf, 42.0, false).unwrap());
+/// assert_eq!(Marker::FixPos(42), write_f64_compact(&mut buf, 42.0, true).unwrap());
+///
+/// assert_eq!(vec![0xca, 0x3f, 0x00, 0x00, 0x00], buf[..5]);
+/// assert_eq!(20, buf.len());
+/// ```
+pub fn write_f64_compact<W: RmpWrite>(wr: &mut W, val: f64, int: bool) -> Result<Marker, ValueWriteError<W::Error>> {
+    // 2^64 and -2^63, the first values out of the ranges of `u64` and `i64`.
+    const U64_END: f64 = 18_446_744_073_709_551_616.0;
+    const I64_MIN: f64 = -9_223_372_036_854_775_808.0;
+
+    if int {
+        if val.is_sign_positive() && val < U64_END && (val as u64) as f64 == val {
+            return write_uint(wr, val as u64);
+        }
+        if (I64_MIN..0.0).contains(&val) && (val as i64) as f64 == val {
+            return write_sint(wr, val as i64);
+        }
+    }
+
+    if f64::from(val as f32) == val {
+        write_f32(wr, val as f32)?;
+        Ok(Marker::F32)
+    } else {
+        write_f64(wr, val)?;
+        Ok(Marker::F64)
+    }
+}
@@ -1 +1,131 @@
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::error;
 
+use super::{write_ext_meta, HeaderBuf, RmpWrite, RmpWriteErr, ValueWriteError};
+use crate::Marker;
+
+/// Encodes and attempts to write an extension with its data, using the most efficient marker for
+/// its length, returning the marker used.
+///
+/// This is [`write_ext_meta`] followed by the data.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// # Panics
+///
+/// Panics if `data` is longer than `u32::MAX` bytes, which can not be encoded.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Marker;
+///
+/// let mut buf = Vec::new();
+///
+/// assert_eq!(Marker::FixExt2, rmp::encode::write_ext(&mut buf, 7, &[0xca, 0xfe]).unwrap());
+/// assert_eq!(Marker::Ext8, rmp::encode::write_ext(&mut buf, 7, &[0x01, 0x02, 0x03]).unwrap());
+///
+/// assert_eq!(vec![0xd5, 0x07, 0xca, 0xfe, 0xc7, 0x03, 0x07, 0x01, 0x02, 0x03], buf);
+/// ```
+#[track_caller]
+pub fn write_ext<W: RmpWrite>(wr: &mut W, ty: i8, data: &[u8]) -> Result<Marker, ValueWriteError<W::Error>> {
+    let len = u32::try_from(data.len()).expect("extension data longer than u32::MAX bytes");
+    let (header, marker) = HeaderBuf::encode(|header| write_ext_meta(header, len, ty));
+    wr.write_header_and_data(header.as_slice(), data)?;
+    Ok(marker)
+}
+
+/// Encodes and attempts to write a timestamp as the extension type `-1`, in the shortest of its
+/// 32, 64 and 96-bit forms that can represent it, returning the marker used.
+///
+/// `secs` is the number of seconds since the Unix epoch, which may be negative, and `nanos` the
+/// number of nanoseconds within that second.
+///
+/// # Errors
+///
+/// This function will return `TimestampWriteError::InvalidNanos` without writing anything if
+/// `nanos` is not less than 1 000 000 000, and `TimestampWriteError::InvalidWrite` on any I/O
+/// error occurred while writing either the marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::TimestampWriteError;
+///
+/// let mut buf = Vec::new();
+///
+/// rmp::encode::write_timestamp(&mut buf, 1, 0).unwrap();
+///
+/// assert_eq!(vec![0xd6, 0xff, 0x00, 0x00, 0x00, 0x01], buf);
+///
+/// let err = rmp::encode::write_timestamp(&mut buf, 1, 1_000_000_000).unwrap_err();
+/// assert!(matches!(err, TimestampWriteError::InvalidNanos(1_000_000_000)));
+/// ```
+pub fn write_timestamp<W: RmpWrite>(wr: &mut W, secs: i64, nanos: u32) -> Result<Marker, TimestampWriteError<W::Error>> {
+    if nanos >= 1_000_000_000 {
+        return Err(TimestampWriteError::InvalidNanos(nanos));
+    }
+    Ok(write_timestamp_data(wr, secs, nanos)?)
+}
+
+fn write_timestamp_data<W: RmpWrite>(wr: &mut W, secs: i64, nanos: u32) -> Result<Marker, ValueWriteError<W::Error>> {
+    match u64::try_from(secs) {
+        Ok(secs) if nanos == 0 && secs <= u64::from(u32::MAX) => {
+            let marker = write_ext_meta(wr, 4, -1)?;
+            wr.write_data_u32(secs as u32)?;
+            Ok(marker)
+        }
+        Ok(secs) if secs < 1 << 34 => {
+            let marker = write_ext_meta(wr, 8, -1)?;
+            wr.write_data_u64(u64::from(nanos) << 34 | secs)?;
+            Ok(marker)
+        }
+        _ => {
+            let marker = write_ext_meta(wr, 12, -1)?;
+            wr.write_data_u32(nanos)?;
+            wr.write_data_i64(secs)?;
+            Ok(marker)
+        }
+    }
+}
+
+/// An error that can occur when attempting to write a timestamp with [`write_timestamp`].
+#[derive(Debug)]
+pub enum TimestampWriteError<E: RmpWriteErr> {
+    /// The nanoseconds are not less than 1 000 000 000.
+    InvalidNanos(u32),
+    /// I/O error while writing the marker or the data.
+    InvalidWrite(ValueWriteError<E>),
+}
+
+impl<E: RmpWriteErr> From<ValueWriteError<E>> for TimestampWriteError<E> {
+    #[cold]
+    fn from(err: ValueWriteError<E>) -> Self {
+        Self::InvalidWrite(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: RmpWriteErr> error::Error for TimestampWriteError<E> {
+    #[cold]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::InvalidNanos(..) => None,
+            Self::InvalidWrite(ref err) => Some(err),
+        }
+    }
+}
+
+impl<E: RmpWriteErr> Display for TimestampWriteError<E> {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::InvalidNanos(nanos) => write!(f, "nanoseconds out of range: {nanos}"),
+            Self::InvalidWrite(..) => f.write_str("error while writing timestamp"),
+        }
+    }
+}
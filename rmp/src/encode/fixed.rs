@@ -0,0 +1,114 @@
+//! Fixed-width aliases of the number writers, see the [module documentation](super#fixed-width-numbers).
+
+use super::{write_f32, write_f64, write_i16, write_i32, write_i64, write_i8, write_u16, write_u32, write_u64, write_u8};
+use super::{RmpWrite, ValueWriteError};
+
+/// Writes an `u8` value as a 2-byte sequence whatever its magnitude, the same as [`write_u8`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_u8_fixed<W: RmpWrite>(wr: &mut W, val: u8) -> Result<(), ValueWriteError<W::Error>> {
+    write_u8(wr, val)
+}
+
+/// Writes an `u16` value as a 3-byte sequence whatever its magnitude, the same as [`write_u16`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_u16_fixed<W: RmpWrite>(wr: &mut W, val: u16) -> Result<(), ValueWriteError<W::Error>> {
+    write_u16(wr, val)
+}
+
+/// Writes an `u32` value as a 5-byte sequence whatever its magnitude, the same as [`write_u32`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_u32_fixed<W: RmpWrite>(wr: &mut W, val: u32) -> Result<(), ValueWriteError<W::Error>> {
+    write_u32(wr, val)
+}
+
+/// Writes an `u64` value as a 9-byte sequence whatever its magnitude, the same as [`write_u64`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_u64_fixed<W: RmpWrite>(wr: &mut W, val: u64) -> Result<(), ValueWriteError<W::Error>> {
+    write_u64(wr, val)
+}
+
+/// Writes an `i8` value as a 2-byte sequence whatever its magnitude, the same as [`write_i8`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_i8_fixed<W: RmpWrite>(wr: &mut W, val: i8) -> Result<(), ValueWriteError<W::Error>> {
+    write_i8(wr, val)
+}
+
+/// Writes an `i16` value as a 3-byte sequence whatever its magnitude, the same as [`write_i16`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_i16_fixed<W: RmpWrite>(wr: &mut W, val: i16) -> Result<(), ValueWriteError<W::Error>> {
+    write_i16(wr, val)
+}
+
+/// Writes an `i32` value as a 5-byte sequence whatever its magnitude, the same as [`write_i32`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_i32_fixed<W: RmpWrite>(wr: &mut W, val: i32) -> Result<(), ValueWriteError<W::Error>> {
+    write_i32(wr, val)
+}
+
+/// Writes an `i64` value as a 9-byte sequence whatever its magnitude, the same as [`write_i64`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_i64_fixed<W: RmpWrite>(wr: &mut W, val: i64) -> Result<(), ValueWriteError<W::Error>> {
+    write_i64(wr, val)
+}
+
+/// Writes an `f32` value as a 5-byte sequence whatever its magnitude, the same as [`write_f32`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_f32_fixed<W: RmpWrite>(wr: &mut W, val: f32) -> Result<(), ValueWriteError<W::Error>> {
+    write_f32(wr, val)
+}
+
+/// Writes an `f64` value as a 9-byte sequence whatever its magnitude, the same as [`write_f64`].
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+#[inline]
+pub fn write_f64_fixed<W: RmpWrite>(wr: &mut W, val: f64) -> Result<(), ValueWriteError<W::Error>> {
+    write_f64(wr, val)
+}
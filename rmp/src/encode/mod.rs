@@ -1,8 +1,34 @@
 //! Provides various functions and structs for MessagePack encoding.
+//!
+//! # Fixed-width numbers
+//!
+//! [`write_uint`] and [`write_sint`] choose the shortest representation of a value, while the
+//! functions named after a type, [`write_u8`] to [`write_u64`], [`write_i8`] to [`write_i64`],
+//! [`write_f32`] and [`write_f64`], always write its full-width marker. Their output has the same
+//! size whatever the value, so a field can be reserved and later overwritten in place. Each also has
+//! an alias with a `_fixed` suffix, such as [`write_u32_fixed`], which spells that out at call sites:
+//!
+//! ```
+//! use rmp::encode::{write_array_len, write_str, write_u32, write_u32_fixed};
+//!
+//! let mut buf = Vec::new();
+//! write_array_len(&mut buf, 2).unwrap();
+//! let pos = buf.len();
+//! write_u32_fixed(&mut buf, 0).unwrap();
+//! write_str(&mut buf, "payload").unwrap();
+//!
+//! // Patch the reserved field once its value is known.
+//! let len = (buf.len() - pos - 5) as u32;
+//! write_u32(&mut &mut buf[pos..pos + 5], len).unwrap();
+//! assert_eq!([0xce, 0x00, 0x00, 0x00, 0x08], buf[pos..pos + 5]);
+//! ```
 
 mod bin;
+#[cfg(feature = "std")]
+mod chunked;
 mod dec;
 mod ext;
+mod fixed;
 mod map;
 mod sint;
 mod str;
@@ -10,7 +36,14 @@ mod uint;
 mod vec;
 
 pub use self::bin::{write_bin, write_bin_len};
-pub use self::dec::{write_f32, write_f64};
+#[cfg(feature = "std")]
+pub use self::chunked::BinWriter;
+pub use self::dec::{write_f32, write_f64, write_f64_compact};
+pub use self::ext::{write_ext, write_timestamp, TimestampWriteError};
+pub use self::fixed::{
+    write_f32_fixed, write_f64_fixed, write_i16_fixed, write_i32_fixed, write_i64_fixed, write_i8_fixed,
+    write_u16_fixed, write_u32_fixed, write_u64_fixed, write_u8_fixed,
+};
 pub use self::sint::{write_i16, write_i32, write_i64, write_i8, write_nfix, write_sint};
 pub use self::str::{write_str, write_str_len};
 pub use self::uint::{write_pfix, write_u16, write_u32, write_u64, write_u8, write_uint, write_uint8};
@@ -22,7 +55,9 @@ use std::error;
 use crate::Marker;
 
 pub mod buffer;
+#[cfg(feature = "alloc")]
 pub use buffer::ByteBuf;
+pub use buffer::{encoded_len_of, BufferFull, CountingWriter, FixedBuf};
 
 #[doc(inline)]
 #[allow(deprecated)]
@@ -108,9 +143,16 @@ mod sealed {
     impl<T: ?Sized + std::io::Write> Sealed for T {}
     #[cfg(not(feature = "std"))]
     impl Sealed for &mut [u8] {}
-    #[cfg(not(feature = "std"))]
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
     impl Sealed for alloc::vec::Vec<u8> {}
+    #[cfg(feature = "alloc")]
     impl Sealed for super::ByteBuf {}
+    #[cfg(not(feature = "std"))]
+    impl Sealed for super::CountingWriter {}
+    impl Sealed for super::HeaderBuf {}
+    impl Sealed for super::FixedBuf<'_> {}
+    #[cfg(feature = "bytes")]
+    impl<B: bytes::BufMut> Sealed for crate::buf::BufMutWriter<B> {}
 }
 
 macro_rules! write_byteorder_utils {
@@ -154,8 +196,32 @@ pub trait RmpWrite: sealed::Sealed {
     /// See also [`std::io::Write::write_all`]
     fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
 
+    /// Write several slices of bytes to the underlying stream, as if they were concatenated.
+    ///
+    /// This will either write all the bytes or return an error. Writers implementing
+    /// [`std::io::Write`] receive the slices in batches through
+    /// [`write_vectored`](std::io::Write::write_vectored), so that a large payload does not need to
+    /// be copied next to its header first.
+    #[inline]
+    fn write_bytes_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), Self::Error> {
+        for buf in bufs {
+            self.write_bytes(buf)?;
+        }
+        Ok(())
+    }
+
     // Internal helper functions to map I/O error into the `DataWriteError` error.
 
+    /// Write the marker and length of a value followed by its data, reporting errors like
+    /// writing the marker and then the rest separately would.
+    #[inline]
+    #[doc(hidden)]
+    fn write_header_and_data(&mut self, header: &[u8], data: &[u8]) -> Result<(), ValueWriteError<Self::Error>> {
+        let (&marker, len) = header.split_first().expect("header starts with a marker");
+        self.write_u8(marker).map_err(ValueWriteError::InvalidMarkerWrite)?;
+        self.write_bytes_vectored(&[len, data]).map_err(ValueWriteError::InvalidDataWrite)
+    }
+
     /// Write a single (signed) byte to this stream.
     #[inline]
     #[doc(hidden)]
@@ -189,6 +255,110 @@ impl<T: std::io::Write> RmpWrite for T {
     fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
         self.write_all(buf)
     }
+
+    #[inline]
+    fn write_bytes_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), Self::Error> {
+        write_all_vectored(self, bufs).map_err(|(_, err)| err)
+    }
+
+    #[inline]
+    fn write_header_and_data(&mut self, header: &[u8], data: &[u8]) -> Result<(), ValueWriteError<Self::Error>> {
+        write_all_vectored(self, &[header, data]).map_err(|(written, err)| {
+            if written == 0 {
+                ValueWriteError::InvalidMarkerWrite(err)
+            } else {
+                ValueWriteError::InvalidDataWrite(err)
+            }
+        })
+    }
+}
+
+/// Writes all the slices with as few calls to `write_vectored` as possible, retrying on EINTR.
+///
+/// On error, also returns the number of bytes that were written.
+#[cfg(feature = "std")]
+fn write_all_vectored<W>(wr: &mut W, mut bufs: &[&[u8]]) -> Result<(), (usize, std::io::Error)>
+    where W: std::io::Write + ?Sized
+{
+    use std::io::{ErrorKind, IoSlice};
+
+    const MAX_SLICES: usize = 8;
+
+    let mut written = 0;
+    // The number of bytes of the first slice which were already written.
+    let mut offset = 0;
+    loop {
+        while bufs.first().is_some_and(|buf| buf.len() == offset) {
+            bufs = &bufs[1..];
+            offset = 0;
+        }
+        if bufs.is_empty() {
+            return Ok(());
+        }
+
+        let count = bufs.len().min(MAX_SLICES);
+        let mut slices = [IoSlice::new(&[]); MAX_SLICES];
+        slices[0] = IoSlice::new(&bufs[0][offset..]);
+        for (slice, buf) in slices[1..count].iter_mut().zip(&bufs[1..count]) {
+            *slice = IoSlice::new(buf);
+        }
+
+        match wr.write_vectored(&slices[..count]) {
+            Ok(0) => return Err((written, ErrorKind::WriteZero.into())),
+            Ok(mut len) => {
+                written += len;
+                while len > 0 {
+                    let rest = bufs[0].len() - offset;
+                    if len < rest {
+                        offset += len;
+                        break;
+                    }
+                    len -= rest;
+                    bufs = &bufs[1..];
+                    offset = 0;
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err((written, err)),
+        }
+    }
+}
+
+/// The marker and length of a value, encoded on the stack before being written along with its
+/// data by [`RmpWrite::write_header_and_data`].
+struct HeaderBuf {
+    buf: [u8; 6],
+    len: usize,
+}
+
+impl HeaderBuf {
+    /// Encodes a header with `f`, which writes at most 6 bytes.
+    #[inline]
+    fn encode<F>(f: F) -> (Self, Marker)
+        where F: FnOnce(&mut Self) -> Result<Marker, ValueWriteError<core::convert::Infallible>>
+    {
+        let mut header = Self { buf: [0; 6], len: 0 };
+        match f(&mut header) {
+            Ok(marker) => (header, marker),
+            Err(ValueWriteError::InvalidMarkerWrite(err) | ValueWriteError::InvalidDataWrite(err)) => match err {},
+        }
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl RmpWrite for HeaderBuf {
+    type Error = core::convert::Infallible;
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.buf[self.len..self.len + buf.len()].copy_from_slice(buf);
+        self.len += buf.len();
+        Ok(())
+    }
 }
 
 /// An error that can occur when attempting to write multi-byte MessagePack value.
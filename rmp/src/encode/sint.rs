@@ -112,7 +112,7 @@ pub fn write_i32<W: RmpWrite>(wr: &mut W, val: i32) -> Result<(), ValueWriteErro
 ///
 /// Note, that this function will encode the given value in 9-byte sequence no matter what, even if
 /// the value can be represented using single byte as a fixnum. Also note, that the first byte will
-/// always be the i16 marker (`0xd3`).
+/// always be the i64 marker (`0xd3`).
 ///
 /// If you need to fit the given buffer efficiently use `write_sint` instead, which automatically
 /// selects the appropriate integer representation.
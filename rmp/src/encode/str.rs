@@ -1,5 +1,5 @@
 use super::{write_marker, RmpWrite};
-use crate::encode::ValueWriteError;
+use crate::encode::{HeaderBuf, ValueWriteError};
 use crate::Marker;
 
 /// Encodes and attempts to write the most efficient string length implementation to the given
@@ -42,6 +42,6 @@ pub fn write_str_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ValueW
 /// marker or the data.
 // TODO: Docs, range check, example, visibility.
 pub fn write_str<W: RmpWrite>(wr: &mut W, data: &str) -> Result<(), ValueWriteError<W::Error>> {
-    write_str_len(wr, data.len() as u32)?;
-    wr.write_bytes(data.as_bytes()).map_err(ValueWriteError::InvalidDataWrite)
+    let (header, _) = HeaderBuf::encode(|header| write_str_len(header, data.len() as u32));
+    wr.write_header_and_data(header.as_slice(), data.as_bytes())
 }
@@ -0,0 +1,61 @@
+//! Asynchronous counterparts of the [`encode`](crate::encode) and [`decode`](crate::decode)
+//! functions, over the [`AsyncRead`] and [`AsyncWrite`] traits of `futures-io`.
+//!
+//! These are the traits of async-std, smol and the `futures` crate. The functions are the same as
+//! the ones of [`rmp::tokio`](crate::tokio), and behave the same way.
+//!
+//! # Examples
+//!
+//! ```
+//! # futures_executor::block_on(async {
+//! use rmp::futures::{read_map_len, read_str_len, write_map_len, write_str};
+//!
+//! let mut buf = Vec::new();
+//! write_map_len(&mut buf, 1).await.unwrap();
+//! write_str(&mut buf, "id").await.unwrap();
+//!
+//! let mut rd = &buf[..];
+//! assert_eq!(1, read_map_len(&mut rd).await.unwrap());
+//! assert_eq!(2, read_str_len(&mut rd).await.unwrap());
+//! assert_eq!(b"id", rd);
+//! # });
+//! ```
+
+use std::future::poll_fn;
+use std::io::{self, ErrorKind};
+use std::mem;
+use std::pin::Pin;
+
+use futures_io::{AsyncRead, AsyncWrite};
+
+/// Fills `buf` from the reader, failing if it ends first.
+async fn read_exact<R>(rd: &mut R, mut buf: &mut [u8]) -> io::Result<()>
+    where R: AsyncRead + Unpin
+{
+    while !buf.is_empty() {
+        match poll_fn(|cx| Pin::new(&mut *rd).poll_read(cx, buf)).await {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(n) => buf = &mut mem::take(&mut buf)[n..],
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Writes the whole `buf` into the writer.
+async fn write_all<W>(wr: &mut W, mut buf: &[u8]) -> io::Result<()>
+    where W: AsyncWrite + Unpin
+{
+    while !buf.is_empty() {
+        match poll_fn(|cx| Pin::new(&mut *wr).poll_write(cx, buf)).await {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => buf = &buf[n..],
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+async_io!();
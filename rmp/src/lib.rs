@@ -1,12 +1,23 @@
 #![doc = include_str!("../README.md")]
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+#[macro_use]
+mod async_io;
+#[cfg(feature = "bytes")]
+pub mod buf;
 pub mod decode;
 pub mod encode;
 mod errors;
+#[cfg(feature = "futures-io")]
+pub mod futures;
 mod marker;
+pub mod stats;
+#[cfg(feature = "tokio")]
+pub mod tokio;
 
 pub use crate::marker::Marker;
 
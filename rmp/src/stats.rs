@@ -0,0 +1,255 @@
+//! Statistics about the composition of encoded data, see [`MarkerStats`].
+
+use core::fmt::{self, Display, Formatter};
+
+use crate::decode::slice::{read_item, Item, SliceReadError};
+use crate::decode::ErrorAt;
+use crate::Marker;
+
+/// A group of markers encoding the same type of value, as counted by [`MarkerStats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Family {
+    /// The nil marker.
+    Nil,
+    /// The true and false markers.
+    Bool,
+    /// The markers of signed and unsigned integers, including the fixints.
+    Int,
+    /// The `f32` and `f64` markers.
+    Float,
+    /// The markers of strings.
+    Str,
+    /// The markers of binaries.
+    Bin,
+    /// The markers of arrays.
+    Array,
+    /// The markers of maps.
+    Map,
+    /// The markers of extensions, including the fixexts.
+    Ext,
+    /// The never used marker `0xc1`.
+    Reserved,
+}
+
+impl Family {
+    /// Every family, in the order of [`MarkerStats::iter`].
+    pub const ALL: [Self; 10] = [
+        Self::Nil,
+        Self::Bool,
+        Self::Int,
+        Self::Float,
+        Self::Str,
+        Self::Bin,
+        Self::Array,
+        Self::Map,
+        Self::Ext,
+        Self::Reserved,
+    ];
+
+    /// Returns the family of the given marker.
+    #[must_use]
+    pub const fn of(marker: Marker) -> Self {
+        match marker {
+            Marker::Null => Self::Nil,
+            Marker::True | Marker::False => Self::Bool,
+            Marker::FixPos(..) | Marker::FixNeg(..) |
+            Marker::U8 | Marker::U16 | Marker::U32 | Marker::U64 |
+            Marker::I8 | Marker::I16 | Marker::I32 | Marker::I64 => Self::Int,
+            Marker::F32 | Marker::F64 => Self::Float,
+            Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => Self::Str,
+            Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => Self::Bin,
+            Marker::FixArray(..) | Marker::Array16 | Marker::Array32 => Self::Array,
+            Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => Self::Map,
+            Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 | Marker::FixExt16 |
+            Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => Self::Ext,
+            Marker::Reserved => Self::Reserved,
+        }
+    }
+
+    /// Returns the name of the family, as shown by the `Display` implementation of
+    /// [`MarkerStats`].
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Nil => "nil",
+            Self::Bool => "bool",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Str => "str",
+            Self::Bin => "bin",
+            Self::Array => "array",
+            Self::Map => "map",
+            Self::Ext => "ext",
+            Self::Reserved => "reserved",
+        }
+    }
+}
+
+/// The number of values of a [`Family`] and the bytes they take.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Counts {
+    /// The number of values.
+    pub count: u64,
+    /// The number of bytes of the values, from their marker to the end of their payload. Arrays
+    /// and maps only count their header, their elements being counted as values of their own.
+    pub bytes: u64,
+}
+
+impl Counts {
+    #[inline]
+    fn add(&mut self, other: Self) {
+        self.count += other.count;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Counts the values and the bytes of encoded data per [`Family`] of markers, to analyze the
+/// composition of the traffic of an application.
+///
+/// The encoded bytes are passed to [`record`](Self::record), either after encoding a message into
+/// a buffer or before decoding one, so the collector works alongside any encoder or decoder. It
+/// neither allocates nor recurses, and is cheap enough to sample production traffic. Collectors of
+/// several threads can be combined with [`merge`](Self::merge).
+///
+/// # Examples
+///
+/// ```
+/// use rmp::stats::{Counts, Family, MarkerStats};
+///
+/// let mut buf = Vec::new();
+/// rmp::encode::write_map_len(&mut buf, 1).unwrap();
+/// rmp::encode::write_str(&mut buf, "temperature").unwrap();
+/// rmp::encode::write_f64(&mut buf, 21.5).unwrap();
+///
+/// let mut stats = MarkerStats::new();
+/// stats.record(&buf).unwrap();
+///
+/// assert_eq!(Counts { count: 1, bytes: 12 }, stats.get(Family::Str));
+/// assert_eq!(Counts { count: 1, bytes: 9 }, stats.get(Family::Float));
+/// assert_eq!(Counts { count: 3, bytes: 22 }, stats.total());
+/// assert_eq!(1, stats.messages());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MarkerStats {
+    families: [Counts; Family::ALL.len()],
+    messages: u64,
+}
+
+impl MarkerStats {
+    /// Creates an empty collector.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { families: [Counts { count: 0, bytes: 0 }; Family::ALL.len()], messages: 0 }
+    }
+
+    /// Records every value of `buf`, which may contain several concatenated messages, returning
+    /// the number of messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SliceReadError`] along with the offset of the value that could not be read, for
+    /// example because the input ends in the middle of it, or is the reserved marker. The values
+    /// before it are still recorded, and so is the reserved marker, but the message they belong to
+    /// is not counted.
+    pub fn record(&mut self, buf: &[u8]) -> Result<u64, ErrorAt<SliceReadError>> {
+        let mut rd = buf;
+        let mut messages = 0;
+        let mut remaining = 0u64;
+
+        while !rd.is_empty() {
+            if remaining == 0 {
+                remaining = 1;
+            }
+            let marker = Marker::from_u8(rd[0]);
+            let start = rd;
+            let item = match read_item(&mut rd) {
+                Ok(item) => item,
+                Err(err) => {
+                    if marker == Marker::Reserved {
+                        self.add(Family::Reserved, 1);
+                    }
+                    self.messages += messages;
+                    return Err(err.at(buf, start));
+                }
+            };
+            self.add(Family::of(marker), start.len() - rd.len());
+
+            remaining -= 1;
+            match item {
+                Item::Array(len) => remaining += u64::from(len),
+                Item::Map(len) => remaining += u64::from(len) * 2,
+                _ => {}
+            }
+            if remaining == 0 {
+                messages += 1;
+            }
+        }
+
+        self.messages += messages;
+        if remaining > 0 {
+            return Err(SliceReadError::InsufficientBytes(1).at(buf, rd));
+        }
+        Ok(messages)
+    }
+
+    #[inline]
+    fn add(&mut self, family: Family, bytes: usize) {
+        self.families[family as usize].add(Counts { count: 1, bytes: bytes as u64 });
+    }
+
+    /// Returns the counts of the given family.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, family: Family) -> Counts {
+        self.families[family as usize]
+    }
+
+    /// Returns the counts of every family, in the order of [`Family::ALL`].
+    pub fn iter(&self) -> impl Iterator<Item = (Family, Counts)> + '_ {
+        Family::ALL.iter().map(move |&family| (family, self.get(family)))
+    }
+
+    /// Returns the counts of all families together.
+    #[must_use]
+    pub fn total(&self) -> Counts {
+        let mut total = Counts::default();
+        for counts in &self.families {
+            total.add(*counts);
+        }
+        total
+    }
+
+    /// Returns the number of complete messages recorded.
+    #[inline]
+    #[must_use]
+    pub fn messages(&self) -> u64 {
+        self.messages
+    }
+
+    /// Adds the counts of `other` to this collector.
+    pub fn merge(&mut self, other: &Self) {
+        for (counts, other) in self.families.iter_mut().zip(&other.families) {
+            counts.add(*other);
+        }
+        self.messages += other.messages;
+    }
+
+    /// Forgets everything recorded so far.
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Shows a table of the families that were seen, with their share of the bytes.
+impl Display for MarkerStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        writeln!(f, "{:<8} {:>12} {:>14} {:>7}", "family", "count", "bytes", "share")?;
+        for (family, counts) in self.iter().filter(|(_, counts)| counts.count > 0) {
+            let share = counts.bytes as f64 * 100.0 / total.bytes as f64;
+            writeln!(f, "{:<8} {:>12} {:>14} {:>6.1}%", family.name(), counts.count, counts.bytes, share)?;
+        }
+        write!(f, "{:<8} {:>12} {:>14} in {} messages", "total", total.count, total.bytes, self.messages)
+    }
+}
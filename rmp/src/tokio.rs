@@ -0,0 +1,52 @@
+//! Asynchronous counterparts of the [`encode`](crate::encode) and [`decode`](crate::decode)
+//! functions, over tokio's [`AsyncRead`] and [`AsyncWrite`].
+//!
+//! Every function reads or writes exactly the bytes its synchronous counterpart does, and fails
+//! the same way, so messages can be processed as they arrive instead of being buffered first.
+//! Like with the synchronous API, the payloads of strings, binaries and extensions are read and
+//! written directly with the reader or writer, after their length.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! use rmp::tokio::{read_array_len, read_int, read_str_len, write_array_len, write_str, write_uint};
+//! use tokio::io::AsyncReadExt;
+//!
+//! let mut buf = Vec::new();
+//! write_array_len(&mut buf, 2).await.unwrap();
+//! write_uint(&mut buf, 300).await.unwrap();
+//! write_str(&mut buf, "le message").await.unwrap();
+//!
+//! let mut rd = &buf[..];
+//! assert_eq!(2, read_array_len(&mut rd).await.unwrap());
+//! assert_eq!(300u16, read_int(&mut rd).await.unwrap());
+//!
+//! let mut data = vec![0; read_str_len(&mut rd).await.unwrap() as usize];
+//! rd.read_exact(&mut data).await.unwrap();
+//! assert_eq!(b"le message", &data[..]);
+//! # }
+//! ```
+
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Fills `buf` from the reader, failing if it ends first.
+#[inline]
+async fn read_exact<R>(rd: &mut R, buf: &mut [u8]) -> io::Result<()>
+    where R: AsyncRead + Unpin
+{
+    rd.read_exact(buf).await.map(drop)
+}
+
+/// Writes the whole `buf` into the writer.
+#[inline]
+async fn write_all<W>(wr: &mut W, buf: &[u8]) -> io::Result<()>
+    where W: AsyncWrite + Unpin
+{
+    wr.write_all(buf).await
+}
+
+async_io!();
@@ -0,0 +1,61 @@
+use bytes::{Buf, Bytes, BytesMut};
+use rmp::buf::{BufMutWriter, BufReader};
+use rmp::decode::bytes::BytesReadError;
+use rmp::decode::{self, ValueReadError};
+use rmp::encode::{self, BufferFull, ValueWriteError};
+use rmp::Marker;
+
+#[test]
+fn pass_roundtrip() {
+    let mut wr = BufMutWriter::new(BytesMut::new());
+    encode::write_array_len(&mut wr, 2).unwrap();
+    encode::write_uint(&mut wr, 300).unwrap();
+    encode::write_str(&mut wr, "le message").unwrap();
+
+    let mut rd = BufReader::new(wr.into_inner().freeze());
+    assert_eq!(2, decode::read_array_len(&mut rd).unwrap());
+    assert_eq!(300u16, decode::read_int(&mut rd).unwrap());
+    assert_eq!(10, decode::read_str_len(&mut rd).unwrap());
+    assert_eq!(5, rd.position());
+    assert_eq!(&b"le message"[..], &rd.get_ref()[..]);
+}
+
+#[test]
+fn pass_read_chained_buffers() {
+    // A u32 split across two buffers.
+    let buf = Bytes::from_static(&[0xce, 0x00, 0x01]).chain(Bytes::from_static(&[0x00, 0x00, 0xc0]));
+    let mut rd = BufReader::new(buf);
+
+    assert_eq!(65536u32, decode::read_u32(&mut rd).unwrap());
+    decode::read_nil(&mut rd).unwrap();
+    assert_eq!(6, rd.position());
+}
+
+#[test]
+fn fail_read_truncated_reports_position() {
+    let mut rd = BufReader::new(Bytes::from_static(&[0xc0, 0xcd, 0x01]));
+    decode::read_nil(&mut rd).unwrap();
+
+    match decode::read_u16(&mut rd) {
+        Err(ValueReadError::InvalidDataRead(BytesReadError::InsufficientBytes { expected: 2, actual: 1, position: 2 })) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_write_into_vec() {
+    let mut wr = BufMutWriter::new(Vec::new());
+    assert_eq!(Marker::U16, encode::write_uint(&mut wr, 300).unwrap());
+    assert_eq!(&[0xcd, 0x01, 0x2c][..], &wr.get_ref()[..]);
+}
+
+#[test]
+fn fail_write_past_limit() {
+    let mut wr = BufMutWriter::new(bytes::BufMut::limit(BytesMut::new(), 4));
+
+    match encode::write_str(&mut wr, "abcd") {
+        Err(ValueWriteError::InvalidDataWrite(BufferFull { needed: 1 })) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(&[0xa4][..], &wr.get_ref().get_ref()[..]);
+}
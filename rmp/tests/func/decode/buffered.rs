@@ -0,0 +1,88 @@
+use std::io::{BufReader, ErrorKind};
+
+use rmp::decode::slice::{Item, SliceReadError};
+use rmp::decode::*;
+
+#[test]
+fn from_buffered_every_capacity() {
+    let mut buf = Vec::new();
+    rmp::encode::write_array_len(&mut buf, 4).unwrap();
+    rmp::encode::write_u64(&mut buf, u64::MAX).unwrap();
+    rmp::encode::write_str(&mut buf, "le message").unwrap();
+    rmp::encode::write_f64(&mut buf, 1.5).unwrap();
+    rmp::encode::write_ext(&mut buf, 3, &[0xaa; 20]).unwrap();
+
+    // Small capacities make every read span the end of the internal buffer.
+    for capacity in 1..=buf.len() + 1 {
+        let mut rd = Buffered::new(BufReader::with_capacity(capacity, &buf[..]));
+
+        assert_eq!(4, read_array_len(&mut rd).unwrap());
+        assert_eq!(u64::MAX, read_int::<u64, _>(&mut rd).unwrap());
+        let mut out = [0; 16];
+        assert_eq!("le message", read_str(&mut rd, &mut out).unwrap());
+        assert_eq!(1.5, read_f64(&mut rd).unwrap());
+        assert_eq!((3, vec![0xaa; 20]), read_ext(&mut rd).unwrap());
+
+        match read_marker(&mut rd) {
+            Err(MarkerReadError(err)) => assert_eq!(ErrorKind::UnexpectedEof, err.kind()),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn from_buffered_truncated() {
+    let buf: &[u8] = &[0xcd, 0x01];
+    let mut rd = Buffered::new(BufReader::with_capacity(1, buf));
+
+    match read_int::<u16, _>(&mut rd) {
+        Err(NumValueReadError::InvalidDataRead(err)) => assert_eq!(ErrorKind::UnexpectedEof, err.kind()),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_buffered_items_every_capacity() {
+    let mut buf = Vec::new();
+    rmp::encode::write_array_len(&mut buf, 4).unwrap();
+    rmp::encode::write_u64(&mut buf, u64::MAX).unwrap();
+    rmp::encode::write_str(&mut buf, &"le message".repeat(30)).unwrap();
+    rmp::encode::write_f64(&mut buf, 1.5).unwrap();
+    rmp::encode::write_ext(&mut buf, 3, &[0xaa; 20]).unwrap();
+
+    for capacity in 1..=buf.len() + 1 {
+        let mut rd = Buffered::new(BufReader::with_capacity(capacity, &buf[..]));
+
+        assert!(rd.read_item_with(|item| item == Item::Array(4)).unwrap());
+        assert!(rd.read_item_with(|item| item == Item::Uint(u64::MAX)).unwrap());
+        assert_eq!(Some("le message".repeat(30)), rd.read_item_with(|item| item.as_str().map(str::to_owned)).unwrap());
+        assert!(rd.read_item_with(|item| item == Item::F64(1.5)).unwrap());
+        assert!(rd.read_item_with(|item| item == Item::Ext(3, &[0xaa; 20])).unwrap());
+
+        let err = rd.read_item_with(|_| ()).unwrap_err();
+        assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+    }
+}
+
+#[test]
+fn from_buffered_item_borrows_buffer() {
+    let buf = [0xa2, b'h', b'i', 0xc4, 0x01, 0xff];
+    let mut rd = Buffered::new(&buf[..]);
+
+    assert_eq!(buf[1..].as_ptr(), rd.read_item_with(|item| item.as_str().unwrap().as_ptr()).unwrap());
+    assert_eq!(buf[5..].as_ptr(), rd.read_item_with(|item| match item {
+        Item::Bin(data) => data.as_ptr(),
+        other => panic!("unexpected item: {other:?}"),
+    }).unwrap());
+}
+
+#[test]
+fn from_buffered_item_errors() {
+    let mut rd = Buffered::new(BufReader::with_capacity(2, &[0xc6, 0xff, 0xff, 0xff, 0xff, 0x00][..]));
+    assert_eq!(ErrorKind::UnexpectedEof, rd.read_item_with(|_| ()).unwrap_err().kind());
+
+    let mut rd = Buffered::new(&[0xc1][..]);
+    let err = rd.read_item_with(|_| ()).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+    assert_eq!(Some(&SliceReadError::TypeMismatch(rmp::Marker::Reserved)), err.get_ref().and_then(|err| err.downcast_ref()));
+}
@@ -143,3 +143,89 @@ fn from_ext32_read_ext_meta() {
     assert_eq!(ExtMeta { typeid: 1, size: 4294967295 }, read_ext_meta(&mut cur).unwrap());
     assert_eq!(6, cur.position());
 }
+
+#[test]
+fn from_timestamp_read_timestamp() {
+    let mut buf = Vec::new();
+    for &(secs, nanos) in &[(0, 0), (4294967295, 0), (4294967296, 0), (17179869183, 999_999_999), (17179869184, 0), (-1, 1), (i64::MIN, 0)] {
+        buf.clear();
+        rmp::encode::write_timestamp(&mut buf, secs, nanos).unwrap();
+
+        let mut cur = Cursor::new(&buf[..]);
+        assert_eq!((secs, nanos), read_timestamp(&mut cur).unwrap());
+        assert_eq!(buf.len() as u64, cur.position());
+    }
+}
+
+#[test]
+fn from_fixext4_read_timestamp_wrong_type() {
+    let buf: &[u8] = &[0xd6, 0x01, 0x00, 0x00, 0x00, 0x00];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(TimestampReadError::ExtTypeMismatch(1)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_fixext2_read_timestamp_invalid_length() {
+    let buf: &[u8] = &[0xd5, 0xff, 0x00, 0x00];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(TimestampReadError::InvalidLength(2)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_fixext8_read_timestamp_invalid_nanos() {
+    // 1 000 000 000 nanoseconds, shifted by 34 bits.
+    let buf: &[u8] = &[0xd7, 0xff, 0xee, 0x6b, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(TimestampReadError::InvalidNanos(1_000_000_000)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn from_ext_read_ext() {
+    for &len in &[0, 1, 3, 16, 300, 70000] {
+        let data = vec![0xaa; len];
+        let mut buf = Vec::new();
+        rmp::encode::write_ext(&mut buf, 9, &data).unwrap();
+
+        let mut cur = Cursor::new(&buf[..]);
+        assert_eq!((9, data), read_ext(&mut cur).unwrap());
+        assert_eq!(buf.len() as u64, cur.position());
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn from_truncated_ext32_read_ext() {
+    // Announces 4 GiB of data, but holds only 2 bytes.
+    let buf: &[u8] = &[0xc9, 0xff, 0xff, 0xff, 0xff, 0x01, 0xaa, 0xbb];
+    let mut cur = Cursor::new(buf);
+
+    match read_ext(&mut cur) {
+        Err(ValueReadError::InvalidDataRead(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn from_str_read_ext_type_mismatch() {
+    let buf: &[u8] = &[0xa1, 0x61];
+    let mut cur = Cursor::new(buf);
+
+    match read_ext(&mut cur) {
+        Err(ValueReadError::TypeMismatch(rmp::Marker::FixStr(1))) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
@@ -1,13 +1,23 @@
 mod array;
 mod bin;
 mod bool;
+#[cfg(feature = "std")]
+mod buffered;
 mod ext;
 mod float;
 mod map;
 mod null;
+mod offset;
+mod peek;
+mod raw;
 mod sint;
+mod skip;
+mod slice;
 mod string;
+#[cfg(feature = "alloc")]
+mod token;
 mod uint;
+mod validate;
 
 #[cfg(feature = "std")]
 pub type Cursor<'a> = std::io::Cursor<&'a [u8]>;
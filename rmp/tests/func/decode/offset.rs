@@ -0,0 +1,39 @@
+use rmp::decode::slice::{self, SliceReadError};
+use rmp::decode::*;
+use rmp::Marker;
+
+#[test]
+fn from_offset_reader_type_mismatch() {
+    let buf: &[u8] = &[0x92, 0x01, 0xa1, 0x78];
+    let mut rd = OffsetReader::new(Bytes::new(buf));
+
+    assert_eq!(2, rd.read_at(read_array_len).unwrap());
+    assert_eq!(1u8, rd.read_at(read_int).unwrap());
+    match rd.read_at(read_int::<u8, _>) {
+        Err(ErrorAt { offset: 2, error: NumValueReadError::TypeMismatch(Marker::FixStr(1)) }) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_offset_reader_truncated() {
+    let buf: &[u8] = &[0xc0, 0xcd, 0x01];
+    let mut rd = OffsetReader::new(Bytes::new(buf));
+
+    rd.read_at(read_nil).unwrap();
+    let err = rd.read_at(read_int::<u16, _>).unwrap_err();
+    assert_eq!(1, err.offset);
+    assert_eq!(2, rd.offset());
+    assert!(err.to_string().ends_with("at offset 1"), "{err}");
+}
+
+#[test]
+fn from_slice_error_at() {
+    let buf: &[u8] = &[0xc0, 0xc0, 0xc3];
+    let mut rd = buf;
+
+    slice::read_nil(&mut rd).unwrap();
+    slice::read_nil(&mut rd).unwrap();
+    let err = slice::read_nil(&mut rd).map_err(|err| err.at(buf, rd)).unwrap_err();
+    assert_eq!(ErrorAt { offset: 2, error: SliceReadError::TypeMismatch(Marker::True) }, err);
+}
@@ -0,0 +1,34 @@
+use rmp::decode::slice::{self, SliceReadError};
+use rmp::Marker;
+
+#[test]
+fn from_slice_peek_marker() {
+    let buf: &[u8] = &[0xcd, 0x01, 0x2c];
+
+    assert_eq!(Ok(Marker::U16), slice::peek_marker(buf));
+    assert_eq!(Ok(Marker::FixPos(1)), slice::peek_marker(&buf[1..]));
+    assert_eq!(Err(SliceReadError::InsufficientBytes(1)), slice::peek_marker(&[]));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn from_buf_read_peek_marker() {
+    use std::io::{BufReader, ErrorKind};
+
+    use rmp::decode::{peek_marker, read_int, MarkerReadError};
+
+    let buf: &[u8] = &[0xcd, 0x01, 0x2c, 0xa0];
+    // A capacity of one byte makes the buffer refill between every value.
+    let mut rd = BufReader::with_capacity(1, buf);
+
+    assert_eq!(Marker::U16, peek_marker(&mut rd).unwrap());
+    assert_eq!(Marker::U16, peek_marker(&mut rd).unwrap());
+    assert_eq!(300u16, read_int(&mut rd).unwrap());
+    assert_eq!(Marker::FixStr(0), peek_marker(&mut rd).unwrap());
+    rmp::decode::read_str_len(&mut rd).unwrap();
+
+    match peek_marker(&mut rd) {
+        Err(MarkerReadError(err)) => assert_eq!(ErrorKind::UnexpectedEof, err.kind()),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
@@ -0,0 +1,94 @@
+use super::Cursor;
+
+use rmp::decode::raw::*;
+
+#[test]
+fn from_raw_u8() {
+    let buf = [0x00, 0xff];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert_eq!(0, read_u8(&mut cur).unwrap());
+    assert_eq!(255, read_u8(&mut cur).unwrap());
+    assert_eq!(2, cur.position());
+}
+
+#[test]
+fn from_raw_u16() {
+    let buf = [0x01, 0x02];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert_eq!(0x0102, read_u16(&mut cur).unwrap());
+    assert_eq!(2, cur.position());
+}
+
+#[test]
+fn from_raw_u32() {
+    let buf = [0x01, 0x02, 0x03, 0x04];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert_eq!(0x01020304, read_u32(&mut cur).unwrap());
+    assert_eq!(4, cur.position());
+}
+
+#[test]
+fn from_raw_u64() {
+    let buf = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert_eq!(0x0102030405060708, read_u64(&mut cur).unwrap());
+    assert_eq!(8, cur.position());
+}
+
+#[test]
+fn from_raw_i8() {
+    let buf = [0x7f, 0x80];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert_eq!(127, read_i8(&mut cur).unwrap());
+    assert_eq!(-128, read_i8(&mut cur).unwrap());
+    assert_eq!(2, cur.position());
+}
+
+#[test]
+fn from_raw_i16() {
+    let buf = [0xff, 0xfe];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert_eq!(-2, read_i16(&mut cur).unwrap());
+    assert_eq!(2, cur.position());
+}
+
+#[test]
+fn from_raw_i32() {
+    let buf = [0x80, 0x00, 0x00, 0x00];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert_eq!(i32::MIN, read_i32(&mut cur).unwrap());
+    assert_eq!(4, cur.position());
+}
+
+#[test]
+fn from_raw_i64() {
+    let buf = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert_eq!(-1, read_i64(&mut cur).unwrap());
+    assert_eq!(8, cur.position());
+}
+
+#[test]
+fn from_raw_u32_insufficient_data() {
+    let buf = [0x01, 0x02, 0x03];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert!(read_u32(&mut cur).is_err());
+}
+
+#[test]
+fn from_raw_does_not_expect_marker() {
+    // 0xcc is the u8 marker, but raw readers treat it as plain data.
+    let buf = [0xcc, 0x01];
+    let mut cur = Cursor::new(&buf[..]);
+
+    assert_eq!(0xcc01, read_u16(&mut cur).unwrap());
+}
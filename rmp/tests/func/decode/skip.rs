@@ -0,0 +1,96 @@
+use super::Cursor;
+
+use rmp::decode::slice::ReservedPolicy;
+use rmp::decode::*;
+use rmp::Marker;
+
+#[test]
+fn from_nested_skip_value() {
+    // [[1, "two"], {"k": nil, "bin": [0xca, 0xfe]}, 1.5, ext(5, [1, 2, 3])], true
+    let buf: &[u8] = &[
+        0x94, 0x92, 0x01, 0xa3, 0x74, 0x77, 0x6f, 0x82, 0xa1, 0x6b, 0xc0, 0xa3, 0x62, 0x69, 0x6e, 0xc4, 0x02, 0xca,
+        0xfe, 0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc7, 0x03, 0x05, 0x01, 0x02, 0x03, 0xc3,
+    ];
+    let mut cur = Cursor::new(buf);
+
+    skip_value(&mut cur).unwrap();
+    assert_eq!(buf.len() as u64 - 1, cur.position());
+    assert!(read_bool(&mut cur).unwrap());
+}
+
+#[test]
+fn from_scalars_skip_value() {
+    let bufs: &[&[u8]] = &[
+        &[0x2a],
+        &[0xe0],
+        &[0xc0],
+        &[0xcd, 0x01, 0x00],
+        &[0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        &[0xca, 0x3f, 0x80, 0x00, 0x00],
+        &[0xd4, 0x01, 0x00],
+        &[0xd8, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        &[0x80],
+        &[0xdc, 0x00, 0x00],
+    ];
+
+    for buf in bufs {
+        let mut cur = Cursor::new(buf);
+        skip_value(&mut cur).unwrap();
+        assert_eq!(buf.len() as u64, cur.position());
+    }
+}
+
+#[test]
+fn from_large_bin_skip_value() {
+    let mut buf = vec![0xc6, 0x00, 0x01, 0x00, 0x01];
+    buf.resize(5 + 65537, 0xaa);
+    buf.push(0xc0);
+    let mut cur = Cursor::new(&buf[..]);
+
+    skip_value(&mut cur).unwrap();
+    assert_eq!(5 + 65537, cur.position());
+}
+
+#[test]
+fn from_truncated_skip_value() {
+    let buf: &[u8] = &[0x92, 0x01, 0xa3, 0x74, 0x77];
+    let mut cur = Cursor::new(buf);
+
+    match skip_value(&mut cur) {
+        Err(ValueReadError::InvalidDataRead(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_reserved_skip_value() {
+    let buf: &[u8] = &[0x91, 0xc1];
+    let mut cur = Cursor::new(buf);
+
+    match skip_value(&mut cur) {
+        Err(ValueReadError::TypeMismatch(Marker::Reserved)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_reserved_skip_value_with() {
+    // {"a": [0xc1, 0xc1]}, 5
+    let buf: &[u8] = &[0x81, 0xa1, b'a', 0x92, 0xc1, 0xc1, 0x05];
+
+    let mut cur = Cursor::new(buf);
+    skip_value_with(&mut cur, ReservedPolicy::Token).unwrap();
+    assert_eq!(6, cur.position());
+
+    let mut cur = Cursor::new(buf);
+    match skip_value_with(&mut cur, ReservedPolicy::Skip) {
+        Err(ValueReadError::InvalidMarkerRead(..)) => assert_eq!(7, cur.position()),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let mut cur = Cursor::new(buf);
+    match skip_value_with(&mut cur, ReservedPolicy::Error) {
+        Err(ValueReadError::TypeMismatch(Marker::Reserved)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
@@ -0,0 +1,86 @@
+use rmp::decode::slice::*;
+use rmp::Marker;
+
+#[test]
+fn from_slice_read_every_item() {
+    let buf: &[u8] = &[
+        0x8a, 0xc0, 0xc2, 0x7f, 0xe0, 0xcd, 0x01, 0x2c, 0xd0, 0x05, 0xca, 0x3f, 0x80, 0x00, 0x00,
+        0xd9, 0x02, 0x69, 0x64, 0xc4, 0x01, 0xff, 0xdd, 0x00, 0x01, 0x00, 0x00, 0xd5, 0x07, 0xca, 0xfe,
+        0xc7, 0x00, 0xfe,
+    ];
+    let mut rd = buf;
+
+    assert_eq!(Ok(Item::Map(10)), read_item(&mut rd));
+    assert_eq!(Ok(Item::Nil), read_item(&mut rd));
+    assert_eq!(Ok(Item::Boolean(false)), read_item(&mut rd));
+    assert_eq!(Ok(Item::Uint(127)), read_item(&mut rd));
+    assert_eq!(Ok(Item::Int(-32)), read_item(&mut rd));
+    assert_eq!(Ok(Item::Uint(300)), read_item(&mut rd));
+    assert_eq!(Ok(Item::Int(5)), read_item(&mut rd));
+    assert_eq!(Ok(Item::F32(1.0)), read_item(&mut rd));
+    assert_eq!(Ok(Item::Str(b"id")), read_item(&mut rd));
+    assert_eq!(Ok(Item::Bin(&[0xff])), read_item(&mut rd));
+    assert_eq!(Ok(Item::Array(65536)), read_item(&mut rd));
+    assert_eq!(Ok(Item::Ext(7, &[0xca, 0xfe])), read_item(&mut rd));
+    assert_eq!(Ok(Item::Ext(-2, &[])), read_item(&mut rd));
+    assert!(rd.is_empty());
+}
+
+#[test]
+fn from_slice_typed_reads() {
+    let buf: &[u8] = &[0xc3, 0xd1, 0xfe, 0xd4, 0xa2, 0x69, 0x64, 0xc4, 0x00, 0x92, 0x80, 0xd4, 0x01, 0x02, 0xcb,
+                       0x3f, 0xe0, 0, 0, 0, 0, 0, 0, 0xc0];
+    let mut rd = buf;
+
+    assert_eq!(Ok(true), read_bool(&mut rd));
+    assert_eq!(Ok(-300i32), read_int(&mut rd));
+    assert_eq!(Ok("id"), read_str(&mut rd));
+    assert_eq!(Ok(&[][..]), read_bin(&mut rd));
+    assert_eq!(Ok(2), read_array_len(&mut rd));
+    assert_eq!(Ok(0), read_map_len(&mut rd));
+    assert_eq!(Ok((1, &[0x02][..])), read_ext(&mut rd));
+    assert_eq!(Ok(0.5), read_f64(&mut rd));
+    assert_eq!(Ok(()), read_nil(&mut rd));
+    assert!(rd.is_empty());
+}
+
+#[test]
+fn from_slice_errors_leave_input_untouched() {
+    let buf: &[u8] = &[0xa3, 0x61, 0x62];
+    let mut rd = buf;
+    assert_eq!(Err(SliceReadError::InsufficientBytes(1)), read_item(&mut rd));
+    assert_eq!(buf, rd);
+
+    let buf: &[u8] = &[0xa1, 0xff];
+    let mut rd = buf;
+    assert!(matches!(read_str(&mut rd), Err(SliceReadError::InvalidUtf8(..))));
+    assert_eq!(Ok(Item::Str(&[0xff])), read_item(&mut rd));
+
+    let buf: &[u8] = &[0xcc, 0xff];
+    let mut rd = buf;
+    assert_eq!(Err(SliceReadError::OutOfRange), read_int::<i8>(&mut rd));
+    assert_eq!(Err(SliceReadError::TypeMismatch(Marker::U8)), read_bool(&mut rd));
+    assert_eq!(Ok(255u8), read_int(&mut rd));
+
+    assert_eq!(Err(SliceReadError::TypeMismatch(Marker::Reserved)), read_item(&mut &[0xc1][..]));
+    assert_eq!(Err(SliceReadError::InsufficientBytes(1)), read_item(&mut &[][..]));
+}
+
+#[test]
+fn from_slice_ref_reads() {
+    // "id", bin [0xca, 0xfe], ext(7, [0x01]), nil
+    let buf: &[u8] = &[0xa2, 0x69, 0x64, 0xc4, 0x02, 0xca, 0xfe, 0xd4, 0x07, 0x01, 0xc0];
+
+    let (val, tail) = read_str_ref(buf).unwrap();
+    assert_eq!("id", val);
+    let (data, tail) = read_bin_ref(tail).unwrap();
+    assert_eq!(&[0xca, 0xfe], data);
+    let (ty, data, tail) = read_ext_ref(tail).unwrap();
+    assert_eq!((7, &[0x01][..]), (ty, data));
+    assert_eq!(&[0xc0], tail);
+
+    assert_eq!(Err(SliceReadError::TypeMismatch(Marker::Null)), read_str_ref(tail));
+    assert_eq!(Err(SliceReadError::TypeMismatch(Marker::FixStr(2))), read_bin_ref(buf));
+    assert_eq!(Err(SliceReadError::TypeMismatch(Marker::FixStr(2))), read_ext_ref(buf));
+    assert!(matches!(read_str_ref(&[0xa1, 0xff]), Err(SliceReadError::InvalidUtf8(..))));
+}
@@ -0,0 +1,110 @@
+use rmp::decode::slice::SliceReadError;
+use rmp::decode::token::{Token, Tokenizer};
+
+#[test]
+fn from_nested_tokenizer() {
+    // [{"k": nil}, [], 1.5, ext(5, [1, 2])], true
+    let buf: &[u8] = &[
+        0x94, 0x81, 0xa1, 0x6b, 0xc0, 0x90, 0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xd5, 0x05, 0x01,
+        0x02, 0xc3,
+    ];
+    let tokens: Result<Vec<_>, _> = Tokenizer::new(buf).collect();
+
+    assert_eq!(
+        vec![
+            (0, Token::ArrayStart(4)),
+            (1, Token::MapStart(1)),
+            (2, Token::Str(b"k")),
+            (4, Token::Nil),
+            (5, Token::MapEnd),
+            (5, Token::ArrayStart(0)),
+            (6, Token::ArrayEnd),
+            (6, Token::F64(1.5)),
+            (15, Token::Ext(5, &[0x01, 0x02])),
+            (19, Token::ArrayEnd),
+            (19, Token::Boolean(true)),
+        ],
+        tokens.unwrap()
+    );
+}
+
+#[test]
+fn from_empty_tokenizer() {
+    assert_eq!(None, Tokenizer::new(&[]).next());
+}
+
+#[test]
+fn from_truncated_tokenizer() {
+    let buf: &[u8] = &[0x92, 0x01];
+    let mut tokens = Tokenizer::new(buf);
+
+    assert_eq!(Some(Ok((0, Token::ArrayStart(2)))), tokens.next());
+    assert_eq!(1, tokens.depth());
+    assert_eq!(Some(Ok((1, Token::Uint(1)))), tokens.next());
+    assert_eq!(Some(Err(SliceReadError::InsufficientBytes(1))), tokens.next());
+    assert_eq!(None, tokens.next());
+    assert_eq!(2, tokens.offset());
+}
+
+#[test]
+fn from_reserved_tokenizer() {
+    let buf: &[u8] = &[0xc0, 0xc1, 0xc0];
+    let mut tokens = Tokenizer::new(buf);
+
+    assert_eq!(Some(Ok((0, Token::Nil))), tokens.next());
+    assert!(matches!(tokens.next(), Some(Err(SliceReadError::TypeMismatch(..)))));
+    assert_eq!(&[0xc1, 0xc0], tokens.remaining());
+    assert_eq!(None, tokens.next());
+}
+
+#[test]
+fn from_reserved_skip_tokenizer() {
+    use rmp::decode::slice::ReservedPolicy;
+
+    // 0xc1, [0xc1, nil], 0xc1
+    let buf: &[u8] = &[0xc1, 0x91, 0xc1, 0xc0, 0xc1];
+    let tokens: Result<Vec<_>, _> = Tokenizer::new(buf).reserved(ReservedPolicy::Skip).collect();
+
+    assert_eq!(vec![(1, Token::ArrayStart(1)), (3, Token::Nil), (4, Token::ArrayEnd)], tokens.unwrap());
+}
+
+#[test]
+fn from_reserved_token_tokenizer() {
+    use rmp::decode::slice::ReservedPolicy;
+
+    // {0xc1: nil}, 0xc1
+    let buf: &[u8] = &[0x81, 0xc1, 0xc0, 0xc1];
+    let tokens: Result<Vec<_>, _> = Tokenizer::new(buf).reserved(ReservedPolicy::Token).collect();
+
+    assert_eq!(
+        vec![
+            (0, Token::MapStart(1)),
+            (1, Token::Reserved),
+            (2, Token::Nil),
+            (3, Token::MapEnd),
+            (3, Token::Reserved),
+        ],
+        tokens.unwrap()
+    );
+}
+
+#[test]
+fn from_deeply_nested_tokenizer() {
+    // 100 000 nested arrays, which would overflow the stack of a naive recursive consumer.
+    let mut buf = vec![0x91; 100_000];
+    buf.push(0xc0);
+
+    assert_eq!(2 * 100_000 + 1, Tokenizer::new(&buf).map(Result::unwrap).count());
+
+    let mut tokens = Tokenizer::new(&buf).max_depth(1024);
+    for _ in 0..1024 {
+        assert!(matches!(tokens.next(), Some(Ok((_, Token::ArrayStart(1))))));
+    }
+    assert_eq!(Some(Err(SliceReadError::DepthLimitExceeded)), tokens.next());
+    assert_eq!(1024, tokens.offset());
+    assert_eq!(None, tokens.next());
+
+    // Scalars are allowed at the maximum depth.
+    let tokens: Result<Vec<_>, _> = Tokenizer::new(&[0x91, 0xc0]).max_depth(1).collect();
+    assert_eq!(3, tokens.unwrap().len());
+}
@@ -0,0 +1,139 @@
+use rmp::decode::*;
+
+#[test]
+fn from_nested_validate() {
+    // [[1, "two"], {"k": nil, "bin": [0xca, 0xfe]}, 1.5, ext(5, [1, 2, 3])]
+    let buf: &[u8] = &[
+        0x94, 0x92, 0x01, 0xa3, 0x74, 0x77, 0x6f, 0x82, 0xa1, 0x6b, 0xc0, 0xa3, 0x62, 0x69, 0x6e, 0xc4, 0x02, 0xca,
+        0xfe, 0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc7, 0x03, 0x05, 0x01, 0x02, 0x03,
+    ];
+
+    assert_eq!(Ok(buf.len()), validate(buf));
+    for len in 0..buf.len() {
+        assert!(matches!(validate(&buf[..len]), Err(ValidateError::InsufficientBytes(..))), "{len}");
+    }
+}
+
+#[test]
+fn from_trailing_bytes_validate() {
+    let buf: &[u8] = &[0x91, 0xc0, 0xc3, 0xc2];
+
+    assert_eq!(Err(ValidateError::TrailingBytes(2)), validate(buf));
+    assert_eq!(Ok(2), Validator::new().allow_trailing(true).validate(buf));
+}
+
+#[test]
+fn from_invalid_utf8_validate() {
+    let buf: &[u8] = &[0x92, 0xc0, 0xa2, 0xc3, 0x28];
+
+    assert!(matches!(validate(buf), Err(ValidateError::InvalidUtf8(2, ..))));
+    assert_eq!(Ok(5), Validator::new().utf8(false).validate(buf));
+}
+
+#[test]
+fn from_reserved_marker_validate() {
+    assert_eq!(Err(ValidateError::ReservedMarker(2)), validate(&[0x81, 0xc0, 0xc1]));
+}
+
+#[test]
+fn from_oversized_length_validate() {
+    // An array claiming 2^32 - 1 elements, followed by only two.
+    let buf: &[u8] = &[0xdd, 0xff, 0xff, 0xff, 0xff, 0xc0, 0xc0];
+
+    assert_eq!(Err(ValidateError::InsufficientBytes(4294967293)), validate(buf));
+}
+
+#[test]
+fn from_non_canonical_validate() {
+    use rmp::Marker;
+
+    let strict = Validator::new().canonical(true);
+    let cases: &[(&[u8], Marker)] = &[
+        (&[0xcf, 0, 0, 0, 0, 0, 0, 0, 0x05], Marker::FixPos(5)),
+        (&[0xd0, 0x05], Marker::FixPos(5)),
+        (&[0xd1, 0xff, 0x80], Marker::I8),
+        (&[0xcd, 0x00, 0xff], Marker::U8),
+        (&[0xd9, 0x01, 0x61], Marker::FixStr(1)),
+        (&[0xc5, 0x00, 0x01, 0xaa], Marker::Bin8),
+        (&[0xdc, 0x00, 0x00], Marker::FixArray(0)),
+        (&[0xdf, 0x00, 0x00, 0x00, 0x00], Marker::FixMap(0)),
+        (&[0xc7, 0x04, 0x01, 0, 0, 0, 0], Marker::FixExt4),
+        // 1.5 as a 64-bit float, which is also a 32-bit one.
+        (&[0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0], Marker::F32),
+        // Timestamps of one second, in the 64 and 96-bit forms.
+        (&[0xd7, 0xff, 0, 0, 0, 0, 0, 0, 0, 0x01], Marker::FixExt4),
+        (&[0xc7, 0x0c, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01], Marker::FixExt4),
+        (&[0xc7, 0x0c, 0xff, 0, 0, 0, 0x01, 0, 0, 0, 0, 0, 0, 0, 0x01], Marker::FixExt8),
+    ];
+    for &(buf, marker) in cases {
+        assert_eq!(Ok(buf.len()), validate(buf));
+        assert_eq!(Err(ValidateError::NonCanonical(0, marker)), strict.validate(buf));
+    }
+
+    // Nested values are checked too.
+    assert_eq!(Err(ValidateError::NonCanonical(2, Marker::FixNeg(-1))), strict.validate(&[0x92, 0xc0, 0xd0, 0xff]));
+}
+
+#[test]
+fn from_canonical_validate() {
+    let strict = Validator::new().canonical(true);
+
+    let mut buf = Vec::new();
+    rmp::encode::write_array_len(&mut buf, 7).unwrap();
+    rmp::encode::write_sint(&mut buf, -33).unwrap();
+    rmp::encode::write_sint(&mut buf, 200).unwrap();
+    rmp::encode::write_uint(&mut buf, u64::MAX).unwrap();
+    rmp::encode::write_str(&mut buf, &"x".repeat(40)).unwrap();
+    rmp::encode::write_bin(&mut buf, &[0; 300]).unwrap();
+    rmp::encode::write_f64(&mut buf, 0.1).unwrap();
+    rmp::encode::write_ext_meta(&mut buf, 3, 1).unwrap();
+    buf.extend_from_slice(&[1, 2, 3]);
+
+    assert_eq!(Ok(buf.len()), strict.validate(&buf));
+
+    // Floats that fit 32 bits, timestamps in their shortest form, and invalid timestamps.
+    assert_eq!(Ok(5), strict.validate(&[0xca, 0x3f, 0xc0, 0, 0]));
+    assert_eq!(Ok(9), strict.validate(&[0xcb, 0x7f, 0xf8, 0, 0, 0, 0, 0, 0x01]));
+    assert_eq!(Ok(10), strict.validate(&[0xd7, 0xff, 0, 0, 0, 0x04, 0, 0, 0, 0x01]));
+    assert_eq!(Ok(10), strict.validate(&[0xd7, 0xff, 0xff, 0xff, 0xff, 0xfc, 0, 0, 0, 0x01]));
+    assert_eq!(Ok(6), strict.validate(&[0xd6, 0x01, 0, 0, 0, 0x01]));
+}
+
+#[test]
+fn from_unsorted_keys_validate() {
+    let strict = Validator::new().canonical(true);
+
+    // {"b": 1, "a": 2}
+    let buf = [0x82, 0xa1, b'b', 0x01, 0xa1, b'a', 0x02];
+    assert_eq!(Ok(buf.len()), validate(&buf));
+    assert_eq!(Err(ValidateError::UnsortedKey(4)), strict.validate(&buf));
+
+    // Keys compare by their encoded bytes, so the shorter string "b" is before "aa", and equal
+    // keys are allowed.
+    assert_eq!(Ok(8), strict.validate(&[0x82, 0xa1, b'b', 0x01, 0xa2, b'a', b'a', 0x02]));
+    assert_eq!(Ok(7), strict.validate(&[0x82, 0xa1, b'a', 0x01, 0xa1, b'a', 0x02]));
+
+    // Keys may be containers, and maps nested in keys, values and arrays are checked too.
+    let buf = [0x82, 0x91, 0x01, 0xc0, 0x91, 0x02, 0x81, 0x02, 0xc0];
+    assert_eq!(Ok(buf.len()), strict.validate(&buf));
+    let buf = [0x92, 0x80, 0x81, 0xa1, b'k', 0x82, 0x02, 0xc0, 0x01, 0xc0];
+    assert_eq!(Err(ValidateError::UnsortedKey(8)), strict.validate(&buf));
+    let buf = [0x82, 0x82, 0x02, 0xc0, 0x01, 0xc0, 0xc0, 0x01, 0xc0];
+    assert_eq!(Err(ValidateError::UnsortedKey(4)), strict.validate(&buf));
+}
+
+#[test]
+fn from_reserved_validate() {
+    use rmp::decode::slice::ReservedPolicy;
+
+    // {0xc1: nil, "k": 0xc1}, with the reserved markers taking the place of a key and a value.
+    let buf: &[u8] = &[0x82, 0xc1, 0xc0, 0xa1, 0x6b, 0xc1];
+
+    assert_eq!(Err(ValidateError::ReservedMarker(1)), validate(buf));
+    assert_eq!(Ok(6), Validator::new().reserved(ReservedPolicy::Token).validate(buf));
+    assert_eq!(
+        Err(ValidateError::InsufficientBytes(1)),
+        Validator::new().reserved(ReservedPolicy::Skip).validate(buf)
+    );
+    assert_eq!(Ok(3), Validator::new().reserved(ReservedPolicy::Skip).validate(&[0xc1, 0x91, 0xc1, 0x01][1..]));
+}
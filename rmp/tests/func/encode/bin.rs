@@ -33,3 +33,184 @@ fn pass_pack_len_u32() {
 
     assert_eq!([0xc6, 0xff, 0xff, 0xff, 0xff], buf);
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn pass_pack_bin_writer_chunks() {
+    use std::io::Write;
+
+    let mut buf = Vec::new();
+    let mut wr = BinWriter::new_bin(&mut buf, 70000).unwrap();
+    for _ in 0..7 {
+        wr.write_all(&[0xaa; 10000]).unwrap();
+    }
+    assert_eq!(0, wr.remaining());
+    wr.finish().unwrap();
+
+    assert_eq!([0xc6, 0x00, 0x01, 0x11, 0x70], buf[..5]);
+    assert_eq!(5 + 70000, buf.len());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn pass_pack_bin_writer_str() {
+    use std::io::Write;
+
+    let mut buf = Vec::new();
+    let mut wr = BinWriter::new_str(&mut buf, 4).unwrap();
+    // The chunks split the two-byte character.
+    wr.write_all(&[0x69, 0xc3]).unwrap();
+    wr.write_all(&[0xa9, 0x21]).unwrap();
+    wr.finish().unwrap();
+
+    assert_eq!([0xa4, 0x69, 0xc3, 0xa9, 0x21], buf[..]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn fail_pack_bin_writer_length_mismatch() {
+    use std::io::{ErrorKind, Write};
+
+    let mut buf = Vec::new();
+    let mut wr = BinWriter::new_bin(&mut buf, 3).unwrap();
+    wr.write_all(&[0x01, 0x02]).unwrap();
+    assert_eq!(ErrorKind::InvalidInput, wr.write_all(&[0x03, 0x04]).unwrap_err().kind());
+    assert_eq!(1, wr.remaining());
+    assert_eq!(ErrorKind::InvalidInput, wr.finish().unwrap_err().kind());
+
+    assert_eq!([0xc4, 0x03, 0x01, 0x02], buf[..]);
+}
+
+#[test]
+fn pass_count_encoded_len() {
+    let data = [0xaa; 70000];
+    let len = encoded_len_of(|wr| write_bin(wr, &data)).unwrap();
+
+    let mut buf = Vec::new();
+    write_bin(&mut buf, &data).unwrap();
+    assert_eq!(buf.len(), len);
+
+    let mut wr = CountingWriter::new();
+    assert!(wr.is_empty());
+    write_bin_len(&mut wr, 3).unwrap();
+    assert_eq!(2, wr.len());
+}
+
+/// A writer accepting at most `limit` bytes per call, recording the slices of every call.
+#[cfg(feature = "std")]
+struct VectoredWriter {
+    calls: Vec<Vec<usize>>,
+    buf: Vec<u8>,
+    limit: usize,
+    interrupt: bool,
+}
+
+#[cfg(feature = "std")]
+impl VectoredWriter {
+    fn new(limit: usize) -> Self {
+        Self { calls: Vec::new(), buf: Vec::new(), limit, interrupt: false }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for VectoredWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_vectored(&[std::io::IoSlice::new(buf)])
+    }
+
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        if std::mem::take(&mut self.interrupt) {
+            return Err(std::io::ErrorKind::Interrupted.into());
+        }
+        self.calls.push(bufs.iter().map(|buf| buf.len()).collect());
+        let mut len = 0;
+        for buf in bufs {
+            let take = buf.len().min(self.limit - len);
+            self.buf.extend_from_slice(&buf[..take]);
+            len += take;
+        }
+        if len == 0 && bufs.iter().any(|buf| !buf.is_empty()) {
+            return Ok(0);
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn pass_pack_bin_vectored() {
+    let data = vec![0xaa; 1000];
+
+    let mut wr = VectoredWriter::new(usize::MAX);
+    write_bin(&mut wr, &data).unwrap();
+    // The header and the data are written with a single call.
+    assert_eq!(vec![vec![3, 1000]], wr.calls);
+    assert_eq!([0xc5, 0x03, 0xe8], wr.buf[..3]);
+    assert_eq!(data, wr.buf[3..]);
+
+    // Partial writes resume where they stopped, and interruptions are retried.
+    let mut wr = VectoredWriter::new(400);
+    wr.interrupt = true;
+    write_str(&mut wr, &"x".repeat(1000)).unwrap();
+    assert_eq!(vec![vec![3, 1000], vec![603], vec![203]], wr.calls);
+    assert_eq!(1003, wr.buf.len());
+
+    let mut wr = VectoredWriter::new(usize::MAX);
+    wr.write_bytes_vectored(&[b"ab", b"", b"cd"]).unwrap();
+    assert_eq!(b"abcd", &wr.buf[..]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn fail_pack_bin_vectored_too_small_buffer() {
+    let mut buf = [0x00; 4];
+    match write_bin(&mut &mut buf[..], &[0x01, 0x02, 0x03]) {
+        Err(ValueWriteError::InvalidDataWrite(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!([0xc4, 0x03, 0x01, 0x02], buf);
+
+    match write_bin(&mut &mut [][..], &[0x01]) {
+        Err(ValueWriteError::InvalidMarkerWrite(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_pack_into_fixed_buf() {
+    use core::mem::MaybeUninit;
+
+    let mut storage = [MaybeUninit::uninit(); 8];
+    let mut buf = FixedBuf::new(&mut storage);
+    assert_eq!(8, buf.capacity());
+
+    write_bin(&mut buf, &[0x01, 0x02, 0x03]).unwrap();
+    assert_eq!(&[0xc4, 0x03, 0x01, 0x02, 0x03], buf.as_slice());
+    assert_eq!(3, buf.remaining());
+
+    // The data does not fit, but the header does.
+    match write_bin(&mut buf, &[0xaa; 2]) {
+        Err(ValueWriteError::InvalidDataWrite(BufferFull { needed: 1 })) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    buf.truncate(5);
+    write_u16(&mut buf, 0xffff).unwrap();
+    assert_eq!(&[0xc4, 0x03, 0x01, 0x02, 0x03, 0xcd, 0xff, 0xff], buf.into_slice());
+}
+
+#[test]
+fn pass_pack_into_initialized_fixed_buf() {
+    let mut storage = [0x00; 2];
+    let mut buf = FixedBuf::from(&mut storage[..]);
+
+    write_u8(&mut buf, 7).unwrap();
+    assert!(write_u8(&mut buf, 7).is_err());
+    buf.clear();
+    assert!(buf.is_empty());
+    write_bin_len(&mut buf, 7).unwrap();
+    assert_eq!([0xc4, 0x07], storage);
+}
@@ -79,3 +79,74 @@ fn pass_pack_meta_32() {
 
     assert_eq!([0xc9, 0xff, 0xff, 0xff, 0xff, 0x10], buf);
 }
+
+#[test]
+fn pass_pack_timestamp32() {
+    let mut buf = [0x00; 6];
+
+    assert_eq!(Marker::FixExt4, write_timestamp(&mut &mut buf[..], 4294967295, 0).unwrap());
+
+    assert_eq!([0xd6, 0xff, 0xff, 0xff, 0xff, 0xff], buf);
+}
+
+#[test]
+fn pass_pack_timestamp64() {
+    let mut buf = [0x00; 10];
+
+    assert_eq!(Marker::FixExt8, write_timestamp(&mut &mut buf[..], 1, 1).unwrap());
+
+    assert_eq!([0xd7, 0xff, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01], buf);
+}
+
+#[test]
+fn pass_pack_timestamp96() {
+    let mut buf = [0x00; 15];
+
+    assert_eq!(Marker::Ext8, write_timestamp(&mut &mut buf[..], -1, 999_999_999).unwrap());
+
+    assert_eq!([0xc7, 0x0c, 0xff, 0x3b, 0x9a, 0xc9, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], buf);
+}
+
+#[test]
+fn fail_pack_timestamp_nanos_out_of_range() {
+    let mut buf = Vec::new();
+
+    match write_timestamp(&mut buf, 0, 1_000_000_000) {
+        Err(TimestampWriteError::InvalidNanos(1_000_000_000)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn fail_pack_timestamp_write() {
+    let mut buf = [0x00; 6];
+
+    match write_timestamp(&mut &mut buf[..], 1, 1) {
+        Err(TimestampWriteError::InvalidWrite(ValueWriteError::InvalidDataWrite(..))) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_pack_ext_with_data() {
+    let cases: &[(usize, Marker, usize)] = &[
+        (0, Marker::Ext8, 3),
+        (1, Marker::FixExt1, 2),
+        (4, Marker::FixExt4, 2),
+        (16, Marker::FixExt16, 2),
+        (17, Marker::Ext8, 3),
+        (256, Marker::Ext16, 4),
+        (65536, Marker::Ext32, 6),
+    ];
+
+    for &(len, marker, header) in cases {
+        let data = vec![0xaa; len];
+        let mut buf = Vec::new();
+
+        assert_eq!(marker, write_ext(&mut buf, -5, &data).unwrap());
+        assert_eq!(header + len, buf.len());
+        assert_eq!(0xfb, buf[header - 1]);
+        assert_eq!(&data[..], &buf[header..]);
+    }
+}
@@ -19,3 +19,29 @@ fn pass_pack_f64() {
 
     assert_eq!([0xcb, 0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], buf);
 }
+
+#[test]
+fn pass_pack_f64_compact() {
+    use rmp::Marker;
+
+    let cases: &[(f64, bool, Marker, &[u8])] = &[
+        (1.5, false, Marker::F32, &[0xca, 0x3f, 0xc0, 0x00, 0x00]),
+        (f64::INFINITY, false, Marker::F32, &[0xca, 0x7f, 0x80, 0x00, 0x00]),
+        (0.1, true, Marker::F64, &[0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a]),
+        (-0.0, true, Marker::F32, &[0xca, 0x80, 0x00, 0x00, 0x00]),
+        (300.0, true, Marker::U16, &[0xcd, 0x01, 0x2c]),
+        (-1.0, true, Marker::FixNeg(-1), &[0xff]),
+        (-9_223_372_036_854_775_808.0, true, Marker::I64, &[0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0]),
+        (18_446_744_073_709_551_616.0, true, Marker::F32, &[0xca, 0x5f, 0x80, 0x00, 0x00]),
+        (1e300, true, Marker::F64, &[0xcb, 0x7e, 0x37, 0xe4, 0x3c, 0x88, 0x00, 0x75, 0x9c]),
+    ];
+
+    for &(val, int, marker, expected) in cases {
+        let mut buf = Vec::new();
+        assert_eq!(marker, write_f64_compact(&mut buf, val, int).unwrap(), "{val}");
+        assert_eq!(expected, &buf[..], "{val}");
+    }
+
+    let mut buf = Vec::new();
+    assert_eq!(Marker::F64, write_f64_compact(&mut buf, f64::NAN, true).unwrap());
+}
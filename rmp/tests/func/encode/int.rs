@@ -250,3 +250,51 @@ fn pass_pack_sint_i64_max() {
 
     assert_eq!([0xcf, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], buf);
 }
+
+#[test]
+fn pass_pack_fixed_width_small_values() {
+    let mut buf = Vec::new();
+    write_u8(&mut buf, 0).unwrap();
+    write_u16(&mut buf, 0).unwrap();
+    write_u32(&mut buf, 0).unwrap();
+    write_u64(&mut buf, 0).unwrap();
+    write_i8(&mut buf, -1).unwrap();
+    write_i16(&mut buf, -1).unwrap();
+    write_i32(&mut buf, -1).unwrap();
+    write_i64(&mut buf, -1).unwrap();
+    write_f32(&mut buf, 0.0).unwrap();
+    write_f64(&mut buf, 0.0).unwrap();
+
+    assert_eq!(2 + 3 + 5 + 9 + 2 + 3 + 5 + 9 + 5 + 9, buf.len());
+    let markers: Vec<u8> = [0, 2, 5, 10, 19, 21, 24, 29, 38, 43].iter().map(|&idx| buf[idx]).collect();
+    assert_eq!(vec![0xcc, 0xcd, 0xce, 0xcf, 0xd0, 0xd1, 0xd2, 0xd3, 0xca, 0xcb], markers);
+}
+
+#[test]
+fn pass_pack_fixed_aliases() {
+    let mut buf = Vec::new();
+    write_u8_fixed(&mut buf, 0).unwrap();
+    write_u16_fixed(&mut buf, 0).unwrap();
+    write_u32_fixed(&mut buf, 0).unwrap();
+    write_u64_fixed(&mut buf, 0).unwrap();
+    write_i8_fixed(&mut buf, -1).unwrap();
+    write_i16_fixed(&mut buf, -1).unwrap();
+    write_i32_fixed(&mut buf, -1).unwrap();
+    write_i64_fixed(&mut buf, -1).unwrap();
+    write_f32_fixed(&mut buf, 0.0).unwrap();
+    write_f64_fixed(&mut buf, 0.0).unwrap();
+
+    let mut expected = Vec::new();
+    write_u8(&mut expected, 0).unwrap();
+    write_u16(&mut expected, 0).unwrap();
+    write_u32(&mut expected, 0).unwrap();
+    write_u64(&mut expected, 0).unwrap();
+    write_i8(&mut expected, -1).unwrap();
+    write_i16(&mut expected, -1).unwrap();
+    write_i32(&mut expected, -1).unwrap();
+    write_i64(&mut expected, -1).unwrap();
+    write_f32(&mut expected, 0.0).unwrap();
+    write_f64(&mut expected, 0.0).unwrap();
+
+    assert_eq!(expected, buf);
+}
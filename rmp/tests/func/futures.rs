@@ -0,0 +1,50 @@
+use futures_executor::block_on;
+use rmp::decode::{ExtMeta, ValueReadError};
+use rmp::futures as rt;
+use rmp::{encode, Marker};
+
+#[test]
+fn pass_futures_io_round_trip() {
+    block_on(async {
+        let mut buf = Vec::new();
+        rt::write_array_len(&mut buf, 5).await.unwrap();
+        rt::write_bool(&mut buf, false).await.unwrap();
+        rt::write_sint(&mut buf, i64::MIN).await.unwrap();
+        rt::write_f32(&mut buf, 1.5).await.unwrap();
+        rt::write_bin(&mut buf, &[0; 300]).await.unwrap();
+        rt::write_ext_meta(&mut buf, 4, -1).await.unwrap();
+
+        let mut expected = Vec::new();
+        encode::write_array_len(&mut expected, 5).unwrap();
+        encode::write_bool(&mut expected, false).unwrap();
+        encode::write_sint(&mut expected, i64::MIN).unwrap();
+        encode::write_f32(&mut expected, 1.5).unwrap();
+        encode::write_bin(&mut expected, &[0; 300]).unwrap();
+        encode::write_ext_meta(&mut expected, 4, -1).unwrap();
+        assert_eq!(expected, buf);
+
+        let mut rd = &buf[..];
+        assert_eq!(5, rt::read_array_len(&mut rd).await.unwrap());
+        assert!(!rt::read_bool(&mut rd).await.unwrap());
+        assert_eq!(i64::MIN, rt::read_int(&mut rd).await.unwrap());
+        assert_eq!(1.5, rt::read_f32(&mut rd).await.unwrap());
+        assert_eq!(300, rt::read_bin_len(&mut rd).await.unwrap());
+        rd = &rd[300..];
+        assert_eq!(ExtMeta { typeid: -1, size: 4 }, rt::read_ext_meta(&mut rd).await.unwrap());
+        assert!(rd.is_empty());
+    });
+}
+
+#[test]
+fn fail_futures_io_truncated() {
+    block_on(async {
+        match rt::read_u64(&mut &[0xcf, 0x00, 0x01][..]).await {
+            Err(ValueReadError::InvalidDataRead(..)) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+        match rt::read_str_len(&mut &[0xc0][..]).await {
+            Err(ValueReadError::TypeMismatch(Marker::Null)) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+    });
+}
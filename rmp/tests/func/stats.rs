@@ -0,0 +1,76 @@
+use rmp::decode::slice::SliceReadError;
+use rmp::stats::{Counts, Family, MarkerStats};
+
+#[test]
+fn pass_record_messages() {
+    // [1, -300, "id", bin[2], ext(1, [0; 4])], {nil: true}
+    let buf: &[u8] = &[
+        0x95, 0x01, 0xd1, 0xfe, 0xd4, 0xa2, 0x69, 0x64, 0xc4, 0x02, 0xca, 0xfe, 0xd6, 0x01, 0x00, 0x00, 0x00, 0x00,
+        0x81, 0xc0, 0xc3,
+    ];
+
+    let mut stats = MarkerStats::new();
+    assert_eq!(Ok(2), stats.record(buf));
+
+    assert_eq!(Counts { count: 2, bytes: 4 }, stats.get(Family::Int));
+    assert_eq!(Counts { count: 1, bytes: 3 }, stats.get(Family::Str));
+    assert_eq!(Counts { count: 1, bytes: 4 }, stats.get(Family::Bin));
+    assert_eq!(Counts { count: 1, bytes: 6 }, stats.get(Family::Ext));
+    assert_eq!(Counts { count: 1, bytes: 1 }, stats.get(Family::Array));
+    assert_eq!(Counts { count: 1, bytes: 1 }, stats.get(Family::Map));
+    assert_eq!(Counts { count: 1, bytes: 1 }, stats.get(Family::Nil));
+    assert_eq!(Counts { count: 1, bytes: 1 }, stats.get(Family::Bool));
+    assert_eq!(Counts::default(), stats.get(Family::Float));
+    assert_eq!(Counts { count: 9, bytes: buf.len() as u64 }, stats.total());
+    assert_eq!(2, stats.messages());
+
+    let mut merged = stats.clone();
+    merged.merge(&stats);
+    assert_eq!(Counts { count: 4, bytes: 8 }, merged.get(Family::Int));
+    assert_eq!(4, merged.messages());
+
+    merged.clear();
+    assert_eq!(MarkerStats::new(), merged);
+}
+
+#[test]
+fn fail_record_truncated() {
+    // nil, [1, 2 (missing)
+    let buf: &[u8] = &[0xc0, 0x92, 0x01];
+
+    let mut stats = MarkerStats::new();
+    let err = stats.record(buf).unwrap_err();
+    assert_eq!(3, err.offset);
+    assert_eq!(SliceReadError::InsufficientBytes(1), err.error);
+    assert_eq!(1, stats.messages());
+    assert_eq!(3, stats.total().count);
+
+    // The payload of the string is missing.
+    let err = stats.record(&[0xa3, 0x61]).unwrap_err();
+    assert_eq!(0, err.offset);
+    assert_eq!(SliceReadError::InsufficientBytes(2), err.error);
+}
+
+#[test]
+fn fail_record_reserved() {
+    let mut stats = MarkerStats::new();
+    let err = stats.record(&[0xc3, 0xc1]).unwrap_err();
+
+    assert_eq!(1, err.offset);
+    assert_eq!(Counts { count: 1, bytes: 1 }, stats.get(Family::Reserved));
+    assert_eq!(1, stats.messages());
+}
+
+#[test]
+fn pass_display_table() {
+    let mut stats = MarkerStats::new();
+    stats.record(&[0x92, 0xc0, 0xc0]).unwrap();
+
+    assert_eq!(
+        "family          count          bytes   share\n\
+         nil                 2              2   66.7%\n\
+         array               1              1   33.3%\n\
+         total               3              3 in 1 messages",
+        stats.to_string()
+    );
+}
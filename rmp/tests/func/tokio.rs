@@ -0,0 +1,86 @@
+use rmp::decode::{self, ExtMeta, NumValueReadError, ValueReadError};
+use rmp::tokio as rt;
+use rmp::{encode, Marker};
+
+#[tokio::test]
+async fn pass_async_encode_matches_sync() {
+    let mut buf = Vec::new();
+    rt::write_nil(&mut buf).await.unwrap();
+    rt::write_bool(&mut buf, true).await.unwrap();
+    rt::write_pfix(&mut buf, 7).await.unwrap();
+    rt::write_nfix(&mut buf, -7).await.unwrap();
+    assert_eq!(Marker::U32, rt::write_uint(&mut buf, 70000).await.unwrap());
+    assert_eq!(Marker::I16, rt::write_sint(&mut buf, -300).await.unwrap());
+    rt::write_f64(&mut buf, 0.5).await.unwrap();
+    rt::write_str(&mut buf, "le message").await.unwrap();
+    rt::write_bin(&mut buf, &[1, 2, 3]).await.unwrap();
+    rt::write_array_len(&mut buf, 20).await.unwrap();
+    rt::write_map_len(&mut buf, 1).await.unwrap();
+    rt::write_ext_meta(&mut buf, 300, 5).await.unwrap();
+
+    let mut expected = Vec::new();
+    encode::write_nil(&mut expected).unwrap();
+    encode::write_bool(&mut expected, true).unwrap();
+    encode::write_pfix(&mut expected, 7).unwrap();
+    encode::write_nfix(&mut expected, -7).unwrap();
+    encode::write_uint(&mut expected, 70000).unwrap();
+    encode::write_sint(&mut expected, -300).unwrap();
+    encode::write_f64(&mut expected, 0.5).unwrap();
+    encode::write_str(&mut expected, "le message").unwrap();
+    encode::write_bin(&mut expected, &[1, 2, 3]).unwrap();
+    encode::write_array_len(&mut expected, 20).unwrap();
+    encode::write_map_len(&mut expected, 1).unwrap();
+    encode::write_ext_meta(&mut expected, 300, 5).unwrap();
+
+    assert_eq!(expected, buf);
+}
+
+#[tokio::test]
+async fn pass_async_decode_matches_sync() {
+    let buf = [0xc0, 0xc3, 0x07, 0xf9, 0xce, 0x00, 0x01, 0x11, 0x70, 0xd1, 0xfe, 0xd4, 0xcb, 0x3f, 0xe0, 0, 0, 0, 0, 0, 0,
+               0xd9, 0x20, 0xc5, 0x01, 0x00, 0xdc, 0x00, 0x14, 0x81, 0xc8, 0x01, 0x2c, 0x05];
+    let mut rd = &buf[..];
+
+    rt::read_nil(&mut rd).await.unwrap();
+    assert!(rt::read_bool(&mut rd).await.unwrap());
+    assert_eq!(7, rt::read_pfix(&mut rd).await.unwrap());
+    assert_eq!(-7, rt::read_nfix(&mut rd).await.unwrap());
+    assert_eq!(70000u64, rt::read_int(&mut rd).await.unwrap());
+    assert_eq!(-300, rt::read_i16(&mut rd).await.unwrap());
+    assert_eq!(0.5, rt::read_f64(&mut rd).await.unwrap());
+    assert_eq!(32, rt::read_str_len(&mut rd).await.unwrap());
+    assert_eq!(256, rt::read_bin_len(&mut rd).await.unwrap());
+    assert_eq!(20, rt::read_array_len(&mut rd).await.unwrap());
+    assert_eq!(1, rt::read_map_len(&mut rd).await.unwrap());
+    assert_eq!(ExtMeta { typeid: 5, size: 300 }, rt::read_ext_meta(&mut rd).await.unwrap());
+    assert!(rd.is_empty());
+
+    let mut sync = &buf[4..9];
+    assert_eq!(70000u64, decode::read_int(&mut sync).unwrap());
+}
+
+#[tokio::test]
+async fn fail_async_decode_type_mismatch_and_eof() {
+    let buf = [0xc3, 0x00];
+    let mut rd = &buf[..];
+
+    match rt::read_nil(&mut rd).await {
+        Err(ValueReadError::TypeMismatch(Marker::True)) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+    // Only the marker was consumed.
+    assert_eq!([0x00], rd);
+
+    match rt::read_int::<i8, _>(&mut &[0xcc, 0xff][..]).await {
+        Err(NumValueReadError::OutOfRange) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+    match rt::read_u32(&mut &[0xce, 0x00][..]).await {
+        Err(ValueReadError::InvalidDataRead(..)) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+    match rt::read_marker(&mut &[][..]).await {
+        Err(..) => {}
+        Ok(marker) => panic!("unexpected marker: {marker:?}"),
+    }
+}
@@ -3,8 +3,15 @@
 extern crate quickcheck;
 
 mod func {
+    #[cfg(feature = "bytes")]
+    mod bytes;
     mod decode;
     mod encode;
     mod mirror;
     mod est;
+    mod stats;
+    #[cfg(feature = "futures-io")]
+    mod futures;
+    #[cfg(feature = "tokio")]
+    mod tokio;
 }
@@ -0,0 +1,47 @@
+use arbitrary::{Arbitrary, Unstructured};
+
+use rmpv::Value;
+
+/// A tiny deterministic xorshift generator, so the smoke test does not need extra dependencies.
+fn entropy(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9e3779b97f4a7c15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn depth(val: &Value) -> usize {
+    match *val {
+        Value::Array(ref vec) => 1 + vec.iter().map(depth).max().unwrap_or(0),
+        Value::Map(ref vec) => 1 + vec.iter().map(|(k, v)| depth(k).max(depth(v))).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+#[test]
+fn pass_arbitrary_values_round_trip() {
+    for seed in 0..256 {
+        let data = entropy(seed, 4096);
+        let mut u = Unstructured::new(&data);
+        let val = Value::arbitrary(&mut u).unwrap();
+
+        assert!(depth(&val) <= 4);
+
+        let buf = rmp_serde::to_vec(&val).unwrap();
+        let decoded: Value = rmp_serde::from_slice(&buf).unwrap();
+
+        // Compare the encoded form, since NaN floats are never equal to themselves.
+        assert_eq!(buf, rmp_serde::to_vec(&decoded).unwrap());
+    }
+}
+
+#[test]
+fn pass_arbitrary_from_empty_input() {
+    let mut u = Unstructured::new(&[]);
+    assert_eq!(Value::Nil, Value::arbitrary(&mut u).unwrap());
+}
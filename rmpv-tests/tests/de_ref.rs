@@ -91,35 +91,35 @@ fn pass_value_map() {
 
 #[test]
 fn pass_uint_from_value() {
-    assert_eq!(i8::min_value(), deserialize_from(ValueRef::from(i8::min_value())).unwrap());
-    assert_eq!(i8::MAX, deserialize_from(ValueRef::from(i8::MAX)).unwrap());
-    assert_eq!(i16::min_value(), deserialize_from(ValueRef::from(i16::min_value())).unwrap());
-    assert_eq!(i16::MAX, deserialize_from(ValueRef::from(i16::MAX)).unwrap());
-    assert_eq!(i32::min_value(), deserialize_from(ValueRef::from(i32::min_value())).unwrap());
-    assert_eq!(i32::MAX, deserialize_from(ValueRef::from(i32::MAX)).unwrap());
-    assert_eq!(i64::min_value(), deserialize_from(ValueRef::from(i64::min_value())).unwrap());
-    assert_eq!(i64::MAX, deserialize_from(ValueRef::from(i64::MAX)).unwrap());
+    assert_eq!(i8::min_value(), deserialize_from::<i8, _>(ValueRef::from(i8::min_value())).unwrap());
+    assert_eq!(i8::MAX, deserialize_from::<i8, _>(ValueRef::from(i8::MAX)).unwrap());
+    assert_eq!(i16::min_value(), deserialize_from::<i16, _>(ValueRef::from(i16::min_value())).unwrap());
+    assert_eq!(i16::MAX, deserialize_from::<i16, _>(ValueRef::from(i16::MAX)).unwrap());
+    assert_eq!(i32::min_value(), deserialize_from::<i32, _>(ValueRef::from(i32::min_value())).unwrap());
+    assert_eq!(i32::MAX, deserialize_from::<i32, _>(ValueRef::from(i32::MAX)).unwrap());
+    assert_eq!(i64::min_value(), deserialize_from::<i64, _>(ValueRef::from(i64::min_value())).unwrap());
+    assert_eq!(i64::MAX, deserialize_from::<i64, _>(ValueRef::from(i64::MAX)).unwrap());
 }
 
 #[test]
 fn pass_sint_from_value() {
-    assert_eq!(0, deserialize_from(ValueRef::from(0)).unwrap());
-    assert_eq!(u8::MAX, deserialize_from(ValueRef::from(u8::MAX)).unwrap());
-    assert_eq!(u16::MAX, deserialize_from(ValueRef::from(u16::MAX)).unwrap());
-    assert_eq!(u32::MAX, deserialize_from(ValueRef::from(u32::MAX)).unwrap());
-    assert_eq!(u64::MAX, deserialize_from(ValueRef::from(u64::MAX)).unwrap());
+    assert_eq!(0, deserialize_from::<i32, _>(ValueRef::from(0)).unwrap());
+    assert_eq!(u8::MAX, deserialize_from::<u8, _>(ValueRef::from(u8::MAX)).unwrap());
+    assert_eq!(u16::MAX, deserialize_from::<u16, _>(ValueRef::from(u16::MAX)).unwrap());
+    assert_eq!(u32::MAX, deserialize_from::<u32, _>(ValueRef::from(u32::MAX)).unwrap());
+    assert_eq!(u64::MAX, deserialize_from::<u64, _>(ValueRef::from(u64::MAX)).unwrap());
 }
 
 #[test]
 fn pass_f32_from_value() {
-    assert_eq!(0.0f32, deserialize_from(ValueRef::from(0.0f32)).unwrap());
-    assert_eq!(std::f32::consts::PI, deserialize_from(ValueRef::from(std::f32::consts::PI)).unwrap());
+    assert_eq!(0.0f32, deserialize_from::<f32, _>(ValueRef::from(0.0f32)).unwrap());
+    assert_eq!(std::f32::consts::PI, deserialize_from::<f32, _>(ValueRef::from(std::f32::consts::PI)).unwrap());
 }
 
 #[test]
 fn pass_f64_from_value() {
-    assert_eq!(0.0, deserialize_from(ValueRef::from(0.0)).unwrap());
-    assert_eq!(std::f64::consts::PI, deserialize_from(ValueRef::from(std::f64::consts::PI)).unwrap());
+    assert_eq!(0.0, deserialize_from::<f64, _>(ValueRef::from(0.0)).unwrap());
+    assert_eq!(std::f64::consts::PI, deserialize_from::<f64, _>(ValueRef::from(std::f64::consts::PI)).unwrap());
 }
 
 #[test]
@@ -340,3 +340,46 @@ fn pass_from_ext() {
         deserialize_from(ValueRef::Ext(42, &[255])).unwrap()
     );
 }
+
+#[test]
+fn pass_struct_borrowing_input_from_decoded_value_ref() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Message<'a> {
+        name: &'a str,
+        #[serde(with = "serde_bytes")]
+        payload: &'a [u8],
+    }
+
+    // ["ping", <bin 3>]
+    let buf = [0x92, 0xa4, b'p', b'i', b'n', b'g', 0xc4, 0x03, 1, 2, 3];
+
+    let msg: Message<'_> = {
+        let val = decode::read_value_ref(&mut &buf[..]).unwrap();
+        // Through a reference, the tree can still be inspected afterwards.
+        assert_eq!(Message { name: "ping", payload: &[1, 2, 3] }, Message::deserialize(&val).unwrap());
+        // By value, the fields borrow from the buffer and outlive the tree.
+        deserialize_from(val).unwrap()
+    };
+
+    assert_eq!(buf[2..].as_ptr(), msg.name.as_ptr());
+    assert_eq!(buf[8..].as_ptr(), msg.payload.as_ptr());
+}
+
+#[test]
+fn pass_value_ref_serialize() {
+    use rmpv::Value;
+
+    // {"a": [nil, true, -1, 1.5], "b": <bin 2>, "c": <ext 5>}
+    let val = ValueRef::Map(vec![
+        (ValueRef::from("a"), ValueRef::Array(vec![ValueRef::Nil, ValueRef::Boolean(true), ValueRef::from(-1), ValueRef::F64(1.5)])),
+        (ValueRef::from("b"), ValueRef::Binary(&[1, 2])),
+        (ValueRef::from("c"), ValueRef::Ext(5, &[9])),
+    ]);
+
+    let mut buf = Vec::new();
+    rmpv::encode::write_value_ref(&mut buf, &val).unwrap();
+    assert_eq!(buf, rmp_serde::to_vec(&val).unwrap());
+
+    assert_eq!(val.to_owned(), rmpv::ext::to_value(&val).unwrap());
+    assert_eq!(Value::from(-1), rmpv::ext::to_value(&val).unwrap()["a"][2]);
+}
@@ -3,7 +3,7 @@ use serde_bytes::ByteBuf;
 use std::collections::BTreeMap;
 
 use rmpv::decode;
-use rmpv::ext::from_value;
+use rmpv::ext::{from_value, from_value_ref};
 use rmpv::Value;
 
 /// Tests that a `Value` is properly decoded from bytes using two different mechanisms: direct
@@ -110,35 +110,35 @@ fn pass_value_map() {
 
 #[test]
 fn pass_uint_from_value() {
-    assert_eq!(i8::min_value(), from_value(Value::from(i8::min_value())).unwrap());
-    assert_eq!(i8::MAX, from_value(Value::from(i8::MAX)).unwrap());
-    assert_eq!(i16::min_value(), from_value(Value::from(i16::min_value())).unwrap());
-    assert_eq!(i16::MAX, from_value(Value::from(i16::MAX)).unwrap());
-    assert_eq!(i32::min_value(), from_value(Value::from(i32::min_value())).unwrap());
-    assert_eq!(i32::MAX, from_value(Value::from(i32::MAX)).unwrap());
-    assert_eq!(i64::min_value(), from_value(Value::from(i64::min_value())).unwrap());
-    assert_eq!(i64::MAX, from_value(Value::from(i64::MAX)).unwrap());
+    assert_eq!(i8::min_value(), from_value::<i8>(Value::from(i8::min_value())).unwrap());
+    assert_eq!(i8::MAX, from_value::<i8>(Value::from(i8::MAX)).unwrap());
+    assert_eq!(i16::min_value(), from_value::<i16>(Value::from(i16::min_value())).unwrap());
+    assert_eq!(i16::MAX, from_value::<i16>(Value::from(i16::MAX)).unwrap());
+    assert_eq!(i32::min_value(), from_value::<i32>(Value::from(i32::min_value())).unwrap());
+    assert_eq!(i32::MAX, from_value::<i32>(Value::from(i32::MAX)).unwrap());
+    assert_eq!(i64::min_value(), from_value::<i64>(Value::from(i64::min_value())).unwrap());
+    assert_eq!(i64::MAX, from_value::<i64>(Value::from(i64::MAX)).unwrap());
 }
 
 #[test]
 fn pass_sint_from_value() {
-    assert_eq!(0, from_value(Value::from(0)).unwrap());
-    assert_eq!(u8::MAX, from_value(Value::from(u8::MAX)).unwrap());
-    assert_eq!(u16::MAX, from_value(Value::from(u16::MAX)).unwrap());
-    assert_eq!(u32::MAX, from_value(Value::from(u32::MAX)).unwrap());
-    assert_eq!(u64::MAX, from_value(Value::from(u64::MAX)).unwrap());
+    assert_eq!(0, from_value::<i32>(Value::from(0)).unwrap());
+    assert_eq!(u8::MAX, from_value::<u8>(Value::from(u8::MAX)).unwrap());
+    assert_eq!(u16::MAX, from_value::<u16>(Value::from(u16::MAX)).unwrap());
+    assert_eq!(u32::MAX, from_value::<u32>(Value::from(u32::MAX)).unwrap());
+    assert_eq!(u64::MAX, from_value::<u64>(Value::from(u64::MAX)).unwrap());
 }
 
 #[test]
 fn pass_f32_from_value() {
-    assert_eq!(0.0f32, from_value(Value::from(0.0f32)).unwrap());
-    assert_eq!(std::f32::consts::PI, from_value(Value::from(std::f32::consts::PI)).unwrap());
+    assert_eq!(0.0f32, from_value::<f32>(Value::from(0.0f32)).unwrap());
+    assert_eq!(std::f32::consts::PI, from_value::<f32>(Value::from(std::f32::consts::PI)).unwrap());
 }
 
 #[test]
 fn pass_f64_from_value() {
-    assert_eq!(0.0, from_value(Value::from(0.0)).unwrap());
-    assert_eq!(std::f64::consts::PI, from_value(Value::from(std::f64::consts::PI)).unwrap());
+    assert_eq!(0.0, from_value::<f64>(Value::from(0.0)).unwrap());
+    assert_eq!(std::f64::consts::PI, from_value::<f64>(Value::from(std::f64::consts::PI)).unwrap());
 }
 
 #[test]
@@ -311,3 +311,126 @@ fn pass_tuple_struct_from_ext() {
         from_value(Value::Ext(42, vec![255])).unwrap()
     );
 }
+
+#[test]
+fn pass_struct_from_value_ref_borrows() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Message<'a> {
+        name: &'a str,
+        #[serde(with = "serde_bytes")]
+        payload: &'a [u8],
+        tags: Vec<&'a str>,
+    }
+
+    let val = Value::Map(vec![
+        (Value::from("name"), Value::from("ping")),
+        (Value::from("payload"), Value::Binary(vec![1, 2, 3])),
+        (Value::from("tags"), Value::Array(vec![Value::from("a")])),
+    ]);
+
+    let msg: Message<'_> = from_value_ref(&val).unwrap();
+    assert_eq!(Message { name: "ping", payload: &[1, 2, 3], tags: vec!["a"] }, msg);
+    assert_eq!(val["name"].as_str().unwrap().as_ptr(), msg.name.as_ptr());
+    assert_eq!(val["payload"].as_slice().unwrap().as_ptr(), msg.payload.as_ptr());
+}
+
+#[test]
+fn fail_borrowed_str_from_value_ref() {
+    // Only strings can be borrowed as `&str`.
+    assert!(from_value_ref::<&str>(&Value::from(42)).is_err());
+}
+
+#[test]
+fn pass_struct_and_enum_from_borrowed_value() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Person {
+        name: String,
+        age: Option<u32>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum {
+        Unit,
+        Tuple(String, u32),
+    }
+
+    let val = Value::Array(vec![Value::from("John"), Value::Nil]);
+    assert_eq!(Person { name: "John".into(), age: None }, Person::deserialize(&val).unwrap());
+    // The value is still available afterwards.
+    assert_eq!(Value::from("John"), val[0]);
+
+    let val = Value::Array(vec![Value::from(1), Value::Array(vec![Value::from("John"), Value::from(42)])]);
+    assert_eq!(Enum::Tuple("John".into(), 42), Enum::deserialize(&val).unwrap());
+    let val = Value::Array(vec![Value::from(0), Value::Array(vec![])]);
+    assert_eq!(Enum::Unit, Enum::deserialize(&val).unwrap());
+
+    let val = Value::Ext(42, vec![255]);
+    assert_eq!(Value::Ext(42, vec![255]), Value::deserialize(&val).unwrap());
+}
+
+#[test]
+fn fail_from_value_reports_path() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Meta {
+        created_at: u64,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Item {
+        meta: Meta,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Doc {
+        items: Vec<Item>,
+    }
+
+    let item = |created_at: Value| Value::Map(vec![
+        (Value::from("meta"), Value::Map(vec![(Value::from("created_at"), created_at)])),
+    ]);
+    let mut items = vec![item(Value::from(1)); 3];
+    items.push(item(Value::from("yesterday")));
+    let val = Value::Map(vec![(Value::from("items"), Value::Array(items))]);
+
+    let err = from_value::<Doc>(val.clone()).unwrap_err();
+    assert_eq!(Some("items[3].meta.created_at"), err.path());
+    assert!(err.to_string().ends_with(" at items[3].meta.created_at"));
+    assert_eq!(Some("items[3].meta.created_at"), from_value_ref::<Doc>(&val).unwrap_err().path());
+    assert_eq!(Some("items[3].meta.created_at"), rmpv::ext::deserialize_from::<Doc, _>(val.as_ref()).unwrap_err().path());
+
+    // Structs encoded as arrays name their elements after the fields.
+    let val = Value::Array(vec![Value::Array(vec![
+        Value::Array(vec![Value::Array(vec![Value::from(1)])]),
+        Value::Array(vec![Value::Array(vec![Value::Nil])]),
+    ])]);
+    assert_eq!(Some("items[1].meta.created_at"), from_value::<Doc>(val).unwrap_err().path());
+
+    // Errors at the root have no path.
+    assert_eq!(None, from_value::<u64>(Value::Nil).unwrap_err().path());
+}
+
+#[test]
+fn fail_from_value_reports_map_keys() {
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+    struct Name(String);
+
+    // Other keys than strings are shown as they are displayed, unlike sequence indices.
+    let val = Value::Map(vec![
+        (Value::from(1), Value::from(1)),
+        (Value::from(7), Value::Nil),
+    ]);
+    assert_eq!(Some("7"), from_value::<BTreeMap<u8, u8>>(val.clone()).unwrap_err().path());
+    assert_eq!(Some("7"), from_value_ref::<BTreeMap<u8, u8>>(&val).unwrap_err().path());
+    let val = Value::Map(vec![(Value::Array(vec![Value::from(1)]), Value::Nil)]);
+    assert_eq!(Some("[1]"), from_value::<BTreeMap<Vec<u8>, u8>>(val).unwrap_err().path());
+
+    // A key that fails is named too, and string keys can still be newtypes.
+    let val = Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("b"), Value::Nil)]);
+    assert_eq!(Some("b"), from_value::<BTreeMap<String, u8>>(val.clone()).unwrap_err().path());
+    assert_eq!(Some("a"), from_value::<BTreeMap<u8, Value>>(val.clone()).unwrap_err().path());
+    let mut map = from_value::<BTreeMap<Name, Option<u8>>>(val).unwrap();
+    assert_eq!(Some(None), map.remove(&Name("b".into())));
+}
@@ -6,7 +6,7 @@ use serde_bytes::{ByteBuf, Bytes};
 
 use rmp_serde::Serializer;
 use rmpv::encode;
-use rmpv::ext::to_value;
+use rmpv::ext::{to_value, to_value_with, ToValueConfig};
 use rmpv::Value;
 
 /// Tests that a `Value` is properly encoded using two different mechanisms: direct serialization
@@ -269,3 +269,119 @@ fn pass_ext_struct_to_value() {
         to_value(ExtStruct((5, ByteBuf::from(vec![10])))).unwrap()
     );
 }
+
+#[test]
+fn pass_named_struct_and_enum_to_value() {
+    #[derive(Serialize)]
+    enum Enum {
+        Unit,
+        Newtype(String),
+        Tuple(String, u32),
+        Struct { name: String, age: u32 },
+    }
+
+    #[derive(Serialize)]
+    struct Doc {
+        id: u32,
+        items: Vec<Enum>,
+    }
+
+    let doc = Doc {
+        id: 1,
+        items: vec![
+            Enum::Unit,
+            Enum::Newtype("John".into()),
+            Enum::Tuple("John".into(), 42),
+            Enum::Struct { name: "John".into(), age: 42 },
+        ],
+    };
+
+    let config = ToValueConfig::new().struct_map(true).named_variants(true);
+    let val = to_value_with(&doc, config).unwrap();
+
+    assert_eq!(Value::from(1), val["id"]);
+    assert_eq!(Value::from("Unit"), val["items"][0]);
+    assert_eq!(Value::from("John"), val["items"][1]["Newtype"]);
+    assert_eq!(Value::from(42), val["items"][2]["Tuple"][1]);
+    assert_eq!(Value::from("John"), val["items"][3]["Struct"]["name"]);
+
+    // The value encodes to the same bytes as rmp-serde's named mode.
+    let mut buf = Vec::new();
+    encode::write_value(&mut buf, &val).unwrap();
+    assert_eq!(rmp_serde::to_vec_named(&doc).unwrap(), buf);
+
+    // Either option can be enabled alone.
+    let val = to_value_with(&doc, ToValueConfig::new().struct_map(true)).unwrap();
+    assert_eq!(Value::Array(vec![Value::from(0), Value::Array(vec![])]), val["items"][0]);
+    let val = to_value_with(&doc, ToValueConfig::new().named_variants(true)).unwrap();
+    assert_eq!(Value::from("Unit"), val[1][0]);
+    assert_eq!(to_value(&doc).unwrap(), to_value_with(&doc, ToValueConfig::default()).unwrap());
+}
+
+#[test]
+fn pass_ext_registry_round_trip() {
+    use rmpv::ext::{Error, ExtRegistry};
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Uuid(#[serde(with = "serde_bytes")] Vec<u8>);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        id: Uuid,
+        name: String,
+    }
+
+    let registry = ExtRegistry::new().register(
+        "Uuid",
+        2,
+        |val| val.as_slice().map(<[u8]>::to_vec).ok_or_else(|| Error::Syntax("expected bytes".into())),
+        |data| if data.len() == 16 { Ok(Value::Binary(data.to_vec())) } else { Err(Error::Syntax("expected 16 bytes".into())) },
+    );
+
+    let user = User { id: Uuid(vec![7; 16]), name: "John".into() };
+    let val = registry.to_value(&user).unwrap();
+    assert_eq!(Value::Array(vec![Value::Ext(2, vec![7; 16]), Value::from("John")]), val);
+    // Without the registry, the struct is its content.
+    assert_eq!(Value::Binary(vec![7; 16]), to_value(&user).unwrap()[0]);
+
+    assert_eq!(user, registry.from_value::<User>(val).unwrap());
+
+    // Errors of the closures are reported, and unregistered tags are left alone.
+    let bad = Value::Array(vec![Value::Ext(2, vec![7; 3]), Value::from("John")]);
+    assert!(registry.from_value::<User>(bad).is_err());
+    let other = Value::Array(vec![Value::Ext(3, vec![1])]);
+    assert_eq!(other, registry.from_value::<Value>(other.clone()).unwrap());
+}
+
+#[test]
+fn fail_to_value_reports_path() {
+    struct Fails;
+
+    impl Serialize for Fails {
+        fn serialize<S: serde::Serializer>(&self, _s: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("cannot serialize"))
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Meta {
+        created_at: Fails,
+    }
+
+    #[derive(Serialize)]
+    struct Item {
+        meta: Option<Meta>,
+    }
+
+    let mut items = BTreeMap::new();
+    items.insert("first", vec![Item { meta: None }, Item { meta: Some(Meta { created_at: Fails }) }]);
+
+    let err = to_value(&items).unwrap_err();
+    assert_eq!(Some("first[1].meta.created_at"), err.path());
+
+    // Other keys than strings are shown as they are displayed.
+    let mut items = BTreeMap::new();
+    items.insert(-3, Item { meta: Some(Meta { created_at: Fails }) });
+    assert_eq!(Some("-3.meta.created_at"), to_value(&items).unwrap_err().path());
+}
@@ -0,0 +1,26 @@
+use proptest::prelude::*;
+
+use rmpv::decode::read_value;
+use rmpv::encode::write_value;
+use rmpv::Value;
+
+fn depth(val: &Value) -> usize {
+    match *val {
+        Value::Array(ref vec) => 1 + vec.iter().map(depth).max().unwrap_or(0),
+        Value::Map(ref vec) => 1 + vec.iter().map(|(k, v)| depth(k).max(depth(v))).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+proptest! {
+    #[test]
+    fn pass_proptest_values_round_trip(val in any::<Value>()) {
+        prop_assert!(depth(&val) <= 4);
+
+        let mut buf = Vec::new();
+        write_value(&mut buf, &val).unwrap();
+
+        // Floats compare by their bits, so NaN values round trip as well.
+        prop_assert_eq!(val, read_value(&mut &buf[..]).unwrap());
+    }
+}
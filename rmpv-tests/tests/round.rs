@@ -72,6 +72,76 @@ fn pass_sint() {
     test_round(i64::MAX, Value::from(i64::MAX));
 }
 
+#[test]
+fn pass_system_time_as_timestamp() {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use rmpv::ext::{from_value, from_value_ref, to_value};
+    use rmpv::Timestamp;
+
+    let time = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+    let val = to_value(time).unwrap();
+    assert_eq!(Value::Timestamp(Timestamp::new(1_700_000_000, 123_456_789).unwrap()), val);
+
+    assert_eq!(time, from_value::<SystemTime>(val.clone()).unwrap());
+    assert_eq!(time, from_value_ref::<SystemTime>(&val).unwrap());
+
+    // Timestamps also read back from the encoded bytes.
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+    let val = rmpv::decode::read_value_ref(&mut &buf[..]).unwrap();
+    assert_eq!(time, SystemTime::deserialize(val).unwrap());
+
+    // Nested times are converted too, and a time before the epoch cannot be read back.
+    let val = to_value(vec![time]).unwrap();
+    assert!(val[0].as_timestamp().is_some());
+    assert!(from_value::<SystemTime>(Value::Timestamp(Timestamp::new(-1, 0).unwrap())).is_err());
+}
+
+#[test]
+fn pass_chrono_datetime_as_timestamp() {
+    use chrono::{DateTime, Utc};
+
+    use rmpv::ext::{from_value, to_value};
+    use rmpv::Timestamp;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        #[serde(with = "rmpv::ext::datetime")]
+        at: DateTime<Utc>,
+    }
+
+    let event = Event { at: DateTime::from_timestamp(-2, 750_000_000).unwrap() };
+    let val = Value::Array(vec![Value::Timestamp(Timestamp::new(-2, 750_000_000).unwrap())]);
+    test_round(event, val);
+
+    // Other extension types and leap seconds are rejected.
+    assert!(from_value::<Event>(Value::Array(vec![Value::Ext(1, vec![0; 4])])).is_err());
+    let leap = Event { at: DateTime::from_timestamp(59, 1_500_000_000).unwrap() };
+    assert!(to_value(&leap).is_err());
+    assert!(rmp_serde::to_vec(&leap).is_err());
+}
+
+#[test]
+fn pass_int128() {
+    // Out of the 64-bit range, 128-bit integers are 16-byte binaries on both paths.
+    test_round(i128::MIN, Value::Binary(i128::MIN.to_be_bytes().to_vec()));
+    test_round(i128::from(i64::MIN) - 1, Value::Binary((i128::from(i64::MIN) - 1).to_be_bytes().to_vec()));
+    test_round(u128::MAX, Value::Binary(u128::MAX.to_be_bytes().to_vec()));
+
+    // Within it, they are plain integers, which rmp-serde also reads back.
+    for n in [i128::from(i64::MIN), -1, 0, i128::from(u64::MAX)] {
+        let val = rmpv::ext::to_value(n).unwrap();
+        assert!(val.is_i64() || val.is_u64());
+        assert_eq!(n, rmpv::ext::from_value::<i128>(val.clone()).unwrap());
+        assert_eq!(n, rmpv::ext::from_value_ref::<i128>(&val).unwrap());
+        assert_eq!(n, rmp_serde::from_slice::<i128>(&rmp_serde::to_vec(&val).unwrap()).unwrap());
+    }
+    assert_eq!(u128::from(u64::MAX), rmpv::ext::from_value::<u128>(Value::from(u64::MAX)).unwrap());
+    assert!(rmpv::ext::from_value::<u128>(Value::from(-1)).is_err());
+    assert!(rmpv::ext::from_value::<i128>(Value::Binary(vec![0; 15])).is_err());
+}
+
 #[test]
 fn pass_f32() {
     test_round(std::f32::MAX, Value::from(std::f32::MAX));
@@ -197,3 +267,37 @@ fn pass_derive_serde_ext_struct() {
         Value::Ext(2, vec![5]),
     );
 }
+
+#[test]
+fn pass_ext_and_timestamp_through_rmp_serde_unchanged() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Envelope {
+        head: Value,
+        body: Option<Value>,
+        rest: Vec<Value>,
+    }
+
+    let ts = Value::Timestamp(rmpv::Timestamp::new(-1, 999_999_999).unwrap());
+    let vals = vec![
+        Value::Ext(5, vec![1, 2, 3]),
+        Value::Ext(-128, vec![]),
+        Value::Ext(1, vec![0; 300]),
+        // Invalid timestamp data stays an extension.
+        Value::Ext(-1, vec![1, 2, 3]),
+        Value::from_ext(-1, vec![0, 0, 0, 1]),
+        ts.clone(),
+        Value::Map(vec![(Value::Ext(2, vec![7]), ts.clone())]),
+    ];
+
+    for val in &vals {
+        let buf = rmp_serde::to_vec(val).unwrap();
+        assert_eq!(*val, rmp_serde::from_slice::<Value>(&buf).unwrap());
+        assert_eq!(*val, rmp_serde::from_slice::<rmpv::ValueRef<'_>>(&buf).unwrap().to_owned());
+    }
+
+    let envelope = Envelope { head: vals[0].clone(), body: Some(ts), rest: vals };
+    let buf = rmp_serde::to_vec_named(&envelope).unwrap();
+    assert_eq!(envelope, rmp_serde::from_slice(&buf).unwrap());
+    let buf = rmp_serde::to_vec(&envelope).unwrap();
+    assert_eq!(envelope, rmp_serde::from_slice(&buf).unwrap());
+}
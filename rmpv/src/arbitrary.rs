@@ -0,0 +1,97 @@
+//! [`Arbitrary`] implementations, for generating random values in property tests and fuzzers.
+//!
+//! Generated values are bounded in both nesting depth and container length, so that a single
+//! value stays small regardless of the amount of input entropy.
+
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{Integer, Utf8String, Value};
+
+/// The maximum nesting depth of a generated value.
+const MAX_DEPTH: usize = 4;
+/// The maximum length of a generated array, map, binary or string.
+const MAX_LEN: usize = 16;
+
+impl<'a> Arbitrary<'a> for Integer {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        if u.arbitrary()? {
+            Ok(Self::from(u.arbitrary::<u64>()?))
+        } else {
+            Ok(Self::from(u.arbitrary::<i64>()?))
+        }
+    }
+
+    #[inline]
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, Some(9))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Utf8String {
+    #[inline]
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from(arbitrary_str(u)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Value {
+    #[inline]
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_value(u, MAX_DEPTH)
+    }
+
+    #[inline]
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, None)
+    }
+}
+
+fn arbitrary_len(u: &mut Unstructured<'_>) -> Result<usize> {
+    u.int_in_range(0..=MAX_LEN)
+}
+
+fn arbitrary_str<'a>(u: &mut Unstructured<'a>) -> Result<&'a str> {
+    let s: &str = u.arbitrary()?;
+    // Truncate on a char boundary to respect `MAX_LEN`.
+    let end = s.char_indices().nth(MAX_LEN).map_or(s.len(), |(idx, ..)| idx);
+    Ok(&s[..end])
+}
+
+fn arbitrary_bytes(u: &mut Unstructured<'_>) -> Result<Vec<u8>> {
+    let len = arbitrary_len(u)?;
+    Ok(u.bytes(len.min(u.len()))?.to_vec())
+}
+
+fn arbitrary_value(u: &mut Unstructured<'_>, depth: usize) -> Result<Value> {
+    // Containers are only generated while there is depth left.
+    let kinds = if depth == 0 { 8 } else { 10 };
+
+    let val = match u.choose_index(kinds)? {
+        0 => Value::Nil,
+        1 => Value::Boolean(u.arbitrary()?),
+        2 => Value::Integer(u.arbitrary()?),
+        3 => Value::F32(u.arbitrary()?),
+        4 => Value::F64(u.arbitrary()?),
+        5 => Value::from(arbitrary_str(u)?),
+        6 => Value::Binary(arbitrary_bytes(u)?),
+        7 => Value::from_ext(u.arbitrary()?, arbitrary_bytes(u)?),
+        8 => {
+            let len = arbitrary_len(u)?;
+            let mut vec = Vec::with_capacity(len);
+            for _ in 0..len {
+                vec.push(arbitrary_value(u, depth - 1)?);
+            }
+            Value::Array(vec)
+        }
+        _ => {
+            let len = arbitrary_len(u)?;
+            let mut vec = Vec::with_capacity(len);
+            for _ in 0..len {
+                vec.push((arbitrary_value(u, depth - 1)?, arbitrary_value(u, depth - 1)?));
+            }
+            Value::Map(vec)
+        }
+    };
+
+    Ok(val)
+}
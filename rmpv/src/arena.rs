@@ -0,0 +1,463 @@
+//! Values allocated from a bump arena, see [`ArenaValue`].
+
+use std::collections::HashSet;
+use std::io::{self, Read};
+use std::{mem, str};
+
+use bumpalo::collections::Vec as BumpVec;
+use bumpalo::Bump;
+use rmp::decode::{read_marker, RmpRead};
+use rmp::Marker;
+
+use crate::decode::{Error, MAX_DEPTH};
+use crate::{Utf8StringRef, Value};
+
+// See https://github.com/3Hren/msgpack-rust/issues/151
+const PREALLOC_MAX: usize = 64 * 1024; // 64 KiB
+
+/// A [`ValueRef`](crate::ValueRef)-like tree whose nodes, strings and binaries are all allocated
+/// from a [`Bump`] arena, as returned by [`read_value_in`].
+///
+/// Decoding allocates nothing from the global allocator for small messages, and the whole tree is
+/// freed at once by resetting or dropping the arena, which makes decoding many short-lived
+/// messages cheap.
+///
+/// # Examples
+///
+/// ```
+/// use bumpalo::Bump;
+/// use rmpv::decode::read_value_in;
+/// use rmpv::ArenaValue;
+///
+/// let mut bump = Bump::new();
+///
+/// for _ in 0..3 {
+///     let val = read_value_in(&bump, &mut &[0x92, 0x01, 0xa1, b'a'][..]).unwrap();
+///     assert_eq!(Some(1), val[0].as_u64());
+///     assert_eq!(Some("a"), val[1].as_str());
+///     bump.reset();
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArenaValue<'b> {
+    /// Nil represents nil.
+    Nil,
+    /// Boolean represents true or false.
+    Boolean(bool),
+    /// Integer represents an integer.
+    Integer(crate::Integer),
+    /// A 32-bit floating point number.
+    F32(f32),
+    /// A 64-bit floating point number.
+    F64(f64),
+    /// String extending Raw type represents a UTF-8 string.
+    String(Utf8StringRef<'b>),
+    /// Binary extending Raw type represents a byte array.
+    Binary(&'b [u8]),
+    /// Array represents a sequence of objects.
+    Array(&'b [ArenaValue<'b>]),
+    /// Map represents key-value pairs of objects.
+    Map(&'b [(ArenaValue<'b>, ArenaValue<'b>)]),
+    /// Extended implements Extension interface: represents a tuple of type information and a byte
+    /// array where type information is an integer whose meaning is defined by applications.
+    Ext(i8, &'b [u8]),
+}
+
+impl<'b> ArenaValue<'b> {
+    /// Returns true if the `ArenaValue` is a Null. Returns false otherwise.
+    #[inline]
+    #[must_use]
+    pub fn is_nil(&self) -> bool {
+        matches!(*self, ArenaValue::Nil)
+    }
+
+    /// If the `ArenaValue` is an integer, return or cast it to a u64.
+    /// Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            ArenaValue::Integer(ref n) => n.as_u64(),
+            _ => None,
+        }
+    }
+
+    /// If the `ArenaValue` is an integer, return or cast it to a i64.
+    /// Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            ArenaValue::Integer(ref n) => n.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// If the `ArenaValue` is a number, return or cast it to a f64.
+    /// Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            ArenaValue::Integer(ref n) => n.as_f64(),
+            ArenaValue::F32(n) => Some(f64::from(n)),
+            ArenaValue::F64(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// If the `ArenaValue` is a valid UTF-8 string, returns it, borrowed from the arena.
+    /// Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'b str> {
+        match *self {
+            ArenaValue::String(s) => s.into_str(),
+            _ => None,
+        }
+    }
+
+    /// If the `ArenaValue` is a binary, returns its bytes, borrowed from the arena.
+    /// Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> Option<&'b [u8]> {
+        match *self {
+            ArenaValue::Binary(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `ArenaValue` is an array, returns its elements. Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_array(&self) -> Option<&'b [ArenaValue<'b>]> {
+        match *self {
+            ArenaValue::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// If the `ArenaValue` is a map, returns its entries. Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_map(&self) -> Option<&'b [(ArenaValue<'b>, ArenaValue<'b>)]> {
+        match *self {
+            ArenaValue::Map(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the first map entry whose key is the string `key`.
+    /// Returns None if the `ArenaValue` is not a map or has no such key.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&'b ArenaValue<'b>> {
+        self.as_map()?.iter().find(|(k, _)| k.as_str() == Some(key)).map(|(_, val)| val)
+    }
+
+    /// Copies the tree out of the arena into an owned [`Value`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bumpalo::Bump;
+    /// use rmpv::decode::read_value_in;
+    /// use rmpv::Value;
+    ///
+    /// let bump = Bump::new();
+    /// let val = read_value_in(&bump, &mut &[0x91, 0xc3][..]).unwrap();
+    ///
+    /// assert_eq!(Value::Array(vec![Value::Boolean(true)]), val.to_owned());
+    /// ```
+    #[must_use]
+    pub fn to_owned(&self) -> Value {
+        match *self {
+            ArenaValue::Nil => Value::Nil,
+            ArenaValue::Boolean(val) => Value::Boolean(val),
+            ArenaValue::Integer(val) => Value::Integer(val),
+            ArenaValue::F32(val) => Value::F32(val),
+            ArenaValue::F64(val) => Value::F64(val),
+            ArenaValue::String(val) => Value::String(val.into()),
+            ArenaValue::Binary(val) => Value::Binary(val.to_vec()),
+            ArenaValue::Array(vec) => Value::Array(vec.iter().map(ArenaValue::to_owned).collect()),
+            ArenaValue::Map(map) => Value::Map(map.iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect()),
+            ArenaValue::Ext(ty, buf) => Value::from_ext(ty, buf.to_vec()),
+        }
+    }
+}
+
+static NIL_ARENA: ArenaValue<'static> = ArenaValue::Nil;
+
+impl<'b> std::ops::Index<usize> for ArenaValue<'b> {
+    type Output = Self;
+
+    fn index(&self, index: usize) -> &Self {
+        self.as_array().and_then(|v| v.get(index)).unwrap_or(&NIL_ARENA)
+    }
+}
+
+impl<'b> std::ops::Index<&str> for ArenaValue<'b> {
+    type Output = Self;
+
+    fn index(&self, index: &str) -> &Self {
+        self.get(index).unwrap_or(&NIL_ARENA)
+    }
+}
+
+/// Attempts to read a complete value from the given reader, allocating the tree from `bump`.
+///
+/// Strings, binaries and extension data are copied into the arena, so the reader needs not
+/// outlive the value.
+///
+/// # Errors
+///
+/// Same as [`read_value`](crate::decode::read_value).
+pub fn read_value_in<'b, R>(bump: &'b Bump, rd: &mut R) -> Result<ArenaValue<'b>, Error>
+    where R: Read
+{
+    let mut st = ArenaState { bump, interner: None };
+    read_value_in_inner(&mut st, rd, MAX_DEPTH as u16, false)
+}
+
+/// Like [`read_value_in`], sharing the map keys already seen by `interner` instead of allocating
+/// them again.
+///
+/// Records of a batch usually repeat the same field names, which are then allocated only once
+/// per arena. Every string key that is valid UTF-8 is interned, whatever its length.
+///
+/// Interning is only offered for arena values: [`Value`] owns each of its strings, so its keys
+/// can not share an allocation without changing its representation. Keys that are not strings
+/// and string values are allocated as usual.
+///
+/// # Errors
+///
+/// Same as [`read_value`](crate::decode::read_value).
+///
+/// # Examples
+///
+/// ```
+/// use bumpalo::Bump;
+/// use rmpv::decode::{read_value_in_interned, KeyInterner};
+///
+/// let bump = Bump::new();
+/// let mut interner = KeyInterner::new();
+///
+/// let buf = [0x81, 0xa2, b'i', b'd', 0x01];
+/// let first = read_value_in_interned(&bump, &mut &buf[..], &mut interner).unwrap();
+/// let second = read_value_in_interned(&bump, &mut &buf[..], &mut interner).unwrap();
+///
+/// let key = |val: rmpv::ArenaValue<'_>| val.as_map().unwrap()[0].0.as_str().unwrap().as_ptr();
+/// assert_eq!(key(first), key(second));
+/// assert_eq!(1, interner.len());
+/// ```
+pub fn read_value_in_interned<'b, R>(bump: &'b Bump, rd: &mut R, interner: &mut KeyInterner<'b>) -> Result<ArenaValue<'b>, Error>
+    where R: Read
+{
+    let mut st = ArenaState { bump, interner: Some(interner) };
+    read_value_in_inner(&mut st, rd, MAX_DEPTH as u16, false)
+}
+
+/// A set of map keys allocated in an arena, shared between the values decoded by
+/// [`read_value_in_interned`].
+///
+/// The interner borrows the arena, so it must be dropped before resetting the arena.
+#[derive(Debug, Default)]
+pub struct KeyInterner<'b> {
+    keys: HashSet<&'b str>,
+    /// Holds each key while it is looked up, so that keys seen before take no arena memory.
+    scratch: Vec<u8>,
+}
+
+impl<'b> KeyInterner<'b> {
+    /// Creates an interner without any key.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct keys interned.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if no key was interned yet.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the interned copy of `key`, allocating it in `bump` when first seen.
+    fn intern(&mut self, bump: &'b Bump, key: &str) -> &'b str {
+        match self.keys.get(key) {
+            Some(&interned) => interned,
+            None => {
+                let interned = &*bump.alloc_str(key);
+                self.keys.insert(interned);
+                interned
+            }
+        }
+    }
+}
+
+/// Settings threaded through the recursive decoding functions.
+struct ArenaState<'b, 'i> {
+    bump: &'b Bump,
+    interner: Option<&'i mut KeyInterner<'b>>,
+}
+
+fn read_bin_in<'b, R: Read>(bump: &'b Bump, rd: &mut R, len: usize) -> Result<&'b [u8], Error> {
+    if len <= PREALLOC_MAX {
+        let buf = bump.alloc_slice_fill_copy(len, 0u8);
+        rd.read_exact(buf).map_err(Error::InvalidDataRead)?;
+        return Ok(buf);
+    }
+
+    let mut buf = Vec::new();
+    read_to_vec(rd, len, &mut buf)?;
+    Ok(bump.alloc_slice_copy(&buf))
+}
+
+/// Appends exactly `len` bytes to `buf`.
+fn read_to_vec<R: Read>(rd: &mut R, len: usize, buf: &mut Vec<u8>) -> Result<(), Error> {
+    // Larger lengths are not trusted until the data actually arrives.
+    buf.reserve(len.min(PREALLOC_MAX));
+    let bytes_read = rd.take(len as u64).read_to_end(buf).map_err(Error::InvalidDataRead)?;
+    if bytes_read != len {
+        return Err(Error::InvalidDataRead(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("Expected {len} bytes, read {bytes_read} bytes"),
+        )));
+    }
+    Ok(())
+}
+
+fn read_str_in<'b, R: Read>(st: &mut ArenaState<'b, '_>, rd: &mut R, len: usize, key: bool) -> Result<ArenaValue<'b>, Error> {
+    if let (true, Some(interner)) = (key, st.interner.as_deref_mut()) {
+        let mut buf = mem::take(&mut interner.scratch);
+        buf.clear();
+        let res = read_to_vec(rd, len, &mut buf).map(|()| match rmp::decode::from_utf8(&buf) {
+            Ok(s) => Utf8StringRef::from(interner.intern(st.bump, s)),
+            Err(err) => Utf8StringRef { s: Err((st.bump.alloc_slice_copy(&buf), err)) },
+        });
+        interner.scratch = buf;
+        return res.map(ArenaValue::String);
+    }
+
+    let buf = read_bin_in(st.bump, rd, len)?;
+    let s = match rmp::decode::from_utf8(buf) {
+        Ok(s) => Utf8StringRef::from(s),
+        Err(err) => Utf8StringRef { s: Err((buf, err)) },
+    };
+    Ok(ArenaValue::String(s))
+}
+
+fn read_ext_in<'b, R: Read>(st: &mut ArenaState<'b, '_>, rd: &mut R, len: usize) -> Result<ArenaValue<'b>, Error> {
+    let ty = rd.read_data_i8()?;
+    Ok(ArenaValue::Ext(ty, read_bin_in(st.bump, rd, len)?))
+}
+
+fn read_array_in<'b, R: Read>(st: &mut ArenaState<'b, '_>, rd: &mut R, len: usize, depth: u16) -> Result<ArenaValue<'b>, Error> {
+    // Note: Do not preallocate a Vec of size `len`.
+    // See https://github.com/3Hren/msgpack-rust/issues/151
+    let mut vec = BumpVec::new_in(st.bump);
+    for _ in 0..len {
+        vec.push(read_value_in_inner(st, rd, depth, false)?);
+    }
+    Ok(ArenaValue::Array(vec.into_bump_slice()))
+}
+
+fn read_map_in<'b, R: Read>(st: &mut ArenaState<'b, '_>, rd: &mut R, len: usize, depth: u16) -> Result<ArenaValue<'b>, Error> {
+    let mut vec = BumpVec::new_in(st.bump);
+    for _ in 0..len {
+        let key = read_value_in_inner(st, rd, depth, true)?;
+        let val = read_value_in_inner(st, rd, depth, false)?;
+        vec.push((key, val));
+    }
+    Ok(ArenaValue::Map(vec.into_bump_slice()))
+}
+
+fn read_value_in_inner<'b, R: Read>(st: &mut ArenaState<'b, '_>, rd: &mut R, depth: u16, key: bool) -> Result<ArenaValue<'b>, Error> {
+    let depth = crate::decode::decrement_depth(depth)?;
+    let val = match read_marker(rd)? {
+        Marker::Null | Marker::Reserved => ArenaValue::Nil,
+        Marker::True => ArenaValue::Boolean(true),
+        Marker::False => ArenaValue::Boolean(false),
+        Marker::FixPos(val) => ArenaValue::Integer(val.into()),
+        Marker::FixNeg(val) => ArenaValue::Integer(val.into()),
+        Marker::U8 => ArenaValue::Integer(rd.read_data_u8()?.into()),
+        Marker::U16 => ArenaValue::Integer(rd.read_data_u16()?.into()),
+        Marker::U32 => ArenaValue::Integer(rd.read_data_u32()?.into()),
+        Marker::U64 => ArenaValue::Integer(rd.read_data_u64()?.into()),
+        Marker::I8 => ArenaValue::Integer(rd.read_data_i8()?.into()),
+        Marker::I16 => ArenaValue::Integer(rd.read_data_i16()?.into()),
+        Marker::I32 => ArenaValue::Integer(rd.read_data_i32()?.into()),
+        Marker::I64 => ArenaValue::Integer(rd.read_data_i64()?.into()),
+        Marker::F32 => ArenaValue::F32(rd.read_data_f32()?),
+        Marker::F64 => ArenaValue::F64(rd.read_data_f64()?),
+        Marker::FixStr(len) => read_str_in(st, rd, len.into(), key)?,
+        Marker::Str8 => {
+            let len = rd.read_data_u8()? as usize;
+            read_str_in(st, rd, len, key)?
+        }
+        Marker::Str16 => {
+            let len = rd.read_data_u16()? as usize;
+            read_str_in(st, rd, len, key)?
+        }
+        Marker::Str32 => {
+            let len = rd.read_data_u32()? as usize;
+            read_str_in(st, rd, len, key)?
+        }
+        Marker::Bin8 => {
+            let len = rd.read_data_u8()? as usize;
+            ArenaValue::Binary(read_bin_in(st.bump, rd, len)?)
+        }
+        Marker::Bin16 => {
+            let len = rd.read_data_u16()? as usize;
+            ArenaValue::Binary(read_bin_in(st.bump, rd, len)?)
+        }
+        Marker::Bin32 => {
+            let len = rd.read_data_u32()? as usize;
+            ArenaValue::Binary(read_bin_in(st.bump, rd, len)?)
+        }
+        Marker::FixArray(len) => read_array_in(st, rd, len.into(), depth)?,
+        Marker::Array16 => {
+            let len = rd.read_data_u16()? as usize;
+            read_array_in(st, rd, len, depth)?
+        }
+        Marker::Array32 => {
+            let len = rd.read_data_u32()? as usize;
+            read_array_in(st, rd, len, depth)?
+        }
+        Marker::FixMap(len) => read_map_in(st, rd, len.into(), depth)?,
+        Marker::Map16 => {
+            let len = rd.read_data_u16()? as usize;
+            read_map_in(st, rd, len, depth)?
+        }
+        Marker::Map32 => {
+            let len = rd.read_data_u32()? as usize;
+            read_map_in(st, rd, len, depth)?
+        }
+        Marker::FixExt1 => read_ext_in(st, rd, 1)?,
+        Marker::FixExt2 => read_ext_in(st, rd, 2)?,
+        Marker::FixExt4 => read_ext_in(st, rd, 4)?,
+        Marker::FixExt8 => read_ext_in(st, rd, 8)?,
+        Marker::FixExt16 => read_ext_in(st, rd, 16)?,
+        Marker::Ext8 => {
+            let len = rd.read_data_u8()? as usize;
+            read_ext_in(st, rd, len)?
+        }
+        Marker::Ext16 => {
+            let len = rd.read_data_u16()? as usize;
+            read_ext_in(st, rd, len)?
+        }
+        Marker::Ext32 => {
+            let len = rd.read_data_u32()? as usize;
+            read_ext_in(st, rd, len)?
+        }
+    };
+    Ok(val)
+}
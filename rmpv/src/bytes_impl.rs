@@ -0,0 +1,73 @@
+//! Conversions between values and `bytes::Bytes`, enabled by the `bytes` feature.
+//!
+//! `Value::Binary` owns a `Vec<u8>`, and adding a `Bytes` variant only when the feature is
+//! enabled would break every exhaustive match on `Value` in crates that do not enable it. Instead,
+//! binaries are moved in and out of `Bytes` without copying where possible, and blobs borrowed by
+//! a [`ValueRef`] decoded from a `Bytes` buffer can be sliced out of it without copying at all.
+
+use ::bytes::Bytes;
+
+use crate::{Value, ValueRef};
+
+impl From<Bytes> for Value {
+    /// Converts the bytes into a binary, reusing their allocation if they are not shared.
+    #[inline]
+    fn from(val: Bytes) -> Self {
+        Self::Binary(val.into())
+    }
+}
+
+impl TryFrom<Value> for Bytes {
+    type Error = Value;
+
+    /// Converts a binary into bytes without copying it, or returns the value back otherwise.
+    #[inline]
+    fn try_from(val: Value) -> Result<Self, Value> {
+        match val {
+            Value::Binary(val) => Ok(val.into()),
+            val => Err(val),
+        }
+    }
+}
+
+impl ValueRef<'_> {
+    /// Returns the data of a binary, string or extension as a slice of `buf`, sharing its
+    /// allocation instead of copying the data.
+    ///
+    /// Returns None for other values, or if the data does not lie within `buf`, which is the case
+    /// unless this value was decoded from it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use rmpv::decode::read_value_ref;
+    ///
+    /// // A message received from the network: ["id", <bin 4>].
+    /// let buf = Bytes::from(vec![0x92, 0xa2, b'i', b'd', 0xc4, 0x04, 1, 2, 3, 4]);
+    ///
+    /// let msg = read_value_ref(&mut &buf[..]).unwrap();
+    /// let blob = msg.index(1).slice_bytes(&buf).unwrap();
+    ///
+    /// assert_eq!(&[1, 2, 3, 4][..], blob);
+    /// assert_eq!(buf[6..].as_ptr(), blob.as_ptr());
+    /// ```
+    #[must_use]
+    pub fn slice_bytes(&self, buf: &Bytes) -> Option<Bytes> {
+        let data = match *self {
+            ValueRef::Binary(data) | ValueRef::Ext(_, data) => data,
+            ValueRef::String(ref val) => match val.s {
+                Ok(s) => s.as_bytes(),
+                Err((data, _)) => data,
+            },
+            _ => return None,
+        };
+
+        // `Bytes::slice_ref` panics on slices from elsewhere, and accepts any empty slice.
+        let start = (data.as_ptr() as usize).checked_sub(buf.as_ptr() as usize)?;
+        if start + data.len() > buf.len() {
+            return None;
+        }
+        Some(buf.slice(start..start + data.len()))
+    }
+}
@@ -0,0 +1,33 @@
+//! Conversions between timestamps and `chrono::DateTime<Utc>`, enabled by the `chrono` feature.
+//!
+//! chrono serializes its types as strings, so `ext::to_value` has nothing to recognize them by.
+//! Fields of this type are mapped to the timestamp extension type with
+//! `#[serde(with = "rmpv::ext::datetime")]` instead.
+
+use std::convert::TryFrom;
+
+use ::chrono::{DateTime, Timelike, Utc};
+
+use crate::Timestamp;
+
+impl TryFrom<DateTime<Utc>> for Timestamp {
+    type Error = DateTime<Utc>;
+
+    /// Converts a date and time into a timestamp, returning it back if it is a leap second, which
+    /// timestamps can not represent.
+    #[inline]
+    fn try_from(time: DateTime<Utc>) -> Result<Self, DateTime<Utc>> {
+        Self::new(time.timestamp(), time.nanosecond()).ok_or(time)
+    }
+}
+
+impl TryFrom<Timestamp> for DateTime<Utc> {
+    type Error = Timestamp;
+
+    /// Converts a timestamp into a date and time, returning it back if it is out of the range of
+    /// chrono.
+    #[inline]
+    fn try_from(ts: Timestamp) -> Result<Self, Timestamp> {
+        Self::from_timestamp(ts.secs(), ts.nanos()).ok_or(ts)
+    }
+}
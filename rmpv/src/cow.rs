@@ -0,0 +1,141 @@
+//! Values borrowing or owning their data, see [`CowValue`].
+
+use std::borrow::Cow;
+
+use crate::{Integer, Utf8String, Value, ValueRef};
+
+/// A value whose strings, binaries and extension data are each either borrowed or owned.
+///
+/// Converting a [`ValueRef`] into a `CowValue` copies no data, only the containers. Parts of the
+/// tree can then be replaced with owned data, and [`into_static`](Self::into_static) or
+/// [`into_owned`](Self::into_owned) only copy the pieces that are still borrowed, reusing the
+/// allocations of the owned ones.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use rmpv::decode::read_value_ref;
+/// use rmpv::{CowValue, Value};
+///
+/// // ["le message", "id"]
+/// let buf = [0x92, 0xaa, 0x6c, 0x65, 0x20, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65, 0xa2, 0x69, 0x64];
+///
+/// let mut val = CowValue::from(read_value_ref(&mut &buf[..]).unwrap());
+/// if let CowValue::Array(ref mut vec) = val {
+///     vec.truncate(1);
+///     vec.push(CowValue::String(Cow::Owned(String::from("tail"))));
+/// }
+///
+/// // Only "le message" is copied, "id" was dropped before ever being copied.
+/// let val: CowValue<'static> = val.into_static();
+/// assert_eq!(Value::Array(vec![Value::from("le message"), Value::from("tail")]), val.into_owned());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum CowValue<'a> {
+    /// Nil represents nil.
+    Nil,
+    /// Boolean represents true or false.
+    Boolean(bool),
+    /// Integer represents an integer.
+    Integer(Integer),
+    /// A 32-bit floating point number.
+    F32(f32),
+    /// A 64-bit floating point number.
+    F64(f64),
+    /// String extending Raw type represents a UTF-8 string.
+    String(Cow<'a, str>),
+    /// A string that is not valid UTF-8, kept as its raw bytes.
+    InvalidString(Cow<'a, [u8]>),
+    /// Binary extending Raw type represents a byte array.
+    Binary(Cow<'a, [u8]>),
+    /// Array represents a sequence of objects.
+    Array(Vec<CowValue<'a>>),
+    /// Map represents key-value pairs of objects.
+    Map(Vec<(CowValue<'a>, CowValue<'a>)>),
+    /// Extended implements Extension interface: represents a tuple of type information and a byte
+    /// array where type information is an integer whose meaning is defined by applications.
+    Ext(i8, Cow<'a, [u8]>),
+}
+
+impl<'a> CowValue<'a> {
+    /// Copies the data that is still borrowed, so that the value no longer depends on the input.
+    #[must_use]
+    pub fn into_static(self) -> CowValue<'static> {
+        match self {
+            CowValue::Nil => CowValue::Nil,
+            CowValue::Boolean(val) => CowValue::Boolean(val),
+            CowValue::Integer(val) => CowValue::Integer(val),
+            CowValue::F32(val) => CowValue::F32(val),
+            CowValue::F64(val) => CowValue::F64(val),
+            CowValue::String(val) => CowValue::String(Cow::Owned(val.into_owned())),
+            CowValue::InvalidString(val) => CowValue::InvalidString(Cow::Owned(val.into_owned())),
+            CowValue::Binary(val) => CowValue::Binary(Cow::Owned(val.into_owned())),
+            CowValue::Array(vec) => CowValue::Array(vec.into_iter().map(CowValue::into_static).collect()),
+            CowValue::Map(map) => {
+                CowValue::Map(map.into_iter().map(|(k, v)| (k.into_static(), v.into_static())).collect())
+            }
+            CowValue::Ext(ty, data) => CowValue::Ext(ty, Cow::Owned(data.into_owned())),
+        }
+    }
+
+    /// Converts into an owned [`Value`], copying only the data that is still borrowed.
+    #[must_use]
+    pub fn into_owned(self) -> Value {
+        match self {
+            CowValue::Nil => Value::Nil,
+            CowValue::Boolean(val) => Value::Boolean(val),
+            CowValue::Integer(val) => Value::Integer(val),
+            CowValue::F32(val) => Value::F32(val),
+            CowValue::F64(val) => Value::F64(val),
+            CowValue::String(val) => Value::from(val.into_owned()),
+            CowValue::InvalidString(val) => Value::String(Utf8String::from(val.into_owned())),
+            CowValue::Binary(val) => Value::Binary(val.into_owned()),
+            CowValue::Array(vec) => Value::Array(vec.into_iter().map(CowValue::into_owned).collect()),
+            CowValue::Map(map) => Value::Map(map.into_iter().map(|(k, v)| (k.into_owned(), v.into_owned())).collect()),
+            CowValue::Ext(ty, data) => Value::from_ext(ty, data.into_owned()),
+        }
+    }
+
+    /// Returns `true` if no data of the value, including the nested ones, is borrowed.
+    #[must_use]
+    pub fn is_owned(&self) -> bool {
+        match *self {
+            CowValue::String(ref val) => matches!(*val, Cow::Owned(..)),
+            CowValue::InvalidString(ref val) | CowValue::Binary(ref val) | CowValue::Ext(_, ref val) => {
+                matches!(*val, Cow::Owned(..))
+            }
+            CowValue::Array(ref vec) => vec.iter().all(CowValue::is_owned),
+            CowValue::Map(ref map) => map.iter().all(|(k, v)| k.is_owned() && v.is_owned()),
+            CowValue::Nil | CowValue::Boolean(..) | CowValue::Integer(..) | CowValue::F32(..) | CowValue::F64(..) => true,
+        }
+    }
+}
+
+impl<'a> From<ValueRef<'a>> for CowValue<'a> {
+    fn from(val: ValueRef<'a>) -> Self {
+        match val {
+            ValueRef::Nil => CowValue::Nil,
+            ValueRef::Boolean(val) => CowValue::Boolean(val),
+            ValueRef::Integer(val) => CowValue::Integer(val),
+            ValueRef::F32(val) => CowValue::F32(val),
+            ValueRef::F64(val) => CowValue::F64(val),
+            ValueRef::String(val) => match val.s {
+                Ok(s) => CowValue::String(Cow::Borrowed(s)),
+                Err((buf, _)) => CowValue::InvalidString(Cow::Borrowed(buf)),
+            },
+            ValueRef::Binary(val) => CowValue::Binary(Cow::Borrowed(val)),
+            ValueRef::Array(vec) => CowValue::Array(vec.into_iter().map(CowValue::from).collect()),
+            ValueRef::Map(map) => CowValue::Map(map.into_iter().map(|(k, v)| (k.into(), v.into())).collect()),
+            ValueRef::Ext(ty, data) => CowValue::Ext(ty, Cow::Borrowed(data)),
+        }
+    }
+}
+
+impl From<CowValue<'_>> for Value {
+    #[inline]
+    fn from(val: CowValue<'_>) -> Self {
+        val.into_owned()
+    }
+}
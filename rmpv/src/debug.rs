@@ -0,0 +1,119 @@
+//! Debug formatting with hex dumps of binary payloads, see [`DebugHex`].
+
+use std::fmt::{self, Debug};
+
+use crate::Value;
+
+/// The number of payload bytes shown by default before the dump is truncated.
+const DEFAULT_LIMIT: usize = 32;
+
+/// Formats a [`Value`] like its `Debug` implementation, except that the payloads of binaries,
+/// extensions and invalid strings are shown as hex dumps prefixed by their length, truncated
+/// after a number of bytes.
+///
+/// Created by [`Value::debug_hex`]. The alternate flag `{:#?}` spreads the tree over several
+/// lines, like it does for `Debug`.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+///
+/// let val = Value::Array(vec![Value::from("id"), Value::Binary(vec![0xca, 0xfe]), Value::Ext(7, vec![0; 100])]);
+///
+/// assert_eq!(
+///     r#"Array(["id", Binary(2 bytes: ca fe), Ext(7, 100 bytes: 00 00 00 00 ..)])"#,
+///     format!("{:?}", val.debug_hex().limit(4)),
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct DebugHex<'a> {
+    val: &'a Value,
+    limit: usize,
+}
+
+impl<'a> DebugHex<'a> {
+    #[inline]
+    pub(crate) const fn new(val: &'a Value) -> Self {
+        Self { val, limit: DEFAULT_LIMIT }
+    }
+
+    /// Sets the number of bytes shown for each payload, 32 by default.
+    #[inline]
+    #[must_use]
+    pub const fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    #[inline]
+    const fn with(self, val: &'a Value) -> Self {
+        Self { val, limit: self.limit }
+    }
+}
+
+impl Debug for DebugHex<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = |data| HexDump { data, limit: self.limit };
+
+        match *self.val {
+            Value::String(ref val) => match val.as_str() {
+                Some(s) => Debug::fmt(s, fmt),
+                None => fmt.debug_tuple("InvalidString").field(&hex(val.as_bytes())).finish(),
+            },
+            Value::Binary(ref data) => fmt.debug_tuple("Binary").field(&hex(data)).finish(),
+            Value::Ext(ty, ref data) => fmt.debug_tuple("Ext").field(&ty).field(&hex(data)).finish(),
+            Value::Array(ref vec) => {
+                let items = Items(vec.iter().map(|val| self.with(val)));
+                fmt.debug_tuple("Array").field(&items).finish()
+            }
+            Value::Map(ref map) => {
+                let entries = Entries(map.iter().map(|(k, v)| (self.with(k), self.with(v))));
+                fmt.debug_tuple("Map").field(&entries).finish()
+            }
+            ref val => Debug::fmt(val, fmt),
+        }
+    }
+}
+
+/// The elements of an array, formatted as a list.
+struct Items<I>(I);
+
+impl<'a, I> Debug for Items<I>
+    where I: Iterator<Item = DebugHex<'a>> + Clone
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list().entries(self.0.clone()).finish()
+    }
+}
+
+/// The entries of a map, formatted as a map.
+struct Entries<I>(I);
+
+impl<'a, I> Debug for Entries<I>
+    where I: Iterator<Item = (DebugHex<'a>, DebugHex<'a>)> + Clone
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_map().entries(self.0.clone()).finish()
+    }
+}
+
+/// A payload, formatted as its length followed by its first bytes in hex.
+struct HexDump<'a> {
+    data: &'a [u8],
+    limit: usize,
+}
+
+impl Debug for HexDump<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{} bytes", self.data.len())?;
+        for (idx, byte) in self.data.iter().take(self.limit).enumerate() {
+            fmt.write_str(if idx == 0 { ": " } else { " " })?;
+            write!(fmt, "{byte:02x}")?;
+        }
+        if self.data.len() > self.limit {
+            fmt.write_str(" ..")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::doc::MessageScan;
+use super::{item_header, read_value_with_options, DecodeOptions, Error};
+use crate::Value;
+
+/// Reads a complete value from the given asynchronous reader, without blocking the executor.
+///
+/// The bytes of the value are buffered as they arrive, and decoded once all of them are read.
+/// Nothing past the end of the value is consumed.
+///
+/// # Errors
+///
+/// Same as [`read_value`](super::read_value).
+///
+/// # Examples
+///
+/// ```
+/// # async fn run() {
+/// use rmpv::decode::read_value_async;
+/// use rmpv::Value;
+///
+/// let buf = [0x92, 0x01, 0xa1, 0x61];
+///
+/// assert_eq!(Value::from(vec![Value::from(1), Value::from("a")]), read_value_async(&mut &buf[..]).await.unwrap());
+/// # }
+/// ```
+#[inline]
+pub async fn read_value_async<R>(rd: &mut R) -> Result<Value, Error>
+    where R: AsyncRead + Unpin
+{
+    read_value_async_with_options(rd, &DecodeOptions::new()).await
+}
+
+/// Reads a complete value from the given asynchronous reader, enforcing the limits and policies
+/// of `opts`.
+///
+/// Lengths and element counts are checked while reading, before buffering the data they announce.
+///
+/// # Errors
+///
+/// Same as [`read_value_with_options`].
+pub async fn read_value_async_with_options<R>(rd: &mut R, opts: &DecodeOptions) -> Result<Value, Error>
+    where R: AsyncRead + Unpin
+{
+    let mut buf = Vec::new();
+    let mut scan = MessageScan::new(opts);
+
+    while scan.next_item()? {
+        let start = buf.len();
+        buf.push(rd.read_u8().await.map_err(Error::InvalidMarkerRead)?);
+        let (header, len, nested) = loop {
+            match item_header(&buf[start..]) {
+                Some(header) => break header,
+                None => buf.push(rd.read_u8().await.map_err(Error::InvalidDataRead)?),
+            }
+        };
+        // The header of extensions ends with the type, after the length.
+        let len = start + header - buf.len() + scan.data_len(len, nested)?;
+        let read = (&mut *rd).take(len as u64).read_to_end(&mut buf).await.map_err(Error::InvalidDataRead)?;
+        if read < len {
+            return Err(Error::InvalidDataRead(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+    }
+
+    read_value_with_options(&mut &buf[..], opts)
+}
@@ -0,0 +1,207 @@
+use std::io::{self, ErrorKind, Read};
+use std::ops::Range;
+
+use super::options::Limits;
+use super::{item_header, read_value_ref_with_options, DecodeOptions, Error};
+use crate::{Integer, Utf8StringRef, ValueRef};
+
+/// A single MessagePack message read from an [`io::Read`], owning the bytes its [`ValueRef`]
+/// views borrow from.
+///
+/// Reading consumes exactly the bytes of one message, so the rest of the reader is left for the
+/// next one. The message is decoded and checked against the decoding limits once, keeping the
+/// positions of its strings, binaries and extensions in the buffer. [`value`](Self::value) builds
+/// the view from them without parsing the message again, and cannot fail.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::ValueRefDoc;
+/// use rmpv::ValueRef;
+///
+/// // ["le message", 42] followed by the next message.
+/// let buf = [0x92, 0xaa, 0x6c, 0x65, 0x20, 0x6d, 0x65, 0x73, 0x73, 0x61, 0x67, 0x65, 0x2a, 0xc0];
+/// let mut rd = &buf[..];
+///
+/// let doc = ValueRefDoc::read(&mut rd).unwrap();
+/// assert_eq!(Some("le message"), doc.value()[0].as_str());
+/// assert_eq!(ValueRef::from(42), doc.value()[1]);
+/// assert_eq!(&[0xc0], rd);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueRefDoc {
+    buf: Vec<u8>,
+    root: Node,
+}
+
+impl ValueRefDoc {
+    /// Reads a complete message from `rd`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`read_value`](super::read_value).
+    #[inline]
+    pub fn read<R: Read>(rd: &mut R) -> Result<Self, Error> {
+        Self::read_with_options(rd, DecodeOptions::new())
+    }
+
+    /// Reads a complete message from `rd`, enforcing the limits and policies of `opts`.
+    ///
+    /// Lengths and element counts are checked while reading, before buffering the data they
+    /// announce.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`read_value_with_options`](super::read_value_with_options).
+    pub fn read_with_options<R: Read>(rd: &mut R, opts: DecodeOptions) -> Result<Self, Error> {
+        let mut buf = Vec::new();
+        let mut scan = MessageScan::new(&opts);
+
+        while scan.next_item()? {
+            let start = buf.len();
+            read_bytes(rd, &mut buf, 1).map_err(Error::InvalidMarkerRead)?;
+            let (header, len, nested) = loop {
+                match item_header(&buf[start..]) {
+                    Some(header) => break header,
+                    None => read_bytes(rd, &mut buf, 1).map_err(Error::InvalidDataRead)?,
+                }
+            };
+            // The header of extensions ends with the type, after the length.
+            let len = start + header - buf.len() + scan.data_len(len, nested)?;
+            read_bytes(rd, &mut buf, len).map_err(Error::InvalidDataRead)?;
+        }
+
+        // Also checks the depth and the duplicate keys, which are left to the decoder.
+        let root = Node::new(&read_value_ref_with_options(&mut &buf[..], &opts)?, &buf);
+        Ok(Self { buf, root })
+    }
+
+    /// Returns a view of the message.
+    ///
+    /// Only the arrays and maps of the view are allocated, and its strings checked to be UTF-8
+    /// again, so it is still worth keeping around when accessed repeatedly.
+    #[must_use]
+    pub fn value(&self) -> ValueRef<'_> {
+        self.root.view(&self.buf)
+    }
+
+    /// Returns the encoded message.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Consumes the document, returning the encoded message.
+    #[inline]
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// A decoded value, with the data it borrowed from the buffer replaced by its position.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Nil,
+    Boolean(bool),
+    Integer(Integer),
+    F32(f32),
+    F64(f64),
+    String(Range<usize>),
+    Binary(Range<usize>),
+    Array(Vec<Node>),
+    Map(Vec<(Node, Node)>),
+    Ext(i8, Range<usize>),
+}
+
+impl Node {
+    /// Converts a value borrowing from `buf`.
+    fn new(val: &ValueRef<'_>, buf: &[u8]) -> Self {
+        let range = |data: &[u8]| {
+            // Empty data may not point into the buffer.
+            let start = if data.is_empty() { 0 } else { data.as_ptr() as usize - buf.as_ptr() as usize };
+            start..start + data.len()
+        };
+
+        match *val {
+            ValueRef::Nil => Self::Nil,
+            ValueRef::Boolean(val) => Self::Boolean(val),
+            ValueRef::Integer(val) => Self::Integer(val),
+            ValueRef::F32(val) => Self::F32(val),
+            ValueRef::F64(val) => Self::F64(val),
+            ValueRef::String(ref val) => Self::String(range(val.as_bytes())),
+            ValueRef::Binary(data) => Self::Binary(range(data)),
+            ValueRef::Array(ref vec) => Self::Array(vec.iter().map(|val| Self::new(val, buf)).collect()),
+            ValueRef::Map(ref vec) => {
+                Self::Map(vec.iter().map(|(key, val)| (Self::new(key, buf), Self::new(val, buf))).collect())
+            }
+            ValueRef::Ext(ty, data) => Self::Ext(ty, range(data)),
+        }
+    }
+
+    /// Builds the value back, borrowing from `buf`.
+    fn view<'a>(&self, buf: &'a [u8]) -> ValueRef<'a> {
+        match *self {
+            Self::Nil => ValueRef::Nil,
+            Self::Boolean(val) => ValueRef::Boolean(val),
+            Self::Integer(val) => ValueRef::Integer(val),
+            Self::F32(val) => ValueRef::F32(val),
+            Self::F64(val) => ValueRef::F64(val),
+            Self::String(ref range) => {
+                let data = &buf[range.clone()];
+                let s = rmp::decode::from_utf8(data).map_err(|err| (data, err));
+                ValueRef::String(Utf8StringRef { s })
+            }
+            Self::Binary(ref range) => ValueRef::Binary(&buf[range.clone()]),
+            Self::Array(ref vec) => ValueRef::Array(vec.iter().map(|val| val.view(buf)).collect()),
+            Self::Map(ref vec) => ValueRef::Map(vec.iter().map(|(key, val)| (key.view(buf), val.view(buf))).collect()),
+            Self::Ext(ty, ref range) => ValueRef::Ext(ty, &buf[range.clone()]),
+        }
+    }
+}
+
+/// Tracks the values left to read before a message is complete, for reading one from a stream
+/// without going past its end.
+pub(crate) struct MessageScan {
+    limits: Limits,
+    pending: u64,
+}
+
+impl MessageScan {
+    pub(crate) const fn new(opts: &DecodeOptions) -> Self {
+        Self { limits: Limits::new(opts), pending: 1 }
+    }
+
+    /// Returns whether another value is left to read, accounting for it.
+    pub(crate) fn next_item(&mut self) -> Result<bool, Error> {
+        if self.pending == 0 {
+            return Ok(false);
+        }
+        self.pending -= 1;
+        self.limits.count()?;
+        Ok(true)
+    }
+
+    /// Accounts for the header of the current value, as parsed by `item_header`, returning the
+    /// length of the data following it.
+    pub(crate) fn data_len(&mut self, len: usize, nested: u64) -> Result<usize, Error> {
+        if nested == 0 {
+            self.limits.check_len(len)?;
+            return Ok(len);
+        }
+        let elements = (len as u64).saturating_mul(nested);
+        self.limits.check_container(len, usize::try_from(elements).unwrap_or(usize::MAX))?;
+        self.pending += elements;
+        Ok(0)
+    }
+}
+
+/// Appends exactly `len` bytes read from `rd` to `buf`, growing it as the data arrives.
+fn read_bytes<R: Read>(rd: &mut R, buf: &mut Vec<u8>, len: usize) -> io::Result<()> {
+    let read = rd.by_ref().take(len as u64).read_to_end(buf)?;
+    if read < len {
+        return Err(io::Error::new(ErrorKind::UnexpectedEof, "failed to fill whole buffer"));
+    }
+    Ok(())
+}
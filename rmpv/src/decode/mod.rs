@@ -3,18 +3,70 @@ use std::fmt::{self, Display, Formatter};
 use std::io::{self, ErrorKind};
 
 use rmp::decode::{MarkerReadError, ValueReadError};
+use rmp::Marker;
 
+use crate::Value;
+
+#[cfg(feature = "tokio")]
+mod async_io;
+mod doc;
+pub mod options;
 pub mod value;
 pub mod value_ref;
 
-pub use self::value::{read_value, read_value_with_max_depth};
-pub use self::value_ref::{read_value_ref, read_value_ref_with_max_depth};
+#[cfg(feature = "tokio")]
+pub use self::async_io::{read_value_async, read_value_async_with_options};
+pub use self::doc::ValueRefDoc;
+#[cfg(feature = "bumpalo")]
+pub use crate::arena::{read_value_in, read_value_in_interned, KeyInterner};
+pub use self::options::DecodeOptions;
+pub use self::value::{
+    read_value, read_value_projected, read_value_validated, read_value_validated_all, read_value_with_alloc_hook,
+    read_value_with_duplicate_keys, read_value_with_max_depth, read_value_with_options,
+};
+pub use self::value_ref::{read_value_ref, read_value_ref_with_max_depth, read_value_ref_with_options, ValueRefIter};
 
 /// The maximum recursion depth before [`Error::DepthLimitExceeded`] is returned.
 pub const MAX_DEPTH: usize = 1024;
 
+/// Specifies how decoding handles a map containing the same key more than once.
+///
+/// Parsers that disagree on which of the repeated entries wins can be tricked into seeing
+/// different documents, so inputs crossing a trust boundary should not be decoded with `Allow`.
+///
+/// Keys are compared with [`Value::total_eq`]: floats by their bits, so `0.0` and `-0.0` are
+/// distinct keys while NaN keys with the same bits are duplicates, and containers by their
+/// contents.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keeps every entry, as they appear in the input.
+    #[default]
+    Allow,
+    /// Fails with [`Error::DuplicateKey`].
+    Reject,
+    /// Keeps the first entry for every key, dropping the later ones.
+    KeepFirst,
+    /// Keeps the value of the last entry for every key, at the position of the first one.
+    KeepLast,
+}
+
+/// Specifies how decoding handles a string whose data is not valid UTF-8.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum InvalidUtf8 {
+    /// Keeps the raw bytes in the string, which reports the [`Utf8Error`](std::str::Utf8Error).
+    #[default]
+    Keep,
+    /// Replaces the invalid sequences with U+FFFD, the replacement character.
+    ///
+    /// This needs an owned string, so [`read_value_ref_with_options`] keeps the raw bytes instead.
+    Lossy,
+    /// Decodes the string as a binary instead.
+    Binary,
+}
+
 /// This type represents all possible errors that can occur when deserializing a value.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Error while reading marker byte.
     InvalidMarkerRead(io::Error),
@@ -22,10 +74,21 @@ pub enum Error {
     InvalidDataRead(io::Error),
     /// The depth limit [`MAX_DEPTH`] was exceeded.
     DepthLimitExceeded,
+    /// A map contains the same key more than once, while decoding with [`DuplicateKeys::Reject`].
+    /// Contains the repeated key.
+    DuplicateKey(Value),
+    /// A string, binary, extension, array or map is longer than [`DecodeOptions::max_len`].
+    LengthLimitExceeded,
+    /// The value is made of more values than [`DecodeOptions::max_total_elements`].
+    ElementLimitExceeded,
+    /// The hook of [`read_value_with_alloc_hook`] refused an allocation.
+    AllocationAborted,
+    /// Bytes are left after a value that must fill the whole input. Contains their number.
+    TrailingData(usize),
 }
 
 #[inline]
-fn decrement_depth(depth: u16) -> Result<u16, Error> {
+pub(crate) fn decrement_depth(depth: u16) -> Result<u16, Error> {
     depth.checked_sub(1).ok_or(Error::DepthLimitExceeded)
 }
 
@@ -37,6 +100,9 @@ impl Error {
             Self::InvalidMarkerRead(ref err) => err.kind(),
             Self::InvalidDataRead(ref err) => err.kind(),
             Self::DepthLimitExceeded => ErrorKind::Unsupported,
+            Self::DuplicateKey(..) | Self::TrailingData(..) => ErrorKind::InvalidData,
+            Self::LengthLimitExceeded | Self::ElementLimitExceeded => ErrorKind::Unsupported,
+            Self::AllocationAborted => ErrorKind::OutOfMemory,
         }
     }
 }
@@ -47,7 +113,12 @@ impl error::Error for Error {
         match *self {
             Self::InvalidMarkerRead(ref err) => Some(err),
             Self::InvalidDataRead(ref err) => Some(err),
-            Self::DepthLimitExceeded => None,
+            Self::DepthLimitExceeded |
+            Self::DuplicateKey(..) |
+            Self::LengthLimitExceeded |
+            Self::ElementLimitExceeded |
+            Self::AllocationAborted |
+            Self::TrailingData(..) => None,
         }
     }
 }
@@ -65,6 +136,21 @@ impl Display for Error {
             Self::DepthLimitExceeded => {
                 write!(fmt, "depth limit exceeded")
             }
+            Self::DuplicateKey(ref key) => {
+                write!(fmt, "duplicate map key {key}")
+            }
+            Self::LengthLimitExceeded => {
+                write!(fmt, "length limit exceeded")
+            }
+            Self::ElementLimitExceeded => {
+                write!(fmt, "element limit exceeded")
+            }
+            Self::AllocationAborted => {
+                write!(fmt, "allocation aborted by hook")
+            }
+            Self::TrailingData(len) => {
+                write!(fmt, "{len} trailing bytes after the value")
+            }
         }
     }
 }
@@ -95,7 +181,50 @@ impl From<Error> for io::Error {
         match val {
             Error::InvalidMarkerRead(err) |
             Error::InvalidDataRead(err) => err,
-            Error::DepthLimitExceeded => Self::new(val.kind(), val),
+            Error::DepthLimitExceeded |
+            Error::DuplicateKey(..) |
+            Error::LengthLimitExceeded |
+            Error::ElementLimitExceeded |
+            Error::AllocationAborted |
+            Error::TrailingData(..) => Self::new(val.kind(), val),
         }
     }
 }
+
+/// Parses the marker and length at the start of `buf`, returning the size of both, the length,
+/// and the number of nested values per unit of length: 0 if the length counts bytes of data, 1
+/// for arrays and 2 for maps.
+pub(crate) fn item_header(buf: &[u8]) -> Option<(usize, usize, u64)> {
+    // Reads the big-endian length of `n` bytes following the marker.
+    let len = |n: usize| buf.get(1..=n).map(|bytes| bytes.iter().fold(0, |len, &b| len << 8 | usize::from(b)));
+
+    let header = match Marker::from_u8(*buf.first()?) {
+        Marker::Null | Marker::True | Marker::False | Marker::Reserved |
+        Marker::FixPos(..) | Marker::FixNeg(..) => (1, 0, 0),
+        Marker::U8 | Marker::I8 => (2, 0, 0),
+        Marker::U16 | Marker::I16 => (3, 0, 0),
+        Marker::U32 | Marker::I32 | Marker::F32 => (5, 0, 0),
+        Marker::U64 | Marker::I64 | Marker::F64 => (9, 0, 0),
+        Marker::FixStr(n) => (1, usize::from(n), 0),
+        Marker::Str8 | Marker::Bin8 => (2, len(1)?, 0),
+        Marker::Str16 | Marker::Bin16 => (3, len(2)?, 0),
+        Marker::Str32 | Marker::Bin32 => (5, len(4)?, 0),
+        // The extension type byte follows the length.
+        Marker::FixExt1 => (2, 1, 0),
+        Marker::FixExt2 => (2, 2, 0),
+        Marker::FixExt4 => (2, 4, 0),
+        Marker::FixExt8 => (2, 8, 0),
+        Marker::FixExt16 => (2, 16, 0),
+        Marker::Ext8 => (3, len(1)?, 0),
+        Marker::Ext16 => (4, len(2)?, 0),
+        Marker::Ext32 => (6, len(4)?, 0),
+        Marker::FixArray(n) => (1, usize::from(n), 1),
+        Marker::Array16 => (3, len(2)?, 1),
+        Marker::Array32 => (5, len(4)?, 1),
+        Marker::FixMap(n) => (1, usize::from(n), 2),
+        Marker::Map16 => (3, len(2)?, 2),
+        Marker::Map32 => (5, len(4)?, 2),
+    };
+
+    Some(header)
+}
@@ -0,0 +1,232 @@
+//! Configurable decoding of values, see [`DecodeOptions`].
+
+use super::{DuplicateKeys, Error, InvalidUtf8, MAX_DEPTH};
+
+/// Options controlling how [`read_value_with_options`](super::read_value_with_options) and
+/// [`read_value_ref_with_options`](super::read_value_ref_with_options) decode a value.
+///
+/// The limits guard against untrusted input: a few bytes of header can declare a string of
+/// gigabytes or millions of nested arrays. By default only the depth is limited, to
+/// [`MAX_DEPTH`], which matches [`read_value`](super::read_value).
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::{read_value_with_options, DecodeOptions, Error};
+///
+/// let opts = DecodeOptions::new()
+///     .max_depth(16)
+///     .max_len(1024)
+///     .max_total_elements(10_000);
+///
+/// // A binary declaring 4 GiB of data, in 5 bytes.
+/// let buf = [0xc6, 0xff, 0xff, 0xff, 0xff];
+///
+/// let err = read_value_with_options(&mut &buf[..], &opts).unwrap_err();
+/// assert!(matches!(err, Error::LengthLimitExceeded));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeOptions {
+    max_depth: usize,
+    max_len: usize,
+    max_total_elements: usize,
+    duplicate_keys: DuplicateKeys,
+    invalid_utf8: InvalidUtf8,
+    strings_as_binary: bool,
+    timestamps: bool,
+}
+
+impl DecodeOptions {
+    /// Creates options with the default depth limit and no other limits.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_depth: MAX_DEPTH,
+            max_len: usize::MAX,
+            max_total_elements: usize::MAX,
+            duplicate_keys: DuplicateKeys::Allow,
+            invalid_utf8: InvalidUtf8::Keep,
+            strings_as_binary: false,
+            timestamps: true,
+        }
+    }
+
+    /// Fails with [`Error::DepthLimitExceeded`] when values are nested deeper than `max_depth`.
+    #[inline]
+    #[must_use]
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Fails with [`Error::LengthLimitExceeded`] when a string, binary or extension is longer than
+    /// `max_len` bytes, or an array or map has more than `max_len` elements.
+    ///
+    /// The length is checked before anything is allocated for the value.
+    #[inline]
+    #[must_use]
+    pub const fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Fails with [`Error::ElementLimitExceeded`] when the whole value is made of more than
+    /// `max_total_elements` values, counting itself, every array element and every map key and
+    /// value.
+    #[inline]
+    #[must_use]
+    pub const fn max_total_elements(mut self, max_total_elements: usize) -> Self {
+        self.max_total_elements = max_total_elements;
+        self
+    }
+
+    /// Handles keys that occur more than once in a map according to `policy`.
+    #[inline]
+    #[must_use]
+    pub const fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Handles strings that are not valid UTF-8 according to `policy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::decode::{read_value_with_options, DecodeOptions, InvalidUtf8};
+    /// use rmpv::Value;
+    ///
+    /// let buf = [0xa2, b'a', 0xff];
+    ///
+    /// let opts = DecodeOptions::new().invalid_utf8(InvalidUtf8::Lossy);
+    /// assert_eq!(Value::from("a\u{fffd}"), read_value_with_options(&mut &buf[..], &opts).unwrap());
+    ///
+    /// let opts = DecodeOptions::new().invalid_utf8(InvalidUtf8::Binary);
+    /// assert_eq!(Value::Binary(vec![b'a', 0xff]), read_value_with_options(&mut &buf[..], &opts).unwrap());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn invalid_utf8(mut self, policy: InvalidUtf8) -> Self {
+        self.invalid_utf8 = policy;
+        self
+    }
+
+    /// Decodes every string as a binary, whether or not it is valid UTF-8.
+    ///
+    /// The MessagePack specification before version 2.0 had a single raw type, using the markers
+    /// of today's strings, for both text and binary data. Legacy peers, such as old embedded
+    /// devices, may thus send binary data as strings. Otherwise, the raw values are decoded as
+    /// strings, with invalid ones handled by [`invalid_utf8`](Self::invalid_utf8).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::decode::{read_value_with_options, DecodeOptions};
+    /// use rmpv::Value;
+    ///
+    /// let buf = [0xa2, b'i', b'd'];
+    ///
+    /// let opts = DecodeOptions::new().strings_as_binary(true);
+    /// assert_eq!(Value::Binary(b"id".to_vec()), read_value_with_options(&mut &buf[..], &opts).unwrap());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn strings_as_binary(mut self, enabled: bool) -> Self {
+        self.strings_as_binary = enabled;
+        self
+    }
+
+    /// Decodes valid timestamp extensions as [`Value::Timestamp`](crate::Value::Timestamp), which
+    /// is the default. When disabled they are kept as [`Value::Ext`](crate::Value::Ext) with type
+    /// [`TIMESTAMP_EXT_TYPE`](crate::TIMESTAMP_EXT_TYPE), as before version 2.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::decode::{read_value_with_options, DecodeOptions};
+    /// use rmpv::{Timestamp, Value};
+    ///
+    /// let buf = [0xd6, 0xff, 0, 0, 0, 1];
+    ///
+    /// let opts = DecodeOptions::new();
+    /// assert_eq!(Value::Timestamp(Timestamp::new(1, 0).unwrap()), read_value_with_options(&mut &buf[..], &opts).unwrap());
+    ///
+    /// let opts = DecodeOptions::new().timestamps(false);
+    /// assert_eq!(Value::Ext(-1, vec![0, 0, 0, 1]), read_value_with_options(&mut &buf[..], &opts).unwrap());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn timestamps(mut self, enabled: bool) -> Self {
+        self.timestamps = enabled;
+        self
+    }
+
+    pub(crate) fn depth(&self) -> u16 {
+        self.max_depth.min(u16::MAX as usize) as u16
+    }
+
+    pub(crate) const fn duplicate_key_policy(&self) -> DuplicateKeys {
+        self.duplicate_keys
+    }
+
+    pub(crate) const fn invalid_utf8_policy(&self) -> InvalidUtf8 {
+        self.invalid_utf8
+    }
+
+    pub(crate) const fn strings_as_binary_enabled(&self) -> bool {
+        self.strings_as_binary
+    }
+
+    pub(crate) const fn timestamps_enabled(&self) -> bool {
+        self.timestamps
+    }
+}
+
+impl Default for DecodeOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The size limits of [`DecodeOptions`], tracked while decoding a single value.
+pub(crate) struct Limits {
+    max_len: usize,
+    remaining: usize,
+}
+
+impl Limits {
+    pub(crate) const UNLIMITED: Self = Self { max_len: usize::MAX, remaining: usize::MAX };
+
+    pub(crate) const fn new(opts: &DecodeOptions) -> Self {
+        Self { max_len: opts.max_len, remaining: opts.max_total_elements }
+    }
+
+    /// Accounts for a value about to be decoded.
+    #[inline]
+    pub(crate) fn count(&mut self) -> Result<(), Error> {
+        self.remaining = self.remaining.checked_sub(1).ok_or(Error::ElementLimitExceeded)?;
+        Ok(())
+    }
+
+    /// Checks the length of a string, binary or extension before reading its data.
+    #[inline]
+    pub(crate) fn check_len(&self, len: usize) -> Result<(), Error> {
+        if len > self.max_len {
+            return Err(Error::LengthLimitExceeded);
+        }
+        Ok(())
+    }
+
+    /// Checks the length of an array or map before reading its `elements` keys and values, so that
+    /// oversized containers are rejected upfront.
+    #[inline]
+    pub(crate) fn check_container(&self, len: usize, elements: usize) -> Result<(), Error> {
+        self.check_len(len)?;
+        if elements > self.remaining {
+            return Err(Error::ElementLimitExceeded);
+        }
+        Ok(())
+    }
+}
@@ -1,87 +1,198 @@
 use std::cmp::min;
 use std::io::{self, Read};
+use std::mem;
+use std::ops::ControlFlow;
 
+use rmp::decode::slice::ReservedPolicy;
 use rmp::decode::{read_marker, RmpRead};
 use rmp::Marker;
 
-use super::Error;
-use crate::{Utf8String, Value};
+use super::options::Limits;
+use super::{DecodeOptions, DuplicateKeys, Error, InvalidUtf8};
+use crate::map_index::KeySet;
+use crate::schema::{Schema, ValidationError, ValidationErrorKind};
+use crate::{push_pointer_key, Utf8String, Value};
 
 // See https://github.com/3Hren/msgpack-rust/issues/151
 const PREALLOC_MAX: usize = 64 * 1024; // 64 KiB
 
-fn read_array_data<R: Read>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<Value>, Error> {
+/// Settings threaded through the recursive decoding functions.
+struct ReadState<'a> {
+    hook: Option<&'a mut dyn FnMut(usize) -> ControlFlow<()>>,
+    duplicate_keys: DuplicateKeys,
+    invalid_utf8: InvalidUtf8,
+    strings_as_binary: bool,
+    timestamps: bool,
+    limits: Limits,
+}
+
+impl<'a> ReadState<'a> {
+    fn new() -> Self {
+        Self { hook: None, duplicate_keys: DuplicateKeys::Allow, invalid_utf8: InvalidUtf8::Keep, strings_as_binary: false, timestamps: true, limits: Limits::UNLIMITED }
+    }
+
+    /// Asks the allocation hook, if any, whether `size` more bytes may be reserved.
+    #[inline]
+    fn reserve(&mut self, size: usize) -> Result<(), Error> {
+        match self.hook.as_mut().map(|hook| hook(size)) {
+            Some(ControlFlow::Break(())) => Err(Error::AllocationAborted),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Makes room for the next element of a container of `len` elements, doubling the capacity so
+/// that no more than twice the elements read so far is ever reserved.
+///
+/// Unlike a plain `push`, this reports the exact capacity to the allocation hook before reserving.
+fn grow<T>(vec: &mut Vec<T>, len: usize, state: &mut ReadState<'_>) -> Result<(), Error> {
+    if vec.len() == vec.capacity() {
+        let additional = min(len - vec.len(), vec.capacity().max(4));
+        state.reserve(additional.saturating_mul(mem::size_of::<T>()))?;
+        vec.reserve_exact(additional);
+    }
+    Ok(())
+}
+
+fn read_array_data<R: Read>(rd: &mut R, mut len: usize, depth: u16, state: &mut ReadState<'_>) -> Result<Vec<Value>, Error> {
     let depth = super::decrement_depth(depth)?;
+    state.limits.check_container(len, len)?;
 
     // Note: Do not preallocate a Vec of size `len`.
     // See https://github.com/3Hren/msgpack-rust/issues/151
     let mut vec = Vec::new();
+    let total = len;
 
     while len > 0 {
-        vec.push(read_value_inner(rd, depth)?);
+        grow(&mut vec, total, state)?;
+        vec.push(read_value_inner(rd, depth, state)?);
         len -= 1;
     }
 
     Ok(vec)
 }
 
-fn read_map_data<R: Read>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<(Value, Value)>, Error> {
+fn read_map_data<R: Read>(rd: &mut R, mut len: usize, depth: u16, state: &mut ReadState<'_>) -> Result<Vec<(Value, Value)>, Error> {
     let depth = super::decrement_depth(depth)?;
+    state.limits.check_container(len, len.saturating_mul(2))?;
 
     // Note: Do not preallocate a Vec of size `len`.
     // See https://github.com/3Hren/msgpack-rust/issues/151
     let mut vec = Vec::new();
+    let mut keys = KeySet::default();
+    let total = len;
 
     while len > 0 {
-        vec.push((read_value_inner(rd, depth)?, read_value_inner(rd, depth)?));
+        grow(&mut vec, total, state)?;
+        let key = read_value_inner(rd, depth, state)?;
+        let val = read_value_inner(rd, depth, state)?;
         len -= 1;
+
+        if state.duplicate_keys == DuplicateKeys::Allow {
+            vec.push((key, val));
+            continue;
+        }
+        match keys.find(&vec, &key) {
+            None => {
+                keys.insert(&key, vec.len());
+                vec.push((key, val));
+            }
+            Some(idx) => match state.duplicate_keys {
+                DuplicateKeys::Reject => return Err(Error::DuplicateKey(key)),
+                DuplicateKeys::KeepLast => vec[idx].1 = val,
+                DuplicateKeys::KeepFirst | DuplicateKeys::Allow => {}
+            },
+        }
     }
 
     Ok(vec)
 }
 
-fn read_str_data<R: Read>(rd: &mut R, len: usize, depth: u16) -> Result<Utf8String, Error> {
+fn read_str_data<R: Read>(rd: &mut R, len: usize, depth: u16, state: &mut ReadState<'_>) -> Result<Value, Error> {
     let depth = super::decrement_depth(depth)?;
+    let buf = read_bin_data(rd, len, depth, state)?;
+    if state.strings_as_binary {
+        return Ok(Value::Binary(buf));
+    }
 
-    match String::from_utf8(read_bin_data(rd, len, depth)?) {
-        Ok(s) => Ok(Utf8String::from(s)),
-        Err(err) => {
-            let e = err.utf8_error();
-            let s = Utf8String {
-                s: Err((err.into_bytes(), e)),
-            };
-            Ok(s)
-        }
+    match rmp::decode::string_from_utf8(buf) {
+        Ok(s) => Ok(Value::from(s)),
+        Err(err) => match state.invalid_utf8 {
+            InvalidUtf8::Keep => {
+                let e = err.utf8_error();
+                let s = Utf8String {
+                    s: Err((err.into_bytes(), e)),
+                };
+                Ok(Value::String(s))
+            }
+            InvalidUtf8::Lossy => Ok(Value::from(String::from_utf8_lossy(err.as_bytes()).into_owned())),
+            InvalidUtf8::Binary => Ok(Value::Binary(err.into_bytes())),
+        },
     }
 }
 
-fn read_bin_data<R: Read>(rd: &mut R, len: usize, depth: u16) -> Result<Vec<u8>, Error> {
+fn read_bin_data<R: Read>(rd: &mut R, len: usize, depth: u16, state: &mut ReadState<'_>) -> Result<Vec<u8>, Error> {
     let _depth = super::decrement_depth(depth)?;
+    state.limits.check_len(len)?;
+
+    if state.hook.is_none() {
+        let mut buf = Vec::with_capacity(min(len, PREALLOC_MAX));
+        rd.take(len as u64).read_to_end(&mut buf).map_err(Error::InvalidDataRead)?;
+        return check_bin_len(buf, len);
+    }
+
+    // Read in chunks of doubling size, so that the reported capacity is exactly the reserved one
+    // and a truncated input never makes us reserve much more than it contains.
+    let mut buf = Vec::new();
+    let mut filled = 0;
+    while filled == buf.len() && buf.len() < len {
+        let additional = min(len - buf.len(), buf.len().max(PREALLOC_MAX));
+        state.reserve(additional)?;
+        buf.reserve_exact(additional);
+        buf.resize(buf.len() + additional, 0);
 
-    let mut buf = Vec::with_capacity(min(len, PREALLOC_MAX));
-    let bytes_read = rd.take(len as u64).read_to_end(&mut buf).map_err(Error::InvalidDataRead)?;
-    if bytes_read != len {
+        while filled < buf.len() {
+            match rd.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(Error::InvalidDataRead(err)),
+            }
+        }
+    }
+    buf.truncate(filled);
+
+    check_bin_len(buf, len)
+}
+
+fn check_bin_len(buf: Vec<u8>, len: usize) -> Result<Vec<u8>, Error> {
+    if buf.len() != len {
         return Err(Error::InvalidDataRead(io::Error::new(
             io::ErrorKind::UnexpectedEof,
-            format!("Expected {len} bytes, read {bytes_read} bytes"),
+            format!("Expected {len} bytes, read {} bytes", buf.len()),
         )));
     }
 
     Ok(buf)
 }
 
-fn read_ext_body<R: Read>(rd: &mut R, len: usize, depth: u16) -> Result<(i8, Vec<u8>), Error> {
+fn read_ext_body<R: Read>(rd: &mut R, len: usize, depth: u16, state: &mut ReadState<'_>) -> Result<Value, Error> {
     let depth = super::decrement_depth(depth)?;
 
     let ty = rd.read_data_i8()?;
-    let vec = read_bin_data(rd, len, depth)?;
+    let vec = read_bin_data(rd, len, depth, state)?;
 
-    Ok((ty, vec))
+    if state.timestamps {
+        Ok(Value::from_ext(ty, vec))
+    } else {
+        Ok(Value::Ext(ty, vec))
+    }
 }
 
 #[inline(never)]
-fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R: Read {
+fn read_value_inner<R>(rd: &mut R, depth: u16, state: &mut ReadState<'_>) -> Result<Value, Error> where R: Read {
     let depth = super::decrement_depth(depth)?;
+    state.limits.count()?;
     let val = match read_marker(rd)? {
         Marker::Null => Value::Nil,
         Marker::True => Value::Boolean(true),
@@ -99,106 +210,94 @@ fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R:
         Marker::F32 => Value::F32(rd.read_data_f32()?),
         Marker::F64 => Value::F64(rd.read_data_f64()?),
         Marker::FixStr(len) => {
-            let res = read_str_data(rd, len as usize, depth)?;
-            Value::String(res)
+            read_str_data(rd, len as usize, depth, state)?
         }
         Marker::Str8 => {
             let len = rd.read_data_u8()?;
-            let res = read_str_data(rd, len as usize, depth)?;
-            Value::String(res)
+            read_str_data(rd, len as usize, depth, state)?
         }
         Marker::Str16 => {
             let len = rd.read_data_u16()?;
-            let res = read_str_data(rd, len as usize, depth)?;
-            Value::String(res)
+            read_str_data(rd, len as usize, depth, state)?
         }
         Marker::Str32 => {
             let len = rd.read_data_u32()?;
-            let res = read_str_data(rd, len as usize, depth)?;
-            Value::String(res)
+            read_str_data(rd, len as usize, depth, state)?
         }
         Marker::FixArray(len) => {
-            let vec = read_array_data(rd, len as usize, depth)?;
+            let vec = read_array_data(rd, len as usize, depth, state)?;
             Value::Array(vec)
         }
         Marker::Array16 => {
             let len = rd.read_data_u16()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
+            let vec = read_array_data(rd, len as usize, depth, state)?;
             Value::Array(vec)
         }
         Marker::Array32 => {
             let len = rd.read_data_u32()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
+            let vec = read_array_data(rd, len as usize, depth, state)?;
             Value::Array(vec)
         }
         Marker::FixMap(len) => {
-            let map = read_map_data(rd, len as usize, depth)?;
+            let map = read_map_data(rd, len as usize, depth, state)?;
             Value::Map(map)
         }
         Marker::Map16 => {
             let len = rd.read_data_u16()?;
-            let map = read_map_data(rd, len as usize, depth)?;
+            let map = read_map_data(rd, len as usize, depth, state)?;
             Value::Map(map)
         }
         Marker::Map32 => {
             let len = rd.read_data_u32()?;
-            let map = read_map_data(rd, len as usize, depth)?;
+            let map = read_map_data(rd, len as usize, depth, state)?;
             Value::Map(map)
         }
         Marker::Bin8 => {
             let len = rd.read_data_u8()?;
-            let vec = read_bin_data(rd, len as usize, depth)?;
+            let vec = read_bin_data(rd, len as usize, depth, state)?;
             Value::Binary(vec)
         }
         Marker::Bin16 => {
             let len = rd.read_data_u16()?;
-            let vec = read_bin_data(rd, len as usize, depth)?;
+            let vec = read_bin_data(rd, len as usize, depth, state)?;
             Value::Binary(vec)
         }
         Marker::Bin32 => {
             let len = rd.read_data_u32()?;
-            let vec = read_bin_data(rd, len as usize, depth)?;
+            let vec = read_bin_data(rd, len as usize, depth, state)?;
             Value::Binary(vec)
         }
         Marker::FixExt1 => {
             let len = 1_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            read_ext_body(rd, len, depth, state)?
         }
         Marker::FixExt2 => {
             let len = 2_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            read_ext_body(rd, len, depth, state)?
         }
         Marker::FixExt4 => {
             let len = 4_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            read_ext_body(rd, len, depth, state)?
         }
         Marker::FixExt8 => {
             let len = 8_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            read_ext_body(rd, len, depth, state)?
         }
         Marker::FixExt16 => {
             let len = 16_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            read_ext_body(rd, len, depth, state)?
         }
         Marker::Ext8 => {
             let len = rd.read_data_u8()? as usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            read_ext_body(rd, len, depth, state)?
         }
         Marker::Ext16 => {
             let len = rd.read_data_u16()? as usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            read_ext_body(rd, len, depth, state)?
         }
         Marker::Ext32 => {
             let len = rd.read_data_u32()? as usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            read_ext_body(rd, len, depth, state)?
         }
         Marker::Reserved => Value::Nil,
     };
@@ -221,7 +320,7 @@ fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R:
 pub fn read_value<R>(rd: &mut R) -> Result<Value, Error>
     where R: Read
 {
-    read_value_inner(rd, super::MAX_DEPTH as _)
+    read_value_inner(rd, super::MAX_DEPTH as _, &mut ReadState::new())
 }
 
 /// Attempts to read bytes from the given reader and interpret them as a [`Value`].
@@ -239,5 +338,387 @@ pub fn read_value<R>(rd: &mut R) -> Result<Value, Error>
 pub fn read_value_with_max_depth<R>(rd: &mut R, max_depth: usize) -> Result<Value, Error>
     where R: Read
 {
-    read_value_inner(rd, max_depth.min(u16::MAX as usize) as u16)
+    read_value_inner(rd, max_depth.min(u16::MAX as usize) as u16, &mut ReadState::new())
+}
+
+/// Attempts to read bytes from the given reader and interpret them as a [`Value`], handling keys
+/// that occur more than once in a map according to `policy`.
+///
+/// Keys are compared with [`Value::total_eq`], as described for [`DuplicateKeys`]: floats by their
+/// bits, so `0.0` and `-0.0` are distinct keys while NaN keys with the same bits are duplicates.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a [`Value`].
+/// All instances of [`ErrorKind::Interrupted`](io::ErrorKind) are handled by this function and the
+/// underlying operation is retried.
+///
+/// [`Error::DuplicateKey`] is returned for the first repeated key found if `policy` is
+/// [`DuplicateKeys::Reject`], and [`Error::DepthLimitExceeded`] if this function recurses
+/// [`MAX_DEPTH`](super::MAX_DEPTH) times.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::{read_value_with_duplicate_keys, DuplicateKeys, Error};
+/// use rmpv::Value;
+///
+/// // {"role": "user", "role": "admin"}
+/// let buf = [0x82, 0xa4, b'r', b'o', b'l', b'e', 0xa4, b'u', b's', b'e', b'r',
+///            0xa4, b'r', b'o', b'l', b'e', 0xa5, b'a', b'd', b'm', b'i', b'n'];
+///
+/// let val = read_value_with_duplicate_keys(&mut &buf[..], DuplicateKeys::KeepFirst).unwrap();
+/// assert_eq!(Value::Map(vec![(Value::from("role"), Value::from("user"))]), val);
+///
+/// let err = read_value_with_duplicate_keys(&mut &buf[..], DuplicateKeys::Reject).unwrap_err();
+/// assert!(matches!(err, Error::DuplicateKey(key) if key == Value::from("role")));
+/// ```
+pub fn read_value_with_duplicate_keys<R>(rd: &mut R, policy: DuplicateKeys) -> Result<Value, Error>
+    where R: Read
+{
+    read_value_with_options(rd, &DecodeOptions::new().duplicate_keys(policy))
+}
+
+/// Attempts to read bytes from the given reader and interpret them as a [`Value`], enforcing the
+/// limits and policies of `opts`.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a [`Value`].
+/// All instances of [`ErrorKind::Interrupted`](io::ErrorKind) are handled by this function and the
+/// underlying operation is retried.
+///
+/// [`Error::DepthLimitExceeded`], [`Error::LengthLimitExceeded`] and
+/// [`Error::ElementLimitExceeded`] are returned as soon as a limit is exceeded, and
+/// [`Error::DuplicateKey`] if a map repeats a key under [`DuplicateKeys::Reject`].
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::{read_value_with_options, DecodeOptions, Error};
+///
+/// // [[], [], []]
+/// let buf = [0x93, 0x90, 0x90, 0x90];
+///
+/// assert!(read_value_with_options(&mut &buf[..], &DecodeOptions::new().max_total_elements(4)).is_ok());
+///
+/// let err = read_value_with_options(&mut &buf[..], &DecodeOptions::new().max_total_elements(3)).unwrap_err();
+/// assert!(matches!(err, Error::ElementLimitExceeded));
+/// ```
+pub fn read_value_with_options<R>(rd: &mut R, opts: &DecodeOptions) -> Result<Value, Error>
+    where R: Read
+{
+    let mut state = ReadState {
+        hook: None,
+        duplicate_keys: opts.duplicate_key_policy(),
+        invalid_utf8: opts.invalid_utf8_policy(),
+        strings_as_binary: opts.strings_as_binary_enabled(),
+        timestamps: opts.timestamps_enabled(),
+        limits: Limits::new(opts),
+    };
+    read_value_inner(rd, opts.depth(), &mut state)
+}
+
+/// Attempts to read bytes from the given reader and interpret them as a [`Value`], asking `hook`
+/// before every allocation made for it.
+///
+/// The hook is called with the size in bytes about to be reserved for an array, map, string,
+/// binary or extension buffer. Buffers grow as their contents are read, so a large one is reported
+/// in several steps, and summing the reported sizes gives the heap memory held by the decoded
+/// value. Returning [`ControlFlow::Break`] from the hook aborts decoding before the memory is
+/// reserved, which allows enforcing memory budgets.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a [`Value`].
+/// All instances of [`ErrorKind::Interrupted`](io::ErrorKind) are handled by this function and the
+/// underlying operation is retried.
+///
+/// [`Error::DepthLimitExceeded`] is returned if this function recurses
+/// [`MAX_DEPTH`](super::MAX_DEPTH) times, and [`Error::AllocationAborted`] if the hook breaks.
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::ControlFlow;
+///
+/// use rmpv::decode::{read_value_with_alloc_hook, Error};
+///
+/// // ["abc", <bin of 4 bytes>]
+/// let buf = [0x92, 0xa3, b'a', b'b', b'c', 0xc4, 0x04, 1, 2, 3, 4];
+///
+/// let mut total = 0;
+/// read_value_with_alloc_hook(&mut &buf[..], &mut |size| {
+///     total += size;
+///     ControlFlow::Continue(())
+/// }).unwrap();
+///
+/// assert_eq!(2 * std::mem::size_of::<rmpv::Value>() + 3 + 4, total);
+///
+/// // Refuse anything larger than the array itself.
+/// let mut budget = 2 * std::mem::size_of::<rmpv::Value>();
+/// let err = read_value_with_alloc_hook(&mut &buf[..], &mut |size| {
+///     match budget.checked_sub(size) {
+///         Some(rest) => { budget = rest; ControlFlow::Continue(()) }
+///         None => ControlFlow::Break(()),
+///     }
+/// }).unwrap_err();
+///
+/// assert!(matches!(err, Error::AllocationAborted));
+/// ```
+#[inline]
+pub fn read_value_with_alloc_hook<R>(rd: &mut R, hook: &mut dyn FnMut(usize) -> ControlFlow<()>) -> Result<Value, Error>
+    where R: Read
+{
+    let mut state = ReadState::new();
+    state.hook = Some(hook);
+    read_value_inner(rd, super::MAX_DEPTH as _, &mut state)
+}
+
+/// Attempts to read a map from the given reader, only decoding the values of the string keys
+/// listed in `keep`.
+///
+/// The values of all other keys are skipped over without being materialized, which makes picking
+/// a few fields out of a large document cheap. The result is a map containing only the kept
+/// entries, in the order they appear in the input. Values that are not maps are decoded in full,
+/// exactly like [`read_value`] does.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a [`Value`].
+/// Skipped values are not decoded, so they can not cause [`Error::DepthLimitExceeded`].
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::read_value_projected;
+/// use rmpv::Value;
+///
+/// // {"id": 1, "blob": <bin>, "name": "a"}
+/// let buf = [0x83, 0xa2, b'i', b'd', 0x01, 0xa4, b'b', b'l', b'o', b'b', 0xc4, 0x02, 0x00, 0x00,
+///            0xa4, b'n', b'a', b'm', b'e', 0xa1, b'a'];
+///
+/// let val = read_value_projected(&mut &buf[..], &["id", "name"]).unwrap();
+/// assert_eq!(Value::Map(vec![
+///     (Value::from("id"), Value::from(1)),
+///     (Value::from("name"), Value::from("a")),
+/// ]), val);
+/// ```
+pub fn read_value_projected<R>(rd: &mut R, keep: &[&str]) -> Result<Value, Error>
+    where R: Read
+{
+    let depth = super::decrement_depth(super::MAX_DEPTH as _)?;
+    let len = match read_marker(rd)? {
+        Marker::FixMap(len) => u32::from(len),
+        Marker::Map16 => u32::from(rd.read_data_u16()?),
+        Marker::Map32 => rd.read_data_u32()?,
+        marker => {
+            // Put the marker back in front of the remaining input and decode the value as usual.
+            let buf = [marker.to_u8()];
+            return read_value_inner(&mut io::Read::chain(&buf[..], rd), super::MAX_DEPTH as _, &mut ReadState::new());
+        }
+    };
+
+    let depth = super::decrement_depth(depth)?;
+    let mut map = Vec::new();
+    for _ in 0..len {
+        let key = read_value_inner(rd, depth, &mut ReadState::new())?;
+        if key.as_str().is_some_and(|key| keep.contains(&key)) {
+            let val = read_value_inner(rd, depth, &mut ReadState::new())?;
+            map.push((key, val));
+        } else {
+            // Like `read_value`, the reserved marker is a nil value of its own.
+            rmp::decode::skip_value_with(rd, ReservedPolicy::Token)?;
+        }
+    }
+
+    Ok(Value::Map(map))
+}
+
+/// A reader that keeps track of how many bytes were read through it.
+struct OffsetReader<'a, R> {
+    rd: &'a mut R,
+    offset: u64,
+}
+
+impl<R: Read> Read for OffsetReader<'_, R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.rd.read(buf)?;
+        self.offset += len as u64;
+        Ok(len)
+    }
+}
+
+/// The state of a validating decode, see [`read_value_validated`].
+struct Validation {
+    path: String,
+    errors: Vec<ValidationError>,
+    fail_fast: bool,
+}
+
+impl Validation {
+    /// Records a violation, returning `None` if decoding should stop.
+    fn report(&mut self, offset: u64, kind: ValidationErrorKind) -> Option<()> {
+        self.errors.push(ValidationError { path: self.path.clone(), offset, kind });
+        if self.fail_fast { None } else { Some(()) }
+    }
+
+    /// Records a decoding error, which always stops decoding.
+    fn fail<T>(&mut self, offset: u64, err: Error) -> Option<T> {
+        let kind = ValidationErrorKind::Decode(err);
+        self.errors.push(ValidationError { path: self.path.clone(), offset, kind });
+        None
+    }
+}
+
+fn read_len<R: Read>(rd: &mut R, marker: Marker) -> Result<u32, Error> {
+    Ok(match marker {
+        Marker::FixArray(len) | Marker::FixMap(len) => u32::from(len),
+        Marker::Array16 | Marker::Map16 => u32::from(rd.read_data_u16()?),
+        _ => rd.read_data_u32()?,
+    })
+}
+
+fn read_value_validated_inner<R>(rd: &mut OffsetReader<'_, R>, schema: &Schema, state: &mut Validation, depth: u16) -> Option<Value>
+    where R: Read
+{
+    let offset = rd.offset;
+    if let Schema::MaxLen(_, ref inner) = *schema {
+        let val = read_value_validated_inner(rd, inner, state, depth)?;
+        if let Some(kind) = schema.check_len(&val) {
+            state.report(offset, kind)?;
+        }
+        return Some(val);
+    }
+
+    let marker = match read_marker(rd) {
+        Ok(marker) => marker,
+        Err(err) => return state.fail(offset, err.into()),
+    };
+
+    match (schema, marker) {
+        (Schema::Array(elem), Marker::FixArray(..) | Marker::Array16 | Marker::Array32) => {
+            let (len, depth) = match read_len(rd, marker).and_then(|len| Ok((len, super::decrement_depth(depth)?))) {
+                Ok(res) => res,
+                Err(err) => return state.fail(offset, err),
+            };
+
+            let mut vec = Vec::new();
+            for idx in 0..len {
+                let prefix = state.path.len();
+                state.path.push('/');
+                state.path.push_str(&idx.to_string());
+                vec.push(read_value_validated_inner(rd, elem, state, depth)?);
+                state.path.truncate(prefix);
+            }
+            Some(Value::Array(vec))
+        }
+        (Schema::Map(fields), Marker::FixMap(..) | Marker::Map16 | Marker::Map32) => {
+            let (len, depth) = match read_len(rd, marker).and_then(|len| Ok((len, super::decrement_depth(depth)?))) {
+                Ok(res) => res,
+                Err(err) => return state.fail(offset, err),
+            };
+
+            let mut map = Vec::new();
+            for _ in 0..len {
+                let key_offset = rd.offset;
+                let key = match read_value_inner(rd, depth, &mut ReadState::new()) {
+                    Ok(key) => key,
+                    Err(err) => return state.fail(key_offset, err),
+                };
+
+                let prefix = state.path.len();
+                push_pointer_key(&mut state.path, &key);
+                let field = key.as_str().and_then(|key| fields.iter().find(|field| field.name == key));
+                let val = read_value_validated_inner(rd, field.map_or(&Schema::Any, |field| &field.schema), state, depth)?;
+                state.path.truncate(prefix);
+                map.push((key, val));
+            }
+
+            for field in fields {
+                if field.required && !map.iter().any(|(key, _)| key.as_str() == Some(&*field.name)) {
+                    state.report(offset, ValidationErrorKind::MissingField(field.name.clone()))?;
+                }
+            }
+            Some(Value::Map(map))
+        }
+        _ => {
+            // Put the marker back in front of the remaining input and decode the value as usual.
+            let buf = [marker.to_u8()];
+            let val = match read_value_inner(&mut io::Read::chain(&buf[..], &mut *rd), depth, &mut ReadState::new()) {
+                Ok(val) => val,
+                Err(err) => return state.fail(offset, err),
+            };
+
+            if let Some(kind) = schema.check(&val) {
+                state.report(offset, kind)?;
+            }
+            Some(val)
+        }
+    }
+}
+
+fn read_value_validated_with<R>(rd: &mut R, schema: &Schema, fail_fast: bool) -> Result<Value, Vec<ValidationError>>
+    where R: Read
+{
+    let mut rd = OffsetReader { rd, offset: 0 };
+    let mut state = Validation { path: String::new(), errors: Vec::new(), fail_fast };
+    match read_value_validated_inner(&mut rd, schema, &mut state, super::MAX_DEPTH as _) {
+        Some(val) if state.errors.is_empty() => Ok(val),
+        _ => Err(state.errors),
+    }
+}
+
+/// Attempts to read a [`Value`] from the given reader, validating it against `schema` as it is
+/// decoded.
+///
+/// Decoding stops at the first violation, so the returned list contains exactly one error. Each
+/// error records the path of the offending value and the offset at which it starts, counted in
+/// bytes read from `rd` by this function. To collect every violation instead, use
+/// [`read_value_validated_all`].
+///
+/// # Errors
+///
+/// Returns the violation if the value does not match `schema`, or a
+/// [`ValidationErrorKind::Decode`] error if the input is not a valid MessagePack value.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::read_value_validated;
+/// use rmpv::schema::{Field, Schema, ValidationErrorKind};
+///
+/// let schema = Schema::Map(vec![Field::required("id", Schema::Integer)]);
+///
+/// // {"id": "1"}
+/// let buf = [0x81, 0xa2, b'i', b'd', 0xa1, b'1'];
+/// let errs = read_value_validated(&mut &buf[..], &schema).unwrap_err();
+///
+/// assert_eq!("/id", errs[0].path);
+/// assert_eq!(4, errs[0].offset);
+/// assert!(matches!(errs[0].kind, ValidationErrorKind::TypeMismatch { expected: "integer", found: "string" }));
+/// ```
+#[inline]
+pub fn read_value_validated<R>(rd: &mut R, schema: &Schema) -> Result<Value, Vec<ValidationError>>
+    where R: Read
+{
+    read_value_validated_with(rd, schema, true)
+}
+
+/// Attempts to read a [`Value`] from the given reader, validating it against `schema` as it is
+/// decoded and collecting every violation.
+///
+/// Unlike [`read_value_validated`], decoding continues past violations, so the whole value is
+/// consumed from `rd` unless it is malformed.
+///
+/// # Errors
+///
+/// Returns all violations in input order, ending with a [`ValidationErrorKind::Decode`] error if
+/// the input is not a valid MessagePack value.
+#[inline]
+pub fn read_value_validated_all<R>(rd: &mut R, schema: &Schema) -> Result<Value, Vec<ValidationError>>
+    where R: Read
+{
+    read_value_validated_with(rd, schema, false)
 }
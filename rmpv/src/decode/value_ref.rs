@@ -1,33 +1,53 @@
 use std;
 use std::io::{self, Cursor, ErrorKind, Read};
-use std::str;
 
 use rmp::decode::{read_marker, RmpRead};
 use rmp::Marker;
 
-use super::Error;
+use super::options::Limits;
+use super::{DecodeOptions, DuplicateKeys, Error, InvalidUtf8};
+use crate::map_index::KeySet;
 use crate::{Utf8StringRef, ValueRef};
 
-fn read_str_data<'a, R>(rd: &mut R, len: usize, depth: u16) -> Result<Utf8StringRef<'a>, Error>
+/// Settings threaded through the recursive decoding functions.
+struct ReadState {
+    duplicate_keys: DuplicateKeys,
+    invalid_utf8: InvalidUtf8,
+    strings_as_binary: bool,
+    limits: Limits,
+}
+
+impl ReadState {
+    const fn new() -> Self {
+        Self { duplicate_keys: DuplicateKeys::Allow, invalid_utf8: InvalidUtf8::Keep, strings_as_binary: false, limits: Limits::UNLIMITED }
+    }
+}
+
+fn read_str_data<'a, R>(rd: &mut R, len: usize, depth: u16, state: &mut ReadState) -> Result<ValueRef<'a>, Error>
     where R: BorrowRead<'a>
 {
     let depth = super::decrement_depth(depth)?;
-    let buf = read_bin_data(rd, len, depth)?;
-    match str::from_utf8(buf) {
-        Ok(s) => Ok(Utf8StringRef::from(s)),
+    let buf = read_bin_data(rd, len, depth, state)?;
+    if state.strings_as_binary {
+        return Ok(ValueRef::Binary(buf));
+    }
+    match rmp::decode::from_utf8(buf) {
+        Ok(s) => Ok(ValueRef::String(Utf8StringRef::from(s))),
+        Err(..) if state.invalid_utf8 == InvalidUtf8::Binary => Ok(ValueRef::Binary(buf)),
         Err(err) => {
             let s = Utf8StringRef {
                 s: Err((buf, err)),
             };
-            Ok(s)
+            Ok(ValueRef::String(s))
         }
     }
 }
 
-fn read_bin_data<'a, R>(rd: &mut R, len: usize, depth: u16) -> Result<&'a [u8], Error>
+fn read_bin_data<'a, R>(rd: &mut R, len: usize, depth: u16, state: &mut ReadState) -> Result<&'a [u8], Error>
     where R: BorrowRead<'a>
 {
     let _depth = super::decrement_depth(depth)?;
+    state.limits.check_len(len)?;
     let buf = rd.fill_buf();
 
     if len > buf.len() {
@@ -41,46 +61,63 @@ fn read_bin_data<'a, R>(rd: &mut R, len: usize, depth: u16) -> Result<&'a [u8],
     Ok(buf)
 }
 
-fn read_ext_body<'a, R>(rd: &mut R, len: usize, depth: u16) -> Result<(i8, &'a [u8]), Error>
+fn read_ext_body<'a, R>(rd: &mut R, len: usize, depth: u16, state: &mut ReadState) -> Result<(i8, &'a [u8]), Error>
     where R: BorrowRead<'a>
 {
     let depth = super::decrement_depth(depth)?;
     let ty = rd.read_data_i8()?;
-    let buf = read_bin_data(rd, len, depth)?;
+    let buf = read_bin_data(rd, len, depth, state)?;
 
     Ok((ty, buf))
 }
 
-fn read_array_data<'a, R>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<ValueRef<'a>>, Error>
+fn read_array_data<'a, R>(rd: &mut R, mut len: usize, depth: u16, state: &mut ReadState) -> Result<Vec<ValueRef<'a>>, Error>
     where R: BorrowRead<'a>
 {
     let depth = super::decrement_depth(depth)?;
+    state.limits.check_container(len, len)?;
     // Note: Do not preallocate a Vec of size `len`.
     // See https://github.com/3Hren/msgpack-rust/issues/151
     let mut vec = Vec::new();
 
     while len > 0 {
-        vec.push(read_value_ref_inner(rd, depth)?);
+        vec.push(read_value_ref_inner(rd, depth, state)?);
         len -= 1;
     }
 
     Ok(vec)
 }
 
-fn read_map_data<'a, R>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<(ValueRef<'a>, ValueRef<'a>)>, Error>
+fn read_map_data<'a, R>(rd: &mut R, mut len: usize, depth: u16, state: &mut ReadState) -> Result<Vec<(ValueRef<'a>, ValueRef<'a>)>, Error>
     where R: BorrowRead<'a>
 {
     let depth = super::decrement_depth(depth)?;
+    state.limits.check_container(len, len.saturating_mul(2))?;
     // Note: Do not preallocate a Vec of size `len`.
     // See https://github.com/3Hren/msgpack-rust/issues/151
     let mut vec = Vec::new();
+    let mut keys = KeySet::default();
 
     while len > 0 {
-        vec.push((
-            read_value_ref_inner(rd, depth)?,
-            read_value_ref_inner(rd, depth)?,
-        ));
+        let key = read_value_ref_inner(rd, depth, state)?;
+        let val = read_value_ref_inner(rd, depth, state)?;
         len -= 1;
+
+        if state.duplicate_keys == DuplicateKeys::Allow {
+            vec.push((key, val));
+            continue;
+        }
+        match keys.find(&vec, &key) {
+            None => {
+                keys.insert(&key, vec.len());
+                vec.push((key, val));
+            }
+            Some(idx) => match state.duplicate_keys {
+                DuplicateKeys::Reject => return Err(Error::DuplicateKey(key.to_owned())),
+                DuplicateKeys::KeepLast => vec[idx].1 = val,
+                DuplicateKeys::KeepFirst | DuplicateKeys::Allow => {}
+            },
+        }
     }
 
     Ok(vec)
@@ -130,10 +167,11 @@ impl<'a> BorrowRead<'a> for Cursor<&'a [u8]> {
     }
 }
 
-fn read_value_ref_inner<'a, R>(rd: &mut R, depth: u16) -> Result<ValueRef<'a>, Error>
+fn read_value_ref_inner<'a, R>(rd: &mut R, depth: u16, state: &mut ReadState) -> Result<ValueRef<'a>, Error>
     where R: BorrowRead<'a>
 {
     let depth = super::decrement_depth(depth)?;
+    state.limits.count()?;
 
     // Reading the marker involves either 1 byte read or nothing. On success consumes strictly
     // 1 byte from the `rd`.
@@ -154,105 +192,101 @@ fn read_value_ref_inner<'a, R>(rd: &mut R, depth: u16) -> Result<ValueRef<'a>, E
         Marker::F32 => ValueRef::F32(rd.read_data_f32()?),
         Marker::F64 => ValueRef::F64(rd.read_data_f64()?),
         Marker::FixStr(len) => {
-            let res = read_str_data(rd, len as usize, depth)?;
-            ValueRef::String(res)
+            read_str_data(rd, len as usize, depth, state)?
         }
         Marker::Str8 => {
             let len = rd.read_data_u8()?;
-            let res = read_str_data(rd, len as usize, depth)?;
-            ValueRef::String(res)
+            read_str_data(rd, len as usize, depth, state)?
         }
         Marker::Str16 => {
             let len = rd.read_data_u16()?;
-            let res = read_str_data(rd, len as usize, depth)?;
-            ValueRef::String(res)
+            read_str_data(rd, len as usize, depth, state)?
         }
         Marker::Str32 => {
             let len = rd.read_data_u32()?;
-            let res = read_str_data(rd, len as usize, depth)?;
-            ValueRef::String(res)
+            read_str_data(rd, len as usize, depth, state)?
         }
         Marker::Bin8 => {
             let len = rd.read_data_u8()?;
-            let res = read_bin_data(rd, len as usize, depth)?;
+            let res = read_bin_data(rd, len as usize, depth, state)?;
             ValueRef::Binary(res)
         }
         Marker::Bin16 => {
             let len = rd.read_data_u16()?;
-            let res = read_bin_data(rd, len as usize, depth)?;
+            let res = read_bin_data(rd, len as usize, depth, state)?;
             ValueRef::Binary(res)
         }
         Marker::Bin32 => {
             let len = rd.read_data_u32()?;
-            let res = read_bin_data(rd, len as usize, depth)?;
+            let res = read_bin_data(rd, len as usize, depth, state)?;
             ValueRef::Binary(res)
         }
         Marker::FixArray(len) => {
-            let vec = read_array_data(rd, len as usize, depth)?;
+            let vec = read_array_data(rd, len as usize, depth, state)?;
             ValueRef::Array(vec)
         }
         Marker::Array16 => {
             let len = rd.read_data_u16()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
+            let vec = read_array_data(rd, len as usize, depth, state)?;
             ValueRef::Array(vec)
         }
         Marker::Array32 => {
             let len = rd.read_data_u32()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
+            let vec = read_array_data(rd, len as usize, depth, state)?;
             ValueRef::Array(vec)
         }
         Marker::FixMap(len) => {
-            let map = read_map_data(rd, len as usize, depth)?;
+            let map = read_map_data(rd, len as usize, depth, state)?;
             ValueRef::Map(map)
         }
         Marker::Map16 => {
             let len = rd.read_data_u16()?;
-            let map = read_map_data(rd, len as usize, depth)?;
+            let map = read_map_data(rd, len as usize, depth, state)?;
             ValueRef::Map(map)
         }
         Marker::Map32 => {
             let len = rd.read_data_u32()?;
-            let map = read_map_data(rd, len as usize, depth)?;
+            let map = read_map_data(rd, len as usize, depth, state)?;
             ValueRef::Map(map)
         }
         Marker::FixExt1 => {
             let len = 1;
-            let (ty, vec) = read_ext_body(rd, len as usize, depth)?;
+            let (ty, vec) = read_ext_body(rd, len as usize, depth, state)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::FixExt2 => {
             let len = 2;
-            let (ty, vec) = read_ext_body(rd, len as usize, depth)?;
+            let (ty, vec) = read_ext_body(rd, len as usize, depth, state)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::FixExt4 => {
             let len = 4;
-            let (ty, vec) = read_ext_body(rd, len as usize, depth)?;
+            let (ty, vec) = read_ext_body(rd, len as usize, depth, state)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::FixExt8 => {
             let len = 8;
-            let (ty, vec) = read_ext_body(rd, len as usize, depth)?;
+            let (ty, vec) = read_ext_body(rd, len as usize, depth, state)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::FixExt16 => {
             let len = 16;
-            let (ty, vec) = read_ext_body(rd, len as usize, depth)?;
+            let (ty, vec) = read_ext_body(rd, len as usize, depth, state)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::Ext8 => {
             let len = rd.read_data_u8()?;
-            let (ty, vec) = read_ext_body(rd, len as usize, depth)?;
+            let (ty, vec) = read_ext_body(rd, len as usize, depth, state)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::Ext16 => {
             let len = rd.read_data_u16()?;
-            let (ty, vec) = read_ext_body(rd, len as usize, depth)?;
+            let (ty, vec) = read_ext_body(rd, len as usize, depth, state)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::Ext32 => {
             let len = rd.read_data_u32()?;
-            let (ty, vec) = read_ext_body(rd, len as usize, depth)?;
+            let (ty, vec) = read_ext_body(rd, len as usize, depth, state)?;
             ValueRef::Ext(ty, vec)
         }
         Marker::Reserved => ValueRef::Nil,
@@ -299,7 +333,7 @@ fn read_value_ref_inner<'a, R>(rd: &mut R, depth: u16) -> Result<ValueRef<'a>, E
 pub fn read_value_ref<'a, R>(rd: &mut R) -> Result<ValueRef<'a>, Error>
     where R: BorrowRead<'a>
 {
-    read_value_ref_inner(rd, super::MAX_DEPTH as _)
+    read_value_ref_inner(rd, super::MAX_DEPTH as _, &mut ReadState::new())
 }
 
 /// Attempts to read the data from the given reader until either a complete MessagePack value
@@ -319,5 +353,129 @@ pub fn read_value_ref<'a, R>(rd: &mut R) -> Result<ValueRef<'a>, Error>
 pub fn read_value_ref_with_max_depth<'a, R>(rd: &mut R, max_depth: usize) -> Result<ValueRef<'a>, Error>
     where R: BorrowRead<'a>
 {
-    read_value_ref_inner(rd, max_depth.min(u16::MAX as _) as u16)
+    read_value_ref_inner(rd, max_depth.min(u16::MAX as _) as u16, &mut ReadState::new())
+}
+
+/// Attempts to read the data from the given reader until either a complete MessagePack value
+/// decoded or an error detected, enforcing the limits and policies of `opts`.
+///
+/// See [`read_value_ref`] for more information on how to use this function.
+///
+/// # Errors
+///
+/// Same as [`read_value_with_options`](super::read_value_with_options).
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::{read_value_ref_with_options, DecodeOptions, Error};
+///
+/// let buf = [0xa5, b'h', b'e', b'l', b'l', b'o'];
+///
+/// let err = read_value_ref_with_options(&mut &buf[..], &DecodeOptions::new().max_len(4)).unwrap_err();
+/// assert!(matches!(err, Error::LengthLimitExceeded));
+/// ```
+#[inline(never)]
+pub fn read_value_ref_with_options<'a, R>(rd: &mut R, opts: &DecodeOptions) -> Result<ValueRef<'a>, Error>
+    where R: BorrowRead<'a>
+{
+    let mut state = ReadState {
+        duplicate_keys: opts.duplicate_key_policy(),
+        invalid_utf8: opts.invalid_utf8_policy(),
+        strings_as_binary: opts.strings_as_binary_enabled(),
+        limits: Limits::new(opts),
+    };
+    read_value_ref_inner(rd, opts.depth(), &mut state)
+}
+
+/// An iterator decoding the values concatenated in a buffer, borrowing from it.
+///
+/// This scans large dumps without copying them: the buffer can be any borrowed byte slice,
+/// including a memory-mapped file, and every yielded [`ValueRef`] points into it. Mapping the file
+/// requires `unsafe` code, which this crate does not contain, so it is left to the caller, as with
+/// the `memmap2` crate below.
+///
+/// The iterator stops after the last value, or after yielding the first error.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::fs::File;
+///
+/// use rmpv::decode::ValueRefIter;
+///
+/// let file = File::open("dump.msgpack").unwrap();
+/// // SAFETY: the file must not be modified while it is mapped.
+/// let map = unsafe { memmap2::Mmap::map(&file).unwrap() };
+///
+/// let mut count = 0;
+/// for val in ValueRefIter::new(&map) {
+///     if val.unwrap()["kind"].as_str() == Some("error") {
+///         count += 1;
+///     }
+/// }
+/// println!("{count} errors");
+/// ```
+///
+/// ```
+/// use rmpv::decode::ValueRefIter;
+/// use rmpv::ValueRef;
+///
+/// let buf = [0x01, 0xa1, b'a', 0xc0];
+/// let vals: Vec<_> = ValueRefIter::new(&buf).collect::<Result<_, _>>().unwrap();
+///
+/// assert_eq!(vec![ValueRef::from(1), ValueRef::from("a"), ValueRef::Nil], vals);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ValueRefIter<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    opts: DecodeOptions,
+    failed: bool,
+}
+
+impl<'a> ValueRefIter<'a> {
+    /// Creates an iterator over the values in `buf`, decoded with the default options.
+    #[inline]
+    #[must_use]
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self::with_options(buf, DecodeOptions::new())
+    }
+
+    /// Creates an iterator over the values in `buf`, decoding each of them with `opts`.
+    #[inline]
+    #[must_use]
+    pub fn with_options(buf: &'a [u8], opts: DecodeOptions) -> Self {
+        Self { buf, offset: 0, opts, failed: false }
+    }
+
+    /// Returns the offset in the buffer of the next value, which is the number of bytes consumed
+    /// by the values yielded so far.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for ValueRefIter<'a> {
+    type Item = Result<ValueRef<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut rd = &self.buf[self.offset..];
+        if rd.is_empty() || self.failed {
+            return None;
+        }
+
+        match read_value_ref_with_options(&mut rd, &self.opts) {
+            Ok(val) => {
+                self.offset = self.buf.len() - rd.len();
+                Some(Ok(val))
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
 }
@@ -0,0 +1,270 @@
+//! Structural diffs between values, see [`diff`].
+//!
+//! A [`Patch`] holds the operations that turn one value into another. It can be converted into a
+//! plain [`Value`] with [`Patch::to_value`], to be sent over MessagePack as a compact delta, and
+//! back with [`Patch::from_value`].
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::Value;
+
+/// This type represents all possible errors that can occur when applying or decoding a patch.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The patch refers to a location that does not exist in the target. Contains the path of the
+    /// failed operation.
+    PathNotFound(Vec<Value>),
+    /// The value does not describe a valid patch.
+    InvalidPatch,
+}
+
+impl error::Error for Error {}
+
+impl Display for Error {
+    #[cold]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::PathNotFound(ref path) => write!(fmt, "patch path {} not found", Value::Array(path.clone())),
+            Self::InvalidPatch => fmt.write_str("value is not a valid patch"),
+        }
+    }
+}
+
+/// A single change within a [`Patch`].
+///
+/// Paths are sequences of segments, each being an array index when applied to an array, or a map
+/// key when applied to a map. The empty path refers to the root value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Op {
+    /// Sets the value at the path. A missing map entry is appended, and an index one past the end
+    /// of an array pushes a new element.
+    Set {
+        /// The location of the value.
+        path: Vec<Value>,
+        /// The new value.
+        value: Value,
+    },
+    /// Removes the map entry at the path.
+    Remove {
+        /// The location of the map entry.
+        path: Vec<Value>,
+    },
+    /// Shortens the array at the path to the given length.
+    Truncate {
+        /// The location of the array.
+        path: Vec<Value>,
+        /// The new length of the array.
+        len: usize,
+    },
+}
+
+const OP_SET: u8 = 0;
+const OP_REMOVE: u8 = 1;
+const OP_TRUNCATE: u8 = 2;
+
+/// A sequence of operations turning one value into another, produced by [`diff`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Patch {
+    ops: Vec<Op>,
+}
+
+impl Patch {
+    /// Returns the operations of this patch, in the order they are applied.
+    #[inline]
+    #[must_use]
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+
+    /// Returns `true` if the patch makes no changes.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Applies the patch to `target`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::PathNotFound`] if an operation refers to a location missing from `target`.
+    /// The operations preceding the failed one stay applied.
+    pub fn apply(&self, target: &mut Value) -> Result<(), Error> {
+        for op in &self.ops {
+            apply_op(target, op).ok_or_else(|| Error::PathNotFound(op.path().to_vec()))?;
+        }
+        Ok(())
+    }
+
+    /// Converts the patch into a value, suitable for encoding.
+    ///
+    /// The patch is an array of operations, each being an array whose first element is its kind:
+    /// `[0, path, value]` for [`Op::Set`], `[1, path]` for [`Op::Remove`] and `[2, path, len]` for
+    /// [`Op::Truncate`], where the path is an array of segments.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        let ops = self.ops.iter().map(|op| {
+            let path = Value::Array(op.path().to_vec());
+            Value::Array(match *op {
+                Op::Set { ref value, .. } => vec![Value::from(OP_SET), path, value.clone()],
+                Op::Remove { .. } => vec![Value::from(OP_REMOVE), path],
+                Op::Truncate { len, .. } => vec![Value::from(OP_TRUNCATE), path, Value::from(len)],
+            })
+        });
+        Value::Array(ops.collect())
+    }
+
+    /// Converts a value produced by [`Patch::to_value`] back into a patch.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidPatch`] if the value does not have the expected layout.
+    pub fn from_value(val: &Value) -> Result<Self, Error> {
+        let ops = val.as_array().ok_or(Error::InvalidPatch)?.iter().map(|op| {
+            let (kind, path, rest) = match op.as_array().map(Vec::as_slice) {
+                Some([kind, Value::Array(path), rest @ ..]) => (kind.as_u64(), path.clone(), rest),
+                _ => return Err(Error::InvalidPatch),
+            };
+            match (kind, rest) {
+                (Some(k), [value]) if k == u64::from(OP_SET) => Ok(Op::Set { path, value: value.clone() }),
+                (Some(k), []) if k == u64::from(OP_REMOVE) => Ok(Op::Remove { path }),
+                (Some(k), [len]) if k == u64::from(OP_TRUNCATE) => {
+                    let len = len.as_u64().and_then(|len| usize::try_from(len).ok()).ok_or(Error::InvalidPatch)?;
+                    Ok(Op::Truncate { path, len })
+                }
+                _ => Err(Error::InvalidPatch),
+            }
+        });
+        Ok(Self { ops: ops.collect::<Result<_, _>>()? })
+    }
+}
+
+impl Op {
+    fn path(&self) -> &[Value] {
+        match *self {
+            Self::Set { ref path, .. } | Self::Remove { ref path } | Self::Truncate { ref path, .. } => path,
+        }
+    }
+}
+
+/// Computes the patch that turns `from` into `to`.
+///
+/// Maps are compared entry by entry and arrays element by element, so that only the parts that
+/// differ end up in the patch. Values and map keys are compared with [`Value::total_eq`], so that
+/// patches also find map entries with NaN keys.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::diff::diff;
+/// use rmpv::msgpack;
+///
+/// let old = msgpack!({"pos": [1, 2], "hp": 100, "name": "orc"});
+/// let new = msgpack!({"pos": [1, 3], "hp": 90, "name": "orc"});
+///
+/// let patch = diff(&old, &new);
+/// assert_eq!(2, patch.ops().len());
+///
+/// let mut state = old.clone();
+/// patch.apply(&mut state).unwrap();
+/// assert_eq!(new, state);
+/// ```
+#[must_use]
+pub fn diff(from: &Value, to: &Value) -> Patch {
+    let mut ops = Vec::new();
+    diff_inner(&mut Vec::new(), from, to, &mut ops);
+    Patch { ops }
+}
+
+fn diff_inner(path: &mut Vec<Value>, from: &Value, to: &Value, ops: &mut Vec<Op>) {
+    match (from, to) {
+        (Value::Map(from), Value::Map(to)) => {
+            for (key, val) in from {
+                path.push(key.clone());
+                match to.iter().find(|(k, _)| k.total_eq(key)) {
+                    Some((_, new)) => diff_inner(path, val, new, ops),
+                    None => ops.push(Op::Remove { path: path.clone() }),
+                }
+                path.pop();
+            }
+            for (key, val) in to {
+                if !from.iter().any(|(k, _)| k.total_eq(key)) {
+                    path.push(key.clone());
+                    ops.push(Op::Set { path: path.clone(), value: val.clone() });
+                    path.pop();
+                }
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => {
+            for (idx, (old, new)) in from.iter().zip(to).enumerate() {
+                path.push(Value::from(idx));
+                diff_inner(path, old, new, ops);
+                path.pop();
+            }
+            if to.len() < from.len() {
+                ops.push(Op::Truncate { path: path.clone(), len: to.len() });
+            }
+            for (idx, val) in to.iter().enumerate().skip(from.len()) {
+                path.push(Value::from(idx));
+                ops.push(Op::Set { path: path.clone(), value: val.clone() });
+                path.pop();
+            }
+        }
+        (from, to) if from.total_eq(to) => {}
+        (_, to) => ops.push(Op::Set { path: path.clone(), value: to.clone() }),
+    }
+}
+
+/// Follows `path` from `val`, returning `None` if it leads nowhere.
+fn lookup<'a>(val: &'a mut Value, path: &[Value]) -> Option<&'a mut Value> {
+    path.iter().try_fold(val, |val, segment| match *val {
+        Value::Array(ref mut vec) => vec.get_mut(usize::try_from(segment.as_u64()?).ok()?),
+        Value::Map(ref mut map) => map.iter_mut().find(|(key, _)| key.total_eq(segment)).map(|(_, val)| val),
+        _ => None,
+    })
+}
+
+fn apply_op(target: &mut Value, op: &Op) -> Option<()> {
+    match *op {
+        Op::Set { ref path, ref value } => {
+            let (last, parent) = match path.split_last() {
+                Some((last, parent)) => (last, lookup(target, parent)?),
+                None => {
+                    *target = value.clone();
+                    return Some(());
+                }
+            };
+            match *parent {
+                Value::Array(ref mut vec) => {
+                    let idx = usize::try_from(last.as_u64()?).ok()?;
+                    match idx.cmp(&vec.len()) {
+                        std::cmp::Ordering::Less => vec[idx] = value.clone(),
+                        std::cmp::Ordering::Equal => vec.push(value.clone()),
+                        std::cmp::Ordering::Greater => return None,
+                    }
+                }
+                Value::Map(ref mut map) => match map.iter_mut().find(|(key, _)| key.total_eq(last)) {
+                    Some((_, val)) => *val = value.clone(),
+                    None => map.push((last.clone(), value.clone())),
+                },
+                _ => return None,
+            }
+        }
+        Op::Remove { ref path } => {
+            let (last, parent) = path.split_last()?;
+            let map = match *lookup(target, parent)? {
+                Value::Map(ref mut map) => map,
+                _ => return None,
+            };
+            let idx = map.iter().position(|(key, _)| key.total_eq(last))?;
+            map.remove(idx);
+        }
+        Op::Truncate { ref path, len } => match *lookup(target, path)? {
+            Value::Array(ref mut vec) if len <= vec.len() => vec.truncate(len),
+            _ => return None,
+        },
+    }
+    Some(())
+}
@@ -0,0 +1,35 @@
+use rmp::encode::ValueWriteError;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use super::{value_size, write_value, Error};
+use crate::Value;
+
+/// Encodes and attempts to write the given value into the asynchronous writer, without blocking
+/// the executor.
+///
+/// The value is encoded into a buffer first, and written with a single `write_all`.
+///
+/// # Errors
+///
+/// This function will return `Error` on any I/O error occurred while writing.
+///
+/// # Examples
+///
+/// ```
+/// # async fn run() {
+/// use rmpv::encode::write_value_async;
+/// use rmpv::Value;
+///
+/// let mut buf = Vec::new();
+/// write_value_async(&mut buf, &Value::from("a")).await.unwrap();
+///
+/// assert_eq!(vec![0xa1, 0x61], buf);
+/// # }
+/// ```
+pub async fn write_value_async<W>(wr: &mut W, val: &Value) -> Result<(), Error>
+    where W: AsyncWrite + Unpin
+{
+    let mut buf = Vec::with_capacity(value_size(val));
+    write_value(&mut buf, val)?;
+    wr.write_all(&buf).await.map_err(ValueWriteError::InvalidDataWrite)
+}
@@ -1,7 +1,14 @@
 pub use rmp::encode::ValueWriteError as Error;
 
+#[cfg(feature = "tokio")]
+mod async_io;
+pub mod options;
 mod value;
 mod value_ref;
 
+#[cfg(feature = "tokio")]
+pub use self::async_io::write_value_async;
+pub use self::options::{write_value_canonical, EncodeOptions, FloatFormat};
+pub(crate) use self::value::value_size;
 pub use self::value::write_value;
 pub use self::value_ref::write_value_ref;
@@ -0,0 +1,410 @@
+//! Configurable encoding of values, see [`EncodeOptions`].
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::io::Write;
+
+use rmp::encode::{
+    write_array_len, write_bin, write_bool, write_ext_meta, write_f32, write_f64, write_i64,
+    write_map_len, write_nil, write_sint, write_str, write_u64, write_uint,
+};
+use rmp::Marker;
+
+use crate::{IntPriv, Integer, Timestamp, Utf8String, Value, TIMESTAMP_EXT_TYPE};
+
+/// This type represents all possible errors that can occur when encoding a value with
+/// [`EncodeOptions`].
+#[derive(Debug)]
+pub enum Error {
+    /// Error while writing the encoded data.
+    InvalidValueWrite(super::Error),
+    /// A map key is not a UTF-8 string, while [`EncodeOptions::string_keys`] is enabled.
+    NonStringKey,
+    /// A float is NaN or infinite, while [`EncodeOptions::reject_non_finite`] is enabled.
+    NonFiniteFloat,
+    /// An extension or a timestamp, while [`EncodeOptions::legacy_raw`] is enabled.
+    LegacyExt,
+}
+
+impl error::Error for Error {
+    #[cold]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::InvalidValueWrite(ref err) => Some(err),
+            Self::NonStringKey | Self::NonFiniteFloat | Self::LegacyExt => None,
+        }
+    }
+}
+
+impl Display for Error {
+    #[cold]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::InvalidValueWrite(ref err) => write!(fmt, "invalid value write: {err}"),
+            Self::NonStringKey => fmt.write_str("map key is not a string"),
+            Self::NonFiniteFloat => fmt.write_str("float is NaN or infinite"),
+            Self::LegacyExt => fmt.write_str("extensions can not be written in the legacy raw format"),
+        }
+    }
+}
+
+impl From<super::Error> for Error {
+    #[cold]
+    fn from(err: super::Error) -> Self {
+        Self::InvalidValueWrite(err)
+    }
+}
+
+/// The format in which floats are written or stored, see [`EncodeOptions::float_format`] and
+/// [`Value::normalize_floats`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// Keeps `F32` and `F64` values as they are.
+    #[default]
+    Preserve,
+    /// Widens every `F32` value to an `F64`, which is always lossless.
+    F64,
+    /// Narrows `F64` values to `F32` when that loses no precision.
+    ///
+    /// NaN payloads may not survive the conversion, so NaN values are kept as they are.
+    Compact,
+}
+
+impl FloatFormat {
+    /// Converts `val` into this format, if it is a float.
+    pub(crate) fn apply(self, val: &mut Value) {
+        match (self, &*val) {
+            (Self::F64, &Value::F32(n)) => *val = Value::F64(f64::from(n)),
+            (Self::Compact, &Value::F64(n)) if f64::from(n as f32) == n => *val = Value::F32(n as f32),
+            _ => {}
+        }
+    }
+}
+
+/// Options controlling how [`Value::encode_with`] encodes a value.
+///
+/// By default every option is disabled, producing the same bytes as
+/// [`write_value`](super::write_value).
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::encode::EncodeOptions;
+/// use rmpv::Value;
+///
+/// let opts = EncodeOptions::new()
+///     .compact_floats(true)
+///     .canonical(true);
+///
+/// let val = Value::Map(vec![
+///     (Value::from("b"), Value::F64(0.5)),
+///     (Value::from("a"), Value::Nil),
+/// ]);
+///
+/// assert_eq!(vec![
+///     0x82,
+///     0xa1, b'a', 0xc0,
+///     0xa1, b'b', 0xca, 0x3f, 0x00, 0x00, 0x00,
+/// ], val.encode_with(&opts).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    float_format: FloatFormat,
+    no_fixext: bool,
+    string_keys: bool,
+    canonical: bool,
+    reject_non_finite: bool,
+    fixed_int_width: bool,
+    legacy_raw: bool,
+}
+
+impl EncodeOptions {
+    /// Creates options with every setting disabled.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            float_format: FloatFormat::Preserve,
+            no_fixext: false,
+            string_keys: false,
+            canonical: false,
+            reject_non_finite: false,
+            fixed_int_width: false,
+            legacy_raw: false,
+        }
+    }
+
+    /// Writes `F64` values as 32-bit floats when that loses no precision.
+    ///
+    /// This is a shorthand for [`float_format`](Self::float_format) with [`FloatFormat::Compact`]
+    /// when enabled, or [`FloatFormat::Preserve`] otherwise.
+    #[inline]
+    #[must_use]
+    pub const fn compact_floats(self, enabled: bool) -> Self {
+        self.float_format(if enabled { FloatFormat::Compact } else { FloatFormat::Preserve })
+    }
+
+    /// Writes floats in the given format, for peers that only accept one of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::encode::{EncodeOptions, FloatFormat};
+    /// use rmpv::Value;
+    ///
+    /// let opts = EncodeOptions::new().float_format(FloatFormat::F64);
+    ///
+    /// assert_eq!(vec![0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0], Value::F32(1.5).encode_with(&opts).unwrap());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+
+    /// Never uses the `fixext` markers, writing every extension with an explicit length instead,
+    /// for peers that do not support them.
+    #[inline]
+    #[must_use]
+    pub const fn no_fixext(mut self, enabled: bool) -> Self {
+        self.no_fixext = enabled;
+        self
+    }
+
+    /// Rejects maps whose keys are not UTF-8 strings with [`Error::NonStringKey`].
+    #[inline]
+    #[must_use]
+    pub const fn string_keys(mut self, enabled: bool) -> Self {
+        self.string_keys = enabled;
+        self
+    }
+
+    /// Writes the canonical encoding, so that equal values always produce identical bytes.
+    ///
+    /// Map entries are sorted by their encoded keys, compared bytewise, regardless of their entry
+    /// order. Every value uses its shortest encoding: `F64` values are written as 32-bit floats
+    /// when that loses no precision, and timestamps in the shortest of their 32, 64 and 96-bit
+    /// forms, including extensions of type -1 holding one. This is the form
+    /// [`rmp::decode::Validator::canonical`] accepts.
+    ///
+    /// A [`float_format`](Self::float_format) other than [`FloatFormat::Preserve`],
+    /// [`fixed_int_width`](Self::fixed_int_width), [`no_fixext`](Self::no_fixext) and
+    /// [`legacy_raw`](Self::legacy_raw) still apply, producing other formats than the canonical
+    /// ones.
+    #[inline]
+    #[must_use]
+    pub const fn canonical(mut self, enabled: bool) -> Self {
+        self.canonical = enabled;
+        self
+    }
+
+    /// Rejects NaN and infinite floats with [`Error::NonFiniteFloat`].
+    #[inline]
+    #[must_use]
+    pub const fn reject_non_finite(mut self, enabled: bool) -> Self {
+        self.reject_non_finite = enabled;
+        self
+    }
+
+    /// Writes every integer using the 64-bit `uint 64` or `int 64` format, instead of the most
+    /// compact one.
+    #[inline]
+    #[must_use]
+    pub const fn fixed_int_width(mut self, enabled: bool) -> Self {
+        self.fixed_int_width = enabled;
+        self
+    }
+
+    /// Writes only the formats of the MessagePack specification before version 2.0, for legacy
+    /// peers such as old embedded devices.
+    ///
+    /// That specification had a single raw type, using the markers of today's strings, except for
+    /// `str 8`. Strings and binaries are then both written as raw, and extensions and timestamps,
+    /// which did not exist, are rejected with [`Error::LegacyExt`]. Such data is decoded back
+    /// with [`DecodeOptions::strings_as_binary`](crate::decode::DecodeOptions::strings_as_binary)
+    /// deciding the type of the raw values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::encode::EncodeOptions;
+    /// use rmpv::Value;
+    ///
+    /// let opts = EncodeOptions::new().legacy_raw(true);
+    ///
+    /// assert_eq!(vec![0xa2, 0xca, 0xfe], Value::Binary(vec![0xca, 0xfe]).encode_with(&opts).unwrap());
+    /// assert_eq!(vec![0xda, 0x00, 0x20], Value::from("x".repeat(32)).encode_with(&opts).unwrap()[..3]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn legacy_raw(mut self, enabled: bool) -> Self {
+        self.legacy_raw = enabled;
+        self
+    }
+}
+
+/// Writes `data` as a raw value of the MessagePack specification before version 2.0, which has no
+/// `str 8` format.
+fn write_legacy_raw<W>(wr: &mut W, data: &[u8]) -> Result<(), super::Error>
+    where W: Write
+{
+    let len = data.len() as u32;
+    let mut buf = [0; 5];
+    let header = match len {
+        0..=31 => {
+            buf[0] = Marker::FixStr(len as u8).to_u8();
+            &buf[..1]
+        }
+        32..=0xffff => {
+            buf[0] = Marker::Str16.to_u8();
+            buf[1..3].copy_from_slice(&(len as u16).to_be_bytes());
+            &buf[..3]
+        }
+        _ => {
+            buf[0] = Marker::Str32.to_u8();
+            buf[1..5].copy_from_slice(&len.to_be_bytes());
+            &buf[..5]
+        }
+    };
+    wr.write_all(header).map_err(super::Error::InvalidMarkerWrite)?;
+    wr.write_all(data).map_err(super::Error::InvalidDataWrite)
+}
+
+/// Encodes and attempts to write the canonical representation of the given value, so that equal
+/// values always produce identical bytes, as needed for hashing or signing them.
+///
+/// Map entries are sorted by their encoded keys, integers use their most compact format, `F64`
+/// values are written as 32-bit floats when that loses no precision and timestamps use their
+/// shortest form. This is the same as encoding with [`EncodeOptions::canonical`] enabled.
+///
+/// # Errors
+///
+/// This function will return `Error` on any I/O error occurred while writing.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::encode::write_value_canonical;
+/// use rmpv::Value;
+///
+/// let lhs = Value::Map(vec![(Value::from("b"), Value::F64(1.0)), (Value::from("a"), Value::from(1))]);
+/// let rhs = Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("b"), Value::F32(1.0))]);
+///
+/// let mut lhs_buf = Vec::new();
+/// let mut rhs_buf = Vec::new();
+/// write_value_canonical(&mut lhs_buf, &lhs).unwrap();
+/// write_value_canonical(&mut rhs_buf, &rhs).unwrap();
+///
+/// assert_eq!(lhs_buf, rhs_buf);
+/// ```
+pub fn write_value_canonical<W>(wr: &mut W, val: &Value) -> Result<(), super::Error>
+    where W: Write
+{
+    let opts = EncodeOptions::new().canonical(true);
+    match write_value_with(wr, val, &opts) {
+        Ok(()) => Ok(()),
+        Err(Error::InvalidValueWrite(err)) => Err(err),
+        Err(Error::NonStringKey | Error::NonFiniteFloat | Error::LegacyExt) => unreachable!("no restriction is enabled"),
+    }
+}
+
+/// Encodes the given value according to `opts`.
+pub(crate) fn write_value_with<W>(wr: &mut W, val: &Value, opts: &EncodeOptions) -> Result<(), Error>
+    where W: Write
+{
+    match *val {
+        Value::Nil => {
+            write_nil(wr).map_err(super::Error::InvalidMarkerWrite)?;
+        }
+        Value::Boolean(val) => {
+            write_bool(wr, val).map_err(super::Error::InvalidMarkerWrite)?;
+        }
+        Value::Integer(Integer { n }) => match n {
+            IntPriv::PosInt(n) if opts.fixed_int_width => write_u64(wr, n)?,
+            IntPriv::NegInt(n) if opts.fixed_int_width => write_i64(wr, n)?,
+            IntPriv::PosInt(n) => {
+                write_uint(wr, n)?;
+            }
+            IntPriv::NegInt(n) => {
+                write_sint(wr, n)?;
+            }
+        },
+        Value::F32(..) | Value::F64(..) => {
+            let mut val = val.clone();
+            match opts.float_format {
+                FloatFormat::Preserve if opts.canonical => FloatFormat::Compact.apply(&mut val),
+                format => format.apply(&mut val),
+            }
+            if opts.reject_non_finite && !val.as_f64().is_some_and(f64::is_finite) {
+                return Err(Error::NonFiniteFloat);
+            }
+            match val {
+                Value::F32(val) => write_f32(wr, val)?,
+                Value::F64(val) => write_f64(wr, val)?,
+                _ => unreachable!("floats stay floats when normalized"),
+            }
+        }
+        Value::String(ref val) if opts.legacy_raw => write_legacy_raw(wr, val.as_bytes())?,
+        Value::Binary(ref val) if opts.legacy_raw => write_legacy_raw(wr, val)?,
+        Value::Ext(..) | Value::Timestamp(..) if opts.legacy_raw => return Err(Error::LegacyExt),
+        Value::String(Utf8String { ref s }) => match *s {
+            Ok(ref val) => write_str(wr, val)?,
+            Err(ref err) => write_bin(wr, &err.0)?,
+        },
+        Value::Binary(ref val) => {
+            write_bin(wr, val)?;
+        }
+        Value::Array(ref vec) => {
+            write_array_len(wr, vec.len() as u32)?;
+            for v in vec {
+                write_value_with(wr, v, opts)?;
+            }
+        }
+        Value::Map(ref map) => {
+            if opts.string_keys && map.iter().any(|(key, _)| key.as_str().is_none()) {
+                return Err(Error::NonStringKey);
+            }
+
+            write_map_len(wr, map.len() as u32)?;
+            if opts.canonical {
+                let mut entries = Vec::with_capacity(map.len());
+                for (key, val) in map {
+                    let mut buf = Vec::new();
+                    write_value_with(&mut buf, key, opts)?;
+                    entries.push((buf, val));
+                }
+                entries.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+
+                for (key, val) in entries {
+                    wr.write_all(&key).map_err(super::Error::InvalidDataWrite)?;
+                    write_value_with(wr, val, opts)?;
+                }
+            } else {
+                for (key, val) in map {
+                    write_value_with(wr, key, opts)?;
+                    write_value_with(wr, val, opts)?;
+                }
+            }
+        }
+        Value::Ext(..) | Value::Timestamp(..) => {
+            let (ty, data) = val.as_ext().unwrap_or_default();
+            // Timestamps keep the form they were decoded in, which is not always the shortest.
+            let shortest = match Timestamp::from_ext_data(data) {
+                Some(ts) if opts.canonical && ty == TIMESTAMP_EXT_TYPE => Timestamp::new(ts.secs(), ts.nanos()),
+                _ => None,
+            };
+            let data = shortest.as_ref().map_or(data, Timestamp::as_ext_data);
+            if opts.no_fixext && matches!(data.len(), 1 | 2 | 4 | 8 | 16) {
+                // Only the `ext 8` format can hold the lengths of the `fixext` formats.
+                let meta = [Marker::Ext8.to_u8(), data.len() as u8, ty as u8];
+                wr.write_all(&meta).map_err(super::Error::InvalidMarkerWrite)?;
+            } else {
+                write_ext_meta(wr, data.len() as u32, ty)?;
+            }
+            wr.write_all(data).map_err(super::Error::InvalidDataWrite)?;
+        }
+    }
+
+    Ok(())
+}
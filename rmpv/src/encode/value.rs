@@ -6,7 +6,7 @@ use rmp::encode::{
 };
 
 use super::Error;
-use crate::{IntPriv, Integer, Utf8String, Value};
+use crate::{IntPriv, Integer, Utf8String, Value, TIMESTAMP_EXT_TYPE};
 
 /// Encodes and attempts to write the most efficient representation of the given Value.
 ///
@@ -62,7 +62,80 @@ pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
             write_ext_meta(wr, data.len() as u32, ty)?;
             wr.write_all(data).map_err(Error::InvalidDataWrite)?;
         }
+        Value::Timestamp(ref ts) => {
+            let data = ts.as_ext_data();
+            write_ext_meta(wr, data.len() as u32, TIMESTAMP_EXT_TYPE)?;
+            wr.write_all(data).map_err(Error::InvalidDataWrite)?;
+        }
     }
 
     Ok(())
 }
+
+/// Returns the number of bytes [`write_value`] writes for the given Value.
+pub(crate) fn value_size(val: &Value) -> usize {
+    match *val {
+        Value::Nil | Value::Boolean(..) => 1,
+        Value::Integer(Integer { n }) => match n {
+            IntPriv::PosInt(n) => uint_size(n),
+            IntPriv::NegInt(n) if n >= -32 => 1,
+            IntPriv::NegInt(n) if n >= -128 => 2,
+            IntPriv::NegInt(n) if n >= -32768 => 3,
+            IntPriv::NegInt(n) if n >= -2147483648 => 5,
+            IntPriv::NegInt(..) => 9,
+        },
+        Value::F32(..) => 5,
+        Value::F64(..) => 9,
+        Value::String(Utf8String { ref s }) => match *s {
+            Ok(ref val) if val.len() < 32 => 1 + val.len(),
+            Ok(ref val) => bin_size(val.len()),
+            Err(ref err) => bin_size(err.0.len()),
+        },
+        Value::Binary(ref val) => bin_size(val.len()),
+        Value::Array(ref vec) => container_size(vec.len()) + vec.iter().map(value_size).sum::<usize>(),
+        Value::Map(ref map) => {
+            container_size(map.len()) + map.iter().map(|(key, val)| value_size(key) + value_size(val)).sum::<usize>()
+        }
+        Value::Ext(_, ref data) => ext_size(data.len()),
+        Value::Timestamp(ref ts) => ext_size(ts.as_ext_data().len()),
+    }
+}
+
+fn uint_size(n: u64) -> usize {
+    match n {
+        0..=127 => 1,
+        128..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// The size of a binary, or of a string of at least 32 bytes, which use the same length prefixes.
+fn bin_size(len: usize) -> usize {
+    let header = match len {
+        0..=0xff => 2,
+        0x100..=0xffff => 3,
+        _ => 5,
+    };
+    header + len
+}
+
+/// The size of the length prefix of an array or map.
+fn container_size(len: usize) -> usize {
+    match len {
+        0..=15 => 1,
+        16..=0xffff => 3,
+        _ => 5,
+    }
+}
+
+fn ext_size(len: usize) -> usize {
+    let header = match len {
+        1 | 2 | 4 | 8 | 16 => 2,
+        0..=0xff => 3,
+        0x100..=0xffff => 4,
+        _ => 6,
+    };
+    header + len
+}
@@ -0,0 +1,133 @@
+//! A view into a single entry of a map value, see [`Value::entry`].
+
+use crate::Value;
+
+/// A view into a single entry of a [`Value::Map`], which may either be vacant or occupied.
+///
+/// This is constructed by [`Value::entry`].
+#[derive(Debug)]
+pub enum Entry<'a> {
+    /// The map has an entry with the key.
+    Occupied(OccupiedEntry<'a>),
+    /// The map has no entry with the key.
+    Vacant(VacantEntry<'a>),
+}
+
+/// An entry of a map that is present. Part of the [`Entry`] enum.
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    map: &'a mut Vec<(Value, Value)>,
+    idx: usize,
+}
+
+/// An entry of a map that is missing. Part of the [`Entry`] enum.
+#[derive(Debug)]
+pub struct VacantEntry<'a> {
+    map: &'a mut Vec<(Value, Value)>,
+    key: Value,
+}
+
+impl<'a> Entry<'a> {
+    /// Looks up `key` in `map`, picking the first entry with an equal key.
+    pub(crate) fn new(map: &'a mut Vec<(Value, Value)>, key: Value) -> Self {
+        match map.iter().position(|(k, _)| *k == key) {
+            Some(idx) => Self::Occupied(OccupiedEntry { map, idx }),
+            None => Self::Vacant(VacantEntry { map, key }),
+        }
+    }
+
+    /// Returns the key of this entry.
+    #[must_use]
+    pub fn key(&self) -> &Value {
+        match *self {
+            Self::Occupied(ref entry) => entry.key(),
+            Self::Vacant(ref entry) => entry.key(),
+        }
+    }
+
+    /// Ensures a value is in the entry by appending `default` if empty, and returns a mutable
+    /// reference to the value in the entry.
+    pub fn or_insert(self, default: Value) -> &'a mut Value {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by appending the result of `default` if empty, and returns
+    /// a mutable reference to the value in the entry.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut Value
+        where F: FnOnce() -> Value
+    {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any potential inserts.
+    #[must_use]
+    pub fn and_modify<F>(self, f: F) -> Self
+        where F: FnOnce(&mut Value)
+    {
+        match self {
+            Self::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Self::Occupied(entry)
+            }
+            Self::Vacant(entry) => Self::Vacant(entry),
+        }
+    }
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Returns the key of this entry.
+    #[must_use]
+    pub fn key(&self) -> &Value {
+        &self.map[self.idx].0
+    }
+
+    /// Returns a reference to the value in the entry.
+    #[must_use]
+    pub fn get(&self) -> &Value {
+        &self.map[self.idx].1
+    }
+
+    /// Returns a mutable reference to the value in the entry.
+    pub fn get_mut(&mut self) -> &mut Value {
+        &mut self.map[self.idx].1
+    }
+
+    /// Converts the entry into a mutable reference to its value, bound to the lifetime of the map.
+    #[must_use]
+    pub fn into_mut(self) -> &'a mut Value {
+        &mut self.map[self.idx].1
+    }
+
+    /// Sets the value of the entry, returning the old one.
+    pub fn insert(&mut self, val: Value) -> Value {
+        std::mem::replace(self.get_mut(), val)
+    }
+
+    /// Removes the entry from the map, returning its value.
+    ///
+    /// The order of the remaining entries is preserved.
+    pub fn remove(self) -> Value {
+        self.map.remove(self.idx).1
+    }
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Returns the key that would be used when inserting a value through this entry.
+    #[must_use]
+    pub fn key(&self) -> &Value {
+        &self.key
+    }
+
+    /// Appends an entry with the key and `val` to the map, returning a mutable reference to the
+    /// value.
+    pub fn insert(self, val: Value) -> &'a mut Value {
+        self.map.push((self.key, val));
+        &mut self.map.last_mut().expect("an entry was just pushed").1
+    }
+}
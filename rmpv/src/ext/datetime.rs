@@ -0,0 +1,87 @@
+//! Serializes a `chrono::DateTime<Utc>` as the timestamp extension type, enabled by the `chrono`
+//! feature.
+//!
+//! chrono serializes its types as strings, which [`to_value`](super::to_value) can not tell apart
+//! from other strings, so the mapping is chosen per field with
+//! `#[serde(with = "rmpv::ext::datetime")]`. It works with any serde format that understands
+//! the MessagePack extension types, such as `rmp-serde`.
+//!
+//! # Examples
+//!
+//! ```
+//! use chrono::{DateTime, Utc};
+//! use rmpv::{Timestamp, Value};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Event {
+//!     #[serde(with = "rmpv::ext::datetime")]
+//!     at: DateTime<Utc>,
+//! }
+//!
+//! let event = Event { at: DateTime::from_timestamp(1_700_000_000, 500).unwrap() };
+//!
+//! let val = rmpv::ext::to_value(&event).unwrap();
+//! let ts = Timestamp::new(1_700_000_000, 500).unwrap();
+//! assert_eq!(Value::Array(vec![Value::Timestamp(ts)]), val);
+//! assert_eq!(event, rmpv::ext::from_value(val).unwrap());
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt::{self, Formatter};
+
+use ::chrono::{DateTime, Utc};
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{self, Serializer};
+use serde_bytes::{ByteBuf, Bytes};
+
+use crate::{Timestamp, MSGPACK_EXT_STRUCT_NAME, TIMESTAMP_EXT_TYPE};
+
+/// Serializes the date and time as a timestamp.
+///
+/// # Errors
+///
+/// Fails on leap seconds, which timestamps can not represent.
+pub fn serialize<S>(time: &DateTime<Utc>, s: S) -> Result<S::Ok, S::Error>
+    where S: Serializer
+{
+    let ts = Timestamp::try_from(*time)
+        .map_err(|time| ser::Error::custom(format_args!("leap second {time} is not a valid timestamp")))?;
+    let value = (TIMESTAMP_EXT_TYPE, Bytes::new(ts.as_ext_data()));
+    s.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &value)
+}
+
+/// Deserializes a timestamp into a date and time.
+///
+/// # Errors
+///
+/// Fails if the value is not a valid timestamp, or is out of the range of chrono.
+pub fn deserialize<'de, D>(d: D) -> Result<DateTime<Utc>, D::Error>
+    where D: Deserializer<'de>
+{
+    d.deserialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, TimestampVisitor)
+}
+
+struct TimestampVisitor;
+
+impl<'de> Visitor<'de> for TimestampVisitor {
+    type Value = DateTime<Utc>;
+
+    #[cold]
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        fmt.write_str("a MessagePack timestamp")
+    }
+
+    fn visit_newtype_struct<D>(self, d: D) -> Result<Self::Value, D::Error>
+        where D: Deserializer<'de>
+    {
+        let (tag, data) = <(i8, ByteBuf)>::deserialize(d)?;
+        if tag != TIMESTAMP_EXT_TYPE {
+            return Err(de::Error::invalid_value(de::Unexpected::Signed(tag.into()), &self));
+        }
+        let ts = Timestamp::from_ext_data(&data)
+            .ok_or_else(|| de::Error::invalid_length(data.len(), &self))?;
+        DateTime::try_from(ts)
+            .map_err(|ts| de::Error::custom(format_args!("timestamp {ts:?} is out of range")))
+    }
+}
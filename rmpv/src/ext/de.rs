@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 use std::fmt::{self, Display, Formatter};
-use std::iter::ExactSizeIterator;
+use std::iter::{ExactSizeIterator, Map};
 use std::slice::Iter;
 use std::vec::IntoIter;
 
@@ -8,10 +8,10 @@ use serde::de::{self, DeserializeSeed, IntoDeserializer, SeqAccess, Unexpected,
 use serde::forward_to_deserialize_any;
 use serde::{self, Deserialize, Deserializer};
 
-use crate::{IntPriv, Integer, Utf8String, Utf8StringRef, Value, ValueRef};
+use crate::{IntPriv, Integer, Timestamp, Utf8String, Utf8StringRef, Value, ValueRef};
 
-use super::{Error, ValueExt};
-use crate::MSGPACK_EXT_STRUCT_NAME;
+use super::{Error, Segment, ValueExt, SYSTEM_TIME_STRUCT_NAME};
+use crate::{MSGPACK_EXT_STRUCT_NAME, TIMESTAMP_EXT_TYPE};
 
 #[inline]
 pub fn from_value<T>(val: Value) -> Result<T, Error>
@@ -20,6 +20,33 @@ pub fn from_value<T>(val: Value) -> Result<T, Error>
     deserialize_from(val)
 }
 
+/// Deserializes a `T` from `val`, borrowing its strings and binaries instead of copying them.
+///
+/// Types holding `&str` or `&[u8]` fields that borrow from `val` can be deserialized this way.
+///
+/// # Errors
+///
+/// Returns [`Error`] if `val` does not match the shape of `T`.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::ext::from_value_ref;
+/// use rmpv::Value;
+///
+/// let val = Value::Array(vec![Value::from("ping"), Value::Binary(vec![1, 2, 3])]);
+///
+/// let (name, payload): (&str, &[u8]) = from_value_ref(&val).unwrap();
+/// assert_eq!("ping", name);
+/// assert_eq!(val[1].as_slice().unwrap().as_ptr(), payload.as_ptr());
+/// ```
+#[inline]
+pub fn from_value_ref<'de, T>(val: &'de Value) -> Result<T, Error>
+    where T: Deserialize<'de>
+{
+    deserialize_from(val)
+}
+
 #[inline]
 pub fn deserialize_from<'de, T, D>(val: D) -> Result<T, Error>
     where T: Deserialize<'de>,
@@ -164,7 +191,7 @@ impl<'de> Deserialize<'de> for Value {
                         let bytes: serde_bytes::ByteBuf = seq.next_element()?
                             .ok_or_else(|| de::Error::invalid_length(1, &self))?;
 
-                        Ok(Value::Ext(tag, bytes.to_vec()))
+                        Ok(Value::from_ext(tag, bytes.to_vec()))
                     }
                 }
 
@@ -350,6 +377,10 @@ impl<'de> Deserializer<'de> for Value {
                 let de = ExtDeserializer::new_owned(tag, data);
                 visitor.visit_newtype_struct(de)
             }
+            Self::Timestamp(ts) => {
+                let de = ExtDeserializer::new_owned(TIMESTAMP_EXT_TYPE, ts.as_ext_data().to_vec());
+                visitor.visit_newtype_struct(de)
+            }
         }
     }
 
@@ -377,6 +408,10 @@ impl<'de> Deserializer<'de> for Value {
                     let ext_de = ExtDeserializer::new_owned(tag, data);
                     return visitor.visit_newtype_struct(ext_de);
                 }
+                Self::Timestamp(ts) => {
+                    let ext_de = ExtDeserializer::new_owned(TIMESTAMP_EXT_TYPE, ts.as_ext_data().to_vec());
+                    return visitor.visit_newtype_struct(ext_de);
+                }
                 other => return Err(de::Error::invalid_type(other.unexpected(), &"expected Ext")),
             }
         }
@@ -391,9 +426,151 @@ impl<'de> Deserializer<'de> for Value {
         ValueBase::deserialize_unit_struct(self, visitor)
     }
 
+    #[inline]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_i128(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_u128(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_struct(self, name, fields, visitor)
+    }
+
     forward_to_deserialize_any! {
         bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit seq
-        bytes byte_buf map tuple_struct struct
+        bytes byte_buf map tuple_struct
+        identifier tuple ignored_any
+    }
+}
+
+impl<'de> Deserializer<'de> for &'de Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        match *self {
+            Value::Nil => visitor.visit_unit(),
+            Value::Boolean(v) => visitor.visit_bool(v),
+            Value::Integer(Integer { n }) => match n {
+                IntPriv::PosInt(v) => visitor.visit_u64(v),
+                IntPriv::NegInt(v) => visitor.visit_i64(v),
+            },
+            Value::F32(v) => visitor.visit_f32(v),
+            Value::F64(v) => visitor.visit_f64(v),
+            Value::String(ref v) => match v.s {
+                Ok(ref v) => visitor.visit_borrowed_str(v),
+                Err(ref v) => visitor.visit_borrowed_bytes(&v.0),
+            },
+            Value::Binary(ref v) => visitor.visit_borrowed_bytes(v),
+            Value::Array(ref v) => {
+                let len = v.len();
+                let mut de = SeqDeserializer::new(v.iter());
+                let seq = visitor.visit_seq(&mut de)?;
+                if de.iter.len() == 0 {
+                    Ok(seq)
+                } else {
+                    Err(de::Error::invalid_length(len, &"fewer elements in array"))
+                }
+            }
+            Value::Map(ref v) => {
+                let len = v.len();
+                let mut de = MapDeserializer::new(v.iter().map(entry_refs as EntryRefs<'de>));
+                let map = visitor.visit_map(&mut de)?;
+                if de.iter.len() == 0 {
+                    Ok(map)
+                } else {
+                    Err(de::Error::invalid_length(len, &"fewer elements in map"))
+                }
+            }
+            Value::Ext(tag, ref data) => {
+                let de = ExtDeserializer::new_ref(tag, data);
+                visitor.visit_newtype_struct(de)
+            }
+            Value::Timestamp(ref ts) => {
+                let de = ExtDeserializer::new_ref(TIMESTAMP_EXT_TYPE, ts.as_ext_data());
+                visitor.visit_newtype_struct(de)
+            }
+        }
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_option(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(self, _name: &str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_enum(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if name == MSGPACK_EXT_STRUCT_NAME {
+            match *self {
+                Value::Ext(tag, ref data) => {
+                    let ext_de = ExtDeserializer::new_ref(tag, data);
+                    return visitor.visit_newtype_struct(ext_de);
+                }
+                Value::Timestamp(ref ts) => {
+                    let ext_de = ExtDeserializer::new_ref(TIMESTAMP_EXT_TYPE, ts.as_ext_data());
+                    return visitor.visit_newtype_struct(ext_de);
+                }
+                ref other => return Err(de::Error::invalid_type(other.unexpected(), &"expected Ext")),
+            }
+        }
+
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_unit_struct(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_i128(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_u128(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_struct(self, name, fields, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit seq
+        bytes byte_buf map tuple_struct
         identifier tuple ignored_any
     }
 }
@@ -484,9 +661,30 @@ impl<'de> Deserializer<'de> for ValueRef<'de> {
         ValueBase::deserialize_unit_struct(self, visitor)
     }
 
+    #[inline]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_i128(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_u128(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_struct(self, name, fields, visitor)
+    }
+
     forward_to_deserialize_any! {
         bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit seq
-        bytes byte_buf map tuple_struct struct
+        bytes byte_buf map tuple_struct
         identifier tuple ignored_any
     }
 }
@@ -608,9 +806,48 @@ impl<'de> Deserializer<'de> for &'de ValueRef<'de> {
         }
     }
 
+    #[inline]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        let data = self.int128_data();
+        deserialize_int128(self, data, true, visitor)
+    }
+
+    #[inline]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        let data = self.int128_data();
+        deserialize_int128(self, data, false, visitor)
+    }
+
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if let Some(ts) = self.timestamp().filter(|_| name == SYSTEM_TIME_STRUCT_NAME) {
+            let fields = [Value::from(ts.secs()), Value::from(ts.nanos())];
+            return visitor.visit_seq(SeqDeserializer::new(fields.into_iter()));
+        }
+
+        match *self {
+            ValueRef::Array(ref v) => {
+                let len = v.len();
+                let mut de = SeqDeserializer::with_fields(v.iter(), fields);
+                let seq = visitor.visit_seq(&mut de)?;
+                if de.iter.len() == 0 {
+                    Ok(seq)
+                } else {
+                    Err(de::Error::invalid_length(len, &"fewer elements in array"))
+                }
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
     forward_to_deserialize_any! {
         bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit seq
-        bytes byte_buf map tuple_struct struct
+        bytes byte_buf map tuple_struct
         identifier tuple ignored_any
     }
 }
@@ -701,11 +938,18 @@ impl<'a, 'de: 'a> Deserializer<'de> for &'a mut ExtDeserializer<'de> {
 
 struct SeqDeserializer<I> {
     iter: I,
+    index: usize,
+    // Field names of the struct being deserialized, used to name its elements in error paths.
+    fields: &'static [&'static str],
 }
 
 impl<I> SeqDeserializer<I> {
     const fn new(iter: I) -> Self {
-        Self { iter }
+        Self::with_fields(iter, &[])
+    }
+
+    const fn with_fields(iter: I, fields: &'static [&'static str]) -> Self {
+        Self { iter, index: 0, fields }
     }
 }
 
@@ -719,7 +963,14 @@ impl<'de, I, U> SeqAccess<'de> for SeqDeserializer<I>
         where T: de::DeserializeSeed<'de>
     {
         match self.iter.next() {
-            Some(val) => seed.deserialize(val).map(Some),
+            Some(val) => {
+                let index = self.index;
+                self.index += 1;
+                match seed.deserialize(val) {
+                    Ok(val) => Ok(Some(val)),
+                    Err(err) => Err(err.at(Segment::field(self.fields.get(index).copied(), index))),
+                }
+            }
             None => Ok(None),
         }
     }
@@ -756,14 +1007,71 @@ impl<'de, I, U> Deserializer<'de> for SeqDeserializer<I>
     }
 }
 
+/// Deserializes a string map key without taking it, so it can name the entry in error paths. The
+/// string is lent to the visitor instead of moved, which only costs a copy if the visitor keeps it.
+struct KeyDeserializer<'a>(&'a Value);
+
+impl<'de> Deserializer<'de> for KeyDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        match *self.0 {
+            Value::String(Utf8String { s: Ok(ref v) }) => visitor.visit_str(v),
+            Value::String(Utf8String { s: Err((ref v, _)) }) => visitor.visit_bytes(v),
+            ref other => other.clone().deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_some(self)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if name == MSGPACK_EXT_STRUCT_NAME {
+            return Err(de::Error::invalid_type(self.0.unexpected(), &"expected Ext"));
+        }
+
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(self, _name: &str, _variants: &'static [&'static str], _visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        Err(de::Error::invalid_type(self.0.unexpected(), &"array, map or int"))
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        Err(de::Error::invalid_type(self.0.unexpected(), &"empty array"))
+    }
+
+    forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 i128 u128 f32 f64 char str string unit seq
+        bytes byte_buf map tuple_struct struct identifier tuple ignored_any
+    }
+}
+
 struct MapDeserializer<I, U> {
     val: Option<U>,
     iter: I,
+    // The current key, kept to name it in error paths.
+    key: Option<U>,
 }
 
 impl<I, U> MapDeserializer<I, U> {
     const fn new(iter: I) -> Self {
-        Self { val: None, iter }
+        Self { val: None, iter, key: None }
     }
 }
 
@@ -779,7 +1087,12 @@ impl<'de, I, U> de::MapAccess<'de> for MapDeserializer<I, U>
         match self.iter.next() {
             Some((key, val)) => {
                 self.val = Some(val);
-                seed.deserialize(key).map(Some)
+                let (res, key) = key.deserialize_key(seed);
+                let key = self.key.insert(key);
+                match res {
+                    Ok(key) => Ok(Some(key)),
+                    Err(err) => Err(err.at(key.segment())),
+                }
             }
             None => Ok(None),
         }
@@ -789,7 +1102,13 @@ impl<'de, I, U> de::MapAccess<'de> for MapDeserializer<I, U>
         where T: DeserializeSeed<'de>
     {
         match self.val.take() {
-            Some(val) => seed.deserialize(val),
+            Some(val) => match seed.deserialize(val) {
+                Ok(val) => Ok(val),
+                Err(err) => Err(match self.key {
+                    Some(ref key) => err.at(key.segment()),
+                    None => err,
+                }),
+            },
             None => Err(de::Error::custom("value is missing")),
         }
     }
@@ -904,12 +1223,12 @@ impl<'de, U: ValueBase<'de> + ValueExt> de::VariantAccess<'de> for VariantDeseri
         }
     }
 
-    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error>
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>,
     {
         match self.value {
             Some(v) => match v.into_iter() {
-                Ok(iter) => Deserializer::deserialize_any(SeqDeserializer::new(iter), visitor),
+                Ok(iter) => Deserializer::deserialize_any(SeqDeserializer::with_fields(iter, fields), visitor),
                 Err(v) => match v.into_map_iter() {
                     Ok(iter) => Deserializer::deserialize_any(MapDeserializer::new(iter), visitor),
                     Err(v) => Err(de::Error::invalid_type(v.unexpected(), &"struct variant")),
@@ -926,11 +1245,12 @@ impl<'de, U: ValueBase<'de> + ValueExt> de::VariantAccess<'de> for VariantDeseri
 pub struct MapRefDeserializer<'de> {
     val: Option<&'de ValueRef<'de>>,
     iter: Iter<'de, (ValueRef<'de>, ValueRef<'de>)>,
+    key: Option<&'de ValueRef<'de>>,
 }
 
 impl<'de> MapRefDeserializer<'de> {
     const fn new(iter: Iter<'de, (ValueRef<'de>, ValueRef<'de>)>) -> Self {
-        Self { val: None, iter }
+        Self { val: None, iter, key: None }
     }
 }
 
@@ -943,7 +1263,11 @@ impl<'de> de::MapAccess<'de> for MapRefDeserializer<'de> {
         match self.iter.next() {
             Some((key, val)) => {
                 self.val = Some(val);
-                seed.deserialize(key).map(Some)
+                self.key = Some(key);
+                match seed.deserialize(key) {
+                    Ok(key) => Ok(Some(key)),
+                    Err(err) => Err(err.at(key.segment())),
+                }
             }
             None => Ok(None),
         }
@@ -953,7 +1277,13 @@ impl<'de> de::MapAccess<'de> for MapRefDeserializer<'de> {
         where T: DeserializeSeed<'de>
     {
         match self.val.take() {
-            Some(val) => seed.deserialize(val),
+            Some(val) => match seed.deserialize(val) {
+                Ok(val) => Ok(val),
+                Err(err) => Err(match self.key {
+                    Some(key) => err.at(key.segment()),
+                    None => err,
+                }),
+            },
             None => Err(de::Error::custom("value is missing")),
         }
     }
@@ -1064,12 +1394,12 @@ impl<'de> de::VariantAccess<'de> for VariantRefDeserializer<'de> {
         }
     }
 
-    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error>
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error>
         where V: Visitor<'de>,
     {
         match self.value {
             Some(ValueRef::Array(v)) => {
-                Deserializer::deserialize_any(SeqDeserializer::new(v.iter()), visitor)
+                Deserializer::deserialize_any(SeqDeserializer::with_fields(v.iter(), fields), visitor)
             }
             Some(ValueRef::Map(v)) => {
                 Deserializer::deserialize_any(MapRefDeserializer::new(v.iter()), visitor)
@@ -1080,6 +1410,20 @@ impl<'de> de::VariantAccess<'de> for VariantRefDeserializer<'de> {
     }
 }
 
+/// Visits the data of a 16-byte binary as a 128-bit integer, signed or not, and any other value
+/// as it is.
+#[inline]
+fn deserialize_int128<'de, D, V>(de: D, data: Option<[u8; 16]>, signed: bool, visitor: V) -> Result<V::Value, Error>
+    where D: Deserializer<'de, Error = Error>,
+          V: Visitor<'de>
+{
+    match data {
+        Some(buf) if signed => visitor.visit_i128(i128::from_be_bytes(buf)),
+        Some(buf) => visitor.visit_u128(u128::from_be_bytes(buf)),
+        None => de.deserialize_any(visitor),
+    }
+}
+
 // TODO: Ugly hack. Needed for avoiding copy-pasting similar code, but I don't like it.
 trait ValueBase<'de>: Deserializer<'de, Error = Error> + ValueExt {
     type Item: ValueBase<'de>;
@@ -1092,6 +1436,57 @@ trait ValueBase<'de>: Deserializer<'de, Error = Error> + ValueExt {
     fn into_iter(self) -> Result<Self::Iter, Self::Item>;
     fn into_map_iter(self) -> Result<Self::MapIter, Self::Item>;
 
+    /// Returns the data of a 16-byte binary, as 128-bit integers out of the 64-bit range are
+    /// stored.
+    fn int128_data(&self) -> Option<[u8; 16]>;
+
+    fn timestamp(&self) -> Option<Timestamp>;
+
+    /// Deserializes a map key, giving it back to name it in error paths.
+    fn deserialize_key<S>(self, seed: S) -> (Result<S::Value, Error>, Self)
+        where S: DeserializeSeed<'de>;
+
+    /// Deserializes a timestamp as the fields serde gives to a `SystemTime`, and arrays with the
+    /// names of the fields for error paths.
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if let Some(ts) = self.timestamp().filter(|_| name == SYSTEM_TIME_STRUCT_NAME) {
+            let fields = [Value::from(ts.secs()), Value::from(ts.nanos())];
+            return visitor.visit_seq(SeqDeserializer::new(fields.into_iter()));
+        }
+
+        match self.into_iter() {
+            Ok(iter) => {
+                let len = iter.len();
+                let mut de = SeqDeserializer::with_fields(iter, fields);
+                let seq = visitor.visit_seq(&mut de)?;
+                if de.iter.len() == 0 {
+                    Ok(seq)
+                } else {
+                    Err(de::Error::invalid_length(len, &"fewer elements in array"))
+                }
+            }
+            Err(other) => other.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        let data = self.int128_data();
+        deserialize_int128(self, data, true, visitor)
+    }
+
+    #[inline]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        let data = self.int128_data();
+        deserialize_int128(self, data, false, visitor)
+    }
+
     #[inline]
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
         where V: Visitor<'de>
@@ -1145,7 +1540,7 @@ trait ValueBase<'de>: Deserializer<'de, Error = Error> + ValueExt {
     }
 }
 
-impl ValueBase<'_> for Value {
+impl<'de> ValueBase<'de> for Value {
     type Item = Self;
     type Iter = IntoIter<Self>;
     type MapIter = IntoIter<(Self, Self)>;
@@ -1171,6 +1566,32 @@ impl ValueBase<'_> for Value {
             other => Err(other),
         }
     }
+
+    #[inline]
+    fn int128_data(&self) -> Option<[u8; 16]> {
+        match *self {
+            Self::Binary(ref v) => v[..].try_into().ok(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn timestamp(&self) -> Option<Timestamp> {
+        self.as_timestamp()
+    }
+
+    #[inline]
+    fn deserialize_key<S>(self, seed: S) -> (Result<S::Value, Error>, Self)
+        where S: DeserializeSeed<'de>
+    {
+        let res = match self {
+            Self::String(..) => seed.deserialize(KeyDeserializer(&self)),
+            // Other keys are cheap to clone, except for the rare binaries, arrays, maps and
+            // extensions.
+            ref key => seed.deserialize(key.clone()),
+        };
+        (res, self)
+    }
 }
 
 impl<'de> ValueBase<'de> for ValueRef<'de> {
@@ -1199,4 +1620,80 @@ impl<'de> ValueBase<'de> for ValueRef<'de> {
             other => Err(other),
         }
     }
+
+    #[inline]
+    fn int128_data(&self) -> Option<[u8; 16]> {
+        match *self {
+            Self::Binary(v) => v.try_into().ok(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn timestamp(&self) -> Option<Timestamp> {
+        match *self {
+            Self::Ext(TIMESTAMP_EXT_TYPE, data) => Timestamp::from_ext_data(data),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn deserialize_key<S>(self, seed: S) -> (Result<S::Value, Error>, Self)
+        where S: DeserializeSeed<'de>
+    {
+        (seed.deserialize(self.clone()), self)
+    }
+}
+
+/// Iterates over the entries of a borrowed map as pairs of references.
+type EntryRefs<'de> = fn(&'de (Value, Value)) -> (&'de Value, &'de Value);
+
+#[inline]
+fn entry_refs(entry: &(Value, Value)) -> (&Value, &Value) {
+    (&entry.0, &entry.1)
+}
+
+impl<'de> ValueBase<'de> for &'de Value {
+    type Item = Self;
+    type Iter = Iter<'de, Value>;
+    type MapIter = Map<Iter<'de, (Value, Value)>, EntryRefs<'de>>;
+    type MapDeserializer = MapDeserializer<Self::MapIter, Self::Item>;
+
+    #[inline]
+    fn is_nil(&self) -> bool {
+        **self == Value::Nil
+    }
+
+    #[inline]
+    fn into_iter(self) -> Result<Self::Iter, Self::Item> {
+        match *self {
+            Value::Array(ref v) => Ok(v.iter()),
+            ref other => Err(other),
+        }
+    }
+
+    #[inline]
+    fn into_map_iter(self) -> Result<Self::MapIter, Self::Item> {
+        match *self {
+            Value::Map(ref v) => Ok(v.iter().map(entry_refs as EntryRefs<'de>)),
+            ref other => Err(other),
+        }
+    }
+
+    #[inline]
+    fn int128_data(&self) -> Option<[u8; 16]> {
+        (**self).int128_data()
+    }
+
+    #[inline]
+    fn timestamp(&self) -> Option<Timestamp> {
+        self.as_timestamp()
+    }
+
+    #[inline]
+    fn deserialize_key<S>(self, seed: S) -> (Result<S::Value, Error>, Self)
+        where S: DeserializeSeed<'de>
+    {
+        (seed.deserialize(self), self)
+    }
 }
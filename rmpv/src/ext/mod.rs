@@ -5,15 +5,83 @@ use serde::de::Unexpected;
 
 use crate::{IntPriv, Integer, Value, ValueRef};
 
-pub use self::de::{deserialize_from, from_value, EnumRefDeserializer};
-pub use self::se::to_value;
+pub use self::de::{deserialize_from, from_value, from_value_ref, EnumRefDeserializer};
+pub use self::registry::ExtRegistry;
+pub use self::se::{to_value, to_value_with, ToValueConfig};
 
+#[cfg(feature = "chrono")]
+pub mod datetime;
 mod de;
+mod registry;
 mod se;
 
+/// The name of the struct serde serializes a `SystemTime` as, which maps to a timestamp.
+const SYSTEM_TIME_STRUCT_NAME: &str = "SystemTime";
+
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     Syntax(String),
+    /// The enclosed error occurred at the given location within the value, such as
+    /// `items[3].meta.created_at`.
+    AtPath {
+        /// The trail of struct fields, map keys and sequence indices leading to the failure.
+        path: String,
+        /// The error that occurred at that location.
+        err: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Returns the location of the failure within the value, if it is not at its root.
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        match *self {
+            Self::Syntax(..) => None,
+            Self::AtPath { ref path, .. } => Some(path),
+        }
+    }
+
+    /// Prepends a path segment to the location of this error, wrapping it in
+    /// [`Error::AtPath`] first if needed.
+    #[cold]
+    fn at(self, segment: Segment<'_>) -> Self {
+        let (path, err) = match self {
+            Self::AtPath { path, err } => (path, err),
+            err => (String::new(), Box::new(err)),
+        };
+
+        let mut segment = match segment {
+            Segment::Name(name) => name.to_owned(),
+            Segment::Index(index) => format!("[{index}]"),
+            Segment::Key(key) => key.to_string(),
+        };
+        if !path.is_empty() && !path.starts_with('[') {
+            segment.push('.');
+        }
+        segment.push_str(&path);
+
+        Self::AtPath { path: segment, err }
+    }
+}
+
+/// A step from a value to one of its elements, as shown in error paths.
+#[derive(Clone, Copy)]
+enum Segment<'a> {
+    /// A struct field or a string key, shown as is.
+    Name(&'a str),
+    /// A sequence element, shown as `[index]`.
+    Index(usize),
+    /// Any other map key, shown with its `Display` implementation.
+    Key(&'a dyn Display),
+}
+
+impl<'a> Segment<'a> {
+    /// Returns the segment of a struct field, or of a sequence element if `name` is `None`.
+    #[inline]
+    fn field(name: Option<&'a str>, index: usize) -> Self {
+        name.map_or(Self::Index(index), Self::Name)
+    }
 }
 
 impl Display for Error {
@@ -21,6 +89,7 @@ impl Display for Error {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
         match *self {
             Self::Syntax(ref err) => write!(fmt, "error while decoding value: {err}"),
+            Self::AtPath { ref path, ref err } => write!(fmt, "{err} at {path}"),
         }
     }
 }
@@ -29,6 +98,9 @@ impl error::Error for Error {}
 
 trait ValueExt {
     fn unexpected(&self) -> Unexpected<'_>;
+
+    /// Returns the segment naming this value as a map key in error paths.
+    fn segment(&self) -> Segment<'_>;
 }
 
 impl ValueExt for Value {
@@ -50,9 +122,26 @@ impl ValueExt for Value {
             Self::Binary(ref v) => Unexpected::Bytes(v),
             Self::Array(..) => Unexpected::Seq,
             Self::Map(..) => Unexpected::Map,
-            Self::Ext(..) => Unexpected::Seq,
+            Self::Ext(..) | Self::Timestamp(..) => Unexpected::Seq,
         }
     }
+
+    #[cold]
+    fn segment(&self) -> Segment<'_> {
+        self.as_str().map_or(Segment::Key(self), Segment::Name)
+    }
+}
+
+impl ValueExt for &Value {
+    #[cold]
+    fn unexpected(&self) -> Unexpected<'_> {
+        (**self).unexpected()
+    }
+
+    #[cold]
+    fn segment(&self) -> Segment<'_> {
+        (**self).segment()
+    }
 }
 
 impl ValueExt for ValueRef<'_> {
@@ -77,4 +166,9 @@ impl ValueExt for ValueRef<'_> {
             ValueRef::Ext(..) => Unexpected::Seq,
         }
     }
+
+    #[cold]
+    fn segment(&self) -> Segment<'_> {
+        self.as_str().map_or(Segment::Key(self), Segment::Name)
+    }
 }
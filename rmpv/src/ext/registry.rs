@@ -0,0 +1,183 @@
+//! Custom extension types for serde conversions, see [`ExtRegistry`].
+
+use std::fmt::{self, Debug};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::visit::{walk_mut, ValueVisitorMut, Walk};
+use crate::Value;
+
+use super::se::to_value_ext;
+use super::{from_value, Error, ToValueConfig};
+
+type Encoder = Box<dyn Fn(Value) -> Result<Vec<u8>, Error> + Send + Sync>;
+type Decoder = Box<dyn Fn(&[u8]) -> Result<Value, Error> + Send + Sync>;
+
+struct ExtHook {
+    name: &'static str,
+    tag: i8,
+    encode: Encoder,
+    decode: Decoder,
+}
+
+/// Associates extension tags with Rust types, for converting them to and from values.
+///
+/// A type is identified by the name it passes to `serialize_newtype_struct`, which is the name of
+/// the struct for `#[derive(Serialize)] struct UserId(u64)`. Serializing it with
+/// [`to_value`](Self::to_value) passes its content to the encode closure, and stores the bytes it
+/// returns as an extension with the registered tag.
+///
+/// Conversely, [`from_value`](Self::from_value) replaces every extension with a registered tag by
+/// the value the decode closure returns for its data, before deserializing the result. Newtype
+/// structs are deserialized from their content, so the decoded value only needs to match it.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::ext::{Error, ExtRegistry};
+/// use rmpv::Value;
+/// use serde::{Serialize, Serializer};
+///
+/// struct UserId(u64);
+///
+/// impl Serialize for UserId {
+///     fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+///         s.serialize_newtype_struct("UserId", &self.0)
+///     }
+/// }
+///
+/// let registry = ExtRegistry::new().register(
+///     "UserId",
+///     2,
+///     |val| val.as_u64().map(|id| id.to_be_bytes().to_vec()).ok_or_else(|| Error::Syntax("expected u64".into())),
+///     |data| data.try_into().map(|buf| Value::from(u64::from_be_bytes(buf))).map_err(|_| Error::Syntax("expected 8 bytes".into())),
+/// );
+///
+/// let val = registry.to_value(&vec![UserId(42)]).unwrap();
+/// assert_eq!(Value::Array(vec![Value::Ext(2, vec![0, 0, 0, 0, 0, 0, 0, 42])]), val);
+///
+/// let ids: Vec<u64> = registry.from_value(val).unwrap();
+/// assert_eq!(vec![42], ids);
+/// ```
+#[derive(Default)]
+pub struct ExtRegistry {
+    hooks: Vec<ExtHook>,
+}
+
+impl ExtRegistry {
+    /// Creates a registry without any extension type.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the newtype struct `name` as the extension `tag`, replacing any extension
+    /// registered with the same name or tag.
+    ///
+    /// `encode` turns the content of the struct into the data of the extension, and `decode`
+    /// turns the data back into a value that deserializes as the content.
+    #[must_use]
+    pub fn register<E, D>(mut self, name: &'static str, tag: i8, encode: E, decode: D) -> Self
+        where E: Fn(Value) -> Result<Vec<u8>, Error> + Send + Sync + 'static,
+              D: Fn(&[u8]) -> Result<Value, Error> + Send + Sync + 'static
+    {
+        self.hooks.retain(|hook| hook.name != name && hook.tag != tag);
+        self.hooks.push(ExtHook {
+            name,
+            tag,
+            encode: Box::new(encode),
+            decode: Box::new(decode),
+        });
+        self
+    }
+
+    /// Converts a `T` into a value, like [`to_value`](super::to_value), encoding the registered
+    /// types as extensions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error`] if serialization or an encode closure fails.
+    #[inline]
+    pub fn to_value<T>(&self, value: &T) -> Result<Value, Error>
+        where T: Serialize + ?Sized
+    {
+        self.to_value_with(value, ToValueConfig::new())
+    }
+
+    /// Converts a `T` into a value, like [`to_value_with`](super::to_value_with), encoding the
+    /// registered types as extensions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error`] if serialization or an encode closure fails.
+    #[inline]
+    pub fn to_value_with<T>(&self, value: &T, config: ToValueConfig) -> Result<Value, Error>
+        where T: Serialize + ?Sized
+    {
+        to_value_ext(value, config, self)
+    }
+
+    /// Deserializes a `T` from `val`, like [`from_value`](super::from_value), after decoding the
+    /// extensions with a registered tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error`] if a decode closure or deserialization fails.
+    pub fn from_value<T>(&self, mut val: Value) -> Result<T, Error>
+        where T: DeserializeOwned
+    {
+        let mut decoder = ExtDecoder { registry: self, err: None };
+        walk_mut(&mut val, &mut decoder);
+        match decoder.err {
+            Some(err) => Err(err),
+            None => from_value(val),
+        }
+    }
+
+    /// Returns the tag and the encode closure registered for the newtype struct `name`.
+    pub(crate) fn encoder(&self, name: &str) -> Option<(i8, &Encoder)> {
+        self.hooks.iter()
+            .find(|hook| hook.name == name)
+            .map(|hook| (hook.tag, &hook.encode))
+    }
+}
+
+impl Debug for ExtRegistry {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_map()
+            .entries(self.hooks.iter().map(|hook| (hook.name, hook.tag)))
+            .finish()
+    }
+}
+
+/// Replaces the extensions with a registered tag by their decoded values.
+struct ExtDecoder<'a> {
+    registry: &'a ExtRegistry,
+    err: Option<Error>,
+}
+
+impl ValueVisitorMut for ExtDecoder<'_> {
+    fn visit_mut(&mut self, val: &mut Value) -> Walk {
+        let (tag, data) = match val.as_ext() {
+            Some(ext) => ext,
+            None => return Walk::Continue,
+        };
+        let hook = match self.registry.hooks.iter().find(|hook| hook.tag == tag) {
+            Some(hook) => hook,
+            None => return Walk::SkipChildren,
+        };
+
+        match (hook.decode)(data) {
+            Ok(decoded) => {
+                *val = decoded;
+                Walk::SkipChildren
+            }
+            Err(err) => {
+                self.err = Some(err);
+                Walk::Stop
+            }
+        }
+    }
+}
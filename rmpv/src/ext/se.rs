@@ -6,9 +6,9 @@ use serde::ser::{
 use serde::Serialize;
 use serde_bytes::Bytes;
 
-use crate::{IntPriv, Integer, Value};
+use crate::{IntPriv, Integer, Timestamp, Value, ValueRef};
 
-use super::Error;
+use super::{Error, ExtRegistry, Segment, ValueExt, SYSTEM_TIME_STRUCT_NAME};
 use crate::MSGPACK_EXT_STRUCT_NAME;
 
 impl Serialize for Value {
@@ -43,8 +43,49 @@ impl Serialize for Value {
                 }
                 state.end()
             }
-            Self::Ext(ty, ref buf) => {
-                let value = (ty, Bytes::new(&buf[..]));
+            Self::Ext(..) | Self::Timestamp(..) => {
+                let (ty, buf) = self.as_ext().unwrap_or_default();
+                let value = (ty, Bytes::new(buf));
+                s.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &value)
+            }
+        }
+    }
+}
+
+impl Serialize for ValueRef<'_> {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        match *self {
+            Self::Nil => s.serialize_unit(),
+            Self::Boolean(v) => s.serialize_bool(v),
+            Self::Integer(Integer { n }) => match n {
+                IntPriv::PosInt(n) => s.serialize_u64(n),
+                IntPriv::NegInt(n) => s.serialize_i64(n),
+            },
+            Self::F32(v) => s.serialize_f32(v),
+            Self::F64(v) => s.serialize_f64(v),
+            Self::String(ref v) => match v.s {
+                Ok(v) => s.serialize_str(v),
+                Err((v, _)) => Bytes::new(v).serialize(s),
+            },
+            Self::Binary(v) => Bytes::new(v).serialize(s),
+            Self::Array(ref array) => {
+                let mut state = s.serialize_seq(Some(array.len()))?;
+                for item in array {
+                    state.serialize_element(item)?;
+                }
+                state.end()
+            }
+            Self::Map(ref map) => {
+                let mut state = s.serialize_map(Some(map.len()))?;
+                for (key, val) in map {
+                    state.serialize_entry(key, val)?;
+                }
+                state.end()
+            }
+            Self::Ext(ty, buf) => {
+                let value = (ty, Bytes::new(buf));
                 s.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &value)
             }
         }
@@ -58,12 +99,88 @@ impl ser::Error for Error {
     }
 }
 
-struct Serializer;
+/// Controls how [`to_value_with`] represents structs and enum variants.
+///
+/// By default, structs become arrays of their fields and enum variants become
+/// `[index, [fields..]]` arrays, as with [`to_value`]. The named forms match what rmp-serde writes
+/// with `with_struct_map` and what MessagePack libraries of other languages usually expect.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::ext::{to_value_with, ToValueConfig};
+/// use rmpv::Value;
+///
+/// let config = ToValueConfig::new().struct_map(true).named_variants(true);
+///
+/// // `Option::Some` is not an enum variant for serde, so use `Result` instead.
+/// let val = to_value_with(Ok::<u32, ()>(42), config).unwrap();
+/// assert_eq!(Value::Map(vec![(Value::from("Ok"), Value::from(42))]), val);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ToValueConfig {
+    struct_map: bool,
+    named_variants: bool,
+}
+
+impl ToValueConfig {
+    /// Creates a config producing the same values as [`to_value`].
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            struct_map: false,
+            named_variants: false,
+        }
+    }
+
+    /// Serializes structs and struct variants as maps from field names to values, instead of
+    /// arrays of values.
+    #[inline]
+    #[must_use]
+    pub const fn struct_map(mut self, enabled: bool) -> Self {
+        self.struct_map = enabled;
+        self
+    }
+
+    /// Serializes enum variants tagged by name instead of by index.
+    ///
+    /// Unit variants become their name, and the others a single-entry map from their name to
+    /// their data: the value of a newtype variant, or the fields of a tuple or struct variant.
+    ///
+    /// [`from_value`](super::from_value) only reads back variants tagged by index.
+    #[inline]
+    #[must_use]
+    pub const fn named_variants(mut self, enabled: bool) -> Self {
+        self.named_variants = enabled;
+        self
+    }
+
+    /// Tags the data of an enum variant, according to `named_variants`.
+    fn variant(self, idx: u32, variant: &'static str, data: Value) -> Value {
+        if self.named_variants {
+            Value::Map(vec![(Value::from(variant), data)])
+        } else {
+            Value::Array(vec![Value::from(idx), data])
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Serializer<'a> {
+    config: ToValueConfig,
+    ext: Option<&'a ExtRegistry>,
+}
 
 /// Convert a `T` into `rmpv::Value` which is an enum that can represent any valid MessagePack data.
 ///
 /// This conversion can fail if `T`'s implementation of `Serialize` decides to fail.
 ///
+/// A `SystemTime` becomes a [`Value::Timestamp`], which [`from_value`](super::from_value) turns
+/// back into a `SystemTime`. A `Duration` stays a struct of its seconds and nanoseconds, as it is
+/// a span rather than an instant. chrono types need `#[serde(with = "rmpv::ext::datetime")]`, see
+/// the `chrono` feature.
+///
 /// ```rust
 /// # use rmpv::Value;
 ///
@@ -73,20 +190,53 @@ struct Serializer;
 /// ```
 #[inline]
 pub fn to_value<T: Serialize>(value: T) -> Result<Value, Error> {
-    value.serialize(Serializer)
+    to_value_with(value, ToValueConfig::new())
+}
+
+/// Convert a `T` into `rmpv::Value`, representing structs and enums as set by `config`.
+///
+/// # Errors
+///
+/// Fails if `T`'s implementation of `Serialize` decides to fail.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use rmpv::ext::{to_value_with, ToValueConfig};
+/// use rmpv::Value;
+///
+/// let mut scores = BTreeMap::new();
+/// scores.insert("John", Err::<u32, &str>("absent"));
+///
+/// let config = ToValueConfig::new().named_variants(true);
+/// let val = to_value_with(&scores, config).unwrap();
+///
+/// assert_eq!(Value::from("absent"), val["John"]["Err"]);
+/// ```
+#[inline]
+pub fn to_value_with<T: Serialize>(value: T, config: ToValueConfig) -> Result<Value, Error> {
+    value.serialize(Serializer { config, ext: None })
 }
 
-impl ser::Serializer for Serializer {
+/// Serializes `value` with the extension types of `ext`, see [`ExtRegistry::to_value`].
+#[inline]
+pub(crate) fn to_value_ext<T: Serialize + ?Sized>(value: &T, config: ToValueConfig, ext: &ExtRegistry) -> Result<Value, Error> {
+    value.serialize(Serializer { config, ext: Some(ext) })
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
     type Ok = Value;
     type Error = Error;
 
-    type SerializeSeq = SerializeVec;
-    type SerializeTuple = SerializeVec;
-    type SerializeTupleStruct = SerializeVec;
-    type SerializeTupleVariant = SerializeTupleVariant;
-    type SerializeMap = DefaultSerializeMap;
-    type SerializeStruct = SerializeVec;
-    type SerializeStructVariant = SerializeStructVariant;
+    type SerializeSeq = SerializeVec<'a>;
+    type SerializeTuple = SerializeVec<'a>;
+    type SerializeTupleStruct = SerializeVec<'a>;
+    type SerializeTupleVariant = SerializeTupleVariant<'a>;
+    type SerializeMap = DefaultSerializeMap<'a>;
+    type SerializeStruct = SerializeStructFields<'a>;
+    type SerializeStructVariant = SerializeStructVariant<'a>;
 
     #[inline]
     fn serialize_bool(self, val: bool) -> Result<Self::Ok, Self::Error> {
@@ -113,6 +263,17 @@ impl ser::Serializer for Serializer {
         Ok(Value::from(val))
     }
 
+    /// Serializes integers out of the 64-bit range as 16-byte big-endian binaries, like rmp-serde.
+    fn serialize_i128(self, val: i128) -> Result<Self::Ok, Self::Error> {
+        if let Ok(val) = i64::try_from(val) {
+            self.serialize_i64(val)
+        } else if let Ok(val) = u64::try_from(val) {
+            self.serialize_u64(val)
+        } else {
+            self.serialize_bytes(&val.to_be_bytes())
+        }
+    }
+
     #[inline]
     fn serialize_u8(self, val: u8) -> Result<Self::Ok, Self::Error> {
         self.serialize_u64(u64::from(val))
@@ -133,6 +294,14 @@ impl ser::Serializer for Serializer {
         Ok(Value::from(val))
     }
 
+    /// Serializes integers out of the 64-bit range as 16-byte big-endian binaries, like rmp-serde.
+    fn serialize_u128(self, val: u128) -> Result<Self::Ok, Self::Error> {
+        match u64::try_from(val) {
+            Ok(val) => self.serialize_u64(val),
+            Err(..) => self.serialize_bytes(&val.to_be_bytes()),
+        }
+    }
+
     #[inline]
     fn serialize_f32(self, val: f32) -> Result<Self::Ok, Self::Error> {
         Ok(Value::F32(val))
@@ -171,7 +340,10 @@ impl ser::Serializer for Serializer {
     }
 
     #[inline]
-    fn serialize_unit_variant(self, _name: &'static str, idx: u32, _variant: &'static str) -> Result<Self::Ok, Self::Error> {
+    fn serialize_unit_variant(self, _name: &'static str, idx: u32, variant: &'static str) -> Result<Self::Ok, Self::Error> {
+        if self.config.named_variants {
+            return Ok(Value::from(variant));
+        }
         let vec = vec![
             Value::from(idx),
             Value::Array(Vec::new())
@@ -190,17 +362,21 @@ impl ser::Serializer for Serializer {
             return ext_se.value();
         }
 
-        to_value(value)
+        if let Some((tag, encode)) = self.ext.and_then(|ext| ext.encoder(name)) {
+            let val = value.serialize(self)?;
+            return Ok(Value::from_ext(tag, encode(val)?));
+        }
+
+        value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(self, _name: &'static str, idx: u32, _variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_newtype_variant<T: ?Sized>(self, _name: &'static str, idx: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
         where T: Serialize
     {
-        let vec = vec![
-            Value::from(idx),
-            Value::Array(vec![to_value(value)?]),
-        ];
-        Ok(Value::Array(vec))
+        let config = self.config;
+        let val = value.serialize(self)?;
+        let data = if config.named_variants { val } else { Value::Array(vec![val]) };
+        Ok(config.variant(idx, variant, data))
     }
 
     #[inline]
@@ -218,6 +394,7 @@ impl ser::Serializer for Serializer {
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let se = SerializeVec {
             vec: Vec::with_capacity(len.unwrap_or(0)),
+            se: self,
         };
         Ok(se)
     }
@@ -230,10 +407,12 @@ impl ser::Serializer for Serializer {
         self.serialize_tuple(len)
     }
 
-    fn serialize_tuple_variant(self, _name: &'static str, idx: u32, _variant: &'static str, len: usize) -> Result<Self::SerializeTupleVariant, Error> {
+    fn serialize_tuple_variant(self, _name: &'static str, idx: u32, variant: &'static str, len: usize) -> Result<Self::SerializeTupleVariant, Error> {
         let se = SerializeTupleVariant {
             idx,
+            variant,
             vec: Vec::with_capacity(len),
+            se: self,
         };
         Ok(se)
     }
@@ -242,20 +421,27 @@ impl ser::Serializer for Serializer {
         let se = DefaultSerializeMap {
             map: Vec::with_capacity(len.unwrap_or(0)),
             next_key: None,
+            se: self,
         };
         Ok(se)
     }
 
     #[inline]
     fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct, Error> {
-        self.serialize_tuple_struct(name, len)
+        let se = SerializeStructFields {
+            name,
+            fields: Vec::with_capacity(len),
+            se: self,
+        };
+        Ok(se)
     }
 
     #[inline]
-    fn serialize_struct_variant(self, _name: &'static str, idx: u32, _variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, Error> {
+    fn serialize_struct_variant(self, name: &'static str, idx: u32, variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, Error> {
         let se = SerializeStructVariant {
             idx,
-            vec: Vec::with_capacity(len),
+            variant,
+            fields: self.serialize_struct(name, len)?,
         };
         Ok(se)
     }
@@ -648,7 +834,7 @@ impl ExtFieldSerializer {
 
     fn value(self) -> Result<Value, Error> {
         match (self.tag, self.binary) {
-            (Some(tag), Some(binary)) => Ok(Value::Ext(tag, binary)),
+            (Some(tag), Some(binary)) => Ok(Value::from_ext(tag, binary)),
             (Some(_), None) => Err(<Error as ser::Error>::custom("expected i8 and bytes")),
             (None, Some(_)) => Err(<Error as ser::Error>::custom("expected i8 and bytes")),
             (None, None) => Err(<Error as ser::Error>::custom("expected i8 and bytes")),
@@ -657,31 +843,44 @@ impl ExtFieldSerializer {
 }
 
 #[doc(hidden)]
-pub struct SerializeVec {
+pub struct SerializeVec<'a> {
     vec: Vec<Value>,
+    se: Serializer<'a>,
 }
 
 /// Default implementation for tuple variant serialization. It packs given enums as a tuple of an
 /// index with a tuple of arguments.
 #[doc(hidden)]
-pub struct SerializeTupleVariant {
+pub struct SerializeTupleVariant<'a> {
     idx: u32,
+    variant: &'static str,
     vec: Vec<Value>,
+    se: Serializer<'a>,
 }
 
 #[doc(hidden)]
-pub struct DefaultSerializeMap {
+pub struct DefaultSerializeMap<'a> {
     map: Vec<(Value, Value)>,
     next_key: Option<Value>,
+    se: Serializer<'a>,
 }
 
+/// Collects the fields of a struct, packed as an array or a map depending on `struct_map`.
 #[doc(hidden)]
-pub struct SerializeStructVariant {
+pub struct SerializeStructFields<'a> {
+    name: &'static str,
+    fields: Vec<(&'static str, Value)>,
+    se: Serializer<'a>,
+}
+
+#[doc(hidden)]
+pub struct SerializeStructVariant<'a> {
     idx: u32,
-    vec: Vec<Value>,
+    variant: &'static str,
+    fields: SerializeStructFields<'a>,
 }
 
-impl SerializeSeq for SerializeVec {
+impl SerializeSeq for SerializeVec<'_> {
     type Ok = Value;
     type Error = Error;
 
@@ -689,7 +888,8 @@ impl SerializeSeq for SerializeVec {
     fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Error>
         where T: Serialize
     {
-        self.vec.push(to_value(value)?);
+        let index = self.vec.len();
+        self.vec.push(value.serialize(self.se).map_err(|err| err.at(Segment::Index(index)))?);
         Ok(())
     }
 
@@ -699,7 +899,7 @@ impl SerializeSeq for SerializeVec {
     }
 }
 
-impl SerializeTuple for SerializeVec {
+impl SerializeTuple for SerializeVec<'_> {
     type Ok = Value;
     type Error = Error;
 
@@ -716,7 +916,7 @@ impl SerializeTuple for SerializeVec {
     }
 }
 
-impl SerializeTupleStruct for SerializeVec {
+impl SerializeTupleStruct for SerializeVec<'_> {
     type Ok = Value;
     type Error = Error;
 
@@ -733,7 +933,7 @@ impl SerializeTupleStruct for SerializeVec {
     }
 }
 
-impl ser::SerializeTupleVariant for SerializeTupleVariant {
+impl ser::SerializeTupleVariant for SerializeTupleVariant<'_> {
     type Ok = Value;
     type Error = Error;
 
@@ -741,17 +941,18 @@ impl ser::SerializeTupleVariant for SerializeTupleVariant {
     fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Error>
         where T: Serialize
     {
-        self.vec.push(to_value(value)?);
+        let index = self.vec.len();
+        self.vec.push(value.serialize(self.se).map_err(|err| err.at(Segment::Index(index)))?);
         Ok(())
     }
 
     #[inline]
     fn end(self) -> Result<Value, Error> {
-        Ok(Value::Array(vec![Value::from(self.idx), Value::Array(self.vec)]))
+        Ok(self.se.config.variant(self.idx, self.variant, Value::Array(self.vec)))
     }
 }
 
-impl ser::SerializeMap for DefaultSerializeMap {
+impl ser::SerializeMap for DefaultSerializeMap<'_> {
     type Ok = Value;
     type Error = Error;
 
@@ -759,7 +960,8 @@ impl ser::SerializeMap for DefaultSerializeMap {
     fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Error>
         where T: Serialize
     {
-        self.next_key = Some(to_value(key)?);
+        let index = self.map.len();
+        self.next_key = Some(key.serialize(self.se).map_err(|err| err.at(Segment::Index(index)))?);
         Ok(())
     }
 
@@ -770,7 +972,8 @@ impl ser::SerializeMap for DefaultSerializeMap {
         // expected failure.
         let key = self.next_key.take()
             .expect("`serialize_value` called before `serialize_key`");
-        self.map.push((key, to_value(value)?));
+        let val = value.serialize(self.se).map_err(|err| err.at(key.segment()))?;
+        self.map.push((key, val));
         Ok(())
     }
 
@@ -780,40 +983,62 @@ impl ser::SerializeMap for DefaultSerializeMap {
     }
 }
 
-impl SerializeStruct for SerializeVec {
+impl SerializeStruct for SerializeStructFields<'_> {
     type Ok = Value;
     type Error = Error;
 
     #[inline]
-    fn serialize_field<T: ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<(), Error>
+    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
         where T: Serialize
     {
-        ser::SerializeSeq::serialize_element(self, value)
+        let val = value.serialize(self.se).map_err(|err| err.at(Segment::Name(key)))?;
+        self.fields.push((key, val));
+        Ok(())
     }
 
-    #[inline]
     fn end(self) -> Result<Value, Error> {
-        ser::SerializeSeq::end(self)
+        if self.name == SYSTEM_TIME_STRUCT_NAME {
+            if let Some(ts) = system_time(&self.fields) {
+                return Ok(Value::Timestamp(ts));
+            }
+        }
+
+        let val = if self.se.config.struct_map {
+            Value::Map(self.fields.into_iter().map(|(key, val)| (Value::from(key), val)).collect())
+        } else {
+            Value::Array(self.fields.into_iter().map(|(_, val)| val).collect())
+        };
+        Ok(val)
     }
 }
 
-impl ser::SerializeStructVariant for SerializeStructVariant {
+/// Returns the timestamp of the fields serde gives to a `SystemTime`.
+fn system_time(fields: &[(&'static str, Value)]) -> Option<Timestamp> {
+    match *fields {
+        [("secs_since_epoch", ref secs), ("nanos_since_epoch", ref nanos)] => {
+            let secs = i64::try_from(secs.as_u64()?).ok()?;
+            let nanos = u32::try_from(nanos.as_u64()?).ok()?;
+            Timestamp::new(secs, nanos)
+        }
+        _ => None,
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant<'_> {
     type Ok = Value;
     type Error = Error;
 
     #[inline]
-    fn serialize_field<T: ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<(), Error>
+    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
         where T: Serialize
     {
-        self.vec.push(to_value(value)?);
-        Ok(())
+        SerializeStruct::serialize_field(&mut self.fields, key, value)
     }
 
     #[inline]
     fn end(self) -> Result<Value, Error> {
-        Ok(Value::Array(vec![
-            Value::from(self.idx),
-            Value::Array(self.vec),
-        ]))
+        let config = self.fields.se.config;
+        let data = SerializeStruct::end(self.fields)?;
+        Ok(config.variant(self.idx, self.variant, data))
     }
 }
@@ -0,0 +1,429 @@
+//! Conversion of values into JSON text.
+//!
+//! MessagePack is a superset of JSON, so not every [`Value`] has a JSON representation. The strict
+//! [`Value::to_canonical_json`] reports binaries, extensions, non-string map keys and non-finite
+//! floats as [`Error`]s, while [`Value::to_debug_json`] substitutes readable stand-ins for them.
+//!
+//! With the `with-serde-json` feature enabled, values can also be converted from and into
+//! `serde_json::Value`, following the same rules: `Value::to_json_value` fails where
+//! `to_canonical_json` would, while `Value::to_json_value_lossy` uses the stand-ins of
+//! `to_debug_json`.
+
+use std::error;
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::{IntPriv, Value, TIMESTAMP_EXT_TYPE};
+
+/// The largest integer magnitude that survives a round-trip through an IEEE 754 double.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// This type represents all possible errors that can occur when converting a value into JSON.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// Binary data has no JSON representation.
+    Binary,
+    /// Extension types have no JSON representation. Contains the extension type.
+    Ext(i8),
+    /// The string is not valid UTF-8.
+    InvalidUtf8,
+    /// JSON object keys must be UTF-8 strings.
+    NonStringKey,
+    /// The map contains the same key more than once.
+    DuplicateKey(String),
+    /// NaN and infinities are not representable in JSON.
+    NonFiniteFloat,
+    /// The integer can not be represented exactly as an IEEE 754 double.
+    IntegerOutOfRange,
+}
+
+impl error::Error for Error {}
+
+impl Display for Error {
+    #[cold]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::Binary => fmt.write_str("binary data can not be represented in JSON"),
+            Self::Ext(ty) => write!(fmt, "extension type {ty} can not be represented in JSON"),
+            Self::InvalidUtf8 => fmt.write_str("string is not valid UTF-8"),
+            Self::NonStringKey => fmt.write_str("map key is not a string"),
+            Self::DuplicateKey(ref key) => write!(fmt, "duplicate map key {key:?}"),
+            Self::NonFiniteFloat => fmt.write_str("NaN and infinite floats can not be represented in JSON"),
+            Self::IntegerOutOfRange => fmt.write_str("integer is out of the IEEE 754 safe integer range"),
+        }
+    }
+}
+
+/// Writes the RFC 8785 canonical JSON form of `val` into `out`.
+pub(crate) fn write_canonical(out: &mut String, val: &Value) -> Result<(), Error> {
+    match *val {
+        Value::Nil => out.push_str("null"),
+        Value::Boolean(true) => out.push_str("true"),
+        Value::Boolean(false) => out.push_str("false"),
+        Value::Integer(ref val) => {
+            let safe = match val.n {
+                IntPriv::PosInt(n) => n <= MAX_SAFE_INTEGER,
+                IntPriv::NegInt(n) => n.unsigned_abs() <= MAX_SAFE_INTEGER,
+            };
+            if !safe {
+                return Err(Error::IntegerOutOfRange);
+            }
+            write!(out, "{val}").unwrap();
+        }
+        Value::F32(val) => {
+            if !val.is_finite() {
+                return Err(Error::NonFiniteFloat);
+            }
+            write_es_number(out, &format!("{val:e}"));
+        }
+        Value::F64(val) => {
+            if !val.is_finite() {
+                return Err(Error::NonFiniteFloat);
+            }
+            write_es_number(out, &format!("{val:e}"));
+        }
+        Value::String(ref val) => {
+            write_json_str(out, val.as_str().ok_or(Error::InvalidUtf8)?);
+        }
+        Value::Binary(..) => return Err(Error::Binary),
+        Value::Ext(ty, ..) => return Err(Error::Ext(ty)),
+        Value::Timestamp(..) => return Err(Error::Ext(TIMESTAMP_EXT_TYPE)),
+        Value::Array(ref vec) => {
+            out.push('[');
+            for (idx, val) in vec.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_canonical(out, val)?;
+            }
+            out.push(']');
+        }
+        Value::Map(ref map) => {
+            let mut entries = Vec::with_capacity(map.len());
+            for (key, val) in map {
+                let key = match *key {
+                    Value::String(ref key) => key.as_str().ok_or(Error::InvalidUtf8)?,
+                    _ => return Err(Error::NonStringKey),
+                };
+                entries.push((key, val));
+            }
+
+            // RFC 8785 orders members by the UTF-16 code units of their names.
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            if let Some(w) = entries.windows(2).find(|w| w[0].0 == w[1].0) {
+                return Err(Error::DuplicateKey(w[0].0.to_owned()));
+            }
+
+            out.push('{');
+            for (idx, (key, val)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_json_str(out, key);
+                out.push(':');
+                write_canonical(out, val)?;
+            }
+            out.push('}');
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a best-effort JSON form of `val` into `out`, never failing.
+///
+/// See [`Value::to_debug_json`] for the representation of non-JSON types.
+pub(crate) fn write_debug(out: &mut String, val: &Value) {
+    write_debug_indented(out, val, "", 0);
+}
+
+/// Writes the form of [`write_debug`] with every nested value on its own line, prefixed by
+/// `indent` repeated once per level, or all on one line if `indent` is empty.
+///
+/// `depth` is the level of `val` itself.
+pub(crate) fn write_debug_indented(out: &mut String, val: &Value, indent: &str, depth: usize) {
+    match *val {
+        Value::Nil => out.push_str("null"),
+        Value::Boolean(true) => out.push_str("true"),
+        Value::Boolean(false) => out.push_str("false"),
+        Value::Integer(ref val) => write!(out, "{val}").unwrap(),
+        Value::F32(val) if val.is_finite() => write_es_number(out, &format!("{val:e}")),
+        Value::F64(val) if val.is_finite() => write_es_number(out, &format!("{val:e}")),
+        Value::F32(val) => write_json_str(out, &val.to_string()),
+        Value::F64(val) => write_json_str(out, &val.to_string()),
+        Value::String(ref val) => write_json_str(out, &String::from_utf8_lossy(val.as_bytes())),
+        Value::Binary(ref val) => {
+            out.push('"');
+            write_base64(out, val);
+            out.push('"');
+        }
+        Value::Ext(..) | Value::Timestamp(..) => {
+            let (ty, data) = val.as_ext().unwrap_or_default();
+            if indent.is_empty() {
+                write!(out, "{{\"type\":{ty},\"data\":\"").unwrap();
+            } else {
+                write!(out, "{{\"type\": {ty}, \"data\": \"").unwrap();
+            }
+            write_hex(out, data);
+            out.push_str("\"}");
+        }
+        Value::Array(ref vec) => {
+            out.push('[');
+            for (idx, val) in vec.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_newline(out, indent, depth + 1);
+                write_debug_indented(out, val, indent, depth + 1);
+            }
+            if !vec.is_empty() {
+                write_newline(out, indent, depth);
+            }
+            out.push(']');
+        }
+        Value::Map(ref map) => {
+            out.push('{');
+            for (idx, (key, val)) in map.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_newline(out, indent, depth + 1);
+                match *key {
+                    Value::String(ref key) => write_json_str(out, &String::from_utf8_lossy(key.as_bytes())),
+                    ref key => {
+                        // Object keys must be strings, so other keys are quoted in their JSON form.
+                        let mut buf = String::new();
+                        write_debug(&mut buf, key);
+                        write_json_str(out, &buf);
+                    }
+                }
+                out.push(':');
+                if !indent.is_empty() {
+                    out.push(' ');
+                }
+                write_debug_indented(out, val, indent, depth + 1);
+            }
+            if !map.is_empty() {
+                write_newline(out, indent, depth);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Starts a new line at `depth` levels of `indent`, unless `indent` is empty.
+fn write_newline(out: &mut String, indent: &str, depth: usize) {
+    if !indent.is_empty() {
+        out.push('\n');
+        for _ in 0..depth {
+            out.push_str(indent);
+        }
+    }
+}
+
+/// Writes `data` as lowercase hex digits.
+fn write_hex(out: &mut String, data: &[u8]) {
+    for byte in data {
+        write!(out, "{byte:02x}").unwrap();
+    }
+}
+
+/// Writes `data` using the standard, padded base64 alphabet.
+fn write_base64(out: &mut String, data: &[u8]) {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    for chunk in data.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (idx, &b)| n | u32::from(b) << (16 - 8 * idx));
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                out.push(char::from(ALPHABET[(n >> (18 - 6 * idx)) as usize & 0x3f]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+}
+
+/// Writes a JSON string literal, escaping only what RFC 8785 requires.
+pub(crate) fn write_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{09}' => out.push_str("\\t"),
+            '\u{0a}' => out.push_str("\\n"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\u{0d}' => out.push_str("\\r"),
+            ch if ch < '\u{20}' => write!(out, "\\u{:04x}", ch as u32).unwrap(),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
+
+/// Formats a finite float following ECMAScript's `Number.prototype.toString`, as RFC 8785 requires.
+///
+/// Takes the shortest round-trip representation produced by the `{:e}` formatter, e.g. `-1.25e3`.
+fn write_es_number(out: &mut String, sci: &str) {
+    let (mantissa, exp) = sci.split_once('e').expect("`{:e}` output always has an exponent");
+    let exp: i32 = exp.parse().expect("`{:e}` output always has an integer exponent");
+    let (negative, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => (true, mantissa),
+        None => (false, mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|&ch| ch != '.').collect();
+    if digits.bytes().all(|b| b == b'0') {
+        // Both zeros, including the negative one, are serialized as "0".
+        out.push('0');
+        return;
+    }
+    if negative {
+        out.push('-');
+    }
+
+    let k = digits.len() as i32;
+    let n = exp + 1;
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat('0').take((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat('0').take(-n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        write!(out, "e{}{}", if n > 0 { '+' } else { '-' }, (n - 1).abs()).unwrap();
+    }
+}
+
+#[cfg(feature = "with-serde-json")]
+impl From<serde_json::Value> for Value {
+    /// Converts a JSON value, turning objects into maps with string keys.
+    ///
+    /// Numbers become integers whenever they fit into an `u64` or an `i64`, and `F64` otherwise.
+    fn from(val: serde_json::Value) -> Self {
+        match val {
+            serde_json::Value::Null => Self::Nil,
+            serde_json::Value::Bool(val) => Self::Boolean(val),
+            serde_json::Value::Number(n) => {
+                if let Some(n) = n.as_u64() {
+                    Self::from(n)
+                } else if let Some(n) = n.as_i64() {
+                    Self::from(n)
+                } else {
+                    n.as_f64().map_or(Self::Nil, Self::F64)
+                }
+            }
+            serde_json::Value::String(val) => Self::from(val),
+            serde_json::Value::Array(vec) => Self::Array(vec.into_iter().map(Self::from).collect()),
+            serde_json::Value::Object(map) => {
+                Self::Map(map.into_iter().map(|(key, val)| (Self::from(key), Self::from(val))).collect())
+            }
+        }
+    }
+}
+
+/// Converts `val` into JSON, failing on unrepresentable values. See [`Value::to_json_value`].
+#[cfg(feature = "with-serde-json")]
+pub(crate) fn to_value(val: &Value) -> Result<serde_json::Value, Error> {
+    let val = match *val {
+        Value::Nil => serde_json::Value::Null,
+        Value::Boolean(val) => serde_json::Value::Bool(val),
+        Value::Integer(ref val) => serde_json::Value::Number(json_int(val.n)),
+        Value::F32(val) => serde_json::Value::Number(json_f32(val).ok_or(Error::NonFiniteFloat)?),
+        Value::F64(val) => serde_json::Value::Number(serde_json::Number::from_f64(val).ok_or(Error::NonFiniteFloat)?),
+        Value::String(ref val) => serde_json::Value::String(val.as_str().ok_or(Error::InvalidUtf8)?.to_owned()),
+        Value::Binary(..) => return Err(Error::Binary),
+        Value::Ext(ty, ..) => return Err(Error::Ext(ty)),
+        Value::Timestamp(..) => return Err(Error::Ext(TIMESTAMP_EXT_TYPE)),
+        Value::Array(ref vec) => serde_json::Value::Array(vec.iter().map(to_value).collect::<Result<_, _>>()?),
+        Value::Map(ref map) => {
+            let mut obj = serde_json::Map::with_capacity(map.len());
+            for (key, val) in map {
+                let key = match *key {
+                    Value::String(ref key) => key.as_str().ok_or(Error::InvalidUtf8)?,
+                    _ => return Err(Error::NonStringKey),
+                };
+                if obj.contains_key(key) {
+                    return Err(Error::DuplicateKey(key.to_owned()));
+                }
+                obj.insert(key.to_owned(), to_value(val)?);
+            }
+            serde_json::Value::Object(obj)
+        }
+    };
+
+    Ok(val)
+}
+
+/// Converts `val` into JSON, never failing. See [`Value::to_json_value_lossy`].
+#[cfg(feature = "with-serde-json")]
+pub(crate) fn to_value_lossy(val: &Value) -> serde_json::Value {
+    match *val {
+        Value::Nil => serde_json::Value::Null,
+        Value::Boolean(val) => serde_json::Value::Bool(val),
+        Value::Integer(ref val) => serde_json::Value::Number(json_int(val.n)),
+        Value::F32(val) => json_f32(val)
+            .map_or_else(|| serde_json::Value::String(val.to_string()), serde_json::Value::Number),
+        Value::F64(val) => serde_json::Number::from_f64(val)
+            .map_or_else(|| serde_json::Value::String(val.to_string()), serde_json::Value::Number),
+        Value::String(ref val) => serde_json::Value::String(String::from_utf8_lossy(val.as_bytes()).into_owned()),
+        Value::Binary(ref val) => {
+            let mut out = String::new();
+            write_base64(&mut out, val);
+            serde_json::Value::String(out)
+        }
+        Value::Ext(..) | Value::Timestamp(..) => {
+            let (ty, data) = val.as_ext().unwrap_or_default();
+            let mut hex = String::new();
+            write_hex(&mut hex, data);
+
+            let mut obj = serde_json::Map::new();
+            obj.insert("type".into(), ty.into());
+            obj.insert("data".into(), hex.into());
+            serde_json::Value::Object(obj)
+        }
+        Value::Array(ref vec) => serde_json::Value::Array(vec.iter().map(to_value_lossy).collect()),
+        Value::Map(ref map) => {
+            let obj = map.iter().map(|(key, val)| {
+                let key = match *key {
+                    Value::String(ref key) => String::from_utf8_lossy(key.as_bytes()).into_owned(),
+                    ref key => {
+                        let mut buf = String::new();
+                        write_debug(&mut buf, key);
+                        buf
+                    }
+                };
+                (key, to_value_lossy(val))
+            });
+            serde_json::Value::Object(obj.collect())
+        }
+    }
+}
+
+#[cfg(feature = "with-serde-json")]
+fn json_int(n: IntPriv) -> serde_json::Number {
+    match n {
+        IntPriv::PosInt(n) => n.into(),
+        IntPriv::NegInt(n) => n.into(),
+    }
+}
+
+/// Widens a finite float through its shortest decimal form, so that `0.1f32` becomes `0.1`
+/// rather than `0.10000000149011612`, matching canonical JSON.
+#[cfg(feature = "with-serde-json")]
+fn json_f32(val: f32) -> Option<serde_json::Number> {
+    if !val.is_finite() {
+        return None;
+    }
+    serde_json::Number::from_f64(val.to_string().parse().ok()?)
+}
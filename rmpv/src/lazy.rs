@@ -0,0 +1,199 @@
+//! Decoding of only the parts of a value that are accessed, see [`LazyValue`].
+
+use std::io::{self, ErrorKind};
+
+use rmp::Marker;
+
+use crate::decode::{item_header, read_value, read_value_ref, Error};
+use crate::{Value, ValueRef};
+
+/// An encoded value whose nested values are only located when accessed.
+///
+/// Looking up a key or an index walks the headers of the preceding siblings to skip over them,
+/// without decoding or allocating anything. When only a few fields of a large document are read,
+/// this is much cheaper than decoding it all into a [`Value`].
+///
+/// The bytes are not validated upfront, so errors are reported by the accesses that reach them.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{LazyValue, Value};
+///
+/// // {"id": 42, "tags": ["a", "b"]}
+/// let buf = [0x82, 0xa2, b'i', b'd', 0x2a, 0xa4, b't', b'a', b'g', b's', 0x92, 0xa1, b'a', 0xa1, b'b'];
+///
+/// let doc = LazyValue::new(&buf).unwrap();
+/// let tags = doc.get("tags").unwrap().unwrap();
+/// assert_eq!(Some(2), tags.len());
+///
+/// let tag = tags.get_index(1).unwrap().unwrap();
+/// assert_eq!(Value::from("b"), tag.to_value().unwrap());
+/// assert_eq!(None, doc.get("missing").unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LazyValue<'a> {
+    /// Starts with the value, and may run past its end.
+    buf: &'a [u8],
+    /// The size of the marker and length.
+    header: usize,
+    len: usize,
+    /// The number of nested values per unit of `len`, as returned by `item_header`.
+    nested: u64,
+}
+
+impl<'a> LazyValue<'a> {
+    /// Wraps the value at the start of `buf`, parsing only its marker and length.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error`] if `buf` is too short to hold the marker and length.
+    pub fn new(buf: &'a [u8]) -> Result<Self, Error> {
+        if buf.is_empty() {
+            return Err(Error::InvalidMarkerRead(ErrorKind::UnexpectedEof.into()));
+        }
+        let (header, len, nested) = item_header(buf).ok_or_else(truncated)?;
+        Ok(Self { buf, header, len, nested })
+    }
+
+    /// Returns `true` if the value is an array.
+    #[inline]
+    #[must_use]
+    pub fn is_array(&self) -> bool {
+        self.nested == 1
+    }
+
+    /// Returns `true` if the value is a map.
+    #[inline]
+    #[must_use]
+    pub fn is_map(&self) -> bool {
+        self.nested == 2
+    }
+
+    /// Returns the number of elements of an array or entries of a map, or None for other values.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> Option<usize> {
+        if self.nested == 0 {
+            None
+        } else {
+            Some(self.len)
+        }
+    }
+
+    /// Returns `true` if the value is an empty array or map, or None for other values.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Returns the encoded bytes of the value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error`] if the value is truncated.
+    pub fn as_bytes(&self) -> Result<&'a [u8], Error> {
+        let size = value_size(self.buf)?;
+        Ok(&self.buf[..size])
+    }
+
+    /// Returns the value of the first entry with the given string key, if this is a map.
+    ///
+    /// Only the keys are compared, so the values of the preceding entries are skipped over.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error`] if the map is truncated before the entry is found.
+    pub fn get(&self, key: &str) -> Result<Option<Self>, Error> {
+        if !self.is_map() {
+            return Ok(None);
+        }
+
+        let mut rest = &self.buf[self.header..];
+        for _ in 0..self.len {
+            let (k, tail) = rest.split_at(value_size(rest)?);
+            let (val, tail) = tail.split_at(value_size(tail)?);
+            if str_data(k) == Some(key.as_bytes()) {
+                return Self::new(val).map(Some);
+            }
+            rest = tail;
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the element at the given index, if this is an array.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error`] if the array is truncated before the element.
+    pub fn get_index(&self, idx: usize) -> Result<Option<Self>, Error> {
+        if !self.is_array() || idx >= self.len {
+            return Ok(None);
+        }
+
+        let mut rest = &self.buf[self.header..];
+        for _ in 0..idx {
+            rest = &rest[value_size(rest)?..];
+        }
+
+        let size = value_size(rest)?;
+        Self::new(&rest[..size]).map(Some)
+    }
+
+    /// Decodes the whole value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error`] as [`read_value`] does.
+    pub fn to_value(&self) -> Result<Value, Error> {
+        read_value(&mut &self.buf[..])
+    }
+
+    /// Decodes the whole value, borrowing strings and binaries from the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error`] as [`read_value_ref`] does.
+    pub fn to_value_ref(&self) -> Result<ValueRef<'a>, Error> {
+        read_value_ref(&mut &self.buf[..])
+    }
+}
+
+#[cold]
+fn truncated() -> Error {
+    Error::InvalidDataRead(io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF"))
+}
+
+/// Returns the size in bytes of the value at the start of `buf`, including its nested values.
+fn value_size(buf: &[u8]) -> Result<usize, Error> {
+    let mut pos = 0usize;
+    let mut pending = 1u64;
+    while pending > 0 {
+        let (header, len, nested) = item_header(&buf[pos..]).ok_or_else(truncated)?;
+        pending -= 1;
+        if nested == 0 {
+            pos = pos.saturating_add(header).saturating_add(len);
+        } else {
+            pos += header;
+            pending += len as u64 * nested;
+        }
+        if pos > buf.len() {
+            return Err(truncated());
+        }
+    }
+
+    Ok(pos)
+}
+
+/// Returns the data of the string encoded in `buf`, or None if it is not a string.
+fn str_data(buf: &[u8]) -> Option<&[u8]> {
+    match Marker::from_u8(*buf.first()?) {
+        Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+            let (header, len, _) = item_header(buf)?;
+            buf.get(header..header + len)
+        }
+        _ => None,
+    }
+}
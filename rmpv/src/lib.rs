@@ -2,19 +2,66 @@
 #![forbid(unsafe_code)]
 
 use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
-use std::ops::Index;
+use std::mem;
+use std::ops::{Index, IndexMut};
+use std::slice;
 use std::str::Utf8Error;
 
+mod macros;
+
+mod cow;
+mod debug;
 pub mod decode;
+pub mod diff;
 pub mod encode;
+mod entry;
+pub mod json;
+mod lazy;
+mod map_index;
+pub mod schema;
+mod shared;
+pub mod stream;
+mod timestamp;
+mod visit;
+
+pub use crate::cow::CowValue;
+pub use crate::debug::DebugHex;
+pub use crate::entry::{Entry, OccupiedEntry, VacantEntry};
+pub use crate::lazy::LazyValue;
+pub use crate::map_index::MapIndex;
+pub use crate::shared::SharedValue;
+use crate::map_index::KeySet;
+pub use crate::stream::transform_stream;
+pub use crate::timestamp::{Timestamp, TIMESTAMP_EXT_TYPE};
+pub use crate::visit::{ValueVisitor, ValueVisitorMut, Walk};
 
 #[cfg(feature = "with-serde")]
 pub mod ext;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg(feature = "bumpalo")]
+mod arena;
+#[cfg(feature = "bumpalo")]
+pub use crate::arena::ArenaValue;
+
+#[cfg(feature = "bytes")]
+mod bytes_impl;
+
+#[cfg(feature = "chrono")]
+mod chrono_impl;
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+
+#[cfg(feature = "proptest")]
+mod proptest;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 enum IntPriv {
     /// Always non-less than zero.
     PosInt(u64),
@@ -25,6 +72,11 @@ enum IntPriv {
 /// Name of Serde newtype struct to Represent Msgpack's Ext
 /// Msgpack Ext: Ext(tag, binary)
 /// Serde data model: _ExtStruct((tag, binary))
+///
+/// `Value` and `ValueRef` serialize extensions and timestamps under this name, and recognize it
+/// when deserialized, so they survive a round trip through `rmp_serde` unchanged, including when
+/// nested in other types. See [`Value::from_ext`] for how timestamps are represented.
+///
 /// Example Serde impl for custom type:
 ///
 /// ```ignore
@@ -40,7 +92,9 @@ pub const MSGPACK_EXT_STRUCT_NAME: &str = "_ExtStruct";
 /// Represents a MessagePack integer, whether signed or unsigned.
 ///
 /// A `Value` or `ValueRef` that contains integer can be constructed using `From` trait.
-#[derive(Copy, Clone, PartialEq)]
+///
+/// Integers are ordered by their numeric value.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Integer {
     n: IntPriv,
 }
@@ -97,6 +151,25 @@ impl Integer {
     }
 }
 
+impl PartialOrd for Integer {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Integer {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.n, other.n) {
+            (IntPriv::PosInt(lhs), IntPriv::PosInt(rhs)) => lhs.cmp(&rhs),
+            (IntPriv::NegInt(lhs), IntPriv::NegInt(rhs)) => lhs.cmp(&rhs),
+            (IntPriv::NegInt(..), IntPriv::PosInt(..)) => Ordering::Less,
+            (IntPriv::PosInt(..), IntPriv::NegInt(..)) => Ordering::Greater,
+        }
+    }
+}
+
 impl Debug for Integer {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         Debug::fmt(&self.n, fmt)
@@ -214,7 +287,9 @@ impl From<isize> for Integer {
 /// it is possible to obtain an underlying bytes that were attempted to convert to a `String`. This
 /// may happen when trying to unpack strings that were decoded using older MessagePack spec with
 /// raw types instead of string/binary.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Strings are compared, ordered and hashed by their bytes, whether they are valid UTF-8 or not.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Utf8String {
     s: Result<String, (Vec<u8>, Utf8Error)>,
 }
@@ -255,7 +330,7 @@ impl Utf8String {
         }
     }
 
-    /// Returns a byte slice of this `Utf8String`'s contents.
+    /// Returns a byte slice of this `Utf8String`'s contents, whether they are valid UTF-8 or not.
     #[inline]
     #[must_use]
     pub fn as_bytes(&self) -> &[u8] {
@@ -282,6 +357,27 @@ impl Utf8String {
         }
     }
 
+    /// Converts a `Utf8String` into a string, replacing invalid UTF-8 sequences with
+    /// `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// Valid strings are returned without copying.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Utf8String;
+    ///
+    /// assert_eq!("le message", Utf8String::from("le message").into_string_lossy());
+    /// assert_eq!("a\u{FFFD}b", Utf8String::from(vec![b'a', 0xff, b'b']).into_string_lossy());
+    /// ```
+    #[must_use]
+    pub fn into_string_lossy(self) -> String {
+        match self.s {
+            Ok(s) => s,
+            Err((buf, _)) => String::from_utf8_lossy(&buf).into_owned(),
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn as_ref(&self) -> Utf8StringRef<'_> {
@@ -292,6 +388,34 @@ impl Utf8String {
     }
 }
 
+impl PartialOrd for Utf8String {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Utf8String {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl Hash for Utf8String {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl AsRef<[u8]> for Utf8String {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
 impl Display for Utf8String {
     #[cold]
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
@@ -316,6 +440,32 @@ impl From<&str> for Utf8String {
     }
 }
 
+/// Keeps the bytes as they are, remembering the error if they are not valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Utf8String;
+///
+/// assert_eq!(Some("ok"), Utf8String::from(b"ok".to_vec()).as_str());
+///
+/// let s = Utf8String::from(vec![0xc3, 0x28]);
+/// assert!(s.is_err());
+/// assert_eq!(&[0xc3, 0x28], s.as_bytes());
+/// ```
+impl From<Vec<u8>> for Utf8String {
+    #[inline]
+    fn from(val: Vec<u8>) -> Self {
+        match String::from_utf8(val) {
+            Ok(s) => Self { s: Ok(s) },
+            Err(err) => {
+                let e = err.utf8_error();
+                Self { s: Err((err.into_bytes(), e)) }
+            }
+        }
+    }
+}
+
 impl<'a> From<Cow<'a, str>> for Utf8String {
     #[inline]
     fn from(val: Cow<'a, str>) -> Self {
@@ -428,6 +578,11 @@ impl<'a> From<Utf8StringRef<'a>> for Utf8String {
 }
 
 /// Represents any valid MessagePack value.
+///
+/// Values are compared with `==` as their contents are, floats following IEEE 754: `NaN` differs
+/// from itself and `0.0` equals `-0.0`. [`Value::total_eq`] and [`Value::total_cmp`] compare
+/// floats by their bits instead, and [`TotalValue`] uses them to make values keys of maps and
+/// sets.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     /// Nil represents nil.
@@ -464,10 +619,41 @@ pub enum Value {
     /// Array represents a sequence of objects.
     Array(Vec<Value>),
     /// Map represents key-value pairs of objects.
+    ///
+    /// Entries keep their encoded order, and looking up a key scans them. For many lookups into
+    /// a large map, build a [`MapIndex`] once with [`Value::map_index`].
     Map(Vec<(Value, Value)>),
     /// Extended implements Extension interface: represents a tuple of type information and a byte
     /// array where type information is an integer whose meaning is defined by applications.
     Ext(i8, Vec<u8>),
+    /// A timestamp, encoded as the extension type `-1` reserved by the MessagePack specification.
+    ///
+    /// Decoders produce this variant instead of `Ext(-1, data)` whenever the data is a valid
+    /// timestamp.
+    Timestamp(Timestamp),
+}
+
+/// Specifies how [`Value::strip_exts`] replaces extension values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StripMode {
+    /// Replaces `Ext(ty, data)` with `Binary(data)`, dropping the type.
+    ToBinary,
+    /// Replaces `Ext(ty, data)` with `Nil`.
+    ToNil,
+    /// Replaces `Ext(ty, data)` with the two-element array `[ty, Binary(data)]`.
+    KeepTag,
+}
+
+/// Specifies how [`Value::merge`] resolves conflicts other than between two maps.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// The merged value replaces the existing one.
+    Replace,
+    /// Like `Replace`, except that the elements of a merged array are appended to an existing
+    /// array.
+    AppendArrays,
+    /// The existing value is kept, so that merging only adds missing map entries.
+    KeepExisting,
 }
 
 impl Value {
@@ -516,6 +702,7 @@ impl Value {
                 ValueRef::Map(val.iter().map(|(k, v)| (k.as_ref(), v.as_ref())).collect())
             }
             Self::Ext(ty, ref buf) => ValueRef::Ext(ty, buf.as_slice()),
+            Self::Timestamp(ref ts) => ValueRef::Ext(TIMESTAMP_EXT_TYPE, ts.as_ext_data()),
         }
     }
 
@@ -799,6 +986,100 @@ impl Value {
         }
     }
 
+    /// Converts a number or a numeric string to an i64, for data from loosely-typed producers.
+    ///
+    /// Floats are truncated toward zero, and values out of the range of i64 saturate to its
+    /// bounds. Strings are parsed after trimming surrounding whitespace, as an integer or failing
+    /// that as a float. Returns `None` for NaN and for any other value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!(Some(42), Value::from(42).as_i64_coerced());
+    /// assert_eq!(Some(-2), Value::F64(-2.9).as_i64_coerced());
+    /// assert_eq!(Some(i64::MAX), Value::from(u64::MAX).as_i64_coerced());
+    /// assert_eq!(Some(7), Value::from(" 7.5 ").as_i64_coerced());
+    ///
+    /// assert_eq!(None, Value::F64(f64::NAN).as_i64_coerced());
+    /// assert_eq!(None, Value::from("seven").as_i64_coerced());
+    /// ```
+    #[must_use]
+    pub fn as_i64_coerced(&self) -> Option<i64> {
+        match *self {
+            Self::Integer(ref n) => match n.n {
+                IntPriv::PosInt(n) => Some(i64::try_from(n).unwrap_or(i64::MAX)),
+                IntPriv::NegInt(n) => Some(n),
+            },
+            Self::String(ref s) => s.as_str().map(str::trim).and_then(|s| {
+                s.parse().ok().or_else(|| s.parse::<f64>().ok().and_then(|n| Value::F64(n).as_i64_coerced()))
+            }),
+            _ => match self.as_f64() {
+                Some(n) if !n.is_nan() => Some(n as i64),
+                _ => None,
+            },
+        }
+    }
+
+    /// Converts a number or a numeric string to a u64, for data from loosely-typed producers.
+    ///
+    /// Floats are truncated toward zero, and values out of the range of u64, including negative
+    /// ones, saturate to its bounds. Strings are parsed after trimming surrounding whitespace, as
+    /// an integer or failing that as a float. Returns `None` for NaN and for any other value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!(Some(42), Value::F32(42.7).as_u64_coerced());
+    /// assert_eq!(Some(0), Value::from(-42).as_u64_coerced());
+    /// assert_eq!(Some(u64::MAX), Value::F64(1e30).as_u64_coerced());
+    /// assert_eq!(Some(18446744073709551615), Value::from("18446744073709551615").as_u64_coerced());
+    ///
+    /// assert_eq!(None, Value::Nil.as_u64_coerced());
+    /// ```
+    #[must_use]
+    pub fn as_u64_coerced(&self) -> Option<u64> {
+        match *self {
+            Self::Integer(ref n) => match n.n {
+                IntPriv::PosInt(n) => Some(n),
+                IntPriv::NegInt(..) => Some(0),
+            },
+            Self::String(ref s) => s.as_str().map(str::trim).and_then(|s| {
+                s.parse().ok().or_else(|| s.parse::<f64>().ok().and_then(|n| Value::F64(n).as_u64_coerced()))
+            }),
+            _ => match self.as_f64() {
+                Some(n) if !n.is_nan() => Some(n as u64),
+                _ => None,
+            },
+        }
+    }
+
+    /// Converts a number or a numeric string to an f64, for data from loosely-typed producers.
+    ///
+    /// Integers beyond 2^53 are rounded to the nearest representable float. Strings are parsed
+    /// after trimming surrounding whitespace. Returns `None` for any other value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!(Some(42.0), Value::from(42).as_f64_coerced());
+    /// assert_eq!(Some(0.5), Value::from("0.5").as_f64_coerced());
+    ///
+    /// assert_eq!(None, Value::Boolean(true).as_f64_coerced());
+    /// ```
+    #[must_use]
+    pub fn as_f64_coerced(&self) -> Option<f64> {
+        match *self {
+            Self::String(ref s) => s.as_str().and_then(|s| s.trim().parse().ok()),
+            _ => self.as_f64(),
+        }
+    }
+
     /// If the `Value` is a String, returns the associated str.
     /// Returns None otherwise.
     ///
@@ -896,7 +1177,7 @@ impl Value {
         }
     }
 
-    /// If the `Value` is an Ext, returns the associated tuple with a ty and slice.
+    /// If the `Value` is an Array, returns a mutable reference to the associated vector.
     /// Returns None otherwise.
     ///
     /// # Examples
@@ -904,94 +1185,1683 @@ impl Value {
     /// ```
     /// use rmpv::Value;
     ///
-    /// assert_eq!(Some((42, &[1, 2, 3, 4, 5][..])), Value::Ext(42, vec![1, 2, 3, 4, 5]).as_ext());
+    /// let mut val = Value::Array(vec![Value::Nil]);
+    /// val.as_array_mut().unwrap().push(Value::from(1));
     ///
-    /// assert_eq!(None, Value::Boolean(true).as_ext());
+    /// assert_eq!(Value::Array(vec![Value::Nil, Value::from(1)]), val);
+    /// assert_eq!(None, Value::Nil.as_array_mut());
     /// ```
     #[inline]
     #[must_use]
-    pub fn as_ext(&self) -> Option<(i8, &[u8])> {
-        if let Self::Ext(ty, buf) = self {
-            Some((*ty, buf))
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        if let Self::Array(ref mut array) = *self {
+            Some(array)
         } else {
             None
         }
     }
-}
-
-static NIL: Value = Value::Nil;
-static NIL_REF: ValueRef<'static> = ValueRef::Nil;
-
-impl Index<usize> for Value {
-    type Output = Self;
-
-    fn index(&self, index: usize) -> &Self {
-        self.as_array().and_then(|v| v.get(index)).unwrap_or(&NIL)
-    }
-}
 
-impl Index<&str> for Value {
-    type Output = Self;
-    fn index(&self, index: &str) -> &Self {
-        if let Self::Map(ref map) = *self {
-            if let Some(found) = map.iter().find(|(key, _val)| {
-                if let Self::String(ref strval) = *key {
-                    if let Some(s) = strval.as_str() {
-                        if s == index { return true; }
-                    }
-                }
-                false
-            }) {
-                return &found.1;
-            }
+    /// If the `Value` is a Map, returns a mutable reference to the associated vector of key-value
+    /// tuples. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![]);
+    /// val.as_map_mut().unwrap().push((Value::from("k"), Value::from(1)));
+    ///
+    /// assert_eq!(Value::from(1), val["k"]);
+    /// assert_eq!(None, Value::Nil.as_map_mut());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_map_mut(&mut self) -> Option<&mut Vec<(Self, Self)>> {
+        if let Self::Map(ref mut map) = *self {
+            Some(map)
+        } else {
+            None
         }
-        &NIL
     }
-}
 
-impl From<bool> for Value {
+    /// Returns an iterator over the elements of an Array, which is empty for any other variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Array(vec![Value::from(1), Value::from(2)]);
+    ///
+    /// assert_eq!(3, val.members().filter_map(Value::as_u64).sum::<u64>());
+    /// assert_eq!(0, Value::from("a").members().count());
+    /// ```
     #[inline]
-    fn from(v: bool) -> Self {
-        Self::Boolean(v)
+    pub fn members(&self) -> slice::Iter<'_, Self> {
+        match *self {
+            Self::Array(ref array) => array.iter(),
+            _ => [].iter(),
+        }
     }
-}
 
-impl From<u8> for Value {
+    /// Returns an iterator over the keys and values of a Map, which is empty for any other
+    /// variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("a"), Value::from(1))]);
+    ///
+    /// for (key, val) in val.entries() {
+    ///     assert_eq!((Some("a"), Some(1)), (key.as_str(), val.as_u64()));
+    /// }
+    /// assert_eq!(0, Value::Nil.entries().count());
+    /// ```
     #[inline]
-    fn from(v: u8) -> Self {
-        Self::Integer(From::from(v))
+    pub fn entries(&self) -> Entries<'_> {
+        let entries = match *self {
+            Self::Map(ref map) => map.iter(),
+            _ => [].iter(),
+        };
+        Entries { entries }
     }
-}
 
-impl From<u16> for Value {
+    /// If the `Value` is an Array, returns the associated vector, consuming the value.
+    /// Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!(Some(vec![Value::Nil]), Value::Array(vec![Value::Nil]).into_array());
+    /// assert_eq!(None, Value::Nil.into_array());
+    /// ```
     #[inline]
-    fn from(v: u16) -> Self {
-        Self::Integer(From::from(v))
+    #[must_use]
+    pub fn into_array(self) -> Option<Vec<Self>> {
+        if let Self::Array(array) = self {
+            Some(array)
+        } else {
+            None
+        }
     }
-}
 
-impl From<u32> for Value {
+    /// If the `Value` is a Map, returns the associated vector of key-value tuples, consuming the
+    /// value. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("k"), Value::Nil)]);
+    ///
+    /// assert_eq!(Some(vec![(Value::from("k"), Value::Nil)]), val.into_map());
+    /// assert_eq!(None, Value::Nil.into_map());
+    /// ```
     #[inline]
-    fn from(v: u32) -> Self {
-        Self::Integer(From::from(v))
+    #[must_use]
+    pub fn into_map(self) -> Option<Vec<(Self, Self)>> {
+        if let Self::Map(map) = self {
+            Some(map)
+        } else {
+            None
+        }
     }
-}
 
-impl From<u64> for Value {
+    /// If the `Value` is an Ext, returns the associated tuple with a ty and slice.
+    /// Returns None otherwise.
+    ///
+    /// A `Timestamp` is an extension too, so its encoded form is returned with the type `-1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{Timestamp, Value};
+    ///
+    /// assert_eq!(Some((42, &[1, 2, 3, 4, 5][..])), Value::Ext(42, vec![1, 2, 3, 4, 5]).as_ext());
+    /// assert_eq!(Some((-1, &[0, 0, 0, 1][..])), Value::from(Timestamp::new(1, 0).unwrap()).as_ext());
+    ///
+    /// assert_eq!(None, Value::Boolean(true).as_ext());
+    /// ```
     #[inline]
-    fn from(v: u64) -> Self {
-        Self::Integer(From::from(v))
+    #[must_use]
+    pub fn as_ext(&self) -> Option<(i8, &[u8])> {
+        match *self {
+            Self::Ext(ty, ref buf) => Some((ty, buf)),
+            Self::Timestamp(ref ts) => Some((TIMESTAMP_EXT_TYPE, ts.as_ext_data())),
+            _ => None,
+        }
     }
-}
 
-impl From<usize> for Value {
+    /// If the `Value` is a Timestamp, returns it. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{Timestamp, Value};
+    ///
+    /// let ts = Timestamp::new(1, 0).unwrap();
+    /// assert_eq!(Some(ts), Value::from(ts).as_timestamp());
+    ///
+    /// assert_eq!(None, Value::Ext(42, vec![0, 0, 0, 1]).as_timestamp());
+    /// ```
     #[inline]
-    fn from(v: usize) -> Self {
-        Self::Integer(From::from(v))
+    #[must_use]
+    pub fn as_timestamp(&self) -> Option<Timestamp> {
+        if let Self::Timestamp(ts) = *self {
+            Some(ts)
+        } else {
+            None
+        }
     }
-}
 
-impl From<i8> for Value {
+    /// Creates an `Ext` value, or a `Timestamp` if the extension is a valid timestamp.
+    ///
+    /// Decoding, both with [`read_value`](decode::read_value) and through serde, represents
+    /// extensions the same way, so values created with this function are unchanged by a round
+    /// trip, whereas an `Ext` holding a valid timestamp comes back as a `Timestamp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{Timestamp, Value, TIMESTAMP_EXT_TYPE};
+    ///
+    /// assert_eq!(Value::Ext(5, vec![1]), Value::from_ext(5, vec![1]));
+    ///
+    /// let ts = Timestamp::new(1, 0).unwrap();
+    /// assert_eq!(Value::Timestamp(ts), Value::from_ext(TIMESTAMP_EXT_TYPE, vec![0, 0, 0, 1]));
+    /// ```
+    #[must_use]
+    pub fn from_ext(ty: i8, data: Vec<u8>) -> Self {
+        match Timestamp::from_ext_data(&data) {
+            Some(ts) if ty == TIMESTAMP_EXT_TYPE => Self::Timestamp(ts),
+            _ => Self::Ext(ty, data),
+        }
+    }
+
+    /// Returns the name of the variant of this `Value`, such as `"integer"` or `"map"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!("nil", Value::Nil.type_name());
+    /// assert_eq!("integer", Value::from(42).type_name());
+    /// assert_eq!("string", Value::from("le message").type_name());
+    /// ```
+    #[must_use]
+    pub const fn type_name(&self) -> &'static str {
+        match *self {
+            Self::Nil => "nil",
+            Self::Boolean(..) => "bool",
+            Self::Integer(..) => "integer",
+            Self::F32(..) => "f32",
+            Self::F64(..) => "f64",
+            Self::String(..) => "string",
+            Self::Binary(..) => "binary",
+            Self::Array(..) => "array",
+            Self::Map(..) => "map",
+            Self::Ext(..) => "ext",
+            Self::Timestamp(..) => "timestamp",
+        }
+    }
+
+    /// Returns the position of the variant in declaration order, which orders values of different
+    /// variants.
+    const fn variant_rank(&self) -> u8 {
+        match *self {
+            Self::Nil => 0,
+            Self::Boolean(..) => 1,
+            Self::Integer(..) => 2,
+            Self::F32(..) => 3,
+            Self::F64(..) => 4,
+            Self::String(..) => 5,
+            Self::Binary(..) => 6,
+            Self::Array(..) => 7,
+            Self::Map(..) => 8,
+            Self::Ext(..) => 9,
+            Self::Timestamp(..) => 10,
+        }
+    }
+
+    /// Returns `true` if both values have the same variant and the same contents, comparing floats
+    /// by their bits.
+    ///
+    /// Unlike `==`, which follows IEEE 754 for floats, this is an equivalence relation: `NaN`
+    /// equals itself when the bits match, and `0.0` differs from `-0.0`. It agrees with
+    /// [`total_cmp`](Self::total_cmp).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_ne!(Value::from(f64::NAN), Value::from(f64::NAN));
+    /// assert!(Value::from(f64::NAN).total_eq(&Value::from(f64::NAN)));
+    ///
+    /// assert_eq!(Value::from(0.0), Value::from(-0.0));
+    /// assert!(!Value::from(0.0).total_eq(&Value::from(-0.0)));
+    /// ```
+    #[must_use]
+    pub fn total_eq(&self, other: &Self) -> bool {
+        self.total_cmp(other) == Ordering::Equal
+    }
+
+    /// Returns the ordering between `self` and `other` in a total order of all values.
+    ///
+    /// Values of different variants are ordered by variant, in declaration order. In particular
+    /// `F32(1.0)`, `F64(1.0)` and `Integer(1)` are three distinct values, and every `F32` sorts
+    /// before every `F64`. Within a variant:
+    ///
+    /// - integers are ordered by their numeric value;
+    /// - floats follow the IEEE 754 total order of [`f64::total_cmp`], that is
+    ///   `-NaN < -inf < ... < -0.0 < 0.0 < ... < inf < NaN`;
+    /// - strings, binaries and extension data are ordered by their bytes, and extensions by their
+    ///   type first;
+    /// - arrays and maps are ordered lexicographically, maps by their entries in order, without
+    ///   sorting the keys.
+    ///
+    /// Wrap values into [`TotalValue`] to use this order, and the matching hash, in collections.
+    #[must_use]
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Nil, Self::Nil) => Ordering::Equal,
+            (Self::Boolean(lhs), Self::Boolean(rhs)) => lhs.cmp(rhs),
+            (Self::Integer(lhs), Self::Integer(rhs)) => lhs.cmp(rhs),
+            (Self::F32(lhs), Self::F32(rhs)) => lhs.total_cmp(rhs),
+            (Self::F64(lhs), Self::F64(rhs)) => lhs.total_cmp(rhs),
+            (Self::String(lhs), Self::String(rhs)) => lhs.cmp(rhs),
+            (Self::Binary(lhs), Self::Binary(rhs)) => lhs.cmp(rhs),
+            (Self::Array(lhs), Self::Array(rhs)) => total_cmp_slices(lhs, rhs, Self::total_cmp),
+            (Self::Map(lhs), Self::Map(rhs)) => total_cmp_slices(lhs, rhs, |(lhs_key, lhs_val), (rhs_key, rhs_val)| {
+                lhs_key.total_cmp(rhs_key).then_with(|| lhs_val.total_cmp(rhs_val))
+            }),
+            (Self::Ext(lhs_ty, lhs), Self::Ext(rhs_ty, rhs)) => (lhs_ty, lhs).cmp(&(rhs_ty, rhs)),
+            (Self::Timestamp(lhs), Self::Timestamp(rhs)) => lhs.cmp(rhs),
+            _ => self.variant_rank().cmp(&other.variant_rank()),
+        }
+    }
+
+    /// Feeds the value into `state`, consistently with [`total_eq`](Self::total_eq).
+    pub(crate) fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.variant_rank().hash(state);
+        match *self {
+            Self::Nil => {}
+            Self::Boolean(val) => val.hash(state),
+            Self::Integer(val) => val.hash(state),
+            Self::F32(val) => val.to_bits().hash(state),
+            Self::F64(val) => val.to_bits().hash(state),
+            Self::String(ref val) => val.hash(state),
+            Self::Binary(ref val) => val.hash(state),
+            Self::Array(ref vec) => {
+                vec.len().hash(state);
+                for val in vec {
+                    val.total_hash(state);
+                }
+            }
+            Self::Map(ref map) => {
+                map.len().hash(state);
+                for (key, val) in map {
+                    key.total_hash(state);
+                    val.total_hash(state);
+                }
+            }
+            Self::Ext(ty, ref data) => {
+                ty.hash(state);
+                data.hash(state);
+            }
+            Self::Timestamp(val) => val.hash(state),
+        }
+    }
+
+    /// If the `Value` is a non-empty Map whose values all share the same variant, returns the
+    /// [`type_name`](Self::type_name) of that variant. Returns None otherwise.
+    ///
+    /// Only the variants are compared, so for example arrays of different element types count
+    /// as the same type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("a"), Value::from(1)),
+    ///     (Value::from("b"), Value::from(2)),
+    /// ]);
+    /// assert_eq!(Some("integer"), val.map_value_type());
+    ///
+    /// assert_eq!(None, Value::Map(vec![]).map_value_type());
+    /// assert_eq!(None, Value::from(42).map_value_type());
+    /// ```
+    #[must_use]
+    pub fn map_value_type(&self) -> Option<&'static str> {
+        let mut values = self.as_map()?.iter().map(|(_, val)| val.type_name());
+        let first = values.next()?;
+        values.all(|ty| ty == first).then_some(first)
+    }
+
+    /// Returns the string form of a scalar `Value`, for display purposes.
+    ///
+    /// Strings are returned as-is, numbers are formatted using their `Display` implementation
+    /// and booleans become `"true"` or `"false"`. Returns `None` for nil, containers, binaries,
+    /// extensions and strings that are not valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!(Some("42".into()), Value::from(42).to_display_string());
+    /// assert_eq!(Some("le message".into()), Value::from("le message").to_display_string());
+    ///
+    /// assert_eq!(None, Value::Nil.to_display_string());
+    /// assert_eq!(None, Value::Array(vec![]).to_display_string());
+    /// ```
+    #[must_use]
+    pub fn to_display_string(&self) -> Option<String> {
+        match *self {
+            Self::Boolean(val) => Some(val.to_string()),
+            Self::Integer(ref val) => Some(val.to_string()),
+            Self::F32(val) => Some(val.to_string()),
+            Self::F64(val) => Some(val.to_string()),
+            Self::String(ref val) => val.as_str().map(String::from),
+            Self::Nil |
+            Self::Binary(..) |
+            Self::Array(..) |
+            Self::Map(..) |
+            Self::Ext(..) |
+            Self::Timestamp(..) => None,
+        }
+    }
+
+    /// Converts the `Value` into canonical JSON text, as specified by RFC 8785 (JCS).
+    ///
+    /// Map keys are sorted, floats use the shortest round-trip form and strings are escaped
+    /// minimally, so equal values always produce identical output. This makes the result
+    /// suitable for hashing or signing.
+    ///
+    /// `F32` values are formatted using their shortest single precision representation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for values that have no JSON counterpart: binaries, extensions, strings
+    /// with invalid UTF-8, non-string or duplicate map keys, NaN or infinite floats, and integers
+    /// outside of the `±(2^53 - 1)` range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("b"), Value::from(1e21)),
+    ///     (Value::from("a"), Value::Array(vec![Value::Nil, Value::from(1.5)])),
+    /// ]);
+    ///
+    /// assert_eq!(r#"{"a":[null,1.5],"b":1e+21}"#, val.to_canonical_json().unwrap());
+    ///
+    /// assert!(Value::Binary(vec![1, 2]).to_canonical_json().is_err());
+    /// ```
+    pub fn to_canonical_json(&self) -> Result<String, json::Error> {
+        let mut out = String::new();
+        json::write_canonical(&mut out, self)?;
+        Ok(out)
+    }
+
+    /// Converts the `Value` into human-readable JSON text, for logging and debugging.
+    ///
+    /// Unlike [`Value::to_canonical_json`] this never fails, mapping types that JSON lacks onto
+    /// readable substitutes:
+    ///
+    /// - binaries become base64 strings;
+    /// - extensions become `{"type": <tag>, "data": "<hex payload>"}` objects;
+    /// - strings with invalid UTF-8 are decoded lossily, with U+FFFD replacement characters;
+    /// - NaN and infinite floats become the strings `"NaN"`, `"inf"` and `"-inf"`;
+    /// - non-string map keys are quoted in their JSON form.
+    ///
+    /// Map entries keep their original order and integers are written with full precision.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("bin"), Value::Binary(vec![1, 2, 3])),
+    ///     (Value::from(42), Value::Ext(7, vec![0xca, 0xfe])),
+    /// ]);
+    ///
+    /// assert_eq!(r#"{"bin":"AQID","42":{"type":7,"data":"cafe"}}"#, val.to_debug_json());
+    /// ```
+    #[must_use]
+    pub fn to_debug_json(&self) -> String {
+        let mut out = String::new();
+        json::write_debug(&mut out, self);
+        out
+    }
+
+    /// Converts the `Value` into indented, human-readable JSON text, for logging large values.
+    ///
+    /// This is the output of [`Value::to_debug_json`], with every array element and map entry on
+    /// its own line, indented by two spaces per level. The alternate form of `Display`, `{:#}`,
+    /// indents the same way, but keeps the notation of `{}` rather than JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("id"), Value::from(42)),
+    ///     (Value::from("data"), Value::Array(vec![Value::Binary(vec![1, 2, 3]), Value::Ext(7, vec![0xca, 0xfe])])),
+    /// ]);
+    ///
+    /// let expected = r#"{
+    ///   "id": 42,
+    ///   "data": [
+    ///     "AQID",
+    ///     {"type": 7, "data": "cafe"}
+    ///   ]
+    /// }"#;
+    /// assert_eq!(expected, val.to_string_pretty());
+    /// ```
+    #[must_use]
+    pub fn to_string_pretty(&self) -> String {
+        self.to_string_pretty_with("  ")
+    }
+
+    /// Converts the `Value` into indented, human-readable JSON text like
+    /// [`Value::to_string_pretty`], using `indent` once per level instead of two spaces.
+    ///
+    /// An empty `indent` puts everything on one line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Array(vec![Value::from(1), Value::Nil]);
+    ///
+    /// assert_eq!("[\n\t1,\n\tnull\n]", val.to_string_pretty_with("\t"));
+    /// ```
+    #[must_use]
+    pub fn to_string_pretty_with(&self, indent: &str) -> String {
+        let mut out = String::new();
+        json::write_debug_indented(&mut out, self, indent, 0);
+        out
+    }
+
+    /// Returns a wrapper whose `Debug` output shows binary and extension payloads as truncated
+    /// hex dumps, for logging messages that carry large payloads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("data"), Value::Binary(vec![0xff; 1024]))]);
+    ///
+    /// assert_eq!(r#"Map({"data": Binary(1024 bytes: ff ff ..)})"#, format!("{:?}", val.debug_hex().limit(2)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn debug_hex(&self) -> DebugHex<'_> {
+        DebugHex::new(self)
+    }
+
+    /// Converts the `Value` into a `serde_json::Value`.
+    ///
+    /// # Errors
+    ///
+    /// Fails on the same values as [`Value::to_canonical_json`], except that integers keep their
+    /// full 64-bit range. Use [`Value::to_json_value_lossy`] to replace them instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::json::Error;
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("id"), Value::from(u64::MAX))]);
+    /// assert_eq!(serde_json::json!({"id": u64::MAX}), val.to_json_value().unwrap());
+    ///
+    /// assert_eq!(Err(Error::Binary), Value::Binary(vec![1]).to_json_value());
+    /// ```
+    #[cfg(feature = "with-serde-json")]
+    pub fn to_json_value(&self) -> Result<serde_json::Value, json::Error> {
+        json::to_value(self)
+    }
+
+    /// Converts the `Value` into a `serde_json::Value`, never failing.
+    ///
+    /// Types that JSON lacks are replaced the same way [`Value::to_debug_json`] does. Use
+    /// [`Value::to_json_value`] instead to reject them. When a map has duplicate keys, the last entry
+    /// wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("bin"), Value::Binary(vec![1, 2, 3])),
+    ///     (Value::from(42), Value::Ext(7, vec![0xca, 0xfe])),
+    /// ]);
+    ///
+    /// assert_eq!(serde_json::json!({
+    ///     "bin": "AQID",
+    ///     "42": {"type": 7, "data": "cafe"},
+    /// }), val.to_json_value_lossy());
+    /// ```
+    #[cfg(feature = "with-serde-json")]
+    #[must_use]
+    pub fn to_json_value_lossy(&self) -> serde_json::Value {
+        json::to_value_lossy(self)
+    }
+
+    /// Recursively merges `other` into `self`.
+    ///
+    /// When both values are maps, the entries of `other` are merged into the entries of `self`
+    /// with an equal key, recursively, and appended if there is none. Any other conflict is
+    /// resolved according to `strategy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{msgpack, MergeStrategy};
+    ///
+    /// let mut config = msgpack!({"port": 80, "hosts": ["a"], "tls": {"enabled": false}});
+    /// config.merge(msgpack!({"hosts": ["b"], "tls": {"cert": "x.pem"}}), MergeStrategy::AppendArrays);
+    ///
+    /// assert_eq!(msgpack!({"port": 80, "hosts": ["a", "b"], "tls": {"enabled": false, "cert": "x.pem"}}), config);
+    /// ```
+    pub fn merge(&mut self, other: Self, strategy: MergeStrategy) {
+        match (self, other) {
+            (Self::Map(map), Self::Map(other)) => {
+                for (key, val) in other {
+                    match map.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, existing)) => existing.merge(val, strategy),
+                        None => map.push((key, val)),
+                    }
+                }
+            }
+            (Self::Array(vec), Self::Array(other)) if strategy == MergeStrategy::AppendArrays => {
+                vec.extend(other);
+            }
+            (_, _) if strategy == MergeStrategy::KeepExisting => {}
+            (this, other) => *this = other,
+        }
+    }
+
+    /// Decodes a MessagePack value from `bytes` and deep-merges it into this `Value`.
+    ///
+    /// This is [`Value::merge`] with [`MergeStrategy::Replace`]: when both sides are maps, entries
+    /// of the decoded map are merged into this map key by key, recursively: nested maps are merged
+    /// in turn, while any other value replaces the existing one. Keys that are not present yet are
+    /// appended. In every other case the decoded value replaces this one entirely.
+    ///
+    /// This is the usual way of layering an overlay configuration file on top of a base one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bytes` does not contain a valid MessagePack value, or
+    /// [`decode::Error::TrailingData`] if it contains anything after it. In both cases `self` is
+    /// left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut config = Value::Map(vec![
+    ///     (Value::from("port"), Value::from(80)),
+    ///     (Value::from("tls"), Value::Map(vec![(Value::from("enabled"), Value::from(false))])),
+    /// ]);
+    ///
+    /// // {"tls": {"enabled": true}}
+    /// let overlay = [0x81, 0xa3, b't', b'l', b's', 0x81, 0xa7, b'e', b'n', b'a', b'b', b'l', b'e', b'd', 0xc3];
+    /// config.merge_from_msgpack(&overlay).unwrap();
+    ///
+    /// assert_eq!(Value::from(80), config["port"]);
+    /// assert_eq!(Value::from(true), config["tls"]["enabled"]);
+    /// ```
+    pub fn merge_from_msgpack(&mut self, mut bytes: &[u8]) -> Result<(), decode::Error> {
+        let other = decode::read_value(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(decode::Error::TrailingData(bytes.len()));
+        }
+        self.merge(other, MergeStrategy::Replace);
+        Ok(())
+    }
+
+    /// Recursively replaces every `Ext` value, including those nested in arrays and maps (both
+    /// keys and values), according to the given [`StripMode`].
+    ///
+    /// This allows forwarding messages to peers that do not understand extension types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{StripMode, Value};
+    ///
+    /// let mut val = Value::Array(vec![Value::Ext(42, vec![1, 2])]);
+    /// val.strip_exts(StripMode::KeepTag);
+    ///
+    /// assert_eq!(Value::Array(vec![
+    ///     Value::Array(vec![Value::from(42), Value::Binary(vec![1, 2])]),
+    /// ]), val);
+    /// ```
+    pub fn strip_exts(&mut self, mode: StripMode) {
+        match *self {
+            Self::Ext(..) | Self::Timestamp(..) => {
+                let (ty, data) = match std::mem::replace(self, Self::Nil) {
+                    Self::Ext(ty, data) => (ty, data),
+                    val => (TIMESTAMP_EXT_TYPE, val.as_ext().unwrap_or_default().1.to_vec()),
+                };
+                *self = match mode {
+                    StripMode::ToBinary => Self::Binary(data),
+                    StripMode::ToNil => Self::Nil,
+                    StripMode::KeepTag => Self::Array(vec![Self::from(ty), Self::Binary(data)]),
+                };
+            }
+            Self::Array(ref mut vec) => {
+                for val in vec {
+                    val.strip_exts(mode);
+                }
+            }
+            Self::Map(ref mut map) => {
+                for (key, val) in map {
+                    key.strip_exts(mode);
+                    val.strip_exts(mode);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Encodes the `Value` into a new buffer, according to the given [`EncodeOptions`].
+    ///
+    /// This is a single configurable entry point for the encoding variations, such as canonical
+    /// map ordering or fixed-width integers. See [`EncodeOptions`] for all of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value violates one of the enabled restrictions, such as
+    /// [`EncodeOptions::string_keys`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::encode::EncodeOptions;
+    /// use rmpv::Value;
+    ///
+    /// let opts = EncodeOptions::new().fixed_int_width(true);
+    ///
+    /// assert_eq!(vec![0xcf, 0, 0, 0, 0, 0, 0, 0, 0x2a], Value::from(42).encode_with(&opts).unwrap());
+    /// ```
+    ///
+    /// [`EncodeOptions`]: encode::EncodeOptions
+    /// [`EncodeOptions::string_keys`]: encode::EncodeOptions::string_keys
+    pub fn encode_with(&self, opts: &encode::EncodeOptions) -> Result<Vec<u8>, encode::options::Error> {
+        let mut buf = Vec::new();
+        encode::options::write_value_with(&mut buf, self, opts)?;
+        Ok(buf)
+    }
+
+    /// Returns the number of bytes [`write_value`](encode::write_value) writes for this value,
+    /// without encoding it.
+    ///
+    /// This walks the whole tree, so it is cheap compared to encoding but not free.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("id"), Value::from(1000))]);
+    ///
+    /// let mut buf = Vec::with_capacity(val.serialized_size());
+    /// rmpv::encode::write_value(&mut buf, &val).unwrap();
+    /// assert_eq!(7, buf.len());
+    /// assert_eq!(buf.len(), val.serialized_size());
+    /// ```
+    #[must_use]
+    pub fn serialized_size(&self) -> usize {
+        encode::value_size(self)
+    }
+
+    /// Converts every float of the tree, including map keys, into the given format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::encode::FloatFormat;
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Array(vec![Value::F64(0.5), Value::F64(0.1)]);
+    /// val.normalize_floats(FloatFormat::Compact);
+    ///
+    /// assert_eq!(Value::Array(vec![Value::F32(0.5), Value::F64(0.1)]), val);
+    /// ```
+    pub fn normalize_floats(&mut self, format: encode::FloatFormat) {
+        match *self {
+            Self::Array(ref mut vec) => vec.iter_mut().for_each(|val| val.normalize_floats(format)),
+            Self::Map(ref mut map) => map.iter_mut().for_each(|(key, val)| {
+                key.normalize_floats(format);
+                val.normalize_floats(format);
+            }),
+            _ => format.apply(self),
+        }
+    }
+
+    /// Returns the number of heap bytes owned by this value, including the nested values.
+    ///
+    /// Strings, binaries and containers count their allocated capacity, so the result reflects the
+    /// memory actually held rather than the length of the data. The inline size of the value
+    /// itself, `size_of::<Value>()`, is not included, since it may not live on the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::mem::size_of;
+    ///
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Array(vec![Value::Binary(vec![0; 100]), Value::Nil]);
+    ///
+    /// assert_eq!(2 * size_of::<Value>() + 100, val.memory_usage());
+    /// assert_eq!(0, Value::from(42).memory_usage());
+    /// ```
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        match *self {
+            Self::Nil | Self::Boolean(..) | Self::Integer(..) | Self::F32(..) | Self::F64(..) | Self::Timestamp(..) => 0,
+            Self::String(Utf8String { ref s }) => match *s {
+                Ok(ref s) => s.capacity(),
+                Err((ref buf, _)) => buf.capacity(),
+            },
+            Self::Binary(ref buf) | Self::Ext(_, ref buf) => buf.capacity(),
+            Self::Array(ref vec) => {
+                vec.capacity() * mem::size_of::<Self>() + vec.iter().map(Self::memory_usage).sum::<usize>()
+            }
+            Self::Map(ref map) => {
+                map.capacity() * mem::size_of::<(Self, Self)>() +
+                    map.iter().map(|(key, val)| key.memory_usage() + val.memory_usage()).sum::<usize>()
+            }
+        }
+    }
+
+    /// Groups the elements of an array by the key extracted from each of them with `key_fn`.
+    ///
+    /// Returns a map from every distinct key to an array of the elements sharing it. Both the
+    /// groups and the elements within each group keep the order in which they appear in the array.
+    ///
+    /// Returns `None` if the `Value` is not an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Array(vec![Value::from(1), Value::from(2), Value::from(3)]);
+    /// let groups = val.group_by(|v| Value::from(v.as_u64().unwrap() % 2 == 0)).unwrap();
+    ///
+    /// assert_eq!(Value::Map(vec![
+    ///     (Value::from(false), Value::Array(vec![Value::from(1), Value::from(3)])),
+    ///     (Value::from(true), Value::Array(vec![Value::from(2)])),
+    /// ]), groups);
+    ///
+    /// assert_eq!(None, Value::Nil.group_by(|v| v.clone()));
+    /// ```
+    pub fn group_by<F>(&self, mut key_fn: F) -> Option<Self>
+        where F: FnMut(&Self) -> Self
+    {
+        let mut groups: Vec<(Self, Self)> = Vec::new();
+        for val in self.as_array()? {
+            let key = key_fn(val);
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, Self::Array(members))) => members.push(val.clone()),
+                _ => groups.push((key, Self::Array(vec![val.clone()]))),
+            }
+        }
+
+        Some(Self::Map(groups))
+    }
+
+    /// Combines the elements of two arrays pairwise with `f`, like [`Iterator::zip`].
+    ///
+    /// The resulting array has the length of the shorter input; any remaining elements of the
+    /// longer one are ignored.
+    ///
+    /// Returns `None` if either `a` or `b` is not an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let ts = Value::Array(vec![Value::from(100), Value::from(200)]);
+    /// let readings = Value::Array(vec![Value::from(0.5), Value::from(0.7), Value::from(0.9)]);
+    ///
+    /// let pairs = Value::zip_arrays(&ts, &readings, |t, r| Value::Array(vec![t.clone(), r.clone()]));
+    /// assert_eq!(Some(Value::Array(vec![
+    ///     Value::Array(vec![Value::from(100), Value::from(0.5)]),
+    ///     Value::Array(vec![Value::from(200), Value::from(0.7)]),
+    /// ])), pairs);
+    ///
+    /// assert_eq!(None, Value::zip_arrays(&ts, &Value::Nil, |t, _| t.clone()));
+    /// ```
+    pub fn zip_arrays<F>(a: &Self, b: &Self, mut f: F) -> Option<Self>
+        where F: FnMut(&Self, &Self) -> Self
+    {
+        let (a, b) = (a.as_array()?, b.as_array()?);
+        Some(Self::Array(a.iter().zip(b).map(|(a, b)| f(a, b)).collect()))
+    }
+
+    /// Recursively renames string map keys according to `mapping`, from old to new names.
+    ///
+    /// Keys missing from `mapping`, as well as keys that are not strings, are left untouched.
+    /// Maps nested in arrays and in map values are renamed too, but map keys themselves are not
+    /// searched.
+    ///
+    /// If a renamed key collides with another key of the same map, the entry that comes last in
+    /// the map wins and the earlier ones are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![
+    ///     (Value::from("user_name"), Value::from("Alice")),
+    ///     (Value::from("name"), Value::from("Bob")),
+    ///     (Value::from("id"), Value::from(1)),
+    /// ]);
+    ///
+    /// let mapping = HashMap::from([("user_name".to_owned(), "name".to_owned())]);
+    /// val.rename_keys(&mapping);
+    ///
+    /// assert_eq!(Value::Map(vec![
+    ///     (Value::from("name"), Value::from("Bob")),
+    ///     (Value::from("id"), Value::from(1)),
+    /// ]), val);
+    /// ```
+    pub fn rename_keys(&mut self, mapping: &HashMap<String, String>) {
+        match *self {
+            Self::Array(ref mut vec) => {
+                for val in vec {
+                    val.rename_keys(mapping);
+                }
+            }
+            Self::Map(ref mut map) => {
+                let mut renamed = Vec::new();
+                for (key, val) in map.iter_mut() {
+                    if let Some(name) = key.as_str().and_then(|key| mapping.get(key)) {
+                        *key = Self::from(name.as_str());
+                        renamed.push(name.as_str());
+                    }
+                    val.rename_keys(mapping);
+                }
+
+                // Last wins: drop entries whose key is taken again by a later one.
+                let mut idx = 0;
+                while idx < map.len() {
+                    let collides = map[idx].0.as_str().is_some_and(|key| {
+                        renamed.contains(&key) && map[idx + 1..].iter().any(|(k, _)| k.as_str() == Some(key))
+                    });
+                    if collides {
+                        map.remove(idx);
+                    } else {
+                        idx += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Looks up a nested value by a JSON Pointer (RFC 6901), such as `/config/servers/0/host`.
+    ///
+    /// Each `/`-separated token selects an array element by its index, written without a sign or
+    /// leading zeros, or a map value by its key.
+    /// A token matches string keys first, with `~1` and `~0` standing for `/` and `~`, and then
+    /// integer keys with the same decimal form. The empty pointer refers to `self`.
+    ///
+    /// Returns `None` if the pointer does not start with `/` or does not lead to a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("servers"), Value::Array(vec![
+    ///         Value::Map(vec![(Value::from("host"), Value::from("localhost"))]),
+    ///     ])),
+    ///     (Value::from(42), Value::from("answer")),
+    /// ]);
+    ///
+    /// assert_eq!(Some(&Value::from("localhost")), val.pointer("/servers/0/host"));
+    /// assert_eq!(Some(&Value::from("answer")), val.pointer("/42"));
+    /// assert_eq!(None, val.pointer("/servers/1"));
+    /// ```
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&Self> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        pointer.strip_prefix('/')?.split('/').try_fold(self, |val, token| {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            match *val {
+                Self::Array(ref vec) => vec.get(pointer_array_idx(&token)?),
+                Self::Map(ref map) => map.get(pointer_key_idx(map, &token)?).map(|(_, val)| val),
+                _ => None,
+            }
+        })
+    }
+
+    /// Looks up a nested value by a JSON Pointer, returning a mutable reference to it.
+    ///
+    /// See [`Value::pointer`] for the pointer syntax.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Array(vec![Value::Map(vec![(Value::from("n"), Value::from(1))])]);
+    ///
+    /// *val.pointer_mut("/0/n").unwrap() = Value::from(2);
+    /// assert_eq!(Value::from(2), val[0]["n"]);
+    /// ```
+    #[must_use]
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Self> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        pointer.strip_prefix('/')?.split('/').try_fold(self, |val, token| {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            match *val {
+                Self::Array(ref mut vec) => vec.get_mut(pointer_array_idx(&token)?),
+                Self::Map(ref mut map) => {
+                    let idx = pointer_key_idx(map, &token)?;
+                    map.get_mut(idx).map(|(_, val)| val)
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Returns the value of the first map entry whose key has the bytes of `key`, as either a
+    /// string or a binary.
+    ///
+    /// Peers written in other languages frequently send string keys as binaries, or with the
+    /// legacy raw format, which these lookups accept as well. Returns `None` if the `Value` is not a
+    /// map or has no such key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::Binary(b"id".to_vec()), Value::from(42))]);
+    ///
+    /// assert_eq!(Some(&Value::from(42)), val.get_str_key("id"));
+    /// assert_eq!(Value::Nil, val["id"]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get_str_key(&self, key: &str) -> Option<&Self> {
+        self.get_bytes_key(key.as_bytes())
+    }
+
+    /// Like [`get_str_key`](Self::get_str_key), returning a mutable reference to the value.
+    #[inline]
+    #[must_use]
+    pub fn get_str_key_mut(&mut self, key: &str) -> Option<&mut Self> {
+        self.get_bytes_key_mut(key.as_bytes())
+    }
+
+    /// Like [`get_str_key`](Self::get_str_key), for keys that are not valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("k"), Value::Nil)]);
+    ///
+    /// assert_eq!(Some(&Value::Nil), val.get_bytes_key(b"k"));
+    /// ```
+    #[must_use]
+    pub fn get_bytes_key(&self, key: &[u8]) -> Option<&Self> {
+        self.as_map()?.iter().find(|(k, _)| k.key_bytes() == Some(key)).map(|(_, val)| val)
+    }
+
+    /// Like [`get_bytes_key`](Self::get_bytes_key), returning a mutable reference to the value.
+    #[must_use]
+    pub fn get_bytes_key_mut(&mut self, key: &[u8]) -> Option<&mut Self> {
+        match *self {
+            Self::Map(ref mut map) => map.iter_mut().find(|(k, _)| k.key_bytes() == Some(key)).map(|(_, val)| val),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes of a string or binary, which the bytes key lookups compare.
+    fn key_bytes(&self) -> Option<&[u8]> {
+        match *self {
+            Self::String(ref s) => Some(s.as_bytes()),
+            Self::Binary(ref v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Takes the value out, leaving [`Nil`](Self::Nil) in its place.
+    ///
+    /// Combined with [`pointer_mut`](Self::pointer_mut) or indexing, this moves a field out of a
+    /// decoded message without cloning it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut msg = Value::Map(vec![
+    ///     (Value::from("payload"), Value::Binary(vec![0; 1024])),
+    /// ]);
+    ///
+    /// let payload = msg.pointer_mut("/payload").unwrap().take();
+    /// assert_eq!(1024, payload.as_slice().unwrap().len());
+    /// assert_eq!(Value::Nil, msg["payload"]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn take(&mut self) -> Self {
+        std::mem::replace(self, Self::Nil)
+    }
+
+    /// Replaces the value with `val`, returning the old one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut msg = Value::Array(vec![Value::from("old")]);
+    ///
+    /// assert_eq!(Value::from("old"), msg[0].replace(Value::from("new")));
+    /// assert_eq!(Value::from("new"), msg[0]);
+    /// ```
+    #[inline]
+    pub fn replace(&mut self, val: Self) -> Self {
+        std::mem::replace(self, val)
+    }
+
+    /// If the `Value` is a Map, builds a [`MapIndex`] over its string keys for fast lookups.
+    /// Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("id"), Value::from(42))]);
+    ///
+    /// assert_eq!(Some(&Value::from(42)), val.map_index().unwrap().get("id"));
+    ///
+    /// assert!(Value::Nil.map_index().is_none());
+    /// ```
+    #[must_use]
+    pub fn map_index(&self) -> Option<MapIndex<'_>> {
+        self.as_map().map(|map| MapIndex::new(map))
+    }
+
+    /// Returns `true` if any map in the tree, including `self`, contains the same key more than
+    /// once.
+    ///
+    /// Keys are compared with [`total_eq`](Self::total_eq), so `0.0` and `-0.0` are distinct keys
+    /// while `NaN` keys with the same bits are the same. See [`DuplicateKeys`](decode::DuplicateKeys)
+    /// for rejecting such maps while decoding instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Array(vec![Value::Map(vec![
+    ///     (Value::from("a"), Value::from(1)),
+    ///     (Value::from("a"), Value::from(2)),
+    /// ])]);
+    ///
+    /// assert!(val.has_duplicate_keys());
+    /// assert!(!Value::Map(vec![(Value::from(1), Value::Nil), (Value::from("1"), Value::Nil)]).has_duplicate_keys());
+    /// ```
+    #[must_use]
+    pub fn has_duplicate_keys(&self) -> bool {
+        match *self {
+            Self::Array(ref vec) => vec.iter().any(Self::has_duplicate_keys),
+            Self::Map(ref map) => {
+                let mut keys = KeySet::default();
+                for (idx, (key, _)) in map.iter().enumerate() {
+                    if keys.find(map, key).is_some() {
+                        return true;
+                    }
+                    keys.insert(key, idx);
+                }
+                map.iter().any(|(key, val)| key.has_duplicate_keys() || val.has_duplicate_keys())
+            }
+            _ => false,
+        }
+    }
+
+    /// Gets the entry for `key` in a Map, for in-place manipulation.
+    ///
+    /// Keys are compared with `==` and the first matching entry is used, while new entries are
+    /// appended to the end of the map. Returns None if the `Value` is not a Map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![(Value::from("hits"), Value::from(1))]);
+    ///
+    /// for key in ["hits", "misses"] {
+    ///     val.entry(key).unwrap()
+    ///         .and_modify(|n| *n = Value::from(n.as_u64().unwrap() + 1))
+    ///         .or_insert(Value::from(1));
+    /// }
+    ///
+    /// assert_eq!(Value::Map(vec![
+    ///     (Value::from("hits"), Value::from(2)),
+    ///     (Value::from("misses"), Value::from(1)),
+    /// ]), val);
+    ///
+    /// assert!(Value::Nil.entry("hits").is_none());
+    /// ```
+    pub fn entry<K>(&mut self, key: K) -> Option<Entry<'_>>
+        where K: Into<Self>
+    {
+        match *self {
+            Self::Map(ref mut map) => Some(Entry::new(map, key.into())),
+            _ => None,
+        }
+    }
+
+    /// Flattens nested arrays and maps into a single-level map from the path of every scalar leaf
+    /// to a copy of that leaf.
+    ///
+    /// Paths join map keys and array indices with `separator`. Map keys that are not strings are
+    /// written in their `Display` form. Empty arrays and maps have no leaves and so do not appear
+    /// in the result, while a scalar `self` is returned under the empty path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("user"), Value::Map(vec![(Value::from("name"), Value::from("Ann"))])),
+    ///     (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::from("b")])),
+    /// ]);
+    ///
+    /// assert_eq!(Value::Map(vec![
+    ///     (Value::from("user.name"), Value::from("Ann")),
+    ///     (Value::from("tags.0"), Value::from("a")),
+    ///     (Value::from("tags.1"), Value::from("b")),
+    /// ]), val.flatten_to_map("."));
+    /// ```
+    #[must_use]
+    pub fn flatten_to_map(&self, separator: &str) -> Self {
+        let mut map = Vec::new();
+        self.flatten_inner(separator, &mut String::new(), &mut map);
+        Self::Map(map)
+    }
+
+    /// Returns every value in the tree, including `self`, for which `pred` returns `true`,
+    /// together with its path.
+    ///
+    /// Paths are JSON Pointers (RFC 6901): array indices and map keys separated by `/`, with
+    /// `~` and `/` in string keys escaped as `~0` and `~1`, and the empty string for `self`.
+    /// Map keys that are not strings are written in their `Display` form. Only map values are
+    /// searched, not keys. Values are returned in depth-first order, parents before children.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("a/b"), Value::from(1)),
+    ///     (Value::from("c"), Value::Array(vec![Value::from(2), Value::from("3")])),
+    /// ]);
+    ///
+    /// let ints = val.query(|v| v.is_u64());
+    /// assert_eq!(vec![
+    ///     ("/a~1b".to_owned(), &Value::from(1)),
+    ///     ("/c/0".to_owned(), &Value::from(2)),
+    /// ], ints);
+    /// ```
+    pub fn query<F>(&self, pred: F) -> Vec<(String, &Self)>
+        where F: Fn(&Self) -> bool
+    {
+        let mut matches = Vec::new();
+        self.query_inner(&pred, &mut String::new(), &mut matches);
+        matches
+    }
+
+    /// Visits every value in the tree, including `self`, depth-first with parents before
+    /// children. See [`ValueVisitor`] for the order in which map entries are visited.
+    ///
+    /// Returns `false` if the visitor stopped the walk with [`Walk::Stop`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{Value, ValueVisitor, Walk};
+    ///
+    /// /// Counts the strings, without looking into binaries.
+    /// struct CountStrings(usize);
+    ///
+    /// impl ValueVisitor for CountStrings {
+    ///     fn visit(&mut self, val: &Value) -> Walk {
+    ///         if val.is_str() {
+    ///             self.0 += 1;
+    ///         }
+    ///         Walk::Continue
+    ///     }
+    /// }
+    ///
+    /// let val = Value::Map(vec![
+    ///     (Value::from("a"), Value::Array(vec![Value::from("b"), Value::from(1)])),
+    /// ]);
+    ///
+    /// let mut visitor = CountStrings(0);
+    /// assert!(val.walk(&mut visitor));
+    /// assert_eq!(2, visitor.0);
+    /// ```
+    pub fn walk<V>(&self, visitor: &mut V) -> bool
+        where V: ValueVisitor + ?Sized
+    {
+        visit::walk(self, visitor)
+    }
+
+    /// Visits every value in the tree, including `self`, allowing the visitor to modify them in
+    /// place. Values are visited in the same order as by [`walk`](Self::walk).
+    ///
+    /// Returns `false` if the visitor stopped the walk with [`Walk::Stop`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{Value, ValueVisitorMut, Walk};
+    ///
+    /// /// Hides the value of every `password` entry.
+    /// struct Redact;
+    ///
+    /// impl ValueVisitorMut for Redact {
+    ///     fn visit_mut(&mut self, _val: &mut Value) -> Walk {
+    ///         Walk::Continue
+    ///     }
+    ///
+    ///     fn visit_entry_mut(&mut self, key: &mut Value, val: &mut Value) -> Walk {
+    ///         if key.as_str() == Some("password") {
+    ///             *val = Value::from("***");
+    ///             return Walk::SkipChildren;
+    ///         }
+    ///         Walk::Continue
+    ///     }
+    /// }
+    ///
+    /// let mut val = Value::Array(vec![
+    ///     Value::Map(vec![(Value::from("password"), Value::from("hunter2"))]),
+    /// ]);
+    ///
+    /// val.walk_mut(&mut Redact);
+    /// assert_eq!("***", val[0]["password"].as_str().unwrap());
+    /// ```
+    pub fn walk_mut<V>(&mut self, visitor: &mut V) -> bool
+        where V: ValueVisitorMut + ?Sized
+    {
+        visit::walk_mut(self, visitor)
+    }
+
+    /// Appends the scalar leaves below `self`, located at `path`, to `map`.
+    fn flatten_inner(&self, separator: &str, path: &mut String, map: &mut Vec<(Self, Self)>) {
+        let len = path.len();
+        let push_segment = |path: &mut String, segment: &dyn Display| {
+            if len > 0 {
+                path.push_str(separator);
+            }
+            path.push_str(&segment.to_string());
+        };
+
+        match *self {
+            Self::Array(ref vec) => {
+                for (idx, val) in vec.iter().enumerate() {
+                    push_segment(path, &idx);
+                    val.flatten_inner(separator, path, map);
+                    path.truncate(len);
+                }
+            }
+            Self::Map(ref entries) => {
+                for (key, val) in entries {
+                    match key.as_str() {
+                        Some(key) => push_segment(path, &key),
+                        None => push_segment(path, key),
+                    }
+                    val.flatten_inner(separator, path, map);
+                    path.truncate(len);
+                }
+            }
+            ref leaf => map.push((Self::from(path.as_str()), leaf.clone())),
+        }
+    }
+
+    /// Appends the matches of `pred` below `self`, located at `path`, to `matches`.
+    fn query_inner<'a, F>(&'a self, pred: &F, path: &mut String, matches: &mut Vec<(String, &'a Self)>)
+        where F: Fn(&Self) -> bool
+    {
+        if pred(self) {
+            matches.push((path.clone(), self));
+        }
+
+        let len = path.len();
+        match *self {
+            Self::Array(ref vec) => {
+                for (idx, val) in vec.iter().enumerate() {
+                    path.push('/');
+                    path.push_str(&idx.to_string());
+                    val.query_inner(pred, path, matches);
+                    path.truncate(len);
+                }
+            }
+            Self::Map(ref map) => {
+                for (key, val) in map {
+                    push_pointer_key(path, key);
+                    val.query_inner(pred, path, matches);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Appends a map key to a JSON Pointer, escaping `~` and `/` in string keys.
+///
+/// Keys that are not strings are written in their `Display` form.
+pub(crate) fn push_pointer_key(path: &mut String, key: &Value) {
+    path.push('/');
+    match key.as_str() {
+        Some(key) => path.push_str(&key.replace('~', "~0").replace('/', "~1")),
+        None => path.push_str(&key.to_string()),
+    }
+}
+
+/// Returns the index of the map entry selected by an unescaped JSON Pointer token.
+///
+/// String keys equal to the token take precedence over integer keys with the same decimal form.
+/// Parses a JSON Pointer token selecting an array element, which RFC 6901 restricts to `0` or a
+/// decimal number without a sign or leading zeros.
+fn pointer_array_idx(token: &str) -> Option<usize> {
+    match token.as_bytes() {
+        [b'0'] => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => token.parse().ok(),
+        _ => None,
+    }
+}
+
+fn pointer_key_idx(map: &[(Value, Value)], token: &str) -> Option<usize> {
+    map.iter().position(|(key, _)| key.as_str() == Some(token)).or_else(|| {
+        map.iter().position(|(key, _)| match *key {
+            Value::Integer(ref n) => n.to_string() == token,
+            _ => false,
+        })
+    })
+}
+
+/// Like [`pointer_key_idx`], for the entries of a [`ValueRef`] map.
+fn pointer_key_idx_ref(map: &[(ValueRef<'_>, ValueRef<'_>)], token: &str) -> Option<usize> {
+    map.iter().position(|(key, _)| key.as_str() == Some(token)).or_else(|| {
+        map.iter().position(|(key, _)| match *key {
+            ValueRef::Integer(ref n) => n.to_string() == token,
+            _ => false,
+        })
+    })
+}
+
+/// An iterator over the keys and values of a map, see [`Value::entries`].
+#[derive(Clone, Debug)]
+pub struct Entries<'a> {
+    entries: slice::Iter<'a, (Value, Value)>,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = (&'a Value, &'a Value);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(key, val)| (key, val))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Entries<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.entries.next_back().map(|(key, val)| (key, val))
+    }
+}
+
+impl ExactSizeIterator for Entries<'_> {}
+
+static NIL: Value = Value::Nil;
+static NIL_REF: ValueRef<'static> = ValueRef::Nil;
+
+/// Compares two slices element by element with `cmp`, then by length.
+fn total_cmp_slices<T>(lhs: &[T], rhs: &[T], cmp: impl Fn(&T, &T) -> Ordering) -> Ordering {
+    for (lhs, rhs) in lhs.iter().zip(rhs) {
+        match cmp(lhs, rhs) {
+            Ordering::Equal => {}
+            ord => return ord,
+        }
+    }
+    lhs.len().cmp(&rhs.len())
+}
+
+/// A [`Value`] compared, ordered and hashed by [`Value::total_cmp`], so that it can be used as a
+/// key of a `HashMap` or `BTreeMap` and collected into sets.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeSet;
+///
+/// use rmpv::{TotalValue, Value};
+///
+/// let set: BTreeSet<TotalValue> = [Value::from("b"), Value::from(f64::NAN), Value::from(1), Value::from("b")]
+///     .into_iter()
+///     .map(TotalValue)
+///     .collect();
+///
+/// assert_eq!(3, set.len());
+/// assert_eq!(Value::from(1), set.first().unwrap().0);
+/// assert!(set.contains(&TotalValue(Value::from(f64::NAN))));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TotalValue(pub Value);
+
+impl From<Value> for TotalValue {
+    #[inline]
+    fn from(val: Value) -> Self {
+        Self(val)
+    }
+}
+
+impl PartialEq for TotalValue {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_eq(&other.0)
+    }
+}
+
+impl Eq for TotalValue {}
+
+impl PartialOrd for TotalValue {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TotalValue {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for TotalValue {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.total_hash(state);
+    }
+}
+
+impl Index<usize> for Value {
+    type Output = Self;
+
+    fn index(&self, index: usize) -> &Self {
+        self.as_array().and_then(|v| v.get(index)).unwrap_or(&NIL)
+    }
+}
+
+impl Index<&str> for Value {
+    type Output = Self;
+    fn index(&self, index: &str) -> &Self {
+        if let Self::Map(ref map) = *self {
+            if let Some(found) = map.iter().find(|(key, _val)| {
+                if let Self::String(ref strval) = *key {
+                    if let Some(s) = strval.as_str() {
+                        if s == index { return true; }
+                    }
+                }
+                false
+            }) {
+                return &found.1;
+            }
+        }
+        &NIL
+    }
+}
+
+/// Mutably indexes into an array.
+///
+/// # Panics
+///
+/// Panics if the value is not an array, or if the index is out of bounds.
+impl IndexMut<usize> for Value {
+    fn index_mut(&mut self, index: usize) -> &mut Self {
+        match *self {
+            Self::Array(ref mut vec) => {
+                let len = vec.len();
+                vec.get_mut(index)
+                    .unwrap_or_else(|| panic!("cannot access index {index} of array of length {len}"))
+            }
+            ref val => panic!("cannot access index {index} of {}", val.type_name()),
+        }
+    }
+}
+
+/// Mutably indexes into a map by a string key.
+///
+/// If the key is missing, a new entry with a `Nil` value is appended to the map and returned. A
+/// `Nil` value is first turned into an empty map, so that `val["a"]["b"] = ...` builds nested maps.
+///
+/// # Panics
+///
+/// Panics if the value is neither a map nor `Nil`.
+impl IndexMut<&str> for Value {
+    fn index_mut(&mut self, index: &str) -> &mut Self {
+        if self.is_nil() {
+            *self = Self::Map(Vec::new());
+        }
+
+        match *self {
+            Self::Map(ref mut map) => {
+                let idx = match map.iter().position(|(key, _)| key.as_str() == Some(index)) {
+                    Some(idx) => idx,
+                    None => {
+                        map.push((Self::from(index), Self::Nil));
+                        map.len() - 1
+                    }
+                };
+                &mut map[idx].1
+            }
+            ref val => panic!("cannot access key {index:?} of {}", val.type_name()),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    #[inline]
+    fn from(v: bool) -> Self {
+        Self::Boolean(v)
+    }
+}
+
+impl From<u8> for Value {
+    #[inline]
+    fn from(v: u8) -> Self {
+        Self::Integer(From::from(v))
+    }
+}
+
+impl From<u16> for Value {
+    #[inline]
+    fn from(v: u16) -> Self {
+        Self::Integer(From::from(v))
+    }
+}
+
+impl From<u32> for Value {
+    #[inline]
+    fn from(v: u32) -> Self {
+        Self::Integer(From::from(v))
+    }
+}
+
+impl From<u64> for Value {
+    #[inline]
+    fn from(v: u64) -> Self {
+        Self::Integer(From::from(v))
+    }
+}
+
+impl From<usize> for Value {
+    #[inline]
+    fn from(v: usize) -> Self {
+        Self::Integer(From::from(v))
+    }
+}
+
+impl From<i8> for Value {
     #[inline]
     fn from(v: i8) -> Self {
         Self::Integer(From::from(v))
@@ -1075,6 +2945,13 @@ impl From<&[u8]> for Value {
     }
 }
 
+impl<const N: usize> From<&[u8; N]> for Value {
+    #[inline]
+    fn from(v: &[u8; N]) -> Self {
+        Self::Binary(v.to_vec())
+    }
+}
+
 impl<'a> From<Cow<'a, [u8]>> for Value {
     #[inline]
     fn from(v: Cow<'a, [u8]>) -> Self {
@@ -1096,6 +2973,66 @@ impl From<Vec<(Self, Self)>> for Value {
     }
 }
 
+impl From<Timestamp> for Value {
+    #[inline]
+    fn from(v: Timestamp) -> Self {
+        Self::Timestamp(v)
+    }
+}
+
+/// Converts the map into a [`Map`](crate::Value::Map), in the iteration order of the `HashMap`,
+/// which is unspecified.
+impl<K, V, S> From<HashMap<K, V, S>> for Value
+where
+    K: Into<Self>,
+    V: Into<Self>,
+{
+    fn from(v: HashMap<K, V, S>) -> Self {
+        Self::Map(v.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+/// Converts the map into a [`Map`](crate::Value::Map), with the entries sorted by key.
+impl<K, V> From<BTreeMap<K, V>> for Value
+where
+    K: Into<Self>,
+    V: Into<Self>,
+{
+    fn from(v: BTreeMap<K, V>) -> Self {
+        Self::Map(v.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+/// Converts `None` into [`Nil`](crate::Value::Nil), and `Some` into the contained value.
+impl<T> From<Option<T>> for Value
+where
+    T: Into<Self>,
+{
+    #[inline]
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Self::Nil, Into::into)
+    }
+}
+
+macro_rules! impl_from_tuple {
+    ($($t: ident $v: ident),+) => {
+        /// Converts the tuple into an [`Array`](crate::Value::Array) of its elements.
+        impl<$($t),+> From<($($t,)+)> for Value
+        where
+            $($t: Into<Self>,)+
+        {
+            fn from(($($v,)+): ($($t,)+)) -> Self {
+                Self::Array(vec![$($v.into()),+])
+            }
+        }
+    };
+}
+
+impl_from_tuple!(A a);
+impl_from_tuple!(A a, B b);
+impl_from_tuple!(A a, B b, C c);
+impl_from_tuple!(A a, B b, C c, D d);
+
 /// Note that an `Iterator<Item = u8>` will be collected into an
 /// [`Array`](crate::Value::Array), rather than a
 /// [`Binary`](crate::Value::Binary)
@@ -1137,6 +3074,31 @@ impl TryFrom<Value> for i64 {
     }
 }
 
+macro_rules! impl_try_from_int {
+    ($t: ty, $as: ident) => {
+        impl TryFrom<Value> for $t {
+            type Error = Value;
+
+            fn try_from(val: Value) -> Result<Self, Self::Error> {
+                match val {
+                    Value::Integer(n) => match n.$as().and_then(|n| <$t>::try_from(n).ok()) {
+                        Some(i) => Ok(i),
+                        None => Err(val),
+                    },
+                    v => Err(v),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from_int!(u8, as_u64);
+impl_try_from_int!(u16, as_u64);
+impl_try_from_int!(u32, as_u64);
+impl_try_from_int!(i8, as_i64);
+impl_try_from_int!(i16, as_i64);
+impl_try_from_int!(i32, as_i64);
+
 impl TryFrom<Value> for f64 {
     type Error = Value;
 
@@ -1163,6 +3125,21 @@ impl TryFrom<Value> for String {
         }
     }
 }
+
+/// Fails unless every key is a valid UTF-8 string. When a key occurs more than once, the last
+/// entry wins.
+impl TryFrom<Value> for HashMap<String, Value> {
+    type Error = Value;
+
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        match val {
+            Value::Map(map) if map.iter().all(|(key, _)| key.as_str().is_some()) => {
+                Ok(map.into_iter().filter_map(|(key, val)| Some((String::try_from(key).ok()?, val))).collect())
+            }
+            v => Err(v),
+        }
+    }
+}
 // The following impl was left out intentionally, see
 // https://github.com/3Hren/msgpack-rust/pull/228#discussion_r359513925
 /*
@@ -1199,10 +3176,68 @@ impl_try_from!(Vec<(Value, Value)>, Map);
 impl_try_from!(Vec<u8>, Binary);
 impl_try_from!(f32, F32);
 impl_try_from!(Utf8String, String);
+impl_try_from!(Timestamp, Timestamp);
+
+impl Value {
+    /// Writes the alternate form of `Display` at the given nesting level.
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, level: usize) -> Result<(), fmt::Error> {
+        fn indent(f: &mut fmt::Formatter<'_>, level: usize) -> Result<(), fmt::Error> {
+            for _ in 0..level {
+                f.write_str("  ")?;
+            }
+            Ok(())
+        }
+
+        match *self {
+            Self::Array(ref vec) if !vec.is_empty() => {
+                f.write_str("[\n")?;
+                for (idx, val) in vec.iter().enumerate() {
+                    indent(f, level + 1)?;
+                    val.fmt_indented(f, level + 1)?;
+                    f.write_str(if idx + 1 < vec.len() { ",\n" } else { "\n" })?;
+                }
+                indent(f, level)?;
+                f.write_str("]")
+            }
+            Self::Map(ref map) if !map.is_empty() => {
+                f.write_str("{\n")?;
+                for (idx, (key, val)) in map.iter().enumerate() {
+                    indent(f, level + 1)?;
+                    key.fmt_indented(f, level + 1)?;
+                    f.write_str(": ")?;
+                    val.fmt_indented(f, level + 1)?;
+                    f.write_str(if idx + 1 < map.len() { ",\n" } else { "\n" })?;
+                }
+                indent(f, level)?;
+                f.write_str("}")
+            }
+            _ => write!(f, "{self}"),
+        }
+    }
+}
 
 impl Display for Value {
+    /// Formats the value in a compact, JSON-like form.
+    ///
+    /// With the alternate flag `{:#}`, every array element and map entry goes on its own line,
+    /// indented by two spaces per level, and is otherwise written the same way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("ids"), Value::Array(vec![Value::from(1), Value::Nil]))]);
+    ///
+    /// assert_eq!(r#"{"ids": [1, nil]}"#, format!("{val}"));
+    /// assert_eq!("{\n  \"ids\": [\n    1,\n    nil\n  ]\n}", format!("{val:#}"));
+    /// ```
     #[cold]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        if f.alternate() {
+            return self.fmt_indented(f, 0);
+        }
+
         match *self {
             Self::Nil => f.write_str("nil"),
             Self::Boolean(val) => Display::fmt(&val, f),
@@ -1242,6 +3277,9 @@ impl Display for Value {
             Self::Ext(ty, ref data) => {
                 write!(f, "[{ty}, {data:?}]")
             }
+            Self::Timestamp(ref ts) => {
+                write!(f, "[{}, {:?}]", TIMESTAMP_EXT_TYPE, ts.as_ext_data())
+            }
         }
     }
 }
@@ -1320,7 +3358,94 @@ impl ValueRef<'_> {
             ValueRef::Map(ref val) => {
                 Value::Map(val.iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect())
             }
-            ValueRef::Ext(ty, buf) => Value::Ext(ty, buf.to_vec()),
+            ValueRef::Ext(ty, buf) => Value::from_ext(ty, buf.to_vec()),
+        }
+    }
+
+    /// Returns the position of the variant in declaration order, which orders values of different
+    /// variants.
+    const fn variant_rank(&self) -> u8 {
+        match *self {
+            ValueRef::Nil => 0,
+            ValueRef::Boolean(..) => 1,
+            ValueRef::Integer(..) => 2,
+            ValueRef::F32(..) => 3,
+            ValueRef::F64(..) => 4,
+            ValueRef::String(..) => 5,
+            ValueRef::Binary(..) => 6,
+            ValueRef::Array(..) => 7,
+            ValueRef::Map(..) => 8,
+            ValueRef::Ext(..) => 9,
+        }
+    }
+
+    /// Returns `true` if both values have the same variant and the same contents, comparing floats
+    /// by their bits. See [`Value::total_eq`].
+    #[must_use]
+    pub fn total_eq(&self, other: &Self) -> bool {
+        self.total_cmp(other) == Ordering::Equal
+    }
+
+    /// Returns the ordering between `self` and `other` in a total order of all values, following
+    /// the rules of [`Value::total_cmp`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    ///
+    /// use rmpv::ValueRef;
+    ///
+    /// assert_eq!(Ordering::Less, ValueRef::F64(-0.0).total_cmp(&ValueRef::F64(0.0)));
+    /// assert_eq!(Ordering::Less, ValueRef::from(42).total_cmp(&ValueRef::from("a")));
+    /// ```
+    #[must_use]
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (ValueRef::Nil, ValueRef::Nil) => Ordering::Equal,
+            (ValueRef::Boolean(lhs), ValueRef::Boolean(rhs)) => lhs.cmp(rhs),
+            (ValueRef::Integer(lhs), ValueRef::Integer(rhs)) => lhs.cmp(rhs),
+            (ValueRef::F32(lhs), ValueRef::F32(rhs)) => lhs.total_cmp(rhs),
+            (ValueRef::F64(lhs), ValueRef::F64(rhs)) => lhs.total_cmp(rhs),
+            (ValueRef::String(lhs), ValueRef::String(rhs)) => lhs.as_bytes().cmp(rhs.as_bytes()),
+            (ValueRef::Binary(lhs), ValueRef::Binary(rhs)) => lhs.cmp(rhs),
+            (ValueRef::Array(lhs), ValueRef::Array(rhs)) => total_cmp_slices(lhs, rhs, Self::total_cmp),
+            (ValueRef::Map(lhs), ValueRef::Map(rhs)) => total_cmp_slices(lhs, rhs, |(lhs_key, lhs_val), (rhs_key, rhs_val)| {
+                lhs_key.total_cmp(rhs_key).then_with(|| lhs_val.total_cmp(rhs_val))
+            }),
+            (ValueRef::Ext(lhs_ty, lhs), ValueRef::Ext(rhs_ty, rhs)) => (lhs_ty, lhs).cmp(&(rhs_ty, rhs)),
+            _ => self.variant_rank().cmp(&other.variant_rank()),
+        }
+    }
+
+    /// Feeds the value into `state`, consistently with [`total_eq`](Self::total_eq).
+    pub(crate) fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.variant_rank().hash(state);
+        match *self {
+            ValueRef::Nil => {}
+            ValueRef::Boolean(val) => val.hash(state),
+            ValueRef::Integer(val) => val.hash(state),
+            ValueRef::F32(val) => val.to_bits().hash(state),
+            ValueRef::F64(val) => val.to_bits().hash(state),
+            ValueRef::String(val) => val.as_bytes().hash(state),
+            ValueRef::Binary(val) => val.hash(state),
+            ValueRef::Array(ref vec) => {
+                vec.len().hash(state);
+                for val in vec {
+                    val.total_hash(state);
+                }
+            }
+            ValueRef::Map(ref map) => {
+                map.len().hash(state);
+                for (key, val) in map {
+                    key.total_hash(state);
+                    val.total_hash(state);
+                }
+            }
+            ValueRef::Ext(ty, data) => {
+                ty.hash(state);
+                data.hash(state);
+            }
         }
     }
 
@@ -1380,6 +3505,195 @@ impl ValueRef<'_> {
     }
 }
 
+impl<'a> ValueRef<'a> {
+    /// Returns true if the `ValueRef` is a Null. Returns false otherwise.
+    #[inline]
+    #[must_use]
+    pub fn is_nil(&self) -> bool {
+        matches!(*self, ValueRef::Nil)
+    }
+
+    /// If the `ValueRef` is a Boolean, returns the associated bool.
+    /// Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        if let ValueRef::Boolean(val) = *self {
+            Some(val)
+        } else {
+            None
+        }
+    }
+
+    /// If the `ValueRef` is an integer, return or cast it to a i64.
+    /// Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            ValueRef::Integer(ref n) => n.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is a number, return or cast it to a f64.
+    /// Returns None otherwise.
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            ValueRef::Integer(ref n) => n.as_f64(),
+            ValueRef::F32(n) => Some(From::from(n)),
+            ValueRef::F64(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is a String, returns the associated str, borrowed from the input.
+    /// Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::ValueRef;
+    ///
+    /// let name = {
+    ///     let val = ValueRef::from("le message");
+    ///     val.as_str()
+    /// };
+    ///
+    /// assert_eq!(Some("le message"), name);
+    /// assert_eq!(None, ValueRef::Boolean(true).as_str());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'a str> {
+        match *self {
+            ValueRef::String(Utf8StringRef { s: Ok(val) }) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is a Binary or a String, returns the associated slice, borrowed from the
+    /// input. Returns None otherwise.
+    #[must_use]
+    pub fn as_slice(&self) -> Option<&'a [u8]> {
+        match *self {
+            ValueRef::Binary(val) => Some(val),
+            ValueRef::String(Utf8StringRef { s: Ok(val) }) => Some(val.as_bytes()),
+            ValueRef::String(Utf8StringRef { s: Err((val, _)) }) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// If the `ValueRef` is a Map, returns the associated vector of key-value tuples.
+    /// Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_map(&self) -> Option<&Vec<(Self, Self)>> {
+        if let ValueRef::Map(ref map) = *self {
+            Some(map)
+        } else {
+            None
+        }
+    }
+
+    /// If the `ValueRef` is an Ext, returns the associated tuple with a ty and slice, borrowed
+    /// from the input. Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_ext(&self) -> Option<(i8, &'a [u8])> {
+        if let ValueRef::Ext(ty, buf) = *self {
+            Some((ty, buf))
+        } else {
+            None
+        }
+    }
+
+    /// If the `ValueRef` is an Ext holding a valid timestamp, returns it. Returns None otherwise.
+    #[inline]
+    #[must_use]
+    pub fn as_timestamp(&self) -> Option<Timestamp> {
+        match *self {
+            ValueRef::Ext(TIMESTAMP_EXT_TYPE, buf) => Timestamp::from_ext_data(buf),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the first entry with the given string key, if this is a map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::ValueRef;
+    ///
+    /// let val = ValueRef::Map(vec![(ValueRef::from("id"), ValueRef::from(42))]);
+    ///
+    /// assert_eq!(Some(&ValueRef::from(42)), val.get("id"));
+    /// assert_eq!(None, val.get("name"));
+    /// ```
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        self.as_map()?
+            .iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, val)| val)
+    }
+
+    /// Looks up a nested value by a JSON Pointer (RFC 6901), such as `/config/servers/0/host`.
+    ///
+    /// See [`Value::pointer`] for the pointer syntax.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::decode::read_value_ref;
+    ///
+    /// // {"servers": [{"host": "localhost"}]}
+    /// let buf = [
+    ///     0x81, 0xa7, b's', b'e', b'r', b'v', b'e', b'r', b's', 0x91,
+    ///     0x81, 0xa4, b'h', b'o', b's', b't', 0xa9, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+    /// ];
+    ///
+    /// let val = read_value_ref(&mut &buf[..]).unwrap();
+    /// assert_eq!(Some("localhost"), val.pointer("/servers/0/host").and_then(|v| v.as_str()));
+    /// assert_eq!(None, val.pointer("/servers/1"));
+    /// ```
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&Self> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        pointer.strip_prefix('/')?.split('/').try_fold(self, |val, token| {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            match *val {
+                ValueRef::Array(ref vec) => vec.get(pointer_array_idx(&token)?),
+                ValueRef::Map(ref map) => map.get(pointer_key_idx_ref(map, &token)?).map(|(_, val)| val),
+                _ => None,
+            }
+        })
+    }
+}
+
+impl<'a> Index<usize> for ValueRef<'a> {
+    type Output = Self;
+
+    fn index(&self, index: usize) -> &Self {
+        match *self {
+            ValueRef::Array(ref vec) => vec.get(index).unwrap_or(&NIL_REF),
+            _ => &NIL_REF,
+        }
+    }
+}
+
+impl<'a> Index<&str> for ValueRef<'a> {
+    type Output = Self;
+
+    fn index(&self, index: &str) -> &Self {
+        self.get(index).unwrap_or(&NIL_REF)
+    }
+}
+
 impl From<u8> for ValueRef<'_> {
     #[inline]
     fn from(v: u8) -> Self {
@@ -0,0 +1,98 @@
+/// Constructs a [`Value`](crate::Value) from a JSON-like literal.
+///
+/// Arrays are written as `[...]` and maps as `{key: value, ...}`. Map keys are single tokens, such
+/// as string or integer literals; wrap any other key expression in parentheses. `nil` stands for
+/// [`Value::Nil`](crate::Value::Nil) and `ext(ty, data)` for an extension of type `ty` with the
+/// bytes of `data`. Any other value is an expression converted with `Value::from`, so byte
+/// strings like `b"\x01"` become binaries.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{msgpack, Value};
+///
+/// let id = 42;
+/// let val = msgpack!({
+///     "key": [1, 2, true],
+///     "bin": b"\x01",
+///     "id": id,
+///     "nested": {"none": nil, 7: -1.5},
+///     "ext": ext(2, [0xaa, 0xbb]),
+/// });
+///
+/// assert_eq!(Value::Map(vec![
+///     (Value::from("key"), Value::Array(vec![Value::from(1), Value::from(2), Value::from(true)])),
+///     (Value::from("bin"), Value::Binary(vec![0x01])),
+///     (Value::from("id"), Value::from(42)),
+///     (Value::from("nested"), Value::Map(vec![
+///         (Value::from("none"), Value::Nil),
+///         (Value::from(7), Value::from(-1.5)),
+///     ])),
+///     (Value::from("ext"), Value::Ext(2, vec![0xaa, 0xbb])),
+/// ]), val);
+/// ```
+#[macro_export]
+macro_rules! msgpack {
+    (nil) => {
+        $crate::Value::Nil
+    };
+    ([]) => {
+        $crate::Value::Array(::std::vec::Vec::new())
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::Array($crate::msgpack!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::Value::Map(::std::vec::Vec::new())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::Value::Map($crate::msgpack!(@map [] $($tt)+))
+    };
+    (ext($ty:expr, $data:expr $(,)?)) => {
+        $crate::Value::Ext($ty, <[u8]>::to_vec(&$data[..]))
+    };
+
+    // Array elements, accumulated in brackets until the input is exhausted.
+    (@array [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] nil $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!(nil),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!([$($array)*]),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] {$($map:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!({$($map)*}),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] ext($($ext:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!(ext($($ext)*)),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] $next:expr $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::Value::from($next),] $($($rest)*)?)
+    };
+
+    // Map entries, accumulated in brackets until the input is exhausted.
+    (@map [$($entries:expr,)*]) => {
+        ::std::vec![$($entries,)*]
+    };
+    (@map [$($entries:expr,)*] $key:tt : nil $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@map [$($entries,)* ($crate::Value::from($key), $crate::msgpack!(nil)),] $($($rest)*)?)
+    };
+    (@map [$($entries:expr,)*] $key:tt : [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@map [$($entries,)* ($crate::Value::from($key), $crate::msgpack!([$($array)*])),] $($($rest)*)?)
+    };
+    (@map [$($entries:expr,)*] $key:tt : {$($map:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@map [$($entries,)* ($crate::Value::from($key), $crate::msgpack!({$($map)*})),] $($($rest)*)?)
+    };
+    (@map [$($entries:expr,)*] $key:tt : ext($($ext:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@map [$($entries,)* ($crate::Value::from($key), $crate::msgpack!(ext($($ext)*))),] $($($rest)*)?)
+    };
+    (@map [$($entries:expr,)*] $key:tt : $val:expr $(, $($rest:tt)*)?) => {
+        $crate::msgpack!(@map [$($entries,)* ($crate::Value::from($key), $crate::Value::from($val)),] $($($rest)*)?)
+    };
+
+    ($other:expr) => {
+        $crate::Value::from($other)
+    };
+}
@@ -0,0 +1,138 @@
+//! Constant time key lookups in large maps, see [`MapIndex`].
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+
+use crate::{Value, ValueRef};
+
+/// A hash index over the string keys of a [`Value::Map`], built by [`Value::map_index`].
+///
+/// Maps are stored as vectors of entries, so looking up a key scans the whole map. When many
+/// lookups are made into the same large map, building an index once makes each of them take
+/// constant time instead, while the map itself keeps its order and representation.
+///
+/// As with indexing by `&str`, the first entry wins when a key occurs more than once.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+///
+/// let config = Value::Map((0..1000).map(|i| {
+///     (Value::from(format!("key{i}")), Value::from(i))
+/// }).collect());
+///
+/// let index = config.map_index().unwrap();
+/// assert_eq!(1000, index.len());
+/// assert_eq!(Some(&Value::from(999)), index.get("key999"));
+/// assert_eq!(None, index.get("missing"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct MapIndex<'a> {
+    map: &'a [(Value, Value)],
+    keys: HashMap<&'a str, usize>,
+}
+
+impl<'a> MapIndex<'a> {
+    pub(crate) fn new(map: &'a [(Value, Value)]) -> Self {
+        let mut keys = HashMap::with_capacity(map.len());
+        for (idx, (key, _)) in map.iter().enumerate() {
+            if let Some(key) = key.as_str() {
+                keys.entry(key).or_insert(idx);
+            }
+        }
+        Self { map, keys }
+    }
+
+    /// Returns the value of the entry with the given string key.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&'a Value> {
+        self.position(key).map(|idx| &self.map[idx].1)
+    }
+
+    /// Returns the position of the entry with the given string key within the map.
+    #[must_use]
+    pub fn position(&self, key: &str) -> Option<usize> {
+        self.keys.get(key).copied()
+    }
+
+    /// Returns `true` if the map has an entry with the given string key.
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.keys.contains_key(key)
+    }
+
+    /// Returns the number of distinct string keys in the index.
+    ///
+    /// Entries whose keys are not valid UTF-8 strings are not indexed.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if no key is indexed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// The positions of the keys of a map, grouped by hash, for finding repeated keys without
+/// comparing every pair.
+///
+/// Keys are hashed and compared by [`Value::total_eq`], which is an equivalence relation for every
+/// type of key, floats and containers included, so each lookup takes constant time on average.
+#[derive(Default)]
+pub(crate) struct KeySet {
+    groups: HashMap<u64, Vec<usize>>,
+}
+
+impl KeySet {
+    /// Records that `key` is at position `idx` of the map.
+    pub(crate) fn insert<K: MapKey>(&mut self, key: &K, idx: usize) {
+        self.groups.entry(key_hash(key)).or_default().push(idx);
+    }
+
+    /// Returns the position of the first entry of `map` equal to `key`, among the recorded ones.
+    pub(crate) fn find<K: MapKey>(&self, map: &[(K, K)], key: &K) -> Option<usize> {
+        let group = self.groups.get(&key_hash(key))?;
+        group.iter().copied().find(|&idx| map[idx].0.total_eq(key))
+    }
+}
+
+/// A map key that can be stored in a [`KeySet`].
+pub(crate) trait MapKey {
+    fn total_eq(&self, other: &Self) -> bool;
+    fn total_hash(&self, state: &mut DefaultHasher);
+}
+
+impl MapKey for Value {
+    #[inline]
+    fn total_eq(&self, other: &Self) -> bool {
+        self.total_eq(other)
+    }
+
+    #[inline]
+    fn total_hash(&self, state: &mut DefaultHasher) {
+        self.total_hash(state);
+    }
+}
+
+impl MapKey for ValueRef<'_> {
+    #[inline]
+    fn total_eq(&self, other: &Self) -> bool {
+        self.total_eq(other)
+    }
+
+    #[inline]
+    fn total_hash(&self, state: &mut DefaultHasher) {
+        self.total_hash(state);
+    }
+}
+
+fn key_hash<K: MapKey>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.total_hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,51 @@
+//! [`Arbitrary`] implementations for proptest, for generating random values in property tests.
+//!
+//! Generated values are bounded like the ones of the `arbitrary` feature, in both nesting depth
+//! and container length.
+
+use ::proptest::arbitrary::{any, Arbitrary};
+use ::proptest::collection::vec;
+use ::proptest::prop_oneof;
+use ::proptest::strategy::{BoxedStrategy, Just, Strategy};
+
+use crate::{Integer, Value};
+
+/// The maximum nesting depth of a generated value.
+const MAX_DEPTH: u32 = 4;
+/// The maximum length of a generated array, map, binary or string.
+const MAX_LEN: usize = 16;
+
+impl Arbitrary for Integer {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        prop_oneof![any::<u64>().prop_map(Self::from), any::<i64>().prop_map(Self::from)].boxed()
+    }
+}
+
+impl Arbitrary for Value {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        let leaf = prop_oneof![
+            Just(Self::Nil),
+            any::<bool>().prop_map(Self::Boolean),
+            any::<Integer>().prop_map(Self::Integer),
+            any::<f32>().prop_map(Self::F32),
+            any::<f64>().prop_map(Self::F64),
+            vec(any::<char>(), 0..=MAX_LEN).prop_map(|s| Self::from(s.into_iter().collect::<String>())),
+            vec(any::<u8>(), 0..=MAX_LEN).prop_map(Self::Binary),
+            (any::<i8>(), vec(any::<u8>(), 0..=MAX_LEN)).prop_map(|(ty, data)| Self::from_ext(ty, data)),
+        ];
+
+        leaf.prop_recursive(MAX_DEPTH, 256, MAX_LEN as u32, |inner| {
+            prop_oneof![
+                vec(inner.clone(), 0..=MAX_LEN).prop_map(Self::Array),
+                vec((inner.clone(), inner), 0..=MAX_LEN).prop_map(Self::Map),
+            ]
+        })
+        .boxed()
+    }
+}
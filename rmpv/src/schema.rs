@@ -0,0 +1,320 @@
+//! Declarative description of the expected shape of values, see [`Schema`].
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::decode;
+use crate::{push_pointer_key, Value};
+
+/// The expected shape of a value.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::schema::{Field, Schema};
+///
+/// // {"id": <integer>, "tags": [<string>, ...], "note": <any, optional>}
+/// let schema = Schema::Map(vec![
+///     Field::required("id", Schema::Integer),
+///     Field::required("tags", Schema::Array(Box::new(Schema::String))),
+///     Field::optional("note", Schema::Any),
+/// ]);
+///
+/// // {"port": <integer in 1..=65535>, "name": <string of up to 8 bytes>}
+/// let schema = Schema::Map(vec![
+///     Field::required("port", Schema::IntegerIn { min: 1, max: 65535 }),
+///     Field::required("name", Schema::MaxLen(8, Box::new(Schema::String))),
+/// ]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Schema {
+    /// Accepts any value.
+    Any,
+    /// Accepts only nil.
+    Nil,
+    /// Accepts booleans.
+    Boolean,
+    /// Accepts integers.
+    Integer,
+    /// Accepts both 32 and 64-bit floats.
+    Float,
+    /// Accepts strings, including ones that are not valid UTF-8.
+    String,
+    /// Accepts binaries.
+    Binary,
+    /// Accepts extensions of any type.
+    Ext,
+    /// Accepts arrays whose elements all match the enclosed schema.
+    Array(Box<Schema>),
+    /// Accepts maps containing the listed fields, keyed by strings.
+    ///
+    /// Keys that are not listed are allowed and accept any value.
+    Map(Vec<Field>),
+    /// Accepts integers between `min` and `max`, inclusive.
+    IntegerIn {
+        /// The smallest accepted integer.
+        min: i64,
+        /// The largest accepted integer.
+        max: i64,
+    },
+    /// Accepts floats between `min` and `max`, inclusive. NaN is never accepted.
+    FloatIn {
+        /// The smallest accepted float.
+        min: f64,
+        /// The largest accepted float.
+        max: f64,
+    },
+    /// Accepts values matching the enclosed schema, whose length is at most the given one.
+    ///
+    /// The length of strings is counted in bytes, the length of extensions is the length of their
+    /// data, and the length of maps is their number of entries.
+    MaxLen(usize, Box<Schema>),
+}
+
+impl Schema {
+    /// Returns the name of the type this schema accepts, as used in error messages.
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            Self::Any => "any",
+            Self::Nil => "nil",
+            Self::Boolean => "bool",
+            Self::Integer | Self::IntegerIn { .. } => "integer",
+            Self::Float | Self::FloatIn { .. } => "float",
+            Self::String => "string",
+            Self::Binary => "binary",
+            Self::Ext => "ext",
+            Self::Array(..) => "array",
+            Self::Map(..) => "map",
+            Self::MaxLen(_, ref schema) => schema.type_name(),
+        }
+    }
+
+    /// Validates an already decoded value, returning every violation found.
+    ///
+    /// This performs the same checks as [`read_value_validated_all`] does while decoding. Since
+    /// there is no input, the [`offset`](ValidationError::offset) of the errors is always zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns all violations, in the order of the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::schema::{Field, Schema, ValidationErrorKind};
+    /// use rmpv::Value;
+    ///
+    /// let schema = Schema::Array(Box::new(Schema::Map(vec![
+    ///     Field::required("id", Schema::IntegerIn { min: 0, max: 100 }),
+    /// ])));
+    /// let val = Value::Array(vec![
+    ///     Value::Map(vec![(Value::from("id"), Value::from(1))]),
+    ///     Value::Map(vec![(Value::from("id"), Value::from(101))]),
+    ///     Value::Map(vec![]),
+    /// ]);
+    ///
+    /// let errs = schema.validate(&val).unwrap_err();
+    ///
+    /// assert_eq!(2, errs.len());
+    /// assert_eq!("/1/id", errs[0].path);
+    /// assert!(matches!(errs[0].kind, ValidationErrorKind::OutOfRange));
+    /// assert_eq!("/2", errs[1].path);
+    /// assert!(matches!(errs[1].kind, ValidationErrorKind::MissingField(ref name) if name == "id"));
+    /// ```
+    ///
+    /// [`read_value_validated_all`]: crate::decode::read_value_validated_all
+    pub fn validate(&self, val: &Value) -> Result<(), Vec<ValidationError>> {
+        let mut path = String::new();
+        let mut errors = Vec::new();
+        self.validate_inner(val, &mut path, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_inner(&self, val: &Value, path: &mut String, errors: &mut Vec<ValidationError>) {
+        match (self, val) {
+            (Self::MaxLen(_, schema), _) => {
+                schema.validate_inner(val, path, errors);
+                if let Some(kind) = self.check_len(val) {
+                    errors.push(ValidationError { path: path.clone(), offset: 0, kind });
+                }
+            }
+            (Self::Array(elem), Value::Array(vec)) => {
+                for (idx, val) in vec.iter().enumerate() {
+                    let prefix = path.len();
+                    path.push('/');
+                    path.push_str(&idx.to_string());
+                    elem.validate_inner(val, path, errors);
+                    path.truncate(prefix);
+                }
+            }
+            (Self::Map(fields), Value::Map(map)) => {
+                for (key, val) in map {
+                    let field = key.as_str().and_then(|key| fields.iter().find(|field| field.name == key));
+                    if let Some(field) = field {
+                        let prefix = path.len();
+                        push_pointer_key(path, key);
+                        field.schema.validate_inner(val, path, errors);
+                        path.truncate(prefix);
+                    }
+                }
+                for field in fields {
+                    if field.required && !map.iter().any(|(key, _)| key.as_str() == Some(&*field.name)) {
+                        let kind = ValidationErrorKind::MissingField(field.name.clone());
+                        errors.push(ValidationError { path: path.clone(), offset: 0, kind });
+                    }
+                }
+            }
+            _ => {
+                if let Some(kind) = self.check(val) {
+                    errors.push(ValidationError { path: path.clone(), offset: 0, kind });
+                }
+            }
+        }
+    }
+
+    /// Checks the type and range of `val`, without looking at its contents.
+    pub(crate) fn check(&self, val: &Value) -> Option<ValidationErrorKind> {
+        if !self.accepts_type(val) {
+            return Some(ValidationErrorKind::TypeMismatch { expected: self.type_name(), found: val.type_name() });
+        }
+
+        let in_range = match *self {
+            Self::IntegerIn { min, max } => val.as_i64().is_some_and(|n| min <= n && n <= max),
+            Self::FloatIn { min, max } => val.as_f64().is_some_and(|n| min <= n && n <= max),
+            _ => true,
+        };
+        if !in_range {
+            return Some(ValidationErrorKind::OutOfRange);
+        }
+        None
+    }
+
+    /// Checks the length of `val` against a [`MaxLen`](Self::MaxLen) schema.
+    pub(crate) fn check_len(&self, val: &Value) -> Option<ValidationErrorKind> {
+        let max = match *self {
+            Self::MaxLen(max, _) => max,
+            _ => return None,
+        };
+        let len = match *val {
+            Value::String(ref s) => s.as_bytes().len(),
+            Value::Binary(ref buf) | Value::Ext(_, ref buf) => buf.len(),
+            Value::Array(ref vec) => vec.len(),
+            Value::Map(ref map) => map.len(),
+            _ => return None,
+        };
+        if len > max {
+            return Some(ValidationErrorKind::TooLong { max, len });
+        }
+        None
+    }
+
+    /// Returns `true` if the type of `val` is accepted, without looking at its contents.
+    pub(crate) fn accepts_type(&self, val: &Value) -> bool {
+        matches!(
+            (self, val),
+            (Self::Any, _) |
+            (Self::Nil, Value::Nil) |
+            (Self::Boolean, Value::Boolean(..)) |
+            (Self::Integer | Self::IntegerIn { .. }, Value::Integer(..)) |
+            (Self::Float | Self::FloatIn { .. }, Value::F32(..) | Value::F64(..)) |
+            (Self::String, Value::String(..)) |
+            (Self::Binary, Value::Binary(..)) |
+            (Self::Ext, Value::Ext(..) | Value::Timestamp(..)) |
+            (Self::Array(..), Value::Array(..)) |
+            (Self::Map(..), Value::Map(..))
+        ) || matches!(*self, Self::MaxLen(_, ref schema) if schema.accepts_type(val))
+    }
+}
+
+/// A string-keyed entry of a [`Schema::Map`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub(crate) name: String,
+    pub(crate) schema: Schema,
+    pub(crate) required: bool,
+}
+
+impl Field {
+    /// Creates a field that must be present in the map.
+    #[must_use]
+    pub fn required<S: Into<String>>(name: S, schema: Schema) -> Self {
+        Self { name: name.into(), schema, required: true }
+    }
+
+    /// Creates a field that may be missing from the map, but must match `schema` when present.
+    #[must_use]
+    pub fn optional<S: Into<String>>(name: S, schema: Schema) -> Self {
+        Self { name: name.into(), schema, required: false }
+    }
+}
+
+/// A violation of a [`Schema`], found while decoding or validating a value.
+#[derive(Debug)]
+pub struct ValidationError {
+    /// The location of the offending value, as a JSON Pointer like [`Value::query`] uses.
+    pub path: String,
+    /// The byte offset at which the offending value starts in the input, or zero for errors
+    /// reported by [`Schema::validate`].
+    pub offset: u64,
+    /// What is wrong with the value.
+    pub kind: ValidationErrorKind,
+}
+
+/// This type represents all possible kinds of [`ValidationError`]s.
+#[derive(Debug)]
+pub enum ValidationErrorKind {
+    /// The value has a different type than the schema expects.
+    TypeMismatch {
+        /// The type accepted by the schema.
+        expected: &'static str,
+        /// The type of the value, as returned by [`Value::type_name`].
+        found: &'static str,
+    },
+    /// A required field is missing from the map.
+    MissingField(String),
+    /// The value is outside the range of a [`Schema::IntegerIn`] or [`Schema::FloatIn`].
+    OutOfRange,
+    /// The value is longer than a [`Schema::MaxLen`] allows.
+    TooLong {
+        /// The longest accepted length.
+        max: usize,
+        /// The length of the value.
+        len: usize,
+    },
+    /// The input could not be decoded at all. This is always the last error reported.
+    Decode(decode::Error),
+}
+
+impl error::Error for ValidationError {
+    #[cold]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.kind {
+            ValidationErrorKind::Decode(ref err) => Some(err),
+            ValidationErrorKind::TypeMismatch { .. } |
+            ValidationErrorKind::MissingField(..) |
+            ValidationErrorKind::OutOfRange |
+            ValidationErrorKind::TooLong { .. } => None,
+        }
+    }
+}
+
+impl Display for ValidationError {
+    #[cold]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self.kind {
+            ValidationErrorKind::TypeMismatch { expected, found } => {
+                write!(fmt, "expected {expected}, found {found}")?;
+            }
+            ValidationErrorKind::MissingField(ref name) => write!(fmt, "missing field {name:?}")?,
+            ValidationErrorKind::OutOfRange => fmt.write_str("value out of range")?,
+            ValidationErrorKind::TooLong { max, len } => write!(fmt, "length {len} exceeds {max}")?,
+            ValidationErrorKind::Decode(ref err) => write!(fmt, "{err}")?,
+        }
+        write!(fmt, " at {:?} (byte offset {})", self.path, self.offset)
+    }
+}
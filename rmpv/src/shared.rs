@@ -0,0 +1,101 @@
+//! An immutable value shared between owners, see [`SharedValue`].
+
+use std::fmt::{self, Display};
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::Value;
+
+/// An immutable [`Value`] that is cheap to clone.
+///
+/// Cloning only increments a reference count, so a decoded message can be handed to many threads
+/// or tasks without copying the tree. The value is accessed through `Deref`, and can be turned
+/// back into an owned `Value` with [`into_value`](Self::into_value), which only copies it if
+/// other clones are still alive.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// use rmpv::{SharedValue, Value};
+///
+/// let msg = SharedValue::from(Value::Array(vec![Value::from(1); 1000]));
+///
+/// let workers: Vec<_> = (0..4).map(|_| {
+///     let msg = msg.clone();
+///     thread::spawn(move || msg.as_array().unwrap().len())
+/// }).collect();
+///
+/// for worker in workers {
+///     assert_eq!(1000, worker.join().unwrap());
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SharedValue(Arc<Value>);
+
+impl SharedValue {
+    /// Wraps `val` to be shared.
+    #[inline]
+    #[must_use]
+    pub fn new(val: Value) -> Self {
+        Self(Arc::new(val))
+    }
+
+    /// Returns the value, without copying it if this is the only clone left.
+    #[must_use]
+    pub fn into_value(self) -> Value {
+        Arc::try_unwrap(self.0).unwrap_or_else(|val| (*val).clone())
+    }
+
+    /// Returns `true` if both share the same value, rather than merely equal ones.
+    #[inline]
+    #[must_use]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for SharedValue {
+    type Target = Value;
+
+    #[inline]
+    fn deref(&self) -> &Value {
+        &self.0
+    }
+}
+
+impl AsRef<Value> for SharedValue {
+    #[inline]
+    fn as_ref(&self) -> &Value {
+        &self.0
+    }
+}
+
+impl From<Value> for SharedValue {
+    #[inline]
+    fn from(val: Value) -> Self {
+        Self::new(val)
+    }
+}
+
+impl From<Arc<Value>> for SharedValue {
+    #[inline]
+    fn from(val: Arc<Value>) -> Self {
+        Self(val)
+    }
+}
+
+impl From<SharedValue> for Value {
+    #[inline]
+    fn from(val: SharedValue) -> Self {
+        val.into_value()
+    }
+}
+
+impl Display for SharedValue {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        Display::fmt(&*self.0, fmt)
+    }
+}
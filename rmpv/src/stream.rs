@@ -0,0 +1,228 @@
+//! Utilities for processing streams of concatenated MessagePack values.
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, ErrorKind, Read, Write};
+
+use crate::decode::options::Limits;
+use crate::decode::{self, item_header, read_value, read_value_with_options, DecodeOptions};
+use crate::encode::{self, write_value};
+use crate::Value;
+
+/// This type represents all possible errors that can occur when transforming a stream.
+#[derive(Debug)]
+pub enum Error {
+    /// Error while reading or decoding an input value.
+    Decode(decode::Error),
+    /// Error while writing an output value.
+    Encode(encode::Error),
+}
+
+impl error::Error for Error {
+    #[cold]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::Decode(ref err) => Some(err),
+            Self::Encode(ref err) => Some(err),
+        }
+    }
+}
+
+impl Display for Error {
+    #[cold]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Self::Decode(ref err) => write!(fmt, "error while decoding input value: {err}"),
+            Self::Encode(ref err) => write!(fmt, "error while encoding output value: {err}"),
+        }
+    }
+}
+
+impl From<decode::Error> for Error {
+    #[cold]
+    fn from(err: decode::Error) -> Self {
+        Self::Decode(err)
+    }
+}
+
+impl From<encode::Error> for Error {
+    #[cold]
+    fn from(err: encode::Error) -> Self {
+        Self::Encode(err)
+    }
+}
+
+/// Reads values from `rd` until EOF, passing each one through `f` and writing the result to `wr`.
+///
+/// Reaching EOF between two values ends the stream successfully, while EOF in the middle of a
+/// value is reported as an error.
+///
+/// # Errors
+///
+/// Returns [`Error::Decode`] if the input is not a valid stream of MessagePack values, and
+/// [`Error::Encode`] on any I/O error while writing. The output is not flushed.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+///
+/// // The values `1`, `nil` and `"a"`.
+/// let input = [0x01, 0xc0, 0xa1, b'a'];
+/// let mut output = Vec::new();
+///
+/// rmpv::transform_stream(&mut &input[..], &mut output, |val| match val {
+///     Value::Nil => Value::from(0),
+///     val => val,
+/// }).unwrap();
+///
+/// assert_eq!(vec![0x01, 0x00, 0xa1, b'a'], output);
+/// ```
+pub fn transform_stream<R, W, F>(rd: &mut R, wr: &mut W, mut f: F) -> Result<(), Error>
+    where R: Read,
+          W: Write,
+          F: FnMut(Value) -> Value
+{
+    loop {
+        // Peek a single byte to tell a clean EOF apart from a truncated value.
+        let mut marker = [0; 1];
+        match rd.read(&mut marker) {
+            Ok(0) => return Ok(()),
+            Ok(..) => {}
+            Err(ref err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(Error::Decode(decode::Error::InvalidMarkerRead(err))),
+        }
+
+        let val = read_value(&mut io::Read::chain(&marker[..], &mut *rd))?;
+        write_value(wr, &f(val))?;
+    }
+}
+
+/// The outcome of [`ValueDecoder::decode`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decoded {
+    /// A complete value was decoded and removed from the buffer.
+    Value(Value),
+    /// The buffered data does not hold a complete value yet.
+    NeedMore,
+}
+
+/// A push-style decoder for a stream of values arriving in arbitrary chunks, such as from a
+/// non-blocking socket.
+///
+/// Chunks are appended with [`feed`](Self::feed), and [`decode`](Self::decode) yields the values
+/// as soon as they are fully buffered. Incomplete values are only scanned for their lengths, not
+/// decoded, and the scan resumes where it stopped when more data arrives, so a value split into
+/// many chunks costs about the same as one arriving at once.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::stream::{Decoded, ValueDecoder};
+/// use rmpv::Value;
+///
+/// let mut decoder = ValueDecoder::new();
+///
+/// // The string "abc" followed by the first byte of `[1]`.
+/// decoder.feed(&[0xa3, b'a']);
+/// assert_eq!(Decoded::NeedMore, decoder.decode().unwrap());
+///
+/// decoder.feed(&[b'b', b'c', 0x91]);
+/// assert_eq!(Decoded::Value(Value::from("abc")), decoder.decode().unwrap());
+/// assert_eq!(Decoded::NeedMore, decoder.decode().unwrap());
+///
+/// decoder.feed(&[0x01]);
+/// assert_eq!(Decoded::Value(Value::Array(vec![Value::from(1)])), decoder.decode().unwrap());
+/// assert_eq!(0, decoder.buffered());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ValueDecoder {
+    buf: Vec<u8>,
+    /// The start of the next value within `buf`.
+    start: usize,
+    /// How far the next value has been scanned, ending on an item boundary.
+    scanned: usize,
+    /// The number of items of the next value that are not scanned yet.
+    pending: u64,
+    opts: DecodeOptions,
+}
+
+impl ValueDecoder {
+    /// Creates a decoder using the default [`DecodeOptions`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_options(DecodeOptions::new())
+    }
+
+    /// Creates a decoder enforcing the limits and policies of `opts` on every value.
+    ///
+    /// The [`max_len`](DecodeOptions::max_len) limit is also checked while scanning, so that a
+    /// value declaring a huge length is rejected before its data is buffered.
+    #[must_use]
+    pub fn with_options(opts: DecodeOptions) -> Self {
+        Self { buf: Vec::new(), start: 0, scanned: 0, pending: 1, opts }
+    }
+
+    /// Appends a chunk of input to the buffer.
+    pub fn feed(&mut self, data: &[u8]) {
+        if self.start > 0 {
+            // Drop the bytes of the values already decoded.
+            self.buf.drain(..self.start);
+            self.scanned -= self.start;
+            self.start = 0;
+        }
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Returns the number of bytes fed, but not yet decoded.
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        self.buf.len() - self.start
+    }
+
+    /// Decodes the next value, if it is completely buffered.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`decode::Error`] if the input is not valid or exceeds the limits of the options.
+    /// The stream can not be resynchronized afterwards, so the decoder should be discarded.
+    pub fn decode(&mut self) -> Result<Decoded, decode::Error> {
+        let limits = Limits::new(&self.opts);
+        while self.pending > 0 {
+            let (size, children) = match item_size(&self.buf[self.scanned..], &limits)? {
+                Some((size, children)) if size <= self.buf.len() - self.scanned => (size, children),
+                _ => return Ok(Decoded::NeedMore),
+            };
+            self.scanned += size;
+            self.pending = self.pending - 1 + children;
+        }
+
+        let val = read_value_with_options(&mut &self.buf[self.start..self.scanned], &self.opts)?;
+        self.start = self.scanned;
+        self.pending = 1;
+        Ok(Decoded::Value(val))
+    }
+}
+
+impl Default for ValueDecoder {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the size in bytes of the item at the start of `buf`, excluding nested values, and the
+/// number of nested values following it. Returns None if `buf` is too short to tell.
+fn item_size(buf: &[u8], limits: &Limits) -> Result<Option<(usize, u64)>, decode::Error> {
+    let (header, len, nested) = match item_header(buf) {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    limits.check_len(len)?;
+
+    if nested == 0 {
+        Ok(Some((header.saturating_add(len), 0)))
+    } else {
+        Ok(Some((header, len as u64 * nested)))
+    }
+}
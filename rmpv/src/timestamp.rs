@@ -0,0 +1,205 @@
+//! The MessagePack timestamp extension type, see [`Timestamp`].
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The extension type reserved for timestamps by the MessagePack specification.
+pub const TIMESTAMP_EXT_TYPE: i8 = -1;
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// An instant in time, stored as the MessagePack timestamp extension type `-1`.
+///
+/// A timestamp is a number of seconds since the Unix epoch, which may be negative, plus a number
+/// of nanoseconds within that second. It keeps the bytes it was decoded from, so re-encoding it
+/// reproduces the input exactly, even for the longer of the 32, 64 and 96-bit forms. Comparisons
+/// only look at the instant, not at its encoding.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Timestamp;
+///
+/// let ts = Timestamp::new(1_700_000_000, 500).unwrap();
+/// assert_eq!(1_700_000_000, ts.secs());
+/// assert_eq!(500, ts.nanos());
+///
+/// // The shortest form that fits is used, here the 64-bit one.
+/// assert_eq!(8, ts.as_ext_data().len());
+/// assert_eq!(Some(ts), Timestamp::from_ext_data(ts.as_ext_data()));
+/// ```
+#[derive(Clone, Copy)]
+pub struct Timestamp {
+    data: [u8; 12],
+    len: u8,
+}
+
+impl Timestamp {
+    /// Creates a timestamp from seconds since the Unix epoch and additional nanoseconds.
+    ///
+    /// Returns `None` if `nanos` is not less than 1 000 000 000.
+    #[must_use]
+    pub fn new(secs: i64, nanos: u32) -> Option<Self> {
+        if nanos >= NANOS_PER_SEC {
+            return None;
+        }
+
+        let mut data = [0; 12];
+        let len = match u64::try_from(secs) {
+            Ok(secs) if nanos == 0 && secs <= u64::from(u32::MAX) => {
+                data[..4].copy_from_slice(&(secs as u32).to_be_bytes());
+                4
+            }
+            Ok(secs) if secs < 1 << 34 => {
+                data[..8].copy_from_slice(&(u64::from(nanos) << 34 | secs).to_be_bytes());
+                8
+            }
+            _ => {
+                data[..4].copy_from_slice(&nanos.to_be_bytes());
+                data[4..].copy_from_slice(&secs.to_be_bytes());
+                12
+            }
+        };
+        Some(Self { data, len })
+    }
+
+    /// Parses the data of a timestamp extension in any of the 32, 64 or 96-bit forms.
+    ///
+    /// Returns `None` if the data has another length, or holds out of range nanoseconds.
+    #[must_use]
+    pub fn from_ext_data(data: &[u8]) -> Option<Self> {
+        let mut buf = [0; 12];
+        buf.get_mut(..data.len())?.copy_from_slice(data);
+        let ts = Self { data: buf, len: data.len() as u8 };
+
+        match data.len() {
+            4 | 8 | 12 if ts.nanos() < NANOS_PER_SEC => Some(ts),
+            _ => None,
+        }
+    }
+
+    /// Returns the encoded data of the timestamp extension, without the type.
+    #[inline]
+    #[must_use]
+    pub fn as_ext_data(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+
+    /// Returns the number of whole seconds since the Unix epoch.
+    #[must_use]
+    pub fn secs(&self) -> i64 {
+        match self.len {
+            4 => i64::from(u32::from_be_bytes([self.data[0], self.data[1], self.data[2], self.data[3]])),
+            8 => (self.data64() & ((1 << 34) - 1)) as i64,
+            _ => i64::from_be_bytes(self.data[4..].try_into().unwrap()),
+        }
+    }
+
+    /// Returns the number of nanoseconds within the second, always less than 1 000 000 000.
+    #[must_use]
+    pub fn nanos(&self) -> u32 {
+        match self.len {
+            4 => 0,
+            8 => (self.data64() >> 34) as u32,
+            _ => u32::from_be_bytes([self.data[0], self.data[1], self.data[2], self.data[3]]),
+        }
+    }
+
+    /// Converts the timestamp into a `SystemTime`.
+    ///
+    /// Returns `None` if the instant can not be represented on this platform.
+    #[must_use]
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let secs = Duration::from_secs(self.secs().unsigned_abs());
+        if self.secs() >= 0 {
+            UNIX_EPOCH.checked_add(secs)?.checked_add(Duration::from_nanos(self.nanos().into()))
+        } else {
+            UNIX_EPOCH.checked_sub(secs)?.checked_add(Duration::from_nanos(self.nanos().into()))
+        }
+    }
+
+    /// Converts a `SystemTime` into a timestamp.
+    ///
+    /// Returns `None` if the number of seconds does not fit into an `i64`.
+    #[must_use]
+    pub fn from_system_time(time: SystemTime) -> Option<Self> {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(dur) => Self::new(i64::try_from(dur.as_secs()).ok()?, dur.subsec_nanos()),
+            Err(err) => {
+                // Before the epoch, the nanoseconds count forward from an earlier second.
+                let dur = err.duration();
+                let secs = i64::try_from(dur.as_secs()).ok()?;
+                match dur.subsec_nanos() {
+                    0 => Self::new(-secs, 0),
+                    nanos => Self::new((-secs).checked_sub(1)?, NANOS_PER_SEC - nanos),
+                }
+            }
+        }
+    }
+
+    fn data64(&self) -> u64 {
+        u64::from_be_bytes(self.data[..8].try_into().unwrap())
+    }
+}
+
+impl Debug for Timestamp {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        fmt.debug_struct("Timestamp")
+            .field("secs", &self.secs())
+            .field("nanos", &self.nanos())
+            .finish()
+    }
+}
+
+impl PartialEq for Timestamp {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (self.secs(), self.nanos()) == (other.secs(), other.nanos())
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.secs(), self.nanos()).cmp(&(other.secs(), other.nanos()))
+    }
+}
+
+impl Hash for Timestamp {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.secs(), self.nanos()).hash(state);
+    }
+}
+
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = SystemTime;
+
+    /// Converts a `SystemTime` into a timestamp, returning it back if it is out of range.
+    #[inline]
+    fn try_from(time: SystemTime) -> Result<Self, SystemTime> {
+        Self::from_system_time(time).ok_or(time)
+    }
+}
+
+impl TryFrom<Timestamp> for SystemTime {
+    type Error = Timestamp;
+
+    /// Converts a timestamp into a `SystemTime`, returning it back if it is out of range.
+    #[inline]
+    fn try_from(ts: Timestamp) -> Result<Self, Timestamp> {
+        ts.to_system_time().ok_or(ts)
+    }
+}
@@ -0,0 +1,93 @@
+//! Traversal of value trees, see [`Value::walk`] and [`Value::walk_mut`].
+
+use crate::Value;
+
+/// Tells a walk how to proceed after visiting a value or a map entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Walk {
+    /// Visits the nested values, then carries on with the following ones.
+    Continue,
+    /// Carries on with the following values, without visiting the nested ones.
+    SkipChildren,
+    /// Ends the walk, without visiting any other value.
+    Stop,
+}
+
+/// A visitor of the values of a tree, passed to [`Value::walk`].
+///
+/// Values are visited depth-first, each one before its nested values. Map entries are first
+/// offered to [`visit_entry`](Self::visit_entry), which sees the key along with the value.
+pub trait ValueVisitor {
+    /// Called for every value of the tree, including the root.
+    fn visit(&mut self, val: &Value) -> Walk;
+
+    /// Called for every entry of a map, before visiting its key and value.
+    ///
+    /// Returning [`Walk::SkipChildren`] skips both the key and the value.
+    #[inline]
+    fn visit_entry(&mut self, key: &Value, val: &Value) -> Walk {
+        let _ = (key, val);
+        Walk::Continue
+    }
+}
+
+/// A visitor that may modify the values of a tree, passed to [`Value::walk_mut`].
+///
+/// Values are visited in the same order as by [`ValueVisitor`]. The nested values are visited
+/// after the visitor returns, so a value replaced by the visitor has the nested values of its
+/// replacement visited.
+pub trait ValueVisitorMut {
+    /// Called for every value of the tree, including the root.
+    fn visit_mut(&mut self, val: &mut Value) -> Walk;
+
+    /// Called for every entry of a map, before visiting its key and value.
+    ///
+    /// Returning [`Walk::SkipChildren`] skips both the key and the value.
+    #[inline]
+    fn visit_entry_mut(&mut self, key: &mut Value, val: &mut Value) -> Walk {
+        let _ = (key, val);
+        Walk::Continue
+    }
+}
+
+/// Walks the tree rooted at `val`, returning `false` if the visitor stopped it.
+pub(crate) fn walk<V>(val: &Value, visitor: &mut V) -> bool
+    where V: ValueVisitor + ?Sized
+{
+    match visitor.visit(val) {
+        Walk::Continue => {}
+        Walk::SkipChildren => return true,
+        Walk::Stop => return false,
+    }
+
+    match *val {
+        Value::Array(ref vec) => vec.iter().all(|val| walk(val, visitor)),
+        Value::Map(ref map) => map.iter().all(|(key, val)| match visitor.visit_entry(key, val) {
+            Walk::Continue => walk(key, visitor) && walk(val, visitor),
+            Walk::SkipChildren => true,
+            Walk::Stop => false,
+        }),
+        _ => true,
+    }
+}
+
+/// Walks the tree rooted at `val`, returning `false` if the visitor stopped it.
+pub(crate) fn walk_mut<V>(val: &mut Value, visitor: &mut V) -> bool
+    where V: ValueVisitorMut + ?Sized
+{
+    match visitor.visit_mut(val) {
+        Walk::Continue => {}
+        Walk::SkipChildren => return true,
+        Walk::Stop => return false,
+    }
+
+    match *val {
+        Value::Array(ref mut vec) => vec.iter_mut().all(|val| walk_mut(val, visitor)),
+        Value::Map(ref mut map) => map.iter_mut().all(|(key, val)| match visitor.visit_entry_mut(key, val) {
+            Walk::Continue => walk_mut(key, visitor) && walk_mut(val, visitor),
+            Walk::SkipChildren => true,
+            Walk::Stop => false,
+        }),
+        _ => true,
+    }
+}
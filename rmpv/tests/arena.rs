@@ -0,0 +1,62 @@
+#![cfg(feature = "bumpalo")]
+
+use bumpalo::Bump;
+use rmpv::decode::{read_value_in, read_value_in_interned, Error, KeyInterner};
+use rmpv::encode::write_value;
+use rmpv::{ArenaValue, Timestamp, Value};
+
+#[test]
+fn read_value_in_arena_matches_read_value() {
+    let val = Value::Map(vec![
+        (Value::from("a"), Value::Array(vec![Value::from(-1), Value::F64(0.5), Value::Nil])),
+        (Value::from("b"), Value::Binary(vec![7; 100_000])),
+        (Value::from(1), Value::Ext(5, vec![1, 2, 3])),
+        (Value::from(2), Value::Timestamp(Timestamp::new(1, 0).unwrap())),
+        (Value::from(3), Value::from("c")),
+    ]);
+    let mut buf = Vec::new();
+    write_value(&mut buf, &val).unwrap();
+
+    let bump = Bump::new();
+    let arena = read_value_in(&bump, &mut &buf[..]).unwrap();
+
+    assert_eq!(val, arena.to_owned());
+    assert_eq!(Some(-1), arena["a"][0].as_i64());
+    assert_eq!(Some(&[7; 100_000][..]), arena["b"].as_slice());
+    assert_eq!(ArenaValue::Nil, arena["missing"][3]);
+}
+
+#[test]
+fn read_value_in_arena_fails_on_truncated_input() {
+    let bump = Bump::new();
+
+    assert!(matches!(read_value_in(&bump, &mut &[0x92, 0x01][..]), Err(Error::InvalidMarkerRead(..))));
+    assert!(matches!(read_value_in(&bump, &mut &[0xc6, 0xff, 0xff, 0xff, 0xff, 0x00][..]), Err(Error::InvalidDataRead(..))));
+}
+
+#[test]
+fn read_value_in_interned_shares_keys() {
+    let record = |id: i64| Value::Map(vec![
+        (Value::from("id"), Value::from(id)),
+        (Value::from("id"), Value::from("id")),
+        (Value::from("x".repeat(65)), Value::Nil),
+    ]);
+    let mut buf = Vec::new();
+    for id in 0..3 {
+        write_value(&mut buf, &record(id)).unwrap();
+    }
+
+    let bump = Bump::new();
+    let mut interner = KeyInterner::new();
+    let mut rd = &buf[..];
+    let vals: Vec<_> = (0..3).map(|_| read_value_in_interned(&bump, &mut rd, &mut interner).unwrap()).collect();
+
+    for (id, val) in vals.iter().enumerate() {
+        assert_eq!(record(id as i64), val.to_owned());
+    }
+    let entry = |idx: usize, entry: usize| vals[idx].as_map().unwrap()[entry];
+    assert_eq!(entry(0, 0).0.as_str().unwrap().as_ptr(), entry(2, 1).0.as_str().unwrap().as_ptr());
+    assert_ne!(entry(0, 0).0.as_str().unwrap().as_ptr(), entry(0, 1).1.as_str().unwrap().as_ptr());
+    assert_eq!(entry(0, 2).0.as_str().unwrap().as_ptr(), entry(1, 2).0.as_str().unwrap().as_ptr());
+    assert_eq!(2, interner.len());
+}
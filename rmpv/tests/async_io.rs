@@ -0,0 +1,34 @@
+#![cfg(feature = "tokio")]
+
+use rmpv::decode::{read_value_async, read_value_async_with_options, DecodeOptions, Error};
+use rmpv::encode::write_value_async;
+use rmpv::Value;
+
+#[tokio::test]
+async fn round_trip_async() {
+    let val = Value::Map(vec![
+        (Value::from("a"), Value::Array(vec![Value::from(1), Value::Binary(vec![0; 300])])),
+        (Value::from("b"), Value::Ext(3, vec![1, 2, 3, 4])),
+    ]);
+
+    let mut buf = Vec::new();
+    write_value_async(&mut buf, &val).await.unwrap();
+    write_value_async(&mut buf, &Value::Nil).await.unwrap();
+
+    let mut rd = &buf[..];
+    assert_eq!(val, read_value_async(&mut rd).await.unwrap());
+    assert_eq!(Value::Nil, read_value_async(&mut rd).await.unwrap());
+    assert!(matches!(read_value_async(&mut rd).await, Err(Error::InvalidMarkerRead(..))));
+}
+
+#[tokio::test]
+async fn read_async_checks_limits() {
+    let buf = [0xdd, 0xff, 0xff, 0xff, 0xff];
+    let opts = DecodeOptions::new().max_total_elements(10);
+
+    let err = read_value_async_with_options(&mut &buf[..], &opts).await.unwrap_err();
+    assert!(matches!(err, Error::ElementLimitExceeded));
+
+    let err = read_value_async(&mut &buf[..3]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidDataRead(..)));
+}
@@ -1,5 +1,12 @@
-use rmpv::decode::{read_value, Error};
-use rmpv::Value;
+use std::ops::ControlFlow;
+
+use rmpv::decode::{
+    read_value, read_value_projected, read_value_ref_with_options, read_value_validated, read_value_validated_all,
+    read_value_with_alloc_hook, read_value_with_duplicate_keys, read_value_with_max_depth, read_value_with_options,
+    DecodeOptions, DuplicateKeys, Error, InvalidUtf8,
+};
+use rmpv::schema::{Field, Schema, ValidationErrorKind};
+use rmpv::{Value, ValueRef};
 
 #[test]
 fn from_null_decode_value() {
@@ -401,3 +408,397 @@ fn invalid_buf_size_arr() {
         Err(e) => panic!("Unexpected error: {e}"),
     }
 }
+
+#[test]
+fn pass_read_value_projected_skips_other_keys() {
+    let mut buf = vec![0x8a];
+    for idx in 0..10u8 {
+        rmpv::encode::write_value(&mut buf, &Value::from(format!("k{idx}"))).unwrap();
+        match idx {
+            2 => rmpv::encode::write_value(&mut buf, &Value::from("kept")).unwrap(),
+            7 => rmpv::encode::write_value(&mut buf, &Value::Array(vec![Value::from(7)])).unwrap(),
+            // Nested deeper than `MAX_DEPTH`, so materializing it would fail.
+            5 => {
+                buf.extend(std::iter::repeat(0x91).take(2 * rmpv::decode::MAX_DEPTH));
+                buf.push(0xc0);
+            }
+            _ => rmpv::encode::write_value(&mut buf, &Value::Map(vec![
+                (Value::from(idx), Value::Binary(vec![idx; 300])),
+                (Value::Nil, Value::Ext(1, vec![idx; 4])),
+            ])).unwrap(),
+        }
+    }
+    assert!(matches!(read_value_with_max_depth(&mut &buf[..], 64), Err(Error::DepthLimitExceeded)));
+
+    let mut rd = &buf[..];
+    let val = read_value_projected(&mut rd, &["k7", "k2", "missing"]).unwrap();
+
+    assert_eq!(Value::Map(vec![
+        (Value::from("k2"), Value::from("kept")),
+        (Value::from("k7"), Value::Array(vec![Value::from(7)])),
+    ]), val);
+    assert!(rd.is_empty());
+}
+
+#[test]
+fn pass_read_value_projected_non_map() {
+    let buf = [0x92, 0x01, 0xa1, b'a'];
+    let val = read_value_projected(&mut &buf[..], &["a"]).unwrap();
+    assert_eq!(Value::Array(vec![Value::from(1), Value::from("a")]), val);
+}
+
+#[test]
+fn pass_read_value_projected_skips_reserved_marker() {
+    // {"a": [0xc1, 1], "b": 0xc1}, decoded by `read_value` with nil for 0xc1.
+    let buf = [0x82, 0xa1, b'a', 0x92, 0xc1, 0x01, 0xa1, b'b', 0xc1];
+    let val = read_value_projected(&mut &buf[..], &["b"]).unwrap();
+    assert_eq!(Value::Map(vec![(Value::from("b"), Value::Nil)]), val);
+}
+
+#[test]
+fn fail_read_value_projected_truncated_skipped_value() {
+    let buf = [0x81, 0xa1, b'a', 0xc4, 0x10, 0x00];
+    assert!(matches!(read_value_projected(&mut &buf[..], &[]), Err(Error::InvalidDataRead(..))));
+}
+
+#[test]
+fn pass_read_value_with_alloc_hook_reports_materialized_sizes() {
+    fn heap_size(val: &Value) -> usize {
+        match *val {
+            Value::String(ref s) => s.as_bytes().len(),
+            Value::Binary(ref data) | Value::Ext(_, ref data) => data.len(),
+            Value::Array(ref vec) => {
+                vec.len() * std::mem::size_of::<Value>() + vec.iter().map(heap_size).sum::<usize>()
+            }
+            Value::Map(ref map) => {
+                map.len() * std::mem::size_of::<(Value, Value)>() +
+                    map.iter().map(|(k, v)| heap_size(k) + heap_size(v)).sum::<usize>()
+            }
+            _ => 0,
+        }
+    }
+
+    let val = Value::Map(vec![
+        (Value::from("name"), Value::from("tenant")),
+        (Value::from("payload"), Value::Binary(vec![0; 100])),
+        (Value::from("items"), Value::Array(vec![
+            Value::from(1),
+            Value::Array(vec![]),
+            Value::Ext(3, vec![1, 2, 3, 4]),
+            Value::Map(vec![(Value::Nil, Value::from("x"))]),
+        ])),
+    ]);
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+
+    let mut sizes = Vec::new();
+    let decoded = read_value_with_alloc_hook(&mut &buf[..], &mut |size| {
+        sizes.push(size);
+        ControlFlow::Continue(())
+    }).unwrap();
+
+    assert_eq!(val, decoded);
+    // One report per non-empty string, binary, extension and container, all small enough to be
+    // reserved at once.
+    assert_eq!(10, sizes.len());
+    assert_eq!(heap_size(&val), sizes.iter().sum::<usize>());
+}
+
+#[test]
+fn pass_read_value_with_alloc_hook_grows_large_containers_in_steps() {
+    let val = Value::Array((0..100).map(Value::from).collect());
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+
+    let mut sizes = Vec::new();
+    read_value_with_alloc_hook(&mut &buf[..], &mut |size| {
+        sizes.push(size / std::mem::size_of::<Value>());
+        ControlFlow::Continue(())
+    }).unwrap();
+
+    assert_eq!(vec![4, 4, 8, 16, 32, 36], sizes);
+}
+
+#[test]
+fn fail_read_value_with_alloc_hook_aborts_partway() {
+    // A binary announcing 16 MiB but truncated after a few bytes.
+    let mut buf = vec![0x92, 0xa3, b'a', b'b', b'c', 0xc6, 0x01, 0x00, 0x00, 0x00];
+    buf.extend_from_slice(&[0; 10]);
+
+    let mut budget = 1024usize;
+    let mut sizes = Vec::new();
+    let err = read_value_with_alloc_hook(&mut &buf[..], &mut |size| {
+        sizes.push(size);
+        match budget.checked_sub(size) {
+            Some(rest) => {
+                budget = rest;
+                ControlFlow::Continue(())
+            }
+            None => ControlFlow::Break(()),
+        }
+    }).unwrap_err();
+
+    assert!(matches!(err, Error::AllocationAborted));
+    // The array and the string were reserved, the first chunk of the binary was refused.
+    assert_eq!(vec![2 * std::mem::size_of::<Value>(), 3, 64 * 1024], sizes);
+}
+
+#[test]
+fn fail_read_value_with_alloc_hook_truncated_binary() {
+    let buf = [0xc6, 0x01, 0x00, 0x00, 0x00, 1, 2, 3];
+
+    let mut total = 0;
+    let err = read_value_with_alloc_hook(&mut &buf[..], &mut |size| {
+        total += size;
+        ControlFlow::Continue(())
+    }).unwrap_err();
+
+    assert!(matches!(err, Error::InvalidDataRead(..)));
+    // Only the first chunk was reserved, not the announced 16 MiB.
+    assert_eq!(64 * 1024, total);
+}
+
+fn user_schema() -> Schema {
+    Schema::Map(vec![
+        Field::required("id", Schema::Integer),
+        Field::required("tags", Schema::Array(Box::new(Schema::String))),
+        Field::optional("score", Schema::Float),
+    ])
+}
+
+#[test]
+fn pass_read_value_validated() {
+    let val = Value::Map(vec![
+        (Value::from("id"), Value::from(7)),
+        (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::from("b")])),
+        (Value::from("extra"), Value::Binary(vec![1, 2])),
+    ]);
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+
+    assert_eq!(val, read_value_validated(&mut &buf[..], &user_schema()).unwrap());
+}
+
+#[test]
+fn fail_read_value_validated_reports_path_and_offset() {
+    // {"id": 7, "tags": ["a", 2, "c"]}
+    let buf = [
+        0x82,
+        0xa2, b'i', b'd', 0x07,
+        0xa4, b't', b'a', b'g', b's', 0x93, 0xa1, b'a', 0x02, 0xa1, b'c',
+    ];
+
+    let errs = read_value_validated(&mut &buf[..], &user_schema()).unwrap_err();
+    assert_eq!(1, errs.len());
+    assert_eq!("/tags/1", errs[0].path);
+    assert_eq!(13, errs[0].offset);
+    assert!(matches!(errs[0].kind, ValidationErrorKind::TypeMismatch { expected: "string", found: "integer" }));
+    assert_eq!(r#"expected string, found integer at "/tags/1" (byte offset 13)"#, errs[0].to_string());
+}
+
+#[test]
+fn fail_read_value_validated_all_collects_every_violation() {
+    // {"tags": [1, "b"], "score": "high"}
+    let buf = [
+        0x82,
+        0xa4, b't', b'a', b'g', b's', 0x92, 0x01, 0xa1, b'b',
+        0xa5, b's', b'c', b'o', b'r', b'e', 0xa4, b'h', b'i', b'g', b'h',
+    ];
+    let mut rd = &buf[..];
+
+    let errs = read_value_validated_all(&mut rd, &user_schema()).unwrap_err();
+    let found: Vec<_> = errs.iter().map(|err| (err.path.as_str(), err.offset)).collect();
+    assert_eq!(vec![("/tags/0", 7), ("/score", 16), ("", 0)], found);
+    assert!(matches!(errs[2].kind, ValidationErrorKind::MissingField(ref name) if name == "id"));
+    assert!(rd.is_empty());
+}
+
+#[test]
+fn fail_read_value_validated_truncated() {
+    let buf = [0x81, 0xa2, b'i', b'd'];
+    let errs = read_value_validated_all(&mut &buf[..], &user_schema()).unwrap_err();
+    assert_eq!(1, errs.len());
+    assert_eq!(("/id", 4), (errs[0].path.as_str(), errs[0].offset));
+    assert!(matches!(errs[0].kind, ValidationErrorKind::Decode(Error::InvalidMarkerRead(..))));
+}
+
+#[test]
+fn fail_validate_ranges_and_lengths() {
+    let schema = Schema::Map(vec![
+        Field::required("port", Schema::IntegerIn { min: 1, max: 65535 }),
+        Field::optional("ratio", Schema::FloatIn { min: 0.0, max: 1.0 }),
+        Field::optional("tags", Schema::MaxLen(2, Box::new(Schema::Array(Box::new(Schema::MaxLen(3, Box::new(Schema::String))))))),
+    ]);
+    let val = Value::Map(vec![
+        (Value::from("port"), Value::from(0)),
+        (Value::from("ratio"), Value::F64(f64::NAN)),
+        (Value::from("tags"), Value::Array(vec![Value::from("abcd"), Value::from("a"), Value::from(1)])),
+    ]);
+
+    let expected = vec![
+        ("/port", "value out of range"),
+        ("/ratio", "value out of range"),
+        ("/tags/0", "length 4 exceeds 3"),
+        ("/tags/2", "expected string, found integer"),
+        ("/tags", "length 3 exceeds 2"),
+    ];
+    let errs = schema.validate(&val).unwrap_err();
+    let found: Vec<_> = errs.iter().map(|err| (err.path.as_str(), err.to_string())).collect();
+    let expected: Vec<_> = expected.into_iter().map(|(path, msg)| (path, format!("{msg} at {path:?} (byte offset 0)"))).collect();
+    assert_eq!(expected, found);
+
+    // Decoding reports the same violations, at their offsets.
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+    let errs = read_value_validated_all(&mut &buf[..], &schema).unwrap_err();
+    let paths: Vec<_> = errs.iter().map(|err| err.path.as_str()).collect();
+    assert_eq!(vec!["/port", "/ratio", "/tags/0", "/tags/2", "/tags"], paths);
+    assert!(matches!(errs[4].kind, ValidationErrorKind::TooLong { max: 2, len: 3 }));
+
+    let val = Value::Map(vec![(Value::from("port"), Value::from(8080))]);
+    assert!(schema.validate(&val).is_ok());
+}
+
+#[test]
+fn from_map_with_duplicate_keys() {
+    // [{1: "a", "x": nil, 1: "b"}]
+    let buf = [0x91, 0x83, 0x01, 0xa1, b'a', 0xa1, b'x', 0xc0, 0x01, 0xa1, b'b'];
+
+    let all = read_value(&mut &buf[..]).unwrap();
+    assert_eq!(3, all[0].as_map().unwrap().len());
+    assert!(all.has_duplicate_keys());
+    assert_eq!(all, read_value_with_duplicate_keys(&mut &buf[..], DuplicateKeys::Allow).unwrap());
+
+    let first = read_value_with_duplicate_keys(&mut &buf[..], DuplicateKeys::KeepFirst).unwrap();
+    assert_eq!(Value::Array(vec![Value::Map(vec![
+        (Value::from(1), Value::from("a")),
+        (Value::from("x"), Value::Nil),
+    ])]), first);
+    assert!(!first.has_duplicate_keys());
+
+    let last = read_value_with_duplicate_keys(&mut &buf[..], DuplicateKeys::KeepLast).unwrap();
+    assert_eq!(Value::Array(vec![Value::Map(vec![
+        (Value::from(1), Value::from("b")),
+        (Value::from("x"), Value::Nil),
+    ])]), last);
+
+    match read_value_with_duplicate_keys(&mut &buf[..], DuplicateKeys::Reject) {
+        Err(Error::DuplicateKey(key)) => assert_eq!(Value::from(1), key),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_map_with_distinct_float_keys() {
+    // {1.0: nil, 1: nil, 1.0: nil}
+    let buf = [0x83, 0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0, 0xc0, 0x01, 0xc0, 0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0, 0xc0];
+
+    let val = read_value_with_duplicate_keys(&mut &buf[..], DuplicateKeys::KeepFirst).unwrap();
+    assert_eq!(Value::Map(vec![(Value::F64(1.0), Value::Nil), (Value::from(1), Value::Nil)]), val);
+}
+
+#[test]
+fn from_map_with_float_and_container_keys() {
+    let val = Value::Map(vec![
+        (Value::F64(f64::NAN), Value::from(1)),
+        (Value::F64(0.0), Value::from(2)),
+        (Value::F64(-0.0), Value::from(3)),
+        (Value::Array(vec![Value::F32(0.5)]), Value::from(4)),
+        (Value::F64(f64::NAN), Value::from(5)),
+        (Value::Array(vec![Value::F32(0.5)]), Value::from(6)),
+    ]);
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+
+    // NaN keys with the same bits are duplicates, while 0.0 and -0.0 are distinct keys.
+    let first = read_value_with_duplicate_keys(&mut &buf[..], DuplicateKeys::KeepFirst).unwrap();
+    assert_eq!(4, first.as_map().unwrap().len());
+    assert_eq!(Some(4), first.as_map().unwrap()[3].1.as_u64());
+    assert!(val.has_duplicate_keys());
+    assert!(!first.has_duplicate_keys());
+}
+
+#[test]
+fn from_map_with_many_array_keys() {
+    // Keys that are not strings or integers must not all collide.
+    let val = Value::Map((0..20_000).map(|i| (Value::Array(vec![Value::from(i)]), Value::Nil)).collect());
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+
+    assert_eq!(val, read_value_with_duplicate_keys(&mut &buf[..], DuplicateKeys::Reject).unwrap());
+}
+
+#[test]
+fn from_value_with_options_limits() {
+    // {"a": [1, 2, 3], "b": "xyz"}
+    let buf = [0x82, 0xa1, b'a', 0x93, 0x01, 0x02, 0x03, 0xa1, b'b', 0xa3, b'x', b'y', b'z'];
+
+    let val = read_value_with_options(&mut &buf[..], &DecodeOptions::new()).unwrap();
+    assert_eq!(val, read_value(&mut &buf[..]).unwrap());
+
+    let opts = DecodeOptions::new().max_depth(16).max_len(3).max_total_elements(8);
+    assert_eq!(val, read_value_with_options(&mut &buf[..], &opts).unwrap());
+
+    let err = read_value_with_options(&mut &buf[..], &DecodeOptions::new().max_depth(1)).unwrap_err();
+    assert!(matches!(err, Error::DepthLimitExceeded));
+
+    let err = read_value_with_options(&mut &buf[..], &DecodeOptions::new().max_len(2)).unwrap_err();
+    assert!(matches!(err, Error::LengthLimitExceeded));
+
+    let err = read_value_with_options(&mut &buf[..], &DecodeOptions::new().max_total_elements(7)).unwrap_err();
+    assert!(matches!(err, Error::ElementLimitExceeded));
+}
+
+#[test]
+fn from_huge_declared_lengths_with_options() {
+    let opts = DecodeOptions::new().max_len(1 << 20).max_total_elements(1 << 20);
+
+    // Each header declares 4 GiB or 4 billion elements, with nothing following it.
+    for buf in [[0xdb, 0xff, 0xff, 0xff, 0xff], [0xc6, 0xff, 0xff, 0xff, 0xff], [0xdd, 0xff, 0xff, 0xff, 0xff]] {
+        let err = read_value_with_options(&mut &buf[..], &opts).unwrap_err();
+        assert!(matches!(err, Error::LengthLimitExceeded), "{err:?}");
+    }
+
+    // A map of 2^19 entries fits the length limit, but not the element limit.
+    let buf = [0xdf, 0x00, 0x08, 0x00, 0x00];
+    let err = read_value_with_options(&mut &buf[..], &opts).unwrap_err();
+    assert!(matches!(err, Error::ElementLimitExceeded));
+}
+
+#[test]
+fn from_invalid_utf8_with_policy() {
+    // ["a\xff", {"\xfe": 1}]
+    let buf = [0x92, 0xa2, b'a', 0xff, 0x81, 0xa1, 0xfe, 0x01];
+
+    let opts = DecodeOptions::new().invalid_utf8(InvalidUtf8::Lossy);
+    let val = read_value_with_options(&mut &buf[..], &opts).unwrap();
+    assert_eq!(Value::Array(vec![Value::from("a\u{fffd}"), Value::Map(vec![(Value::from("\u{fffd}"), Value::from(1))])]), val);
+
+    let opts = DecodeOptions::new().invalid_utf8(InvalidUtf8::Binary);
+    let val = read_value_with_options(&mut &buf[..], &opts).unwrap();
+    assert_eq!(Value::Array(vec![Value::Binary(vec![b'a', 0xff]), Value::Map(vec![(Value::Binary(vec![0xfe]), Value::from(1))])]), val);
+    assert_eq!(ValueRef::Binary(&[b'a', 0xff]), read_value_ref_with_options(&mut &buf[..], &opts).unwrap()[0]);
+
+    let val = read_value_with_options(&mut &buf[..], &DecodeOptions::new()).unwrap();
+    assert!(matches!(val[0], Value::String(ref s) if s.is_err()));
+}
+
+#[test]
+fn from_strings_as_binary() {
+    // {"k": "\xff"}, as written by a peer predating the bin formats.
+    let buf = [0x81, 0xa1, b'k', 0xa1, 0xff];
+
+    let opts = DecodeOptions::new().strings_as_binary(true);
+    let val = read_value_with_options(&mut &buf[..], &opts).unwrap();
+    assert_eq!(Value::Map(vec![(Value::Binary(vec![b'k']), Value::Binary(vec![0xff]))]), val);
+    assert_eq!(
+        ValueRef::Map(vec![(ValueRef::Binary(b"k"), ValueRef::Binary(&[0xff]))]),
+        read_value_ref_with_options(&mut &buf[..], &opts).unwrap()
+    );
+
+    // The legacy encoding is decoded back according to the policy.
+    let val = Value::Array(vec![Value::from("id"), Value::Binary(vec![0xca, 0xfe])]);
+    let buf = val.encode_with(&rmpv::encode::EncodeOptions::new().legacy_raw(true)).unwrap();
+    let val = read_value_with_options(&mut &buf[..], &DecodeOptions::new().invalid_utf8(InvalidUtf8::Binary)).unwrap();
+    assert_eq!(Value::Array(vec![Value::from("id"), Value::Binary(vec![0xca, 0xfe])]), val);
+}
@@ -1,4 +1,5 @@
-use rmpv::decode::{read_value_ref, Error};
+use rmpv::decode::{read_value_ref, read_value_ref_with_options, DecodeOptions, DuplicateKeys, Error, ValueRefDoc};
+use std::io::Read;
 use rmpv::ValueRef;
 
 #[test]
@@ -676,3 +677,153 @@ fn into_owned() {
     assert_eq!(expected, val.to_owned());
     assert_eq!(expected.as_ref(), val);
 }
+
+#[test]
+fn from_value_with_options() {
+    // [{"k": 1, "k": 2}, "long string"]
+    let buf = [
+        0x92,
+        0x82, 0xa1, b'k', 0x01, 0xa1, b'k', 0x02,
+        0xab, b'l', b'o', b'n', b'g', b' ', b's', b't', b'r', b'i', b'n', b'g',
+    ];
+
+    let opts = DecodeOptions::new().duplicate_keys(DuplicateKeys::KeepLast);
+    assert_eq!(ValueRef::Array(vec![
+        ValueRef::Map(vec![(ValueRef::from("k"), ValueRef::from(2))]),
+        ValueRef::from("long string"),
+    ]), read_value_ref_with_options(&mut &buf[..], &opts).unwrap());
+
+    let opts = DecodeOptions::new().duplicate_keys(DuplicateKeys::Reject);
+    let err = read_value_ref_with_options(&mut &buf[..], &opts).unwrap_err();
+    assert!(matches!(err, Error::DuplicateKey(key) if key.as_str() == Some("k")));
+
+    let err = read_value_ref_with_options(&mut &buf[..], &DecodeOptions::new().max_len(10)).unwrap_err();
+    assert!(matches!(err, Error::LengthLimitExceeded));
+
+    let err = read_value_ref_with_options(&mut &buf[..], &DecodeOptions::new().max_total_elements(6)).unwrap_err();
+    assert!(matches!(err, Error::ElementLimitExceeded));
+}
+
+#[test]
+fn navigate_decoded_value_ref() {
+    use rmpv::{Timestamp, Value};
+
+    let val = Value::Map(vec![
+        (Value::from("name"), Value::from("John")),
+        (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::Binary(vec![1, 2])])),
+        (Value::from(7), Value::Map(vec![(Value::from("ok"), Value::Boolean(true))])),
+        (Value::from("at"), Value::from(Timestamp::new(1, 0).unwrap())),
+        (Value::from("score"), Value::F32(0.5)),
+    ]);
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+
+    let name = {
+        let val = read_value_ref(&mut &buf[..]).unwrap();
+
+        assert_eq!(Some(&ValueRef::from("a")), val.pointer("/tags/0"));
+        assert_eq!(Some(&[1, 2][..]), val.pointer("/tags/1").and_then(ValueRef::as_slice));
+        assert_eq!(Some(true), val.pointer("/7/ok").and_then(ValueRef::as_bool));
+        assert_eq!(None, val.pointer("/tags/2"));
+        assert_eq!(None, val.pointer("/tags/+1"));
+        assert_eq!(None, val.pointer("/tags/01"));
+        assert_eq!(Some(&val), val.pointer(""));
+
+        assert_eq!(ValueRef::from("a"), val["tags"][0]);
+        assert!(val["missing"][3].is_nil());
+        assert_eq!(Some(0.5), val["score"].as_f64());
+        assert_eq!(None, val["name"].as_i64());
+        assert_eq!(Timestamp::new(1, 0), val["at"].as_timestamp());
+        assert_eq!(Some(-1), val["at"].as_ext().map(|(ty, _)| ty));
+        assert_eq!(1, val.get("tags").and_then(ValueRef::as_array).map_or(0, |v| v.len() - 1));
+        assert_eq!(5, val.as_map().unwrap().len());
+
+        // Strings borrow from the buffer, so they outlive the tree.
+        val["name"].as_str()
+    };
+
+    assert_eq!(Some("John"), name);
+}
+
+#[test]
+fn read_doc_from_reader() {
+    // {"a": [1, "b"]} followed by nil, through a reader that does not borrow.
+    let buf = [0x81, 0xa1, 0x61, 0x92, 0x01, 0xa1, 0x62, 0xc0];
+    let mut rd = std::io::Cursor::new(&buf[..]).chain(&[][..]);
+
+    let doc = ValueRefDoc::read(&mut rd).unwrap();
+    assert_eq!(&buf[..7], doc.as_bytes());
+    assert_eq!(Some("b"), doc.value()["a"][1].as_str());
+
+    let doc = ValueRefDoc::read(&mut rd).unwrap();
+    assert_eq!(ValueRef::Nil, doc.value());
+    assert!(matches!(ValueRefDoc::read(&mut rd), Err(Error::InvalidMarkerRead(..))));
+
+    let err = ValueRefDoc::read(&mut &buf[..3]).unwrap_err();
+    assert!(matches!(err, Error::InvalidMarkerRead(..)));
+
+    let opts = DecodeOptions::new().max_len(0);
+    let err = ValueRefDoc::read_with_options(&mut &[0xdb, 0xff, 0xff, 0xff, 0xff][..], opts).unwrap_err();
+    assert!(matches!(err, Error::LengthLimitExceeded));
+}
+
+#[test]
+fn read_doc_with_ext_and_strings() {
+    // [ext(1, [2]), ext8(3, [4, 5]), "", invalid "\xff", bin [6], 1.5] followed by nil. The type
+    // of extensions follows their length, and must be read before their data.
+    let buf = [
+        0x96, 0xd4, 0x01, 0x02, 0xc7, 0x02, 0x03, 0x04, 0x05, 0xa0, 0xa1, 0xff, 0xc4, 0x01, 0x06,
+        0xca, 0x3f, 0xc0, 0x00, 0x00, 0xc0,
+    ];
+    let mut rd = &buf[..];
+
+    let doc = ValueRefDoc::read(&mut rd).unwrap();
+    assert_eq!(&[0xc0], rd);
+    assert_eq!(&buf[..buf.len() - 1], doc.as_bytes());
+
+    // The view built from the decoded message is the same as decoding it again.
+    let val = read_value_ref(&mut doc.as_bytes()).unwrap();
+    assert_eq!(val, doc.value());
+    assert_eq!(val, doc.clone().value());
+    assert_eq!(Some((3, &[4, 5][..])), doc.value()[1].as_ext());
+    assert!(doc.value()[3].as_str().is_none());
+}
+
+#[test]
+fn promote_value_ref_through_cow() {
+    use std::borrow::Cow;
+
+    use rmpv::{CowValue, Value};
+
+    // {"k": ["v", bin [1], ext(5, [2]), invalid "\xff"]}
+    let buf = [0x81, 0xa1, b'k', 0x94, 0xa1, b'v', 0xc4, 0x01, 0x01, 0xd4, 0x05, 0x02, 0xa1, 0xff];
+    let val = read_value_ref(&mut &buf[..]).unwrap();
+    let expected = val.to_owned();
+
+    let mut cow = CowValue::from(val);
+    assert!(!cow.is_owned());
+    if let CowValue::Map(ref mut map) = cow {
+        map[0].0 = CowValue::String(Cow::Owned("k".into()));
+    }
+
+    let cow = cow.into_static();
+    assert!(cow.is_owned());
+    assert_eq!(expected, Value::from(cow));
+}
+
+#[test]
+fn iterate_concatenated_value_refs() {
+    use rmpv::decode::ValueRefIter;
+
+    let buf = [0x92, 0x01, 0x02, 0xa1, b'a', 0x92, 0x01];
+    let mut iter = ValueRefIter::new(&buf);
+
+    assert_eq!(ValueRef::Array(vec![ValueRef::from(1), ValueRef::from(2)]), iter.next().unwrap().unwrap());
+    assert_eq!(ValueRef::from("a"), iter.next().unwrap().unwrap());
+    assert_eq!(5, iter.offset());
+    assert!(matches!(iter.next(), Some(Err(Error::InvalidMarkerRead(..)))));
+    assert!(iter.next().is_none());
+    assert_eq!(5, iter.offset());
+
+    assert_eq!(0, ValueRefIter::new(&[]).count());
+}
@@ -0,0 +1,98 @@
+use rmpv::diff::{diff, Error, Op, Patch};
+use rmpv::{msgpack, Value};
+
+#[test]
+fn pass_diff_and_apply() {
+    let from = msgpack!({
+        "players": [{"name": "a", "hp": 10}, {"name": "b", "hp": 7}, {"name": "c", "hp": 1}],
+        "round": 3,
+        "paused": false,
+        1: "int key",
+    });
+    let to = msgpack!({
+        "players": [{"name": "a", "hp": 9}, {"name": "b", "hp": 7, "buff": true}],
+        "round": 4,
+        1: "int key",
+        "winner": nil,
+    });
+
+    let patch = diff(&from, &to);
+    assert_eq!(&[
+        Op::Set { path: vec![Value::from("players"), Value::from(0), Value::from("hp")], value: Value::from(9) },
+        Op::Set { path: vec![Value::from("players"), Value::from(1), Value::from("buff")], value: Value::from(true) },
+        Op::Truncate { path: vec![Value::from("players")], len: 2 },
+        Op::Set { path: vec![Value::from("round")], value: Value::from(4) },
+        Op::Remove { path: vec![Value::from("paused")] },
+        Op::Set { path: vec![Value::from("winner")], value: Value::Nil },
+    ], patch.ops());
+
+    let mut state = from.clone();
+    patch.apply(&mut state).unwrap();
+    assert_eq!(to, state);
+
+    assert!(diff(&to, &to).is_empty());
+}
+
+#[test]
+fn pass_diff_grows_arrays_and_replaces_types() {
+    let from = msgpack!({"list": [1], "val": [1, 2]});
+    let to = msgpack!({"list": [1, 2, 3], "val": {"a": 1}});
+
+    let mut state = from.clone();
+    diff(&from, &to).apply(&mut state).unwrap();
+    assert_eq!(to, state);
+
+    let mut state = msgpack!(1);
+    diff(&state.clone(), &msgpack!("root")).apply(&mut state).unwrap();
+    assert_eq!(msgpack!("root"), state);
+}
+
+#[test]
+fn pass_patch_roundtrips_through_msgpack() {
+    let from = msgpack!({"a": [1, 2, 3], "b": {"c": 1}, "d": 1});
+    let to = msgpack!({"a": [0, 2], "b": {"e": b"\x01"}});
+    let patch = diff(&from, &to);
+
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &patch.to_value()).unwrap();
+    let decoded = Patch::from_value(&rmpv::decode::read_value(&mut &buf[..]).unwrap()).unwrap();
+    assert_eq!(patch, decoded);
+
+    // The patch applies to any value with the same structure, not only to `from`.
+    let mut other = msgpack!({"a": [5, 6, 7], "b": {"c": 2, "x": 1}, "d": 2, "y": 3});
+    decoded.apply(&mut other).unwrap();
+    assert_eq!(msgpack!({"a": [0, 6], "b": {"x": 1, "e": b"\x01"}, "y": 3}), other);
+}
+
+#[test]
+fn pass_diff_with_nan_keys() {
+    let from = Value::Map(vec![(Value::F64(f64::NAN), Value::from(1)), (Value::F64(0.0), Value::from(2))]);
+    let to = Value::Map(vec![(Value::F64(f64::NAN), Value::from(3)), (Value::F64(-0.0), Value::from(2))]);
+
+    let patch = diff(&from, &to);
+    assert_eq!(3, patch.ops().len());
+
+    let mut state = from.clone();
+    patch.apply(&mut state).unwrap();
+    assert!(to.total_eq(&state));
+    assert!(diff(&to, &to).is_empty());
+}
+
+#[test]
+fn fail_apply_missing_path() {
+    let patch = diff(&msgpack!({"a": {"b": 1}}), &msgpack!({"a": {"b": 2}}));
+    let mut target = msgpack!({"c": 1});
+    assert_eq!(Err(Error::PathNotFound(vec![Value::from("a"), Value::from("b")])), patch.apply(&mut target));
+
+    let patch = diff(&msgpack!([1]), &msgpack!([1, 2]));
+    assert!(patch.apply(&mut msgpack!([])).is_err());
+}
+
+#[test]
+fn fail_invalid_patch_value() {
+    assert_eq!(Err(Error::InvalidPatch), Patch::from_value(&msgpack!({})));
+    assert_eq!(Err(Error::InvalidPatch), Patch::from_value(&msgpack!([[0, []]])));
+    assert_eq!(Err(Error::InvalidPatch), Patch::from_value(&msgpack!([[3, [], 1]])));
+    assert_eq!(Err(Error::InvalidPatch), Patch::from_value(&msgpack!([[2, [], -1]])));
+    assert_eq!(Ok(Patch::default()), Patch::from_value(&msgpack!([])));
+}
@@ -0,0 +1,219 @@
+use rmpv::encode::options::Error;
+use rmpv::encode::{write_value, write_value_canonical, EncodeOptions, FloatFormat};
+use rmpv::{Timestamp, Value};
+
+#[test]
+fn pass_encode_with_default_options() {
+    let val = Value::Map(vec![
+        (Value::from("b"), Value::Array(vec![Value::from(1), Value::from(-300), Value::F64(1.5)])),
+        (Value::from(2), Value::Ext(1, vec![0x01, 0x02])),
+    ]);
+
+    let mut expected = Vec::new();
+    write_value(&mut expected, &val).unwrap();
+
+    assert_eq!(expected, val.encode_with(&EncodeOptions::default()).unwrap());
+}
+
+#[test]
+fn pass_encode_with_combined_options() {
+    let opts = EncodeOptions::new()
+        .compact_floats(true)
+        .no_fixext(true)
+        .string_keys(true)
+        .canonical(true)
+        .reject_non_finite(true)
+        .fixed_int_width(true);
+
+    let val = Value::Map(vec![
+        (Value::from("z"), Value::Array(vec![Value::from(1), Value::from(-1)])),
+        (Value::from("f"), Value::Array(vec![Value::F64(0.5), Value::F64(0.1)])),
+        (Value::from("e"), Value::Ext(5, vec![0xaa, 0xbb])),
+    ]);
+
+    let expected = vec![
+        0x83,
+        // "e": ext 8 instead of fixext 2
+        0xa1, b'e', 0xc7, 0x02, 0x05, 0xaa, 0xbb,
+        // "f": 0.5 fits into f32, 0.1 does not
+        0xa1, b'f', 0x92,
+        0xca, 0x3f, 0x00, 0x00, 0x00,
+        0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a,
+        // "z": 64-bit integers only
+        0xa1, b'z', 0x92,
+        0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+        0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    ];
+    assert_eq!(expected, val.encode_with(&opts).unwrap());
+}
+
+#[test]
+fn pass_encode_canonical_ignores_entry_order() {
+    let opts = EncodeOptions::new().canonical(true);
+
+    let lhs = Value::Map(vec![
+        (Value::from(300), Value::from("a")),
+        (Value::from(1), Value::Map(vec![(Value::from("y"), Value::Nil), (Value::from("x"), Value::Nil)])),
+    ]);
+    let rhs = Value::Map(vec![
+        (Value::from(1), Value::Map(vec![(Value::from("x"), Value::Nil), (Value::from("y"), Value::Nil)])),
+        (Value::from(300), Value::from("a")),
+    ]);
+
+    assert_eq!(lhs.encode_with(&opts).unwrap(), rhs.encode_with(&opts).unwrap());
+}
+
+#[test]
+fn pass_write_value_canonical() {
+    let val = Value::Map(vec![
+        (Value::from("b"), Value::Array(vec![Value::F64(0.5), Value::F64(0.1), Value::from(-1)])),
+        (Value::from("a"), Value::Map(vec![(Value::from(2), Value::Nil), (Value::from(1), Value::Nil)])),
+    ]);
+
+    let mut buf = Vec::new();
+    write_value_canonical(&mut buf, &val).unwrap();
+
+    let expected = [
+        0x82,
+        0xa1, b'a', 0x82, 0x01, 0xc0, 0x02, 0xc0,
+        0xa1, b'b', 0x93,
+        0xca, 0x3f, 0x00, 0x00, 0x00,
+        0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a,
+        0xff,
+    ];
+    assert_eq!(&expected[..], &buf[..]);
+}
+
+#[test]
+fn pass_encode_canonical_shortest_forms() {
+    let opts = EncodeOptions::new().canonical(true);
+
+    // The 96-bit form of a timestamp that fits into the 32-bit one.
+    let long = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x2a];
+    let val = Value::Array(vec![
+        Value::Timestamp(Timestamp::from_ext_data(&long).unwrap()),
+        Value::Ext(-1, long.to_vec()),
+        Value::Ext(1, long.to_vec()),
+        Value::F64(0.5),
+    ]);
+
+    let buf = val.encode_with(&opts).unwrap();
+
+    let expected = [
+        0x94,
+        0xd6, 0xff, 0x00, 0x00, 0x00, 0x2a,
+        0xd6, 0xff, 0x00, 0x00, 0x00, 0x2a,
+        // Other extension types are left alone.
+        0xc7, 0x0c, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x2a,
+        0xca, 0x3f, 0x00, 0x00, 0x00,
+    ];
+    assert_eq!(&expected[..], &buf[..]);
+    rmp::decode::Validator::new().canonical(true).validate(&buf).unwrap();
+}
+
+#[test]
+fn fail_encode_with_non_string_key() {
+    let opts = EncodeOptions::new().string_keys(true);
+    let val = Value::Array(vec![Value::Map(vec![(Value::from(1), Value::Nil)])]);
+
+    match val.encode_with(&opts) {
+        Err(Error::NonStringKey) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn fail_encode_with_non_finite_float() {
+    let opts = EncodeOptions::new().reject_non_finite(true);
+
+    for val in [Value::F64(f64::NAN), Value::F32(f32::INFINITY), Value::F64(f64::NEG_INFINITY)] {
+        match val.encode_with(&opts) {
+            Err(Error::NonFiniteFloat) => (),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    // Non-finite floats are allowed by default.
+    assert_eq!(vec![0xca, 0x7f, 0x80, 0x00, 0x00], Value::F32(f32::INFINITY).encode_with(&EncodeOptions::new()).unwrap());
+}
+
+#[test]
+fn pass_encode_legacy_raw() {
+    let opts = EncodeOptions::new().legacy_raw(true);
+    let val = Value::Array(vec![Value::from("id"), Value::Binary(vec![0xca, 0xfe]), Value::from(1)]);
+
+    assert_eq!(vec![0x93, 0xa2, b'i', b'd', 0xa2, 0xca, 0xfe, 0x01], val.encode_with(&opts).unwrap());
+
+    // There is no `str 8` format before 2.0, so the longer raws start with `str 16`.
+    let buf = Value::Binary(vec![0; 255]).encode_with(&opts).unwrap();
+    assert_eq!(&[0xda, 0x00, 0xff], &buf[..3]);
+    assert_eq!(258, buf.len());
+    let buf = Value::from("x".repeat(0x10000)).encode_with(&opts).unwrap();
+    assert_eq!(&[0xdb, 0x00, 0x01, 0x00, 0x00], &buf[..5]);
+}
+
+#[test]
+fn fail_encode_legacy_raw_ext() {
+    let opts = EncodeOptions::new().legacy_raw(true);
+
+    match Value::Array(vec![Value::Ext(1, vec![0])]).encode_with(&opts) {
+        Err(Error::LegacyExt) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_serialized_size_matches_encoding() {
+    let mut vals = vec![
+        Value::Nil,
+        Value::from(true),
+        Value::F32(1.5),
+        Value::F64(1.5),
+        // A string that is not valid UTF-8.
+        rmpv::decode::read_value(&mut &[0xa2, 0xff, 0xfe][..]).unwrap(),
+        Value::from(rmpv::Timestamp::new(1, 0).unwrap()),
+        Value::from(rmpv::Timestamp::new(-1, 0).unwrap()),
+    ];
+    for n in [0, 127, 128, 255, 256, 65535, 65536, u64::from(u32::MAX), u64::from(u32::MAX) + 1, u64::MAX] {
+        vals.push(Value::from(n));
+    }
+    for n in [-1, -32, -33, -128, -129, -32768, -32769, i64::from(i32::MIN), i64::from(i32::MIN) - 1, i64::MIN] {
+        vals.push(Value::from(n));
+    }
+    for len in [0, 1, 2, 3, 4, 8, 16, 17, 31, 32, 255, 256, 65535, 65536] {
+        vals.push(Value::from("a".repeat(len)));
+        vals.push(Value::Binary(vec![0; len]));
+        vals.push(Value::Ext(5, vec![0; len]));
+        vals.push(Value::Array(vec![Value::Nil; len]));
+        vals.push(Value::Map(vec![(Value::from(1), Value::from("v")); len]));
+    }
+
+    for val in vals {
+        let mut buf = Vec::new();
+        write_value(&mut buf, &val).unwrap();
+        assert_eq!(buf.len(), val.serialized_size(), "{}", val.type_name());
+    }
+}
+
+#[test]
+fn pass_encode_with_float_format() {
+    let val = Value::Array(vec![Value::F32(0.5), Value::F64(0.25), Value::F64(0.1), Value::F64(f64::NAN)]);
+
+    let widened = val.encode_with(&EncodeOptions::new().float_format(FloatFormat::F64)).unwrap();
+    let mut expected = vec![0x94];
+    for n in [0.5, 0.25, 0.1] {
+        expected.push(0xcb);
+        expected.extend_from_slice(&f64::to_be_bytes(n));
+    }
+    expected.push(0xcb);
+    expected.extend_from_slice(&f64::to_be_bytes(f64::NAN));
+    assert_eq!(expected, widened);
+
+    let mut normalized = val.clone();
+    normalized.normalize_floats(FloatFormat::Compact);
+    assert!(matches!(normalized[1], Value::F32(n) if n == 0.25));
+    assert!(matches!(normalized[2], Value::F64(..)));
+    assert!(matches!(normalized[3], Value::F64(n) if n.is_nan()));
+    assert_eq!(normalized.encode_with(&EncodeOptions::new()).unwrap(),
+               val.encode_with(&EncodeOptions::new().float_format(FloatFormat::Compact)).unwrap());
+}
@@ -0,0 +1,200 @@
+use rmpv::json::Error;
+use rmpv::Value;
+
+#[test]
+fn pass_canonical_json_ignores_key_order() {
+    let lhs = Value::Map(vec![
+        (Value::from("name"), Value::from("le message")),
+        (Value::from("id"), Value::from(42)),
+        (Value::from("tags"), Value::Map(vec![
+            (Value::from("z"), Value::Boolean(true)),
+            (Value::from("a"), Value::Nil),
+        ])),
+    ]);
+    let rhs = Value::Map(vec![
+        (Value::from("tags"), Value::Map(vec![
+            (Value::from("a"), Value::Nil),
+            (Value::from("z"), Value::Boolean(true)),
+        ])),
+        (Value::from("id"), Value::from(42)),
+        (Value::from("name"), Value::from("le message")),
+    ]);
+
+    let expected = r#"{"id":42,"name":"le message","tags":{"a":null,"z":true}}"#;
+    assert_eq!(expected, lhs.to_canonical_json().unwrap());
+    assert_eq!(expected, rhs.to_canonical_json().unwrap());
+}
+
+#[test]
+fn pass_canonical_json_numbers() {
+    let cases = [
+        (Value::from(0.0), "0"),
+        (Value::from(-0.0), "0"),
+        (Value::from(1.0), "1"),
+        (Value::from(-1.5), "-1.5"),
+        (Value::from(100.0), "100"),
+        (Value::from(123456789.0), "123456789"),
+        (Value::from(1e21), "1e+21"),
+        (Value::from(1e20), "100000000000000000000"),
+        (Value::from(1e-7), "1e-7"),
+        (Value::from(0.000001), "0.000001"),
+        (Value::from(1.2345e-10), "1.2345e-10"),
+        (Value::from(0.1f32), "0.1"),
+        (Value::from(9007199254740991u64), "9007199254740991"),
+        (Value::from(-9007199254740991i64), "-9007199254740991"),
+    ];
+
+    for (val, expected) in cases {
+        assert_eq!(expected, val.to_canonical_json().unwrap());
+    }
+}
+
+#[test]
+fn pass_canonical_json_string_escapes() {
+    let val = Value::from("\"\\\u{8}\t\n\u{c}\r\u{1f}/é€");
+    assert_eq!(r#""\"\\\b\t\n\f\r\u001f/é€""#, val.to_canonical_json().unwrap());
+}
+
+#[test]
+fn pass_canonical_json_sorts_by_utf16() {
+    // U+10000 is encoded as a surrogate pair, which sorts before U+FB33 in UTF-16.
+    let val = Value::Map(vec![
+        (Value::from("\u{fb33}"), Value::from(1)),
+        (Value::from("\u{10000}"), Value::from(2)),
+    ]);
+    assert_eq!("{\"\u{10000}\":2,\"\u{fb33}\":1}", val.to_canonical_json().unwrap());
+}
+
+#[test]
+fn fail_canonical_json_unrepresentable() {
+    assert_eq!(Err(Error::Binary), Value::Binary(vec![1]).to_canonical_json());
+    assert_eq!(Err(Error::Ext(5)), Value::Ext(5, vec![1]).to_canonical_json());
+    assert_eq!(Err(Error::NonFiniteFloat), Value::from(f64::NAN).to_canonical_json());
+    assert_eq!(Err(Error::IntegerOutOfRange), Value::from(u64::MAX).to_canonical_json());
+    assert_eq!(Err(Error::NonStringKey), Value::Map(vec![(Value::from(1), Value::Nil)]).to_canonical_json());
+
+    let dup = Value::Map(vec![(Value::from("a"), Value::Nil), (Value::from("a"), Value::Nil)]);
+    assert_eq!(Err(Error::DuplicateKey("a".into())), dup.to_canonical_json());
+}
+
+#[test]
+fn pass_debug_json_is_valid_json() {
+    let invalid_str = rmpv::decode::read_value(&mut &[0xa3, b'a', 0xff, b'"'][..]).unwrap();
+    assert!(invalid_str.as_str().is_none());
+
+    let val = Value::Map(vec![
+        (Value::from("bin"), Value::Binary(vec![0xde, 0xad, 0xbe, 0xef])),
+        (Value::from("ext"), Value::Ext(-1, vec![0x00, 0x0f, 0xff])),
+        (Value::from("str"), invalid_str),
+        (Value::from(1), Value::Array(vec![Value::from(f64::NAN), Value::from(u64::MAX)])),
+    ]);
+
+    let json = val.to_debug_json();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    let expected = serde_json::json!({
+        "bin": "3q2+7w==",
+        "ext": {"type": -1, "data": "000fff"},
+        "str": "a\u{fffd}\"",
+        "1": ["NaN", u64::MAX],
+    });
+    assert_eq!(expected, parsed);
+}
+
+#[test]
+fn pass_debug_json_base64_padding() {
+    assert_eq!(r#""""#, Value::Binary(vec![]).to_debug_json());
+    assert_eq!(r#""Zg==""#, Value::Binary(b"f".to_vec()).to_debug_json());
+    assert_eq!(r#""Zm8=""#, Value::Binary(b"fo".to_vec()).to_debug_json());
+    assert_eq!(r#""Zm9v""#, Value::Binary(b"foo".to_vec()).to_debug_json());
+    assert_eq!(r#""Zm9vYg==""#, Value::Binary(b"foob".to_vec()).to_debug_json());
+}
+
+#[test]
+fn pass_pretty_json_layout() {
+    let val = Value::Map(vec![
+        (Value::from("empty"), Value::Array(vec![])),
+        (Value::from(1), Value::Map(vec![(Value::from("a"), Value::Map(vec![]))])),
+        (Value::Array(vec![Value::from(2)]), Value::F64(f64::NAN)),
+    ]);
+
+    let expected = r#"{
+    "empty": [],
+    "1": {
+        "a": {}
+    },
+    "[2]": "NaN"
+}"#;
+    assert_eq!(expected, val.to_string_pretty_with("    "));
+    assert_eq!(val.to_debug_json(), val.to_string_pretty_with(""));
+}
+
+#[test]
+#[cfg(feature = "with-serde-json")]
+fn pass_from_json_value() {
+    let val = Value::from(serde_json::json!({
+        "id": 42,
+        "neg": -1,
+        "big": u64::MAX,
+        "ratio": 0.5,
+        "tags": ["a", null, true],
+    }));
+
+    assert_eq!(Some(42), val["id"].as_u64());
+    assert_eq!(Some(-1), val["neg"].as_i64());
+    assert_eq!(Some(u64::MAX), val["big"].as_u64());
+    assert_eq!(Value::F64(0.5), val["ratio"]);
+    assert_eq!(Value::Array(vec![Value::from("a"), Value::Nil, Value::Boolean(true)]), val["tags"]);
+}
+
+#[test]
+#[cfg(feature = "with-serde-json")]
+fn pass_json_value_round_trip() {
+    let json = serde_json::json!({"name": "le message", "ids": [1, -2, 3.25], "nested": {"ok": false}});
+
+    let val = Value::from(json.clone());
+    assert_eq!(json, val.to_json_value().unwrap());
+}
+
+#[test]
+#[cfg(feature = "with-serde-json")]
+fn pass_f32_into_json_value_uses_shortest_form() {
+    assert_eq!(serde_json::json!(0.1), Value::F32(0.1).to_json_value().unwrap());
+}
+
+#[test]
+#[cfg(feature = "with-serde-json")]
+fn fail_into_json_value() {
+    assert_eq!(Err(Error::Binary), Value::Binary(vec![1]).to_json_value());
+    assert_eq!(Err(Error::Ext(7)), Value::Ext(7, vec![1]).to_json_value());
+    assert_eq!(Err(Error::NonFiniteFloat), Value::F64(f64::NAN).to_json_value());
+    assert_eq!(Err(Error::NonStringKey), Value::Map(vec![
+        (Value::from(1), Value::Nil),
+    ]).to_json_value());
+    assert_eq!(Err(Error::DuplicateKey("a".into())), Value::Map(vec![
+        (Value::from("a"), Value::Nil),
+        (Value::from("a"), Value::from(1)),
+    ]).to_json_value());
+}
+
+#[test]
+#[cfg(feature = "with-serde-json")]
+fn pass_into_json_value_lossy() {
+    let val = Value::Array(vec![
+        Value::Binary(vec![1, 2, 3]),
+        Value::Ext(7, vec![0xca, 0xfe]),
+        Value::F64(f64::INFINITY),
+        Value::Map(vec![
+            (Value::from(42), Value::from("int key")),
+            (Value::from("a"), Value::from(1)),
+            (Value::from("a"), Value::from(2)),
+        ]),
+    ]);
+
+    assert_eq!(serde_json::json!([
+        "AQID",
+        {"type": 7, "data": "cafe"},
+        "inf",
+        {"42": "int key", "a": 2},
+    ]), val.to_json_value_lossy());
+}
@@ -0,0 +1,62 @@
+use rmpv::decode::Error;
+use rmpv::encode::write_value;
+use rmpv::{LazyValue, Value};
+
+fn encode(val: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_value(&mut buf, val).unwrap();
+    buf
+}
+
+#[test]
+fn get_skips_nested_siblings() {
+    let val = Value::Map(vec![
+        (Value::from("skipped"), Value::Array(vec![
+            Value::Map(vec![(Value::from("id"), Value::from(1))]),
+            Value::Binary(vec![0; 300]),
+            Value::Ext(1, vec![0; 20]),
+        ])),
+        (Value::from(1), Value::from("id")),
+        (Value::from("id"), Value::from(u64::MAX)),
+    ]);
+    let buf = encode(&val);
+    let doc = LazyValue::new(&buf).unwrap();
+
+    assert!(doc.is_map());
+    assert_eq!(Some(3), doc.len());
+    assert_eq!(Some(false), doc.is_empty());
+    assert_eq!(Value::from(u64::MAX), doc.get("id").unwrap().unwrap().to_value().unwrap());
+    assert_eq!(None, doc.get("missing").unwrap());
+
+    let skipped = doc.get("skipped").unwrap().unwrap();
+    assert!(skipped.is_array());
+    assert_eq!(encode(&val["skipped"]), skipped.as_bytes().unwrap());
+    assert_eq!(None, skipped.get("id").unwrap());
+    assert_eq!(None, skipped.get_index(3).unwrap());
+
+    let ext = skipped.get_index(2).unwrap().unwrap();
+    assert_eq!(None, ext.len());
+    assert_eq!(Value::Ext(1, vec![0; 20]), ext.to_value().unwrap());
+}
+
+#[test]
+fn as_bytes_excludes_trailing_values() {
+    let buf = [0x92, 0x01, 0xa1, b'a', 0xc0];
+    let val = LazyValue::new(&buf).unwrap();
+
+    assert_eq!(&buf[..4], val.as_bytes().unwrap());
+}
+
+#[test]
+fn truncated_input_fails_on_access() {
+    // A map of two entries, the second one missing.
+    let buf = [0x82, 0xa1, b'a', 0x01];
+    let doc = LazyValue::new(&buf).unwrap();
+
+    assert_eq!(Value::from(1), doc.get("a").unwrap().unwrap().to_value().unwrap());
+    assert!(matches!(doc.get("b"), Err(Error::InvalidDataRead(..))));
+    assert!(matches!(doc.as_bytes(), Err(Error::InvalidDataRead(..))));
+
+    assert!(matches!(LazyValue::new(&[]), Err(Error::InvalidMarkerRead(..))));
+    assert!(matches!(LazyValue::new(&[0xdc, 0x00]), Err(Error::InvalidDataRead(..))));
+}
@@ -0,0 +1,118 @@
+use rmpv::decode::{self, DecodeOptions};
+use rmpv::encode::write_value;
+use rmpv::stream::{Decoded, Error, ValueDecoder};
+use rmpv::{transform_stream, Value};
+
+fn increment(val: Value) -> Value {
+    match val {
+        Value::Integer(n) => match n.as_i64() {
+            Some(n) => Value::from(n + 1),
+            None => Value::Integer(n),
+        },
+        Value::Array(vec) => Value::Array(vec.into_iter().map(increment).collect()),
+        Value::Map(map) => Value::Map(map.into_iter().map(|(k, v)| (k, increment(v))).collect()),
+        val => val,
+    }
+}
+
+#[test]
+fn pass_transform_stream_increments_integers() {
+    let input = [
+        Value::from(1),
+        Value::from("le message"),
+        Value::Array(vec![Value::from(-1), Value::Nil]),
+        Value::Map(vec![(Value::from("n"), Value::from(41))]),
+    ];
+    let mut buf = Vec::new();
+    for val in &input {
+        write_value(&mut buf, val).unwrap();
+    }
+
+    let mut out = Vec::new();
+    transform_stream(&mut &buf[..], &mut out, increment).unwrap();
+
+    let mut expected = Vec::new();
+    for val in &[
+        Value::from(2),
+        Value::from("le message"),
+        Value::Array(vec![Value::from(0), Value::Nil]),
+        Value::Map(vec![(Value::from("n"), Value::from(42))]),
+    ] {
+        write_value(&mut expected, val).unwrap();
+    }
+    assert_eq!(expected, out);
+}
+
+#[test]
+fn pass_transform_empty_stream() {
+    let mut out = Vec::new();
+    transform_stream(&mut &[][..], &mut out, increment).unwrap();
+    assert!(out.is_empty());
+}
+
+#[test]
+fn fail_transform_truncated_stream() {
+    // A complete `1` followed by a truncated array of two elements.
+    let buf = [0x01, 0x92, 0x01];
+    let mut out = Vec::new();
+
+    match transform_stream(&mut &buf[..], &mut out, increment) {
+        Err(Error::Decode(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(vec![0x02], out);
+}
+
+#[test]
+fn pass_value_decoder_byte_by_byte() {
+    let input = [
+        Value::from(1),
+        Value::Map(vec![(Value::from("key"), Value::Array(vec![Value::Nil, Value::F64(1.5)]))]),
+        Value::Binary(vec![0; 300]),
+        Value::Ext(7, vec![1, 2, 3]),
+    ];
+    let mut buf = Vec::new();
+    for val in &input {
+        write_value(&mut buf, val).unwrap();
+    }
+
+    let mut decoder = ValueDecoder::new();
+    let mut output = Vec::new();
+    for byte in buf {
+        decoder.feed(&[byte]);
+        while let Decoded::Value(val) = decoder.decode().unwrap() {
+            output.push(val);
+        }
+    }
+
+    assert_eq!(&input[..], &output[..]);
+    assert_eq!(0, decoder.buffered());
+}
+
+#[test]
+fn pass_value_decoder_many_values_in_one_chunk() {
+    let mut decoder = ValueDecoder::new();
+    decoder.feed(&[0x01, 0x02, 0x92, 0x03]);
+
+    assert_eq!(Decoded::Value(Value::from(1)), decoder.decode().unwrap());
+    assert_eq!(Decoded::Value(Value::from(2)), decoder.decode().unwrap());
+    assert_eq!(Decoded::NeedMore, decoder.decode().unwrap());
+    assert_eq!(2, decoder.buffered());
+
+    decoder.feed(&[0x04, 0xc0]);
+    assert_eq!(Decoded::Value(Value::Array(vec![Value::from(3), Value::from(4)])), decoder.decode().unwrap());
+    assert_eq!(Decoded::Value(Value::Nil), decoder.decode().unwrap());
+    assert_eq!(Decoded::NeedMore, decoder.decode().unwrap());
+}
+
+#[test]
+fn fail_value_decoder_rejects_huge_length_early() {
+    let mut decoder = ValueDecoder::with_options(DecodeOptions::new().max_len(1024));
+
+    // A binary of 4 GiB.
+    decoder.feed(&[0xc6, 0xff, 0xff]);
+    assert_eq!(Decoded::NeedMore, decoder.decode().unwrap());
+
+    decoder.feed(&[0xff, 0xff]);
+    assert!(matches!(decoder.decode(), Err(decode::Error::LengthLimitExceeded)));
+}
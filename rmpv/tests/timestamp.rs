@@ -0,0 +1,142 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rmpv::decode::{read_value, read_value_with_options, DecodeOptions};
+use rmpv::encode::write_value;
+use rmpv::{Timestamp, Value};
+
+fn roundtrip(buf: &[u8]) -> Value {
+    let val = read_value(&mut &buf[..]).unwrap();
+
+    let mut out = Vec::new();
+    write_value(&mut out, &val).unwrap();
+    assert_eq!(buf, &out[..]);
+
+    val
+}
+
+#[test]
+fn from_timestamp32_decode_value() {
+    let val = roundtrip(&[0xd6, 0xff, 0x00, 0x00, 0x00, 0x2a]);
+
+    let ts = val.as_timestamp().unwrap();
+    assert_eq!(42, ts.secs());
+    assert_eq!(0, ts.nanos());
+}
+
+#[test]
+fn from_timestamp64_decode_value() {
+    let val = roundtrip(&[0xd7, 0xff, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x2a]);
+
+    let ts = val.as_timestamp().unwrap();
+    assert_eq!(42, ts.secs());
+    assert_eq!(1, ts.nanos());
+}
+
+#[test]
+fn from_timestamp96_decode_value() {
+    let buf = [
+        0xc7, 0x0c, 0xff,
+        0x00, 0x00, 0x00, 0x01,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    ];
+    let val = roundtrip(&buf);
+
+    let ts = val.as_timestamp().unwrap();
+    assert_eq!(-2, ts.secs());
+    assert_eq!(1, ts.nanos());
+}
+
+#[test]
+fn from_timestamp96_keeps_non_shortest_form() {
+    let buf = [
+        0xc7, 0x0c, 0xff,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2a,
+    ];
+    let val = roundtrip(&buf);
+
+    assert_eq!(Value::from(Timestamp::new(42, 0).unwrap()), val);
+}
+
+#[test]
+fn from_invalid_timestamp_decode_ext() {
+    // Nanoseconds must be less than one second.
+    let buf = [0xd7, 0xff, 0xff, 0xff, 0xff, 0xfc, 0x00, 0x00, 0x00, 0x00];
+    assert_eq!(Value::Ext(-1, buf[2..].to_vec()), roundtrip(&buf));
+
+    // No timestamp form is 2 bytes long.
+    let buf = [0xd5, 0xff, 0x00, 0x01];
+    assert_eq!(Value::Ext(-1, buf[2..].to_vec()), roundtrip(&buf));
+}
+
+#[test]
+fn from_timestamp_decode_ext_when_disabled() {
+    // [<timestamp 32>]
+    let buf = [0x91, 0xd6, 0xff, 0x00, 0x00, 0x00, 0x01];
+    let opts = DecodeOptions::new().timestamps(false);
+
+    let val = read_value_with_options(&mut &buf[..], &opts).unwrap();
+    assert_eq!(Value::Array(vec![Value::Ext(-1, vec![0, 0, 0, 1])]), val);
+
+    let mut out = Vec::new();
+    write_value(&mut out, &val).unwrap();
+    assert_eq!(&buf[..], &out[..]);
+}
+
+#[test]
+fn new_uses_shortest_form() {
+    assert_eq!(4, Timestamp::new(u32::MAX.into(), 0).unwrap().as_ext_data().len());
+    assert_eq!(8, Timestamp::new(u32::MAX.into(), 1).unwrap().as_ext_data().len());
+    assert_eq!(8, Timestamp::new((1 << 34) - 1, 999_999_999).unwrap().as_ext_data().len());
+    assert_eq!(12, Timestamp::new(1 << 34, 0).unwrap().as_ext_data().len());
+    assert_eq!(12, Timestamp::new(-1, 0).unwrap().as_ext_data().len());
+
+    assert_eq!(None, Timestamp::new(0, 1_000_000_000));
+}
+
+#[test]
+fn system_time_roundtrip() {
+    let times = [
+        UNIX_EPOCH,
+        UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789),
+        UNIX_EPOCH - Duration::new(1, 0),
+        UNIX_EPOCH - Duration::new(1, 250_000_000),
+    ];
+
+    for time in times {
+        let ts = Timestamp::try_from(time).unwrap();
+        assert_eq!(time, SystemTime::try_from(ts).unwrap());
+    }
+
+    let ts = Timestamp::from_system_time(UNIX_EPOCH - Duration::new(1, 250_000_000)).unwrap();
+    assert_eq!(-2, ts.secs());
+    assert_eq!(750_000_000, ts.nanos());
+}
+
+#[test]
+fn timestamp_is_ext() {
+    let val = Value::from(Timestamp::new(1, 0).unwrap());
+
+    assert!(val.is_ext());
+    assert_eq!(Some((-1, &[0x00, 0x00, 0x00, 0x01][..])), val.as_ext());
+    assert_eq!("timestamp", val.type_name());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono_roundtrip() {
+    use chrono::{DateTime, Utc};
+
+    for (secs, nanos) in [(0, 0), (1_700_000_000, 123_456_789), (-2, 750_000_000)] {
+        let time = DateTime::from_timestamp(secs, nanos).unwrap();
+        let ts = Timestamp::try_from(time).unwrap();
+        assert_eq!((secs, nanos), (ts.secs(), ts.nanos()));
+        assert_eq!(time, DateTime::<Utc>::try_from(ts).unwrap());
+    }
+
+    // Leap seconds have no timestamp, and not every timestamp is within the range of chrono.
+    let leap = DateTime::from_timestamp(59, 1_500_000_000).unwrap();
+    assert_eq!(Err(leap), Timestamp::try_from(leap));
+    let ts = Timestamp::new(i64::MAX, 0).unwrap();
+    assert_eq!(Err(ts), DateTime::<Utc>::try_from(ts));
+}
@@ -1,4 +1,6 @@
-use rmpv::Value;
+use std::mem::size_of;
+
+use rmpv::{Utf8String, Value, ValueRef};
 
 #[test]
 fn display_nil() {
@@ -56,6 +58,31 @@ fn display_ext() {
     assert_eq!("[1, [100, 42]]", format!("{}", Value::Ext(1, vec![100, 42])));
 }
 
+#[test]
+fn display_alternate() {
+    let val = Value::Map(vec![
+        (Value::from("name"), Value::from("a, b")),
+        (Value::from(1), Value::Array(vec![Value::Binary(vec![1, 2]), Value::Array(vec![])])),
+        (Value::Nil, Value::Map(vec![(Value::Boolean(true), Value::Ext(1, vec![100, 42]))])),
+    ]);
+
+    let expected = r#"{
+  "name": "a, b",
+  1: [
+    [1, 2],
+    []
+  ],
+  nil: {
+    true: [1, [100, 42]]
+  }
+}"#;
+    assert_eq!(expected, format!("{val:#}"));
+    assert_eq!(r#"{"name": "a, b", 1: [[1, 2], []], nil: {true: [1, [100, 42]]}}"#, format!("{val}"));
+
+    assert_eq!("[]", format!("{:#}", Value::Array(vec![])));
+    assert_eq!("\"s\"", format!("{:#}", Value::from("s")));
+}
+
 #[test]
 fn from_bool() {
     assert_eq!(Value::Boolean(true), Value::from(true));
@@ -191,8 +218,743 @@ fn try_from_val() {
     use rmpv::Utf8String;
     use std::convert::TryInto;
 
-  assert_eq!(false, Value::Boolean(false).try_into().unwrap());
+  assert_eq!(false, TryInto::<bool>::try_into(Value::Boolean(false)).unwrap());
   assert_eq!(Utf8String::from("spook"), Value::from("spook").try_into().unwrap());
   assert_eq!(String::from("spook"), TryInto::<String>::try_into(Value::from("spook")).unwrap());
   assert_eq!(vec![0], TryInto::<Vec<u8>>::try_into(Value::Binary(vec![0u8])).unwrap());
 }
+
+#[test]
+fn try_from_val_narrow_ints() {
+    use std::convert::TryFrom;
+
+    assert_eq!(Ok(255), u8::try_from(Value::from(255)));
+    assert_eq!(Ok(-128), i8::try_from(Value::from(-128)));
+    assert_eq!(Ok(-1), i32::try_from(Value::from(-1)));
+    assert_eq!(Ok(u32::MAX), u32::try_from(Value::from(u32::MAX)));
+
+    // The value is handed back on failure.
+    assert_eq!(Err(Value::from(256)), u8::try_from(Value::from(256)));
+    assert_eq!(Err(Value::from(-1)), u16::try_from(Value::from(-1)));
+    assert_eq!(Err(Value::from("1")), i16::try_from(Value::from("1")));
+}
+
+#[test]
+fn try_from_val_hash_map() {
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    let map = HashMap::try_from(Value::Map(vec![
+        (Value::from("id"), Value::from(42)),
+        (Value::from("name"), Value::from("le message")),
+    ])).unwrap();
+    assert_eq!(Some(&Value::from(42)), map.get("id"));
+    assert_eq!(Some(&Value::from("le message")), map.get("name"));
+
+    let val = Value::Map(vec![(Value::from(1), Value::Nil)]);
+    assert_eq!(Err(val.clone()), HashMap::try_from(val));
+}
+
+#[test]
+fn merge_from_msgpack() {
+    fn encode(val: &Value) -> Vec<u8> {
+        let mut buf = Vec::new();
+        rmpv::encode::write_value(&mut buf, val).unwrap();
+        buf
+    }
+
+    let mut config = Value::Map(vec![
+        (Value::from("name"), Value::from("base")),
+        (Value::from("server"), Value::Map(vec![
+            (Value::from("host"), Value::from("localhost")),
+            (Value::from("port"), Value::from(80)),
+        ])),
+        (Value::from("tags"), Value::Array(vec![Value::from("a")])),
+    ]);
+
+    let first = encode(&Value::Map(vec![
+        (Value::from("server"), Value::Map(vec![(Value::from("port"), Value::from(8080))])),
+        (Value::from("tags"), Value::Array(vec![Value::from("b")])),
+    ]));
+    let second = encode(&Value::Map(vec![
+        (Value::from("server"), Value::Map(vec![(Value::from("tls"), Value::from(true))])),
+        (Value::from("debug"), Value::from(true)),
+    ]));
+
+    config.merge_from_msgpack(&first).unwrap();
+    config.merge_from_msgpack(&second).unwrap();
+
+    let expected = Value::Map(vec![
+        (Value::from("name"), Value::from("base")),
+        (Value::from("server"), Value::Map(vec![
+            (Value::from("host"), Value::from("localhost")),
+            (Value::from("port"), Value::from(8080)),
+            (Value::from("tls"), Value::from(true)),
+        ])),
+        (Value::from("tags"), Value::Array(vec![Value::from("b")])),
+        (Value::from("debug"), Value::from(true)),
+    ]);
+    assert_eq!(expected, config);
+
+    // Truncated input leaves the value untouched.
+    assert!(config.merge_from_msgpack(&second[..second.len() - 1]).is_err());
+    assert_eq!(expected, config);
+
+    // So does a valid value followed by garbage.
+    let mut garbage = first.clone();
+    garbage.extend_from_slice(&[0xc1, 0xff]);
+    assert!(matches!(config.merge_from_msgpack(&garbage), Err(rmpv::decode::Error::TrailingData(2))));
+    assert_eq!(expected, config);
+}
+
+#[test]
+fn strip_exts() {
+    use rmpv::StripMode;
+
+    let val = Value::Map(vec![
+        (Value::from("a"), Value::Ext(1, vec![0x01])),
+        (Value::Ext(2, vec![0x02]), Value::Array(vec![
+            Value::from(42),
+            Value::Ext(-1, vec![0x03, 0x04]),
+        ])),
+    ]);
+
+    let mut to_binary = val.clone();
+    to_binary.strip_exts(StripMode::ToBinary);
+    assert_eq!(Value::Map(vec![
+        (Value::from("a"), Value::Binary(vec![0x01])),
+        (Value::Binary(vec![0x02]), Value::Array(vec![
+            Value::from(42),
+            Value::Binary(vec![0x03, 0x04]),
+        ])),
+    ]), to_binary);
+
+    let mut to_nil = val.clone();
+    to_nil.strip_exts(StripMode::ToNil);
+    assert_eq!(Value::Map(vec![
+        (Value::from("a"), Value::Nil),
+        (Value::Nil, Value::Array(vec![Value::from(42), Value::Nil])),
+    ]), to_nil);
+
+    let mut keep_tag = val;
+    keep_tag.strip_exts(StripMode::KeepTag);
+    assert_eq!(Value::Map(vec![
+        (Value::from("a"), Value::Array(vec![Value::from(1), Value::Binary(vec![0x01])])),
+        (Value::Array(vec![Value::from(2), Value::Binary(vec![0x02])]), Value::Array(vec![
+            Value::from(42),
+            Value::Array(vec![Value::from(-1), Value::Binary(vec![0x03, 0x04])]),
+        ])),
+    ]), keep_tag);
+}
+
+#[test]
+fn to_display_string() {
+    assert_eq!(Some("le message".to_string()), Value::from("le message").to_display_string());
+    assert_eq!(Some("42".to_string()), Value::from(42).to_display_string());
+    assert_eq!(Some("-42".to_string()), Value::from(-42).to_display_string());
+    assert_eq!(Some("18446744073709551615".to_string()), Value::from(u64::MAX).to_display_string());
+    assert_eq!(Some("1.5".to_string()), Value::F32(1.5).to_display_string());
+    assert_eq!(Some("0.25".to_string()), Value::F64(0.25).to_display_string());
+    assert_eq!(Some("true".to_string()), Value::Boolean(true).to_display_string());
+    assert_eq!(Some("false".to_string()), Value::Boolean(false).to_display_string());
+
+    assert_eq!(None, Value::Nil.to_display_string());
+    assert_eq!(None, Value::Binary(vec![1]).to_display_string());
+    assert_eq!(None, Value::Ext(1, vec![1]).to_display_string());
+    assert_eq!(None, Value::Array(vec![Value::from(1)]).to_display_string());
+    assert_eq!(None, Value::Map(vec![(Value::from("a"), Value::from(1))]).to_display_string());
+}
+
+#[test]
+fn map_value_type() {
+    let uniform = Value::Map(vec![
+        (Value::from("a"), Value::from(1)),
+        (Value::from("b"), Value::from(-2)),
+        (Value::from("c"), Value::from(u64::MAX)),
+    ]);
+    assert_eq!(Some("integer"), uniform.map_value_type());
+
+    let strings = Value::Map(vec![(Value::from(1), Value::from("x")), (Value::Nil, Value::from("y"))]);
+    assert_eq!(Some("string"), strings.map_value_type());
+
+    let mixed = Value::Map(vec![
+        (Value::from("a"), Value::from(1)),
+        (Value::from("b"), Value::from("2")),
+    ]);
+    assert_eq!(None, mixed.map_value_type());
+
+    let floats = Value::Map(vec![
+        (Value::from("a"), Value::F32(1.0)),
+        (Value::from("b"), Value::F64(1.0)),
+    ]);
+    assert_eq!(None, floats.map_value_type());
+
+    assert_eq!(None, Value::Map(vec![]).map_value_type());
+    assert_eq!(None, Value::Array(vec![Value::from(1)]).map_value_type());
+}
+
+#[test]
+fn group_by() {
+    let record = |category: &str, item: &str| Value::Map(vec![
+        (Value::from("category"), Value::from(category)),
+        (Value::from("item"), Value::from(item)),
+    ]);
+    let val = Value::Array(vec![
+        record("fruit", "apple"),
+        record("vegetable", "carrot"),
+        record("fruit", "pear"),
+        Value::Map(vec![(Value::from("item"), Value::from("rock"))]),
+    ]);
+
+    let groups = val.group_by(|rec| rec["category"].clone()).unwrap();
+
+    assert_eq!(Value::Map(vec![
+        (Value::from("fruit"), Value::Array(vec![record("fruit", "apple"), record("fruit", "pear")])),
+        (Value::from("vegetable"), Value::Array(vec![record("vegetable", "carrot")])),
+        (Value::Nil, Value::Array(vec![Value::Map(vec![(Value::from("item"), Value::from("rock"))])])),
+    ]), groups);
+
+    assert_eq!(Some(Value::Map(vec![])), Value::Array(vec![]).group_by(|v| v.clone()));
+    assert_eq!(None, Value::Map(vec![]).group_by(|v| v.clone()));
+}
+
+#[test]
+fn zip_arrays() {
+    let a = Value::Array(vec![Value::from(1), Value::from(2), Value::from(3)]);
+    let b = Value::Array(vec![Value::from(10), Value::from(-20)]);
+    let add = |a: &Value, b: &Value| Value::from(a.as_i64().unwrap() + b.as_i64().unwrap());
+
+    assert_eq!(Some(Value::Array(vec![Value::from(11), Value::from(-18)])), Value::zip_arrays(&a, &b, add));
+    assert_eq!(Some(Value::Array(vec![])), Value::zip_arrays(&a, &Value::Array(vec![]), add));
+    assert_eq!(None, Value::zip_arrays(&Value::from(1), &b, add));
+    assert_eq!(None, Value::zip_arrays(&a, &Value::Map(vec![]), add));
+}
+
+#[test]
+fn query() {
+    let val = Value::Map(vec![
+        (Value::from("title"), Value::from("A rather long title")),
+        (Value::from("tags"), Value::Array(vec![Value::from("short"), Value::from("considerably longer")])),
+        (Value::from("meta"), Value::Map(vec![
+            (Value::from("author"), Value::from("Someone Else")),
+            (Value::from(7), Value::from("seventh entry!")),
+            (Value::from("x"), Value::from(12345678901u64)),
+        ])),
+    ]);
+
+    let long = val.query(|v| v.as_str().is_some_and(|s| s.len() > 10));
+    assert_eq!(vec![
+        ("/title".to_owned(), &Value::from("A rather long title")),
+        ("/tags/1".to_owned(), &Value::from("considerably longer")),
+        ("/meta/author".to_owned(), &Value::from("Someone Else")),
+        ("/meta/7".to_owned(), &Value::from("seventh entry!")),
+    ], long);
+
+    assert_eq!(vec![(String::new(), &val)], val.query(|v| v.is_map() && v["title"].is_str()));
+    assert!(val.query(|v| v.is_nil()).is_empty());
+}
+
+#[test]
+fn rename_keys() {
+    use std::collections::HashMap;
+
+    let mapping = HashMap::from([("old".to_owned(), "new".to_owned())]);
+
+    let mut val = Value::Map(vec![
+        (Value::from("old"), Value::from(1)),
+        (Value::from("keep"), Value::Array(vec![
+            Value::Map(vec![(Value::from("old"), Value::from(2)), (Value::from(3), Value::from("old"))]),
+        ])),
+    ]);
+    val.rename_keys(&mapping);
+    assert_eq!(Value::Map(vec![
+        (Value::from("new"), Value::from(1)),
+        (Value::from("keep"), Value::Array(vec![
+            Value::Map(vec![(Value::from("new"), Value::from(2)), (Value::from(3), Value::from("old"))]),
+        ])),
+    ]), val);
+
+    // Collisions keep the last entry, whether it was renamed or not.
+    let mut val = Value::Map(vec![
+        (Value::from("new"), Value::from("existing")),
+        (Value::from("old"), Value::from("renamed")),
+    ]);
+    val.rename_keys(&mapping);
+    assert_eq!(Value::Map(vec![(Value::from("new"), Value::from("renamed"))]), val);
+
+    let mut val = Value::Map(vec![
+        (Value::from("old"), Value::from("renamed")),
+        (Value::from("x"), Value::Nil),
+        (Value::from("new"), Value::from("existing")),
+    ]);
+    val.rename_keys(&mapping);
+    assert_eq!(Value::Map(vec![
+        (Value::from("x"), Value::Nil),
+        (Value::from("new"), Value::from("existing")),
+    ]), val);
+}
+
+#[test]
+fn flatten_to_map() {
+    let val = Value::Map(vec![
+        (Value::from("id"), Value::from(1)),
+        (Value::from("user"), Value::Map(vec![
+            (Value::from("name"), Value::from("Ann")),
+            (Value::from("roles"), Value::Array(vec![Value::from("admin"), Value::Nil])),
+            (Value::from("empty"), Value::Map(vec![])),
+        ])),
+        (Value::from(7), Value::Binary(vec![1])),
+    ]);
+
+    let flat = val.flatten_to_map("/");
+    let expected = Value::Map(vec![
+        (Value::from("id"), Value::from(1)),
+        (Value::from("user/name"), Value::from("Ann")),
+        (Value::from("user/roles/0"), Value::from("admin")),
+        (Value::from("user/roles/1"), Value::Nil),
+        (Value::from("7"), Value::Binary(vec![1])),
+    ]);
+    assert_eq!(expected, flat);
+
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &flat).unwrap();
+    assert_eq!(expected, rmpv::decode::read_value(&mut &buf[..]).unwrap());
+
+    assert_eq!(Value::Map(vec![(Value::from(""), Value::from(true))]), Value::from(true).flatten_to_map("."));
+    assert_eq!(Value::Map(vec![]), Value::Array(vec![]).flatten_to_map("."));
+}
+
+#[test]
+fn pointer() {
+    let mut val = Value::Map(vec![
+        (Value::from("config"), Value::Map(vec![
+            (Value::from("servers"), Value::Array(vec![
+                Value::Map(vec![(Value::from("host"), Value::from("a.example"))]),
+                Value::Map(vec![(Value::from("host"), Value::from("b.example"))]),
+            ])),
+            (Value::from("a/b~c"), Value::from(1)),
+        ])),
+        (Value::from(-3), Value::from("negative")),
+        (Value::from(5), Value::from("integer key")),
+        (Value::from("5"), Value::from("string key")),
+    ]);
+
+    assert_eq!(Some(&val.clone()), val.pointer(""));
+    assert_eq!(Some(&Value::from("b.example")), val.pointer("/config/servers/1/host"));
+    assert_eq!(Some(&Value::from(1)), val.pointer("/config/a~1b~0c"));
+    assert_eq!(Some(&Value::from("negative")), val.pointer("/-3"));
+    assert_eq!(Some(&Value::from("string key")), val.pointer("/5"));
+
+    assert_eq!(None, val.pointer("config"));
+    assert_eq!(None, val.pointer("/config/servers/2"));
+    assert_eq!(None, val.pointer("/config/servers/x"));
+    assert_eq!(None, val.pointer("/config/servers/0/host/0"));
+    assert_eq!(None, val.pointer("/missing"));
+
+    // Array indices are `0` or digits without a sign or leading zeros.
+    assert_eq!(Some(&Value::from("a.example")), val.pointer("/config/servers/0/host"));
+    for token in ["+1", "01", "00", "-0", " 1", "1 ", ""] {
+        assert_eq!(None, val.pointer(&format!("/config/servers/{token}/host")), "{token:?}");
+        assert!(val.pointer_mut(&format!("/config/servers/{token}/host")).is_none(), "{token:?}");
+    }
+
+    *val.pointer_mut("/config/servers/0/host").unwrap() = Value::from("c.example");
+    assert_eq!(Value::from("c.example"), val["config"]["servers"][0]["host"]);
+    assert!(val.pointer_mut("/config/servers/0/port").is_none());
+}
+
+#[test]
+fn index_mut() {
+    let mut val = Value::Map(vec![
+        (Value::from("user"), Value::Map(vec![(Value::from("id"), Value::from(1))])),
+        (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::from("b")])),
+    ]);
+
+    val["user"]["id"] = Value::from(2);
+    val["tags"][1] = Value::from("c");
+    val["user"]["address"]["city"] = Value::from("Paris");
+
+    assert_eq!(Value::Map(vec![
+        (Value::from("user"), Value::Map(vec![
+            (Value::from("id"), Value::from(2)),
+            (Value::from("address"), Value::Map(vec![(Value::from("city"), Value::from("Paris"))])),
+        ])),
+        (Value::from("tags"), Value::Array(vec![Value::from("a"), Value::from("c")])),
+    ]), val);
+}
+
+#[test]
+#[should_panic(expected = "cannot access index 2 of array of length 2")]
+fn index_mut_out_of_bounds() {
+    let mut val = Value::Array(vec![Value::Nil, Value::Nil]);
+    val[2] = Value::from(1);
+}
+
+#[test]
+#[should_panic(expected = "cannot access key \"a\" of integer")]
+fn index_mut_not_a_map() {
+    let mut val = Value::from(1);
+    val["a"] = Value::Nil;
+}
+
+#[test]
+fn msgpack_macro() {
+    use rmpv::msgpack;
+
+    assert_eq!(Value::Nil, msgpack!(nil));
+    assert_eq!(Value::from(-1), msgpack!(-1));
+    assert_eq!(Value::Array(vec![]), msgpack!([]));
+    assert_eq!(Value::Map(vec![]), msgpack!({}));
+    assert_eq!(Value::Ext(-1, vec![1, 2]), msgpack!(ext(-1, vec![1, 2])));
+
+    let key = "dynamic";
+    let val = msgpack!([
+        nil,
+        [[], {}],
+        {(key): [1, -2, 3.5], "bin": b"\x01\x02", "ext": ext(5, b"ab"),},
+        "tail",
+        1 + 1,
+    ]);
+
+    assert_eq!(Value::Array(vec![
+        Value::Nil,
+        Value::Array(vec![Value::Array(vec![]), Value::Map(vec![])]),
+        Value::Map(vec![
+            (Value::from("dynamic"), Value::Array(vec![Value::from(1), Value::from(-2), Value::from(3.5)])),
+            (Value::from("bin"), Value::Binary(vec![1, 2])),
+            (Value::from("ext"), Value::Ext(5, b"ab".to_vec())),
+        ]),
+        Value::from("tail"),
+        Value::from(2),
+    ]), val);
+}
+
+#[test]
+fn merge_strategies() {
+    use rmpv::{msgpack, MergeStrategy};
+
+    let base = msgpack!({"name": "base", "list": [1, 2], "nested": {"a": 1, "b": [1]}});
+    let overlay = msgpack!({"name": "overlay", "list": [3], "nested": {"b": [2], "c": 3}});
+
+    let mut val = base.clone();
+    val.merge(overlay.clone(), MergeStrategy::Replace);
+    assert_eq!(msgpack!({"name": "overlay", "list": [3], "nested": {"a": 1, "b": [2], "c": 3}}), val);
+
+    let mut val = base.clone();
+    val.merge(overlay.clone(), MergeStrategy::AppendArrays);
+    assert_eq!(msgpack!({"name": "overlay", "list": [1, 2, 3], "nested": {"a": 1, "b": [1, 2], "c": 3}}), val);
+
+    let mut val = base.clone();
+    val.merge(overlay, MergeStrategy::KeepExisting);
+    assert_eq!(msgpack!({"name": "base", "list": [1, 2], "nested": {"a": 1, "b": [1], "c": 3}}), val);
+
+    // A map only merges into another map.
+    let mut val = msgpack!([1]);
+    val.merge(msgpack!({"a": 1}), MergeStrategy::AppendArrays);
+    assert_eq!(msgpack!({"a": 1}), val);
+}
+
+#[test]
+fn entry() {
+    use rmpv::Entry;
+
+    let mut val = Value::Map(vec![
+        (Value::from("a"), Value::from(1)),
+        (Value::from(2), Value::Map(vec![])),
+    ]);
+
+    *val.entry("a").unwrap().or_insert(Value::Nil) = Value::from(10);
+    val.entry("b").unwrap().or_insert_with(|| Value::from("new"));
+    val.entry(2).unwrap()
+        .or_insert(Value::Nil)
+        .entry("nested").unwrap()
+        .or_insert(Value::Boolean(true));
+
+    assert_eq!(Value::Map(vec![
+        (Value::from("a"), Value::from(10)),
+        (Value::from(2), Value::Map(vec![(Value::from("nested"), Value::Boolean(true))])),
+        (Value::from("b"), Value::from("new")),
+    ]), val);
+
+    match val.entry("a").unwrap() {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(&Value::from("a"), entry.key());
+            assert_eq!(Value::from(10), entry.insert(Value::from(11)));
+            assert_eq!(Value::from(11), entry.remove());
+        }
+        Entry::Vacant(..) => panic!("expected an occupied entry"),
+    }
+    assert!(matches!(val.entry("a"), Some(Entry::Vacant(..))));
+    assert!(Value::from(1).entry("a").is_none());
+}
+
+#[test]
+fn map_index() {
+    let val = Value::Map(vec![
+        (Value::from("a"), Value::from(1)),
+        (Value::from(2), Value::from("int key")),
+        (Value::from("b"), Value::from(2)),
+        (Value::from("a"), Value::from(3)),
+    ]);
+
+    let index = val.map_index().unwrap();
+    assert_eq!(2, index.len());
+    assert_eq!(Some(&Value::from(1)), index.get("a"));
+    assert_eq!(Some(2), index.position("b"));
+    assert!(!index.contains_key("2"));
+
+    assert!(Value::Map(vec![]).map_index().unwrap().is_empty());
+    assert!(Value::from("a").map_index().is_none());
+}
+
+#[test]
+fn value_total_order() {
+    let sorted = [
+        Value::Nil,
+        Value::from(false),
+        Value::from(true),
+        Value::from(i64::MIN),
+        Value::from(-1),
+        Value::from(0),
+        Value::from(u64::MAX),
+        Value::from(f32::NEG_INFINITY),
+        Value::from(f32::NAN),
+        Value::from(-f64::NAN),
+        Value::from(-0.0),
+        Value::from(0.0),
+        Value::from(f64::NAN),
+        Value::from(""),
+        Value::from("a"),
+        Value::from("b"),
+        Value::Binary(vec![0]),
+        Value::Array(vec![]),
+        Value::Array(vec![Value::Nil]),
+        Value::Map(vec![(Value::from(1), Value::Nil)]),
+        Value::Ext(-2, vec![0xff]),
+        Value::Ext(1, vec![]),
+        Value::from(rmpv::Timestamp::new(-1, 0).unwrap()),
+        Value::from(rmpv::Timestamp::new(0, 0).unwrap()),
+    ];
+
+    for (i, lhs) in sorted.iter().enumerate() {
+        for (j, rhs) in sorted.iter().enumerate() {
+            assert_eq!(i.cmp(&j), lhs.total_cmp(rhs), "{lhs} <=> {rhs}");
+            assert_eq!(i == j, lhs.total_eq(rhs), "{lhs} == {rhs}");
+        }
+    }
+
+    // The ValueRef order follows the same rules, for the variants both have.
+    let sorted = &sorted[..sorted.len() - 2];
+    for (i, lhs) in sorted.iter().enumerate() {
+        for (j, rhs) in sorted.iter().enumerate() {
+            assert_eq!(i.cmp(&j), lhs.as_ref().total_cmp(&rhs.as_ref()), "{lhs} <=> {rhs}");
+        }
+    }
+}
+
+#[test]
+fn value_eq_follows_ieee_754() {
+    assert_ne!(Value::from(f64::NAN), Value::from(f64::NAN));
+    assert_ne!(Value::Array(vec![Value::from(f32::NAN)]), Value::Array(vec![Value::from(f32::NAN)]));
+    assert_eq!(Value::from(0.0), Value::from(-0.0));
+    assert_eq!(ValueRef::F64(0.0), ValueRef::F64(-0.0));
+
+    assert!(!Value::from(0.0).total_eq(&Value::from(-0.0)));
+    assert!(Value::Array(vec![Value::from(f32::NAN)]).total_eq(&Value::Array(vec![Value::from(f32::NAN)])));
+    assert!(ValueRef::F64(f64::NAN).total_eq(&ValueRef::F64(f64::NAN)));
+}
+
+#[test]
+fn value_hash_set_and_map_keys() {
+    use std::collections::{BTreeMap, HashSet};
+
+    use rmpv::TotalValue;
+
+    let set: HashSet<TotalValue> = vec![
+        Value::from(f64::NAN),
+        Value::from(f64::NAN),
+        Value::from(1),
+        Value::from(1u8),
+        Value::from(1.0),
+        Value::from(0.0),
+        Value::from(-0.0),
+        Value::String(rmpv::Utf8String::from("k")),
+        Value::from("k"),
+    ].into_iter().map(TotalValue).collect();
+    assert_eq!(6, set.len());
+    assert!(set.contains(&TotalValue(Value::from(f64::NAN))));
+
+    let mut map = BTreeMap::new();
+    map.insert(TotalValue(Value::from("b")), 2);
+    map.insert(TotalValue(Value::from("a")), 1);
+    map.insert(TotalValue(Value::from(3)), 0);
+    assert_eq!(vec![Value::from(3), Value::from("a"), Value::from("b")], map.into_keys().map(|key| key.0).collect::<Vec<_>>());
+}
+
+#[test]
+fn shared_value_clones_share_the_tree() {
+    use rmpv::SharedValue;
+
+    let val = Value::Map(vec![(Value::from("k"), Value::Array(vec![Value::from(1)]))]);
+    let shared = SharedValue::from(val.clone());
+    let clone = shared.clone();
+
+    assert!(shared.ptr_eq(&clone));
+    assert!(!shared.ptr_eq(&SharedValue::from(val.clone())));
+    assert_eq!(shared, SharedValue::from(val.clone()));
+    assert_eq!(&Value::from(1), &clone["k"][0]);
+    assert_eq!(val.to_string(), clone.to_string());
+
+    // Both the copy left behind by a live clone and the unwrapped last one are equal.
+    assert_eq!(val, clone.into_value());
+    assert_eq!(val, Value::from(shared));
+}
+
+#[test]
+#[cfg(feature = "bytes")]
+fn bytes_conversions_share_data() {
+    use bytes::Bytes;
+    use rmpv::decode::read_value_ref;
+
+    let val = Value::from(Bytes::from(vec![1, 2, 3]));
+    assert_eq!(Value::Binary(vec![1, 2, 3]), val);
+    assert_eq!(Ok(Bytes::from_static(&[1, 2, 3])), Bytes::try_from(val));
+    assert_eq!(Err(Value::from("s")), Bytes::try_from(Value::from("s")));
+
+    // ["ab", <ext 1>, 1]
+    let buf = Bytes::from(vec![0x93, 0xa2, b'a', b'b', 0xd4, 0x07, 0xff, 0x01]);
+    let msg = read_value_ref(&mut &buf[..]).unwrap();
+    assert_eq!(Some(buf.slice(2..4)), msg.index(0).slice_bytes(&buf));
+    assert_eq!(Some(buf.slice(6..7)), msg.index(1).slice_bytes(&buf));
+    assert_eq!(None, msg.index(2).slice_bytes(&buf));
+    assert_eq!(None, msg.index(0).slice_bytes(&Bytes::from_static(b"ab")));
+}
+
+#[test]
+fn from_maps_options_and_tuples() {
+    use std::collections::{BTreeMap, HashMap};
+
+    let btree: BTreeMap<_, _> = [("b", 2), ("a", 1)].into_iter().collect();
+    assert_eq!(Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("b"), Value::from(2))]), Value::from(btree));
+
+    let hash: HashMap<_, _> = [(1u8, Some("x"))].into_iter().collect();
+    assert_eq!(Value::Map(vec![(Value::from(1), Value::from("x"))]), Value::from(hash));
+
+    assert_eq!(Value::Nil, Value::from(None::<i32>));
+    assert_eq!(Value::from(3), Value::from(Some(3)));
+
+    assert_eq!(Value::Array(vec![Value::from(1)]), Value::from((1,)));
+    assert_eq!(Value::Array(vec![Value::from("a"), Value::Nil, Value::from(true), Value::from(1.5)]), Value::from(("a", None::<u8>, true, 1.5)));
+}
+
+#[test]
+fn take_and_replace_leave_rest_of_tree() {
+    let mut val = Value::Map(vec![
+        (Value::from("a"), Value::Array(vec![Value::from(1), Value::from(2)])),
+        (Value::from("b"), Value::from("keep")),
+    ]);
+
+    assert_eq!(Value::from(2), val.pointer_mut("/a/1").unwrap().take());
+    assert_eq!(Value::Array(vec![Value::from(1), Value::Nil]), val.replace(Value::Nil)["a"]);
+    assert_eq!(Value::Nil, val);
+    assert_eq!(Value::Nil, Value::Nil.take());
+}
+
+#[test]
+fn mutable_and_consuming_container_accessors() {
+    let mut val = Value::Map(vec![(Value::from("list"), Value::Array(vec![]))]);
+
+    val["list"].as_array_mut().unwrap().push(Value::from(1));
+    val.as_map_mut().unwrap().push((Value::from("n"), Value::Nil));
+    assert_eq!(None, val.as_array_mut());
+
+    let mut entries = val.into_map().unwrap();
+    assert_eq!(Some(vec![Value::from(1)]), entries.remove(0).1.into_array());
+    assert_eq!(None, entries.remove(0).1.into_map());
+}
+
+#[test]
+fn str_key_lookups_accept_binary_keys() {
+    let mut val = Value::Map(vec![
+        (Value::from(1), Value::from("int")),
+        (Value::Binary(b"name".to_vec()), Value::from("bin")),
+        (Value::from("name"), Value::from("str")),
+        (Value::from("id"), Value::from(1)),
+    ]);
+
+    assert_eq!(Some(&Value::from("bin")), val.get_str_key("name"));
+    assert_eq!(Some(&Value::from(1)), val.get_bytes_key(b"id"));
+    assert_eq!(None, val.get_str_key("missing"));
+    assert_eq!(None, Value::from("name").get_str_key("name"));
+
+    *val.get_str_key_mut("id").unwrap() = Value::from(2);
+    assert_eq!(Value::from(2), val["id"]);
+}
+
+#[test]
+fn members_and_entries_iterate_any_variant() {
+    let array = Value::Array(vec![Value::from(1), Value::from("a")]);
+    let map = Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("b"), Value::Nil)]);
+
+    assert_eq!(vec![&Value::from(1), &Value::from("a")], array.members().collect::<Vec<_>>());
+    assert_eq!(0, array.entries().len());
+    assert_eq!(0, map.members().len());
+    assert_eq!(
+        vec![(&Value::from("b"), &Value::Nil), (&Value::from("a"), &Value::from(1))],
+        map.entries().rev().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn memory_usage_counts_nested_capacity() {
+    let mut names = String::with_capacity(32);
+    names.push_str("abc");
+    let map = Value::Map(vec![(Value::from(names), Value::Ext(1, vec![0; 10]))]);
+    let val = Value::Array(vec![map, Value::from(1)]);
+
+    let expected = 2 * size_of::<Value>() + size_of::<(Value, Value)>() + 32 + 10;
+    assert_eq!(expected, val.memory_usage());
+}
+
+#[test]
+fn coerced_accessors_truncate_and_saturate() {
+    assert_eq!(Some(i64::MIN), Value::F64(-1e300).as_i64_coerced());
+    assert_eq!(Some(3), Value::F32(3.99).as_i64_coerced());
+    assert_eq!(Some(-3), Value::from("-3").as_i64_coerced());
+    assert_eq!(Some(0), Value::from("-5").as_u64_coerced());
+    assert_eq!(Some(u64::MAX), Value::from(u64::MAX).as_u64_coerced());
+    assert_eq!(None, Value::F32(f32::NAN).as_u64_coerced());
+    assert_eq!(None, Value::Binary(b"1".to_vec()).as_i64_coerced());
+    assert_eq!(Some(1.5), Value::F32(1.5).as_f64_coerced());
+    assert_eq!(None, Value::from("").as_f64_coerced());
+}
+
+#[test]
+fn utf8_string_from_bytes_keeps_invalid_payload() {
+    fn len<T: AsRef<[u8]>>(val: &T) -> usize {
+        val.as_ref().len()
+    }
+
+    let s = Utf8String::from(vec![0xf0, 0x9f, 0x92]);
+    assert!(s.is_err());
+    assert_eq!(3, len(&s));
+    assert_eq!("\u{FFFD}", s.clone().into_string_lossy());
+    assert_eq!(vec![0xf0, 0x9f, 0x92], s.into_bytes());
+}
+
+#[test]
+fn debug_hex_truncates_payloads() {
+    let val = Value::Array(vec![
+        Value::from(1),
+        Value::Binary((0..40).collect()),
+        Value::String(Utf8String::from(vec![0xff])),
+        Value::Map(vec![]),
+    ]);
+
+    let out = format!("{:?}", val.debug_hex());
+    assert!(out.starts_with("Array([Integer(PosInt(1)), Binary(40 bytes: 00 01 02"));
+    assert!(out.contains(" 1e 1f ..), InvalidString(1 bytes: ff), Map({})])"));
+
+    let pretty = format!("{:#?}", val.debug_hex().limit(0));
+    assert!(pretty.contains("\n        Binary(\n            40 bytes ..,\n        ),\n"));
+}
@@ -0,0 +1,103 @@
+use rmpv::{Value, ValueVisitor, ValueVisitorMut, Walk};
+
+/// Records the visited values and entry keys, stopping at `stop_at`.
+struct Recorder {
+    visited: Vec<String>,
+    stop_at: Option<Value>,
+}
+
+impl ValueVisitor for Recorder {
+    fn visit(&mut self, val: &Value) -> Walk {
+        self.visited.push(val.to_string());
+        match self.stop_at {
+            Some(ref stop_at) if stop_at == val => Walk::Stop,
+            _ if val.is_bin() => Walk::SkipChildren,
+            _ => Walk::Continue,
+        }
+    }
+
+    fn visit_entry(&mut self, key: &Value, _val: &Value) -> Walk {
+        if key.as_str() == Some("skip") {
+            return Walk::SkipChildren;
+        }
+        Walk::Continue
+    }
+}
+
+fn sample() -> Value {
+    Value::Map(vec![
+        (Value::from("a"), Value::Array(vec![Value::from(1), Value::from(2)])),
+        (Value::from("skip"), Value::Array(vec![Value::from(3)])),
+        (Value::from("b"), Value::Nil),
+    ])
+}
+
+#[test]
+fn walk_visits_depth_first() {
+    let mut visitor = Recorder { visited: Vec::new(), stop_at: None };
+
+    assert!(sample().walk(&mut visitor));
+    assert_eq!(vec![r#"{"a": [1, 2], "skip": [3], "b": nil}"#, r#""a""#, "[1, 2]", "1", "2", r#""b""#, "nil"], visitor.visited);
+}
+
+#[test]
+fn walk_stops() {
+    let mut visitor = Recorder { visited: Vec::new(), stop_at: Some(Value::from(1)) };
+
+    assert!(!sample().walk(&mut visitor));
+    assert_eq!(Some("1"), visitor.visited.last().map(|s| &s[..]));
+    assert_eq!(4, visitor.visited.len());
+}
+
+/// Renames keys to upper case and turns integers into strings.
+struct Rewrite;
+
+impl ValueVisitorMut for Rewrite {
+    fn visit_mut(&mut self, val: &mut Value) -> Walk {
+        if let Some(n) = val.as_i64() {
+            *val = Value::from(n.to_string());
+        }
+        Walk::Continue
+    }
+
+    fn visit_entry_mut(&mut self, key: &mut Value, _val: &mut Value) -> Walk {
+        if let Some(s) = key.as_str() {
+            *key = Value::from(s.to_uppercase());
+        }
+        // The key itself is a string now, so it is left as is when visited.
+        Walk::Continue
+    }
+}
+
+#[test]
+fn walk_mut_rewrites_in_place() {
+    let mut val = sample();
+
+    assert!(val.walk_mut(&mut Rewrite));
+    assert_eq!(Value::Map(vec![
+        (Value::from("A"), Value::Array(vec![Value::from("1"), Value::from("2")])),
+        (Value::from("SKIP"), Value::Array(vec![Value::from("3")])),
+        (Value::from("B"), Value::Nil),
+    ]), val);
+}
+
+#[test]
+fn walk_mut_visits_replacement_children() {
+    struct Expand;
+
+    impl ValueVisitorMut for Expand {
+        fn visit_mut(&mut self, val: &mut Value) -> Walk {
+            match *val {
+                Value::Nil => *val = Value::Array(vec![Value::from(true)]),
+                Value::Boolean(..) => *val = Value::from(false),
+                _ => {}
+            }
+            Walk::Continue
+        }
+    }
+
+    let mut val = Value::Nil;
+    val.walk_mut(&mut Expand);
+
+    assert_eq!(Value::Array(vec![Value::from(false)]), val);
+}